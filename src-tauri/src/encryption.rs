@@ -1,5 +1,5 @@
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
-use ring::aead::{self, BoundKey, Nonce, NonceSequence, UnboundKey, AES_256_GCM};
+use ring::aead::{self, BoundKey, Nonce, NonceSequence, UnboundKey, AES_256_GCM, NONCE_LEN};
 use ring::rand::{SecureRandom, SystemRandom};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -38,6 +38,13 @@ impl NonceSequence for FixedNonce {
     }
 }
 
+// 系统钥匙串中保存主密钥的条目
+const KEYCHAIN_SERVICE: &str = "fishmind";
+const KEYCHAIN_MASTER_KEY: &str = "master-key";
+
+// 包装数据密钥时使用的附加认证数据
+const WRAPPED_KEY_AAD: &[u8] = b"fishmind-data-key";
+
 impl KeyStore {
     pub fn new() -> Self {
         KeyStore {
@@ -45,6 +52,94 @@ impl KeyStore {
             data_keys: Mutex::new(HashMap::new()),
         }
     }
+
+    // 使用已有的主密钥创建密钥存储
+    pub fn with_master_key(key: Vec<u8>) -> Self {
+        KeyStore {
+            master_key: Mutex::new(Some(key)),
+            data_keys: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // 从系统钥匙串加载主密钥，不存在时生成并保存到钥匙串
+    pub fn from_keychain() -> Result<Self, String> {
+        let entry = keyring::Entry::new(KEYCHAIN_SERVICE, KEYCHAIN_MASTER_KEY)
+            .map_err(|e| format!("Failed to open keychain: {}", e))?;
+        let key = match entry.get_secret() {
+            Ok(key) => key,
+            Err(keyring::Error::NoEntry) => {
+                let key = random_key()?;
+                entry
+                    .set_secret(&key)
+                    .map_err(|e| format!("Failed to save master key to keychain: {}", e))?;
+                key
+            }
+            Err(e) => return Err(format!("Failed to read master key from keychain: {}", e)),
+        };
+        if key.len() != 32 {
+            return Err("Invalid master key in keychain".to_string());
+        }
+        Ok(Self::with_master_key(key))
+    }
+
+    // 用主密钥加密数据密钥，返回 nonce 与密文拼接后的字节，可以与数据保存在一起
+    pub fn wrap_key(&self, key: &[u8]) -> Result<Vec<u8>, String> {
+        let master_key = self.master_aead_key()?;
+        let mut nonce = [0u8; NONCE_LEN];
+        SystemRandom::new()
+            .fill(&mut nonce)
+            .map_err(|e| e.to_string())?;
+
+        let mut in_out = key.to_vec();
+        master_key
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                aead::Aad::from(WRAPPED_KEY_AAD),
+                &mut in_out,
+            )
+            .map_err(|_| "Failed to wrap data key".to_string())?;
+
+        let mut wrapped = nonce.to_vec();
+        wrapped.extend_from_slice(&in_out);
+        Ok(wrapped)
+    }
+
+    // 解密 wrap_key 包装的数据密钥
+    pub fn unwrap_key(&self, wrapped: &[u8]) -> Result<Vec<u8>, String> {
+        let master_key = self.master_aead_key()?;
+        if wrapped.len() < NONCE_LEN {
+            return Err("Wrapped data key is too short".to_string());
+        }
+        let (nonce, ciphertext) = wrapped.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|e| e.to_string())?;
+
+        let mut in_out = ciphertext.to_vec();
+        let key = master_key
+            .open_in_place(nonce, aead::Aad::from(WRAPPED_KEY_AAD), &mut in_out)
+            .map_err(|_| {
+                "Failed to unwrap data key, the master key may have changed".to_string()
+            })?;
+        Ok(key.to_vec())
+    }
+
+    fn master_aead_key(&self) -> Result<aead::LessSafeKey, String> {
+        let master_key = self.master_key.lock().map_err(|e| e.to_string())?;
+        let master_key = master_key
+            .as_ref()
+            .ok_or_else(|| "Master key not initialized".to_string())?;
+        UnboundKey::new(&AES_256_GCM, master_key)
+            .map(aead::LessSafeKey::new)
+            .map_err(|e| e.to_string())
+    }
+}
+
+// 生成随机的 AES-256 密钥
+fn random_key() -> Result<Vec<u8>, String> {
+    let mut key = vec![0; 32]; // AES-256 需要 32 字节密钥
+    SystemRandom::new()
+        .fill(&mut key)
+        .map_err(|e| e.to_string())?;
+    Ok(key)
 }
 
 // 初始化加密服务
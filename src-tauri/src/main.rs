@@ -4,9 +4,11 @@
     windows_subsystem = "windows"
 )]
 
+// 加密命令尚未注册（前端目前使用模拟实现），这里只使用其中的主密钥存储
+#[allow(dead_code)]
+mod encryption;
 mod mcp;
 
-use env_logger;
 use log::info;
 use mcp::{client::AppState, commands::*};
 use std::sync::Arc;
@@ -20,18 +22,53 @@ async fn run_sqlite_tests() -> Result<String, String> {
 }
 
 fn main() {
-    // 设置日志级别
-    std::env::set_var("RUST_LOG", "info");
+    // 沙箱启动器模式：应用限制后直接执行 MCP 服务器命令
+    #[cfg(target_os = "linux")]
+    if std::env::args().nth(1).as_deref() == Some(mcp::sandbox::LAUNCHER_FLAG) {
+        mcp::sandbox::run_launcher();
+    }
 
-    // 初始化日志系统
-    env_logger::init();
+    // 模拟 MCP 服务器模式：用于测试和离线开发，stdio 模式下不能向标准输出写日志
+    if std::env::args().nth(1).as_deref() == Some(mcp::mock_server::MOCK_SERVER_FLAG) {
+        mcp::mock_server::run_from_args(std::env::args().skip(2).collect());
+        return;
+    }
+
+    // MCP 服务器模式：通过 stdio 向其他 MCP 客户端提供聊天记录和工具目录
+    if std::env::args().nth(1).as_deref() == Some(mcp::server::SERVER_FLAG) {
+        mcp::server::run_from_args(std::env::args().skip(2).collect());
+        return;
+    }
+
+    // MCP 网关模式：通过 stdio 统一提供所有已启用服务器的工具、资源和提示
+    if std::env::args().nth(1).as_deref() == Some(mcp::gateway::GATEWAY_FLAG) {
+        mcp::gateway::run_from_args(std::env::args().skip(2).collect());
+        return;
+    }
+
+    // 初始化日志系统，所有日志行在输出前统一脱敏
+    mcp::logging::init();
     info!("应用启动");
 
     tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
         .setup(|app| {
             // 初始化应用状态
-            app.manage(Arc::new(AppState::new()));
+            let data_dir = app.path().app_data_dir()?;
+            let state = Arc::new(AppState::with_data_dir(data_dir));
+            state
+                .placeholder_resolver
+                .set_app_handle(app.handle().clone());
+            state.oauth_manager.set_app_handle(app.handle().clone());
+            state
+                .elicitation_manager
+                .set_app_handle(app.handle().clone());
+            app.manage(state.clone());
+
+            // 后台并行连接已启用的 MCP 服务器，不阻塞窗口启动
+            tauri::async_runtime::spawn(async move {
+                state.auto_connect_servers().await;
+            });
             Ok(())
         })
         .plugin(tauri_plugin_fs::init())
@@ -44,13 +81,82 @@ fn main() {
             get_mcp_client_status,
             get_all_mcp_client_statuses,
             mcp_repair_client,
+            // MCP 服务器配置命令
+            list_mcp_servers,
+            save_mcp_server,
+            delete_mcp_server,
+            set_mcp_server_enabled,
+            connect_mcp_server,
+            import_mcp_servers,
+            export_mcp_servers,
+            // MCP 配置占位符与密钥命令
+            respond_mcp_config_input,
+            clear_mcp_config_inputs,
+            set_mcp_secret,
+            delete_mcp_secret,
+            list_mcp_secrets,
+            // MCP OAuth 命令
+            get_mcp_oauth_status,
+            logout_mcp_oauth,
+            // fishmind MCP 服务命令
+            start_fishmind_mcp_server,
+            stop_fishmind_mcp_server,
+            get_fishmind_mcp_server_status,
+            // MCP 网关命令
+            start_mcp_gateway,
+            stop_mcp_gateway,
+            get_mcp_gateway_status,
             // MCP 操作命令
             list_mcp_tools,
             call_mcp_tool,
+            call_mcp_tools_batch,
             list_mcp_resources,
             read_mcp_resource,
             list_mcp_prompts,
             get_mcp_prompt,
+            // MCP 会话录制命令
+            start_mcp_recording,
+            stop_mcp_recording,
+            // MCP 参数补全命令
+            complete_mcp_argument,
+            // MCP 协议检查器命令
+            start_mcp_inspector,
+            stop_mcp_inspector,
+            send_raw_mcp_request,
+            // MCP 限流命令
+            get_mcp_client_limits,
+            set_mcp_client_limits,
+            // MCP 熔断命令
+            reset_mcp_circuit_breaker,
+            // MCP 调用统计命令
+            get_mcp_metrics,
+            export_mcp_metrics,
+            // MCP 工具结果缓存命令
+            get_mcp_tool_cache,
+            set_mcp_tool_cache_config,
+            clear_mcp_tool_cache,
+            // MCP 资源限制命令
+            get_mcp_resource_limits,
+            set_mcp_resource_limits,
+            // MCP 工具审批命令
+            respond_mcp_tool_approval,
+            get_mcp_approval_policy,
+            set_mcp_approval_policy,
+            clear_mcp_approval_decisions,
+            // MCP 结构化输入命令
+            respond_mcp_elicitation,
+            list_mcp_elicitations,
+            // MCP 审计日志命令
+            query_mcp_audit_log,
+            export_mcp_audit_log,
+            get_mcp_audit_retention,
+            set_mcp_audit_retention,
+            // MCP 日志脱敏命令
+            get_mcp_redaction_config,
+            set_mcp_redaction_config,
+            // MCP 日志命令
+            get_mcp_logging_config,
+            set_mcp_log_level,
             // 添加其他命令
             run_sqlite_tests,
         ])
@@ -0,0 +1,275 @@
+use crate::mcp::types::*;
+use log::{debug, info, warn};
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::oneshot;
+
+/// 审批请求事件名
+pub const APPROVAL_REQUEST_EVENT: &str = "mcp-tool-approval-request";
+
+/// 发出审批请求的回调，失败时审批请求立即结束
+pub type ApprovalNotifier<'a> =
+    dyn Fn(&ApprovalRequestEvent) -> Result<(), String> + Send + Sync + 'a;
+
+/// 审批通过的结果
+#[derive(Debug, Clone)]
+pub struct ApprovalOutcome {
+    /// 审批人，策略自动放行时为 None
+    pub approved_by: Option<String>,
+}
+
+/// 工具调用审批管理器
+pub struct ApprovalManager {
+    policy: Mutex<ApprovalPolicy>,
+    pending: Mutex<HashMap<String, oneshot::Sender<ApprovalResponse>>>,
+    // 会话内记住的决定: (客户端ID, 工具名) -> 是否允许
+    remembered: Mutex<HashMap<(String, String), bool>>,
+    next_id: AtomicU64,
+}
+
+impl ApprovalManager {
+    /// 创建审批管理器，默认策略为总是允许
+    pub fn new() -> Self {
+        Self {
+            policy: Mutex::new(ApprovalPolicy::default()),
+            pending: Mutex::new(HashMap::new()),
+            remembered: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// 获取当前策略
+    pub fn get_policy(&self) -> ApprovalPolicy {
+        self.policy.lock().unwrap().clone()
+    }
+
+    /// 替换当前策略，会校验所有正则表达式
+    pub fn set_policy(&self, policy: ApprovalPolicy) -> Result<(), String> {
+        let modes = std::iter::once(&policy.default_mode).chain(
+            policy
+                .servers
+                .values()
+                .flat_map(|s| s.default_mode.iter().chain(s.tools.values())),
+        );
+        for mode in modes {
+            if let ApprovalMode::AllowIfMatch { arguments } = mode {
+                for pattern in arguments.values() {
+                    Regex::new(pattern)
+                        .map_err(|e| format!("Invalid argument pattern '{}': {}", pattern, e))?;
+                }
+            }
+        }
+
+        info!("[MCP] 更新工具审批策略");
+        *self.policy.lock().unwrap() = policy;
+        Ok(())
+    }
+
+    /// 清除会话内记住的决定
+    pub fn clear_remembered(&self) {
+        info!("[MCP] 清除会话内记住的审批决定");
+        self.remembered.lock().unwrap().clear();
+    }
+
+    /// 解析某个工具适用的审批模式
+    pub fn resolve_mode(&self, client_id: &str, tool_name: &str) -> ApprovalMode {
+        let policy = self.policy.lock().unwrap();
+        policy
+            .servers
+            .get(client_id)
+            .and_then(|server| server.tools.get(tool_name).or(server.default_mode.as_ref()))
+            .unwrap_or(&policy.default_mode)
+            .clone()
+    }
+
+    /// 检查工具调用是否允许执行
+    ///
+    /// 策略为询问时会发送审批请求事件并挂起，直到前端通过
+    /// `respond_mcp_tool_approval` 返回结果或等待超时。没有应用句柄（无界面）时
+    /// 无法询问用户，需要询问的调用直接失败。
+    pub async fn check(
+        &self,
+        app: Option<&AppHandle>,
+        client_id: &str,
+        tool_name: &str,
+        arguments: &serde_json::Value,
+    ) -> Result<ApprovalOutcome, String> {
+        let notify = |event: &ApprovalRequestEvent| -> Result<(), String> {
+            let app = app.ok_or_else(|| {
+                format!(
+                    "Tool '{}' requires approval, but no user interface is available",
+                    event.tool_name
+                )
+            })?;
+            app.emit(APPROVAL_REQUEST_EVENT, event)
+                .map_err(|e| format!("Failed to emit approval request: {}", e))
+        };
+        self.check_with(client_id, tool_name, arguments, &notify)
+            .await
+    }
+
+    /// 使用指定的通知方式检查工具调用是否允许执行
+    pub async fn check_with(
+        &self,
+        client_id: &str,
+        tool_name: &str,
+        arguments: &serde_json::Value,
+        notify: &ApprovalNotifier<'_>,
+    ) -> Result<ApprovalOutcome, String> {
+        let mode = self.resolve_mode(client_id, tool_name);
+        debug!(
+            "[MCP] 工具审批模式: {:?}, 客户端ID: {}, 工具: {}",
+            mode, client_id, tool_name
+        );
+
+        match mode {
+            ApprovalMode::AlwaysAllow => return Ok(ApprovalOutcome { approved_by: None }),
+            ApprovalMode::Deny => {
+                warn!(
+                    "[MCP] 审批策略拒绝工具调用, 客户端ID: {}, 工具: {}",
+                    client_id, tool_name
+                );
+                return Err(format!(
+                    "Tool '{}' is denied by the approval policy",
+                    tool_name
+                ));
+            }
+            ApprovalMode::AllowIfMatch {
+                arguments: patterns,
+            } => {
+                if arguments_match(&patterns, arguments) {
+                    return Ok(ApprovalOutcome { approved_by: None });
+                }
+                info!("[MCP] 工具参数不匹配审批规则，转为询问用户: {}", tool_name);
+            }
+            ApprovalMode::Ask => {}
+        }
+
+        let key = (client_id.to_string(), tool_name.to_string());
+        if let Some(allowed) = self.remembered.lock().unwrap().get(&key) {
+            info!(
+                "[MCP] 使用会话内记住的审批决定: {}, 工具: {}",
+                allowed, tool_name
+            );
+            return if *allowed {
+                Ok(ApprovalOutcome { approved_by: None })
+            } else {
+                Err(format!("Tool '{}' was rejected by the user", tool_name))
+            };
+        }
+
+        self.ask(client_id, tool_name, arguments, notify).await
+    }
+
+    /// 发送审批请求并等待用户响应
+    ///
+    /// 调用方的 future 被丢弃时，审批请求随之从等待列表中移除。
+    async fn ask(
+        &self,
+        client_id: &str,
+        tool_name: &str,
+        arguments: &serde_json::Value,
+        notify: &ApprovalNotifier<'_>,
+    ) -> Result<ApprovalOutcome, String> {
+        let request_id = format!("approval-{}", self.next_id.fetch_add(1, Ordering::SeqCst));
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(request_id.clone(), tx);
+        let _guard = PendingGuard {
+            manager: self,
+            request_id: &request_id,
+        };
+
+        let event = ApprovalRequestEvent {
+            request_id: request_id.clone(),
+            client_id: client_id.to_string(),
+            tool_name: tool_name.to_string(),
+            arguments: arguments.clone(),
+        };
+        info!(
+            "[MCP] 等待用户审批工具调用, 请求ID: {}, 工具: {}",
+            request_id, tool_name
+        );
+        notify(&event)?;
+
+        let timeout = Duration::from_secs(self.policy.lock().unwrap().ask_timeout_secs);
+        let response = match tokio::time::timeout(timeout, rx).await {
+            Ok(Ok(response)) => response,
+            Ok(Err(_)) => return Err(format!("Approval request '{}' was cancelled", request_id)),
+            Err(_) => {
+                warn!("[MCP] 工具审批等待超时, 请求ID: {}", request_id);
+                return Err(format!(
+                    "Approval request for tool '{}' timed out",
+                    tool_name
+                ));
+            }
+        };
+
+        if response.remember {
+            self.remembered.lock().unwrap().insert(
+                (client_id.to_string(), tool_name.to_string()),
+                response.approved,
+            );
+        }
+
+        if response.approved {
+            info!("[MCP] 用户批准工具调用: {}", tool_name);
+            Ok(ApprovalOutcome {
+                approved_by: response.user,
+            })
+        } else {
+            info!("[MCP] 用户拒绝工具调用: {}", tool_name);
+            Err(format!("Tool '{}' was rejected by the user", tool_name))
+        }
+    }
+
+    /// 处理前端返回的审批结果
+    pub fn respond(&self, response: ApprovalResponse) -> Result<(), String> {
+        let sender = self
+            .pending
+            .lock()
+            .unwrap()
+            .remove(&response.request_id)
+            .ok_or_else(|| {
+                format!(
+                    "Approval request '{}' not found or already handled",
+                    response.request_id
+                )
+            })?;
+
+        sender
+            .send(response)
+            .map_err(|r| format!("Approval request '{}' is no longer waiting", r.request_id))
+    }
+}
+
+// 审批请求结束（包括调用方取消）时从等待列表中移除
+struct PendingGuard<'a> {
+    manager: &'a ApprovalManager,
+    request_id: &'a str,
+}
+
+impl Drop for PendingGuard<'_> {
+    fn drop(&mut self) {
+        self.manager.pending.lock().unwrap().remove(self.request_id);
+    }
+}
+
+/// 检查参数是否满足所有正则规则
+///
+/// 缺失的参数视为不匹配，非字符串参数按其 JSON 文本匹配。
+fn arguments_match(patterns: &HashMap<String, String>, arguments: &serde_json::Value) -> bool {
+    patterns.iter().all(|(name, pattern)| {
+        let value = match arguments.get(name) {
+            Some(serde_json::Value::String(s)) => s.clone(),
+            Some(other) => other.to_string(),
+            None => return false,
+        };
+        Regex::new(&format!("^(?:{})$", pattern))
+            .map(|re| re.is_match(&value))
+            .unwrap_or(false)
+    })
+}
@@ -0,0 +1,232 @@
+#[cfg(test)]
+mod tests {
+    use crate::mcp::approval::ApprovalManager;
+    use crate::mcp::types::{
+        ApprovalMode, ApprovalPolicy, ApprovalRequestEvent, ApprovalResponse, ServerApprovalPolicy,
+    };
+    use serde_json::json;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+
+    // 创建带有单个服务器策略的审批策略
+    fn policy_with_server(server: ServerApprovalPolicy) -> ApprovalPolicy {
+        let mut policy = ApprovalPolicy::default();
+        policy.servers.insert("test-client".to_string(), server);
+        policy
+    }
+
+    // 测试工具级策略优先于服务器和全局策略
+    #[test]
+    fn test_resolve_mode_precedence() {
+        let manager = ApprovalManager::new();
+        let mut tools = HashMap::new();
+        tools.insert("write_file".to_string(), ApprovalMode::Deny);
+        manager
+            .set_policy(policy_with_server(ServerApprovalPolicy {
+                default_mode: Some(ApprovalMode::Ask),
+                tools,
+            }))
+            .unwrap();
+
+        assert_eq!(
+            manager.resolve_mode("test-client", "write_file"),
+            ApprovalMode::Deny
+        );
+        assert_eq!(
+            manager.resolve_mode("test-client", "read_file"),
+            ApprovalMode::Ask
+        );
+        assert_eq!(
+            manager.resolve_mode("other-client", "write_file"),
+            ApprovalMode::AlwaysAllow
+        );
+    }
+
+    // 测试非法正则表达式被拒绝
+    #[test]
+    fn test_set_policy_rejects_invalid_pattern() {
+        let manager = ApprovalManager::new();
+        let mut arguments = HashMap::new();
+        arguments.insert("path".to_string(), "([".to_string());
+        let policy = ApprovalPolicy {
+            default_mode: ApprovalMode::AllowIfMatch { arguments },
+            ..Default::default()
+        };
+
+        assert!(manager.set_policy(policy).is_err());
+    }
+
+    // 测试拒绝策略
+    #[tokio::test]
+    async fn test_deny_mode() {
+        let manager = ApprovalManager::new();
+        manager
+            .set_policy(ApprovalPolicy {
+                default_mode: ApprovalMode::Deny,
+                ..Default::default()
+            })
+            .unwrap();
+
+        let result = manager
+            .check(None, "test-client", "any_tool", &json!({}))
+            .await;
+        assert!(result.is_err());
+    }
+
+    // 测试参数匹配时直接放行
+    #[tokio::test]
+    async fn test_allow_if_match() {
+        let manager = ApprovalManager::new();
+        let mut arguments = HashMap::new();
+        arguments.insert("path".to_string(), "/tmp/.*".to_string());
+        manager
+            .set_policy(ApprovalPolicy {
+                default_mode: ApprovalMode::AllowIfMatch { arguments },
+                ask_timeout_secs: 0,
+                ..Default::default()
+            })
+            .unwrap();
+
+        let allowed = manager
+            .check(
+                None,
+                "test-client",
+                "read",
+                &json!({ "path": "/tmp/a.txt" }),
+            )
+            .await;
+        assert!(allowed.is_ok());
+
+        // 不匹配时转为询问，没有界面时直接失败
+        let asked = manager
+            .check(
+                None,
+                "test-client",
+                "read",
+                &json!({ "path": "/etc/passwd" }),
+            )
+            .await;
+        assert!(asked.is_err());
+    }
+
+    // 测试询问模式等待用户响应并记住决定
+    #[tokio::test]
+    async fn test_ask_and_remember() {
+        let manager = Arc::new(ApprovalManager::new());
+        manager
+            .set_policy(ApprovalPolicy {
+                default_mode: ApprovalMode::Ask,
+                ..Default::default()
+            })
+            .unwrap();
+
+        let (tx, mut requests) = mpsc::unbounded_channel();
+        let waiting = manager.clone();
+        let handle = tokio::spawn(async move {
+            let arguments = json!({ "query": "select 1" });
+            let notify = move |event: &ApprovalRequestEvent| {
+                tx.send(event.request_id.clone()).map_err(|e| e.to_string())
+            };
+            waiting
+                .check_with("test-client", "query", &arguments, &notify)
+                .await
+        });
+
+        let request_id = requests.recv().await.unwrap();
+        manager
+            .respond(ApprovalResponse {
+                request_id,
+                approved: true,
+                remember: true,
+                user: Some("alice".to_string()),
+            })
+            .unwrap();
+
+        let outcome = handle.await.unwrap().unwrap();
+        assert_eq!(outcome.approved_by.as_deref(), Some("alice"));
+
+        // 记住的决定不会再次询问
+        let again = manager
+            .check(None, "test-client", "query", &json!({}))
+            .await;
+        assert!(again.is_ok());
+    }
+
+    // 测试没有界面时需要询问的调用立即失败
+    #[tokio::test]
+    async fn test_ask_without_ui() {
+        let manager = ApprovalManager::new();
+        manager
+            .set_policy(ApprovalPolicy {
+                default_mode: ApprovalMode::Ask,
+                ..Default::default()
+            })
+            .unwrap();
+
+        let result = tokio::time::timeout(
+            Duration::from_secs(1),
+            manager.check(None, "test-client", "query", &json!({})),
+        )
+        .await
+        .expect("check should not wait without a UI");
+        assert!(result.unwrap_err().contains("no user interface"));
+        assert!(manager
+            .respond(ApprovalResponse {
+                request_id: "approval-1".to_string(),
+                approved: true,
+                remember: false,
+                user: None,
+            })
+            .is_err());
+    }
+
+    // 测试调用方取消后审批请求从等待列表中移除
+    #[tokio::test]
+    async fn test_cancelled_ask_removed() {
+        let manager = Arc::new(ApprovalManager::new());
+        manager
+            .set_policy(ApprovalPolicy {
+                default_mode: ApprovalMode::Ask,
+                ..Default::default()
+            })
+            .unwrap();
+
+        let (tx, mut requests) = mpsc::unbounded_channel();
+        let waiting = manager.clone();
+        let handle = tokio::spawn(async move {
+            let notify = move |event: &ApprovalRequestEvent| {
+                tx.send(event.request_id.clone()).map_err(|e| e.to_string())
+            };
+            waiting
+                .check_with("test-client", "query", &json!({}), &notify)
+                .await
+        });
+
+        let request_id = requests.recv().await.unwrap();
+        handle.abort();
+        let _ = handle.await;
+
+        let result = manager.respond(ApprovalResponse {
+            request_id,
+            approved: true,
+            remember: false,
+            user: None,
+        });
+        assert!(result.unwrap_err().contains("not found"));
+    }
+
+    // 测试响应不存在的审批请求
+    #[test]
+    fn test_respond_unknown_request() {
+        let manager = ApprovalManager::new();
+        let result = manager.respond(ApprovalResponse {
+            request_id: "approval-404".to_string(),
+            approved: true,
+            remember: false,
+            user: None,
+        });
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,276 @@
+use crate::mcp::{redact, types::*};
+use chrono::{Duration as ChronoDuration, Utc};
+use log::{error, info};
+use ring::digest;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Instant;
+
+// 每追加多少条记录检查一次保留策略
+const RETENTION_CHECK_INTERVAL: u64 = 500;
+
+impl AuditEntry {
+    /// 创建审计记录，耗时从 `started` 开始计算
+    pub fn new(client_id: &str, operation: &str, started: Instant) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            client_id: client_id.to_string(),
+            server_name: None,
+            server_version: None,
+            operation: operation.to_string(),
+            target: None,
+            arguments_hash: None,
+            arguments: None,
+            duration_ms: started.elapsed().as_millis() as u64,
+            outcome: AuditOutcome::Success,
+            error: None,
+            approved_by: None,
+        }
+    }
+
+    /// 设置操作对象
+    pub fn with_target(mut self, target: &str) -> Self {
+        self.target = Some(target.to_string());
+        self
+    }
+
+    /// 记录脱敏后的参数及其摘要
+    ///
+    /// 摘要基于脱敏后的参数计算，避免通过摘要枚举出被隐藏的取值。
+    pub fn with_arguments(mut self, arguments: &serde_json::Value) -> Self {
+        let redacted = redact::redact_value(arguments);
+        self.arguments_hash = Some(hash_arguments(&redacted));
+        self.arguments = Some(redacted);
+        self
+    }
+
+    /// 设置服务器信息
+    pub fn with_server(mut self, server_info: Option<&ServerInfo>) -> Self {
+        if let Some(info) = server_info {
+            self.server_name = Some(info.name.clone());
+            self.server_version = Some(info.version.clone());
+        }
+        self
+    }
+
+    /// 设置审批人
+    pub fn with_approver(mut self, approved_by: Option<String>) -> Self {
+        self.approved_by = approved_by;
+        self
+    }
+
+    /// 根据操作结果设置结果和错误
+    pub fn with_result<T>(self, result: &Result<T, String>) -> Self {
+        match result {
+            Ok(_) => self,
+            Err(e) => self.with_outcome(AuditOutcome::Error, Some(e)),
+        }
+    }
+
+    /// 根据 MCP 响应设置结果和错误
+    pub fn with_response<T>(self, result: &Result<McpResponse<T>, String>) -> Self {
+        match result {
+            Ok(response) if response.success => self,
+            Ok(response) => self.with_outcome(AuditOutcome::Error, response.error.as_ref()),
+            Err(e) => self.with_outcome(AuditOutcome::Error, Some(e)),
+        }
+    }
+
+    /// 设置结果和错误，错误信息会被脱敏
+    pub fn with_outcome(mut self, outcome: AuditOutcome, error: Option<&String>) -> Self {
+        self.outcome = outcome;
+        self.error = error.map(|e| redact::redact_text(e));
+        self
+    }
+
+    fn matches(&self, query: &AuditQuery) -> bool {
+        query
+            .client_id
+            .as_ref()
+            .is_none_or(|c| &self.client_id == c)
+            && query
+                .operation
+                .as_ref()
+                .is_none_or(|o| &self.operation == o)
+            && query
+                .target
+                .as_ref()
+                .is_none_or(|t| self.target.as_ref() == Some(t))
+            && query.outcome.as_ref().is_none_or(|o| &self.outcome == o)
+            && query.since.is_none_or(|since| self.timestamp >= since)
+            && query.until.is_none_or(|until| self.timestamp <= until)
+    }
+}
+
+/// 计算参数的 SHA-256 摘要
+pub fn hash_arguments(arguments: &serde_json::Value) -> String {
+    let canonical = serde_json::to_string(arguments).unwrap_or_default();
+    digest::digest(&digest::SHA256, canonical.as_bytes())
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// 追加写入的 JSONL 审计日志
+pub struct AuditLog {
+    path: PathBuf,
+    state: Mutex<AuditLogState>,
+}
+
+struct AuditLogState {
+    retention: AuditRetention,
+    appended: u64,
+}
+
+impl AuditLog {
+    /// 打开审计日志文件，不存在时创建，并立即应用保留策略
+    pub fn open(path: PathBuf) -> Result<Self, String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create audit log directory: {}", e))?;
+        }
+        info!("[MCP] 打开审计日志: {}", path.display());
+
+        let log = Self {
+            path,
+            state: Mutex::new(AuditLogState {
+                retention: AuditRetention::default(),
+                appended: 0,
+            }),
+        };
+        log.enforce_retention()?;
+        Ok(log)
+    }
+
+    /// 追加一条审计记录
+    pub fn append(&self, entry: &AuditEntry) -> Result<(), String> {
+        let line = serde_json::to_string(entry)
+            .map_err(|e| format!("Failed to serialize audit entry: {}", e))?;
+
+        let mut state = self.state.lock().unwrap();
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("Failed to open audit log: {}", e))?;
+        writeln!(file, "{}", line).map_err(|e| format!("Failed to write audit log: {}", e))?;
+
+        state.appended += 1;
+        let check_retention = state.appended.is_multiple_of(RETENTION_CHECK_INTERVAL);
+        drop(state);
+
+        if check_retention {
+            self.enforce_retention()?;
+        }
+        Ok(())
+    }
+
+    /// 按条件查询审计记录，结果按时间倒序
+    pub fn query(&self, query: &AuditQuery) -> Result<Vec<AuditEntry>, String> {
+        let mut entries: Vec<AuditEntry> = self
+            .read_entries()?
+            .into_iter()
+            .filter(|e| e.matches(query))
+            .collect();
+        entries.reverse();
+
+        Ok(entries
+            .into_iter()
+            .skip(query.offset.unwrap_or(0))
+            .take(query.limit.unwrap_or(usize::MAX))
+            .collect())
+    }
+
+    /// 按条件导出审计记录到 JSONL 文件，返回导出条数
+    pub fn export(&self, query: &AuditQuery, path: &Path) -> Result<usize, String> {
+        let mut entries = self.query(query)?;
+        entries.reverse();
+
+        let mut file =
+            File::create(path).map_err(|e| format!("Failed to create export file: {}", e))?;
+        for entry in &entries {
+            let line = serde_json::to_string(entry)
+                .map_err(|e| format!("Failed to serialize audit entry: {}", e))?;
+            writeln!(file, "{}", line)
+                .map_err(|e| format!("Failed to write export file: {}", e))?;
+        }
+
+        info!(
+            "[MCP] 导出审计日志 {} 条到 {}",
+            entries.len(),
+            path.display()
+        );
+        Ok(entries.len())
+    }
+
+    /// 获取保留策略
+    pub fn retention(&self) -> AuditRetention {
+        self.state.lock().unwrap().retention.clone()
+    }
+
+    /// 设置保留策略并立即执行清理
+    pub fn set_retention(&self, retention: AuditRetention) -> Result<(), String> {
+        self.state.lock().unwrap().retention = retention;
+        self.enforce_retention()
+    }
+
+    /// 删除超出保留策略的旧记录
+    fn enforce_retention(&self) -> Result<(), String> {
+        let state = self.state.lock().unwrap();
+        let entries = self.read_entries()?;
+        // 保留天数过大时日期会溢出，此时不按时间清理
+        let cutoff = ChronoDuration::try_days(state.retention.max_age_days as i64)
+            .and_then(|max_age| Utc::now().checked_sub_signed(max_age));
+
+        let kept: Vec<&AuditEntry> = entries
+            .iter()
+            .filter(|e| cutoff.is_none_or(|cutoff| e.timestamp >= cutoff))
+            .collect();
+        let skip = kept.len().saturating_sub(state.retention.max_entries);
+        if skip == 0 && kept.len() == entries.len() {
+            return Ok(());
+        }
+
+        // 先写入临时文件再替换，避免清理过程中丢失记录
+        let tmp_path = self.path.with_extension("jsonl.tmp");
+        let mut file = File::create(&tmp_path)
+            .map_err(|e| format!("Failed to create audit log temp file: {}", e))?;
+        for entry in &kept[skip..] {
+            let line = serde_json::to_string(entry)
+                .map_err(|e| format!("Failed to serialize audit entry: {}", e))?;
+            writeln!(file, "{}", line).map_err(|e| format!("Failed to write audit log: {}", e))?;
+        }
+        fs::rename(&tmp_path, &self.path)
+            .map_err(|e| format!("Failed to replace audit log: {}", e))?;
+
+        info!(
+            "[MCP] 审计日志清理完成, 删除 {} 条记录",
+            entries.len() - (kept.len() - skip)
+        );
+        Ok(())
+    }
+
+    fn read_entries(&self) -> Result<Vec<AuditEntry>, String> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(format!("Failed to open audit log: {}", e)),
+        };
+
+        let mut entries = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = line.map_err(|e| format!("Failed to read audit log: {}", e))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(&line) {
+                Ok(entry) => entries.push(entry),
+                Err(e) => error!("[MCP] 跳过无法解析的审计记录: {}", e),
+            }
+        }
+        Ok(entries)
+    }
+}
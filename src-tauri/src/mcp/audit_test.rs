@@ -0,0 +1,161 @@
+#[cfg(test)]
+mod tests {
+    use crate::mcp::audit::{hash_arguments, AuditLog};
+    use crate::mcp::types::{AuditEntry, AuditOutcome, AuditQuery, AuditRetention};
+    use serde_json::json;
+    use std::path::PathBuf;
+    use std::time::Instant;
+
+    // 为每个测试创建独立的日志文件路径
+    fn temp_log_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "fishmind-audit-test-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir.join("mcp_audit.jsonl")
+    }
+
+    // 测试追加和按条件查询
+    #[test]
+    fn test_append_and_query() {
+        let log = AuditLog::open(temp_log_path("query")).unwrap();
+
+        log.append(
+            &AuditEntry::new("client-a", "call_tool", Instant::now())
+                .with_target("search")
+                .with_arguments(&json!({ "q": "rust" })),
+        )
+        .unwrap();
+        log.append(
+            &AuditEntry::new("client-b", "call_tool", Instant::now())
+                .with_target("write")
+                .with_outcome(AuditOutcome::Denied, Some(&"denied".to_string())),
+        )
+        .unwrap();
+        log.append(&AuditEntry::new("client-a", "list_tools", Instant::now()))
+            .unwrap();
+
+        let all = log.query(&AuditQuery::default()).unwrap();
+        assert_eq!(all.len(), 3);
+        // 结果按时间倒序
+        assert_eq!(all[0].operation, "list_tools");
+
+        let client_a = log
+            .query(&AuditQuery {
+                client_id: Some("client-a".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(client_a.len(), 2);
+
+        let denied = log
+            .query(&AuditQuery {
+                outcome: Some(AuditOutcome::Denied),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(denied.len(), 1);
+        assert_eq!(denied[0].target.as_deref(), Some("write"));
+
+        let limited = log
+            .query(&AuditQuery {
+                limit: Some(1),
+                offset: Some(1),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(limited.len(), 1);
+        assert_eq!(limited[0].client_id, "client-b");
+    }
+
+    // 测试参数摘要与键顺序无关
+    #[test]
+    fn test_hash_arguments() {
+        let a = hash_arguments(&json!({ "a": 1, "b": 2 }));
+        let b = hash_arguments(&json!({ "b": 2, "a": 1 }));
+        assert_eq!(a, b);
+        assert_eq!(a.len(), 64);
+    }
+
+    // 测试参数摘要基于脱敏后的参数，敏感值不同时摘要相同
+    #[test]
+    fn test_hash_redacted_arguments() {
+        let entry = |password: &str| {
+            AuditEntry::new("client-a", "call_tool", Instant::now())
+                .with_arguments(&json!({ "user": "alice", "password": password }))
+        };
+        let a = entry("hunter2");
+        let b = entry("correct horse");
+        assert_eq!(a.arguments_hash, b.arguments_hash);
+        assert_eq!(
+            a.arguments_hash,
+            Some(hash_arguments(a.arguments.as_ref().unwrap()))
+        );
+        assert_ne!(
+            a.arguments_hash,
+            Some(hash_arguments(
+                &json!({ "user": "alice", "password": "hunter2" })
+            ))
+        );
+    }
+
+    // 测试保留策略按条数清理旧记录
+    #[test]
+    fn test_retention_max_entries() {
+        let log = AuditLog::open(temp_log_path("retention")).unwrap();
+        for i in 0..5 {
+            log.append(
+                &AuditEntry::new("client", "call_tool", Instant::now())
+                    .with_target(&format!("tool-{}", i)),
+            )
+            .unwrap();
+        }
+
+        log.set_retention(AuditRetention {
+            max_entries: 2,
+            max_age_days: 90,
+        })
+        .unwrap();
+
+        let entries = log.query(&AuditQuery::default()).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].target.as_deref(), Some("tool-4"));
+        assert_eq!(entries[1].target.as_deref(), Some("tool-3"));
+    }
+
+    // 测试保留天数过大时不会因日期溢出而失败，日志仍然可用
+    #[test]
+    fn test_retention_max_age_overflow() {
+        let log = AuditLog::open(temp_log_path("retention-overflow")).unwrap();
+        log.append(&AuditEntry::new("client", "call_tool", Instant::now()))
+            .unwrap();
+
+        log.set_retention(AuditRetention {
+            max_entries: 10,
+            max_age_days: u32::MAX,
+        })
+        .unwrap();
+
+        log.append(&AuditEntry::new("client", "list_tools", Instant::now()))
+            .unwrap();
+        assert_eq!(log.query(&AuditQuery::default()).unwrap().len(), 2);
+    }
+
+    // 测试导出
+    #[test]
+    fn test_export() {
+        let path = temp_log_path("export");
+        let log = AuditLog::open(path.clone()).unwrap();
+        log.append(&AuditEntry::new("client", "initialize", Instant::now()))
+            .unwrap();
+
+        let export_path = path.with_file_name("export.jsonl");
+        let count = log.export(&AuditQuery::default(), &export_path).unwrap();
+        assert_eq!(count, 1);
+
+        let content = std::fs::read_to_string(export_path).unwrap();
+        assert_eq!(content.lines().count(), 1);
+    }
+}
@@ -0,0 +1,163 @@
+use crate::mcp::client::AppState;
+use crate::mcp::types::*;
+use log::{error, info};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// 批量调用中单个调用完成的事件名
+pub const TOOL_BATCH_EVENT: &str = "mcp-tool-batch-result";
+
+/// 每个客户端同时执行的默认最大调用数
+pub const DEFAULT_MAX_CONCURRENT_PER_CLIENT: usize = 4;
+
+/// 单个批次的最大调用数
+pub const MAX_BATCH_SIZE: usize = 64;
+
+static NEXT_BATCH_ID: AtomicU64 = AtomicU64::new(1);
+
+/// 批量调用工具，每个调用完成时发送 [`TOOL_BATCH_EVENT`] 事件
+pub async fn call_tools(
+    state: Arc<AppState>,
+    app: Option<AppHandle>,
+    request: ToolBatchRequest,
+) -> Result<ToolBatchResult, String> {
+    let emitter = app.clone();
+    call_tools_with(state, app, request, move |event| {
+        if let Some(app) = &emitter {
+            if let Err(e) = app.emit(TOOL_BATCH_EVENT, event) {
+                error!("[MCP] 发送批量调用事件失败: {}", e);
+            }
+        }
+    })
+    .await
+}
+
+/// 批量调用工具，每个调用完成时调用 `on_result`
+///
+/// 不同客户端的调用并行执行，同一客户端同时执行的调用数不超过上限（客户端自身的限流仍然生效）。
+/// 每个调用单独审批，单个调用失败不影响其他调用。
+pub async fn call_tools_with(
+    state: Arc<AppState>,
+    app: Option<AppHandle>,
+    request: ToolBatchRequest,
+    mut on_result: impl FnMut(&ToolBatchEvent),
+) -> Result<ToolBatchResult, String> {
+    if request.calls.len() > MAX_BATCH_SIZE {
+        return Err(format!(
+            "Batch contains {} calls, the maximum is {}",
+            request.calls.len(),
+            MAX_BATCH_SIZE
+        ));
+    }
+
+    let batch_id = request
+        .batch_id
+        .unwrap_or_else(|| format!("batch-{}", NEXT_BATCH_ID.fetch_add(1, Ordering::SeqCst)));
+    let max_concurrent = request
+        .max_concurrent_per_client
+        .unwrap_or(DEFAULT_MAX_CONCURRENT_PER_CLIENT)
+        .max(1);
+    let total = request.calls.len();
+    info!(
+        "[MCP] 开始批量调用工具, 批次ID: {}, 调用数: {}",
+        batch_id, total
+    );
+
+    let started = Instant::now();
+    let mut semaphores: HashMap<String, Arc<Semaphore>> = HashMap::new();
+    let mut results: Vec<Option<ToolBatchItem>> = vec![None; total];
+    // 任务ID -> 调用，任务异常退出时仍然需要返回该调用的结果
+    let mut spawned = HashMap::new();
+    let mut tasks = JoinSet::new();
+    for (index, call) in request.calls.into_iter().enumerate() {
+        let semaphore = semaphores
+            .entry(call.client_id.clone())
+            .or_insert_with(|| Arc::new(Semaphore::new(max_concurrent)))
+            .clone();
+
+        let state = state.clone();
+        let app = app.clone();
+        let spawned_call = call.clone();
+        let handle = tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            let call_started = Instant::now();
+            let result = state.call_tool_checked(app.as_ref(), call.clone()).await;
+            item(
+                index,
+                &call,
+                result,
+                call_started.duration_since(started).as_millis() as u64,
+                call_started.elapsed().as_millis() as u64,
+            )
+        });
+        spawned.insert(handle.id(), (index, spawned_call));
+    }
+
+    let mut completed = 0;
+    while let Some(joined) = tasks.join_next_with_id().await {
+        let result = match joined {
+            Ok((_, result)) => result,
+            Err(e) => {
+                error!("[MCP] 批量调用任务异常退出: {}", e);
+                let (index, call) = &spawned[&e.id()];
+                item(
+                    *index,
+                    call,
+                    Err(format!("Tool call task failed: {}", e)),
+                    0,
+                    0,
+                )
+            }
+        };
+        completed += 1;
+        on_result(&ToolBatchEvent {
+            batch_id: batch_id.clone(),
+            completed,
+            total,
+            result: result.clone(),
+        });
+        let index = result.index;
+        results[index] = Some(result);
+    }
+
+    let results: Vec<ToolBatchItem> = results.into_iter().flatten().collect();
+    info!(
+        "[MCP] 批量调用完成, 批次ID: {}, 成功: {}/{}",
+        batch_id,
+        results.iter().filter(|result| result.success).count(),
+        total
+    );
+    Ok(ToolBatchResult {
+        batch_id,
+        results,
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+fn item(
+    index: usize,
+    call: &ToolCallRequest,
+    result: Result<McpResponse<serde_json::Value>, String>,
+    started_ms: u64,
+    duration_ms: u64,
+) -> ToolBatchItem {
+    let (success, data, error) = match result {
+        Ok(response) => (response.success, response.data, response.error),
+        Err(e) => (false, None, Some(e)),
+    };
+    ToolBatchItem {
+        index,
+        client_id: call.client_id.clone(),
+        tool_name: call.tool_name.clone(),
+        success,
+        data,
+        error,
+        started_ms,
+        duration_ms,
+    }
+}
@@ -0,0 +1,95 @@
+#[cfg(test)]
+mod tests {
+    use crate::mcp::batch::{call_tools_with, MAX_BATCH_SIZE};
+    use crate::mcp::client::AppState;
+    use crate::mcp::mock_server::{connected_state, MockMcpServer};
+    use crate::mcp::types::{ToolBatchRequest, ToolCallRequest};
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+
+    fn call(client_id: &str, tool_name: &str, params: Value) -> ToolCallRequest {
+        ToolCallRequest {
+            client_id: client_id.to_string(),
+            tool_name: tool_name.to_string(),
+            params,
+        }
+    }
+
+    // 测试跨客户端并行执行、按顺序返回结果和单个调用失败
+    #[tokio::test]
+    async fn test_batch_results() {
+        let state =
+            connected_state(&[("a", MockMcpServer::new()), ("b", MockMcpServer::new())]).await;
+        let request = ToolBatchRequest {
+            batch_id: Some("turn-1".to_string()),
+            calls: vec![
+                call("a", "slow", json!({ "ms": 200 })),
+                call("a", "echo", json!({ "text": "hi" })),
+                call("b", "slow", json!({ "ms": 200 })),
+                call("a", "missing", json!({})),
+                call("offline", "echo", json!({ "text": "hi" })),
+            ],
+            max_concurrent_per_client: Some(1),
+        };
+
+        let mut events = Vec::new();
+        let result = call_tools_with(state, None, request, |event| events.push(event.clone()))
+            .await
+            .unwrap();
+
+        assert_eq!(result.batch_id, "turn-1");
+        let indexes: Vec<usize> = result.results.iter().map(|r| r.index).collect();
+        assert_eq!(indexes, vec![0, 1, 2, 3, 4]);
+
+        let results = &result.results;
+        assert!(results[0].success && results[1].success && results[2].success);
+        assert_eq!(
+            results[1].data.as_ref().unwrap()["content"][0]["text"],
+            "hi"
+        );
+        assert!(!results[3].success);
+        assert!(results[3].error.is_some());
+        assert!(!results[4].success);
+        assert_eq!(results[4].client_id, "offline");
+
+        // 同一客户端的调用依次执行，不同客户端的调用并行执行
+        assert!(results[0].duration_ms >= 200);
+        let mut on_a: Vec<_> = results.iter().filter(|r| r.client_id == "a").collect();
+        on_a.sort_by_key(|r| r.started_ms);
+        for pair in on_a.windows(2) {
+            assert!(pair[1].started_ms >= pair[0].started_ms + pair[0].duration_ms);
+        }
+        assert!(results[2].started_ms < 150);
+        assert!(result.duration_ms < 600);
+
+        assert_eq!(events.len(), 5);
+        assert!(events
+            .iter()
+            .all(|event| event.batch_id == "turn-1" && event.total == 5));
+        let completed: Vec<usize> = events.iter().map(|event| event.completed).collect();
+        assert_eq!(completed, vec![1, 2, 3, 4, 5]);
+    }
+
+    // 测试批次大小上限和空批次
+    #[tokio::test]
+    async fn test_batch_limits() {
+        let state = Arc::new(AppState::new());
+        let request = ToolBatchRequest {
+            batch_id: None,
+            calls: vec![call("a", "echo", json!({})); MAX_BATCH_SIZE + 1],
+            max_concurrent_per_client: None,
+        };
+        assert!(call_tools_with(state.clone(), None, request, |_| {})
+            .await
+            .is_err());
+
+        let empty = ToolBatchRequest {
+            batch_id: None,
+            calls: Vec::new(),
+            max_concurrent_per_client: None,
+        };
+        let result = call_tools_with(state, None, empty, |_| {}).await.unwrap();
+        assert!(result.batch_id.starts_with("batch-"));
+        assert!(result.results.is_empty());
+    }
+}
@@ -0,0 +1,174 @@
+use crate::mcp::types::{BreakerState, BreakerStatus, CircuitBreakerConfig};
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 熔断错误前缀，熔断期间的调用会立即以此错误失败
+pub const CIRCUIT_OPEN_ERROR: &str = "CIRCUIT_OPEN";
+
+struct BreakerInner {
+    state: BreakerState,
+    consecutive_failures: u32,
+    opened_at: Option<(Instant, DateTime<Utc>)>,
+    // 半开状态下是否已有探测调用在进行
+    probe_in_flight: bool,
+    last_error: Option<String>,
+}
+
+/// 客户端熔断器
+///
+/// 连续失败达到阈值后熔断，冷却结束后进入半开状态并只放行一次探测调用，
+/// 探测成功则恢复，失败则重新熔断。
+pub struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    inner: Mutex<BreakerInner>,
+}
+
+impl CircuitBreaker {
+    /// 根据配置创建熔断器
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            inner: Mutex::new(BreakerInner {
+                state: BreakerState::Closed,
+                consecutive_failures: 0,
+                opened_at: None,
+                probe_in_flight: false,
+                last_error: None,
+            }),
+        }
+    }
+
+    /// 检查是否允许发起调用，熔断期间返回错误
+    ///
+    /// 调用结束后通过返回的许可记录结果，半开状态下的探测调用被取消时按失败处理，
+    /// 避免熔断器一直停留在半开状态。
+    pub fn check(&self, client_id: &str) -> Result<BreakerPermit<'_>, String> {
+        let mut inner = self.inner.lock().unwrap();
+        let cooldown = Duration::from_secs(self.config.cooldown_secs);
+
+        let probe = match inner.state {
+            BreakerState::Closed => false,
+            BreakerState::Open => {
+                let elapsed = inner
+                    .opened_at
+                    .map(|(opened, _)| opened.elapsed())
+                    .unwrap_or(cooldown);
+                if elapsed >= cooldown {
+                    info!("[MCP] 熔断器进入半开状态, 客户端ID: {}", client_id);
+                    inner.state = BreakerState::HalfOpen;
+                    inner.probe_in_flight = true;
+                    true
+                } else {
+                    return Err(open_error(client_id, cooldown - elapsed));
+                }
+            }
+            BreakerState::HalfOpen if !inner.probe_in_flight => {
+                inner.probe_in_flight = true;
+                true
+            }
+            BreakerState::HalfOpen => return Err(open_error(client_id, Duration::ZERO)),
+        };
+        Ok(BreakerPermit {
+            breaker: self,
+            client_id: client_id.to_string(),
+            probe,
+            recorded: false,
+        })
+    }
+
+    /// 记录调用成功
+    pub fn record_success(&self, client_id: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.state != BreakerState::Closed {
+            info!("[MCP] 熔断器恢复, 客户端ID: {}", client_id);
+        }
+        inner.state = BreakerState::Closed;
+        inner.consecutive_failures = 0;
+        inner.opened_at = None;
+        inner.probe_in_flight = false;
+    }
+
+    /// 记录调用失败或超时
+    pub fn record_failure(&self, client_id: &str, error: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.consecutive_failures += 1;
+        inner.last_error = Some(error.to_string());
+        inner.probe_in_flight = false;
+
+        let should_open = inner.state == BreakerState::HalfOpen
+            || inner.consecutive_failures >= self.config.failure_threshold.max(1);
+        if should_open {
+            warn!(
+                "[MCP] 熔断器打开, 客户端ID: {}, 连续失败次数: {}",
+                client_id, inner.consecutive_failures
+            );
+            inner.state = BreakerState::Open;
+            inner.opened_at = Some((Instant::now(), Utc::now()));
+        }
+    }
+
+    /// 手动重置熔断器
+    pub fn reset(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.state = BreakerState::Closed;
+        inner.consecutive_failures = 0;
+        inner.opened_at = None;
+        inner.probe_in_flight = false;
+    }
+
+    /// 当前状态快照
+    pub fn status(&self) -> BreakerStatus {
+        let inner = self.inner.lock().unwrap();
+        BreakerStatus {
+            state: inner.state,
+            consecutive_failures: inner.consecutive_failures,
+            opened_at: inner.opened_at.map(|(_, at)| at),
+            last_error: inner.last_error.clone(),
+        }
+    }
+}
+
+/// 熔断器放行的一次调用
+///
+/// 探测调用在记录结果之前被丢弃（例如调用方的 future 被取消）时记为失败并重新熔断。
+pub struct BreakerPermit<'a> {
+    breaker: &'a CircuitBreaker,
+    client_id: String,
+    probe: bool,
+    recorded: bool,
+}
+
+impl BreakerPermit<'_> {
+    /// 记录调用成功
+    pub fn record_success(mut self) {
+        self.recorded = true;
+        self.breaker.record_success(&self.client_id);
+    }
+
+    /// 记录调用失败或超时
+    pub fn record_failure(mut self, error: &str) {
+        self.recorded = true;
+        self.breaker.record_failure(&self.client_id, error);
+    }
+}
+
+impl Drop for BreakerPermit<'_> {
+    fn drop(&mut self) {
+        if self.probe && !self.recorded {
+            warn!("[MCP] 探测调用被取消, 客户端ID: {}", self.client_id);
+            self.breaker
+                .record_failure(&self.client_id, "Probe call was cancelled");
+        }
+    }
+}
+
+fn open_error(client_id: &str, retry_after: Duration) -> String {
+    format!(
+        "{}: server '{}' is failing and calls are suspended, retry after {} s",
+        CIRCUIT_OPEN_ERROR,
+        client_id,
+        retry_after.as_secs().max(1)
+    )
+}
@@ -0,0 +1,102 @@
+#[cfg(test)]
+mod tests {
+    use crate::mcp::breaker::{CircuitBreaker, CIRCUIT_OPEN_ERROR};
+    use crate::mcp::types::{BreakerState, CircuitBreakerConfig};
+
+    fn breaker(failure_threshold: u32, cooldown_secs: u64) -> CircuitBreaker {
+        CircuitBreaker::new(CircuitBreakerConfig {
+            failure_threshold,
+            cooldown_secs,
+        })
+    }
+
+    // 测试连续失败达到阈值后熔断
+    #[test]
+    fn test_opens_after_consecutive_failures() {
+        let breaker = breaker(3, 60);
+
+        breaker.record_failure("test-client", "timeout");
+        breaker.record_failure("test-client", "timeout");
+        assert!(breaker.check("test-client").is_ok());
+        assert_eq!(breaker.status().state, BreakerState::Closed);
+
+        breaker.record_failure("test-client", "timeout");
+        let status = breaker.status();
+        assert_eq!(status.state, BreakerState::Open);
+        assert_eq!(status.consecutive_failures, 3);
+        assert!(status.opened_at.is_some());
+        assert_eq!(status.last_error.as_deref(), Some("timeout"));
+
+        let err = breaker.check("test-client").err().unwrap();
+        assert!(err.starts_with(CIRCUIT_OPEN_ERROR));
+    }
+
+    // 测试成功调用会清零连续失败次数
+    #[test]
+    fn test_success_resets_failures() {
+        let breaker = breaker(2, 60);
+
+        breaker.record_failure("test-client", "error");
+        breaker.record_success("test-client");
+        breaker.record_failure("test-client", "error");
+
+        assert_eq!(breaker.status().state, BreakerState::Closed);
+        assert_eq!(breaker.status().consecutive_failures, 1);
+    }
+
+    // 测试冷却后半开状态只放行一次探测调用
+    #[test]
+    fn test_half_open_probe() {
+        let breaker = breaker(1, 0);
+
+        breaker.record_failure("test-client", "error");
+        assert_eq!(breaker.status().state, BreakerState::Open);
+
+        let probe = breaker.check("test-client").unwrap();
+        assert_eq!(breaker.status().state, BreakerState::HalfOpen);
+        assert!(breaker.check("test-client").is_err());
+
+        // 探测失败后重新熔断
+        probe.record_failure("error");
+        assert_eq!(breaker.status().state, BreakerState::Open);
+
+        // 探测成功后恢复
+        breaker.check("test-client").unwrap().record_success();
+        assert_eq!(breaker.status().state, BreakerState::Closed);
+        assert!(breaker.check("test-client").is_ok());
+    }
+
+    // 测试探测调用被取消时重新熔断，之后仍可以再次探测
+    #[test]
+    fn test_dropped_probe() {
+        let breaker = breaker(1, 0);
+
+        breaker.record_failure("test-client", "error");
+        drop(breaker.check("test-client").unwrap());
+        let status = breaker.status();
+        assert_eq!(status.state, BreakerState::Open);
+        assert_eq!(
+            status.last_error.as_deref(),
+            Some("Probe call was cancelled")
+        );
+
+        // 熔断关闭时丢弃许可不影响状态
+        breaker.check("test-client").unwrap().record_success();
+        drop(breaker.check("test-client").unwrap());
+        assert_eq!(breaker.status().state, BreakerState::Closed);
+        assert_eq!(breaker.status().consecutive_failures, 0);
+    }
+
+    // 测试手动重置
+    #[test]
+    fn test_reset() {
+        let breaker = breaker(1, 60);
+
+        breaker.record_failure("test-client", "error");
+        assert!(breaker.check("test-client").is_err());
+
+        breaker.reset();
+        assert_eq!(breaker.status().state, BreakerState::Closed);
+        assert!(breaker.check("test-client").is_ok());
+    }
+}
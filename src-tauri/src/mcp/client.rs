@@ -1,44 +1,154 @@
-use crate::mcp::types::*;
+use crate::encryption::KeyStore;
+use crate::mcp::{
+    approval::ApprovalManager,
+    audit::AuditLog,
+    breaker::CircuitBreaker,
+    completion::{self, CompletionCache},
+    elicitation::ElicitationManager,
+    history::HistoryStore,
+    http_client::build_http_client,
+    http_sse::{HttpSseTransport, HttpSseTransportHandle},
+    inspector::{raw_message, InspectingHandle, InspectorEvent, InspectorSlot, RawSender},
+    limits::ClientLimiter,
+    logging,
+    metrics::{CallMetrics, CallOutcome},
+    oauth::OAuthManager,
+    placeholders::PlaceholderResolver,
+    prompts,
+    recorder::{RecorderSlot, RecordingHandle, ReplayTransport, ReplayTransportHandle},
+    redact,
+    registry::ServerRegistry,
+    resources::{self, ResourceLimits},
+    sandbox,
+    secrets::SecretStore,
+    server_transport::SseServer,
+    stdio::{StdioProcessTransport, StdioProcessTransportHandle},
+    tool_cache::{ToolCache, ToolHints},
+    types::*,
+};
 use chrono::{DateTime, Utc};
 use log::{debug, error, info, warn};
 use mcp_client_fishcode2025::{
     client::{ClientCapabilities, ClientInfo, McpClient, McpClientTrait},
-    transport::{
-        sse::SseTransportHandle, stdio::StdioTransportHandle, SseTransport, StdioTransport,
-        Transport,
-    },
+    transport::Transport,
     Error as McpError, McpService,
 };
 use mcp_core_fishcode2025::protocol::JsonRpcMessage;
-use std::any::type_name;
-use std::{collections::HashMap, sync::Arc, time::Duration};
-use tauri::async_runtime;
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tauri::AppHandle;
 use tokio::sync::Mutex;
+use tokio::task::JoinSet;
+use tracing::Instrument;
+
+// 未配置 timeout_secs 时请求的超时时间
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
 
 // 定义类型别名，简化代码
-type McpSseService = McpService<SseTransportHandle>;
-type McpStdioService = McpService<StdioTransportHandle>;
+type McpSseService = McpService<InspectingHandle<RecordingHandle<HttpSseTransportHandle>>>;
+type McpStdioService = McpService<InspectingHandle<RecordingHandle<StdioProcessTransportHandle>>>;
+type McpReplayService = McpService<InspectingHandle<ReplayTransportHandle>>;
 type McpSseClient = McpClient<McpSseService>;
 type McpStdioClient = McpClient<McpStdioService>;
+type McpReplayClient = McpClient<McpReplayService>;
 
 // 定义客户端枚举
 enum McpClientEnum {
     Sse(McpSseClient),
     Stdio(McpStdioClient),
+    Replay(McpReplayClient),
 }
 
 /// MCP 客户端实例
 struct ClientInstance {
     id: String,
-    client: McpClientEnum,
+    // 工具调用在释放管理器锁后执行，因此客户端以 Arc 共享
+    client: Arc<McpClientEnum>,
     status: ClientStatus,
     connected_at: Option<DateTime<Utc>>,
     server_info: Option<ServerInfo>,
+    sandbox: Option<SandboxProfile>,
+    limiter: Arc<ClientLimiter>,
+    breaker: Arc<CircuitBreaker>,
+    // 创建客户端时使用的请求，用于重连
+    config: InitializeClientRequest,
+    // 会话录制器，与传输句柄共享
+    recorder: Arc<RecorderSlot>,
+    // 协议检查器，与传输句柄共享
+    inspector: Arc<InspectorSlot>,
+    // 直接发送消息的传输句柄
+    raw: Arc<dyn RawSender>,
+    // 最近一次列出的提示参数定义，用于在发送前校验必填参数
+    prompt_arguments: std::sync::Mutex<HashMap<String, Vec<PromptArgument>>>,
+    // 工具调用统计
+    metrics: Arc<CallMetrics>,
+}
+
+impl ClientInstance {
+    /// 生成客户端状态响应
+    fn status_response(&self) -> ClientStatusResponse {
+        ClientStatusResponse {
+            id: self.id.clone(),
+            status: self.status.clone(),
+            error: match &self.status {
+                ClientStatus::Error(e) => Some(e.clone()),
+                _ => None,
+            },
+            connected_at: self.connected_at,
+            server_info: self.server_info.clone(),
+            sandbox: self.sandbox.clone(),
+            circuit_breaker: Some(self.breaker.status()),
+        }
+    }
+}
+
+/// 建立连接所需的共享组件
+///
+/// 从管理器中取出后连接时不需要持有管理器锁，OAuth 授权和输入占位符等待用户时不阻塞其他操作。
+#[derive(Clone)]
+struct Connector {
+    resolver: Arc<PlaceholderResolver>,
+    oauth: Arc<OAuthManager>,
+    elicitation: Option<Arc<ElicitationManager>>,
+}
+
+impl Connector {
+    /// 建立连接并创建客户端实例
+    async fn connect(&self, request: InitializeClientRequest) -> Result<ClientInstance, String> {
+        McpClientManager::connect(
+            request,
+            self.resolver.clone(),
+            self.oauth.clone(),
+            self.elicitation.clone(),
+        )
+        .await
+    }
+}
+
+// 修复客户端的下一步
+enum RepairStep {
+    // 客户端已连接，无需修复
+    Connected(Box<ClientStatusResponse>),
+    // 使用保存的配置重新连接
+    Reconnect(Box<InitializeClientRequest>, Connector),
 }
 
 /// MCP 客户端管理器
 pub struct McpClientManager {
     clients: HashMap<String, ClientInstance>,
+    audit_log: Option<Arc<AuditLog>>,
+    resolver: Arc<PlaceholderResolver>,
+    oauth: Arc<OAuthManager>,
+    // 未指定录制文件路径时使用的目录
+    recordings_dir: Option<PathBuf>,
+    resource_limits: ResourceLimits,
+    // 处理服务器的结构化输入请求，未设置时不声明 elicitation 能力
+    elicitation: Option<Arc<ElicitationManager>>,
+    tool_cache: Arc<ToolCache>,
 }
 
 impl McpClientManager {
@@ -47,33 +157,91 @@ impl McpClientManager {
         info!("[MCP] 创建新的客户端管理器");
         Self {
             clients: HashMap::new(),
+            audit_log: None,
+            resolver: Arc::new(PlaceholderResolver::new(Arc::new(SecretStore::in_memory()))),
+            oauth: Arc::new(OAuthManager::new(Arc::new(SecretStore::in_memory()))),
+            recordings_dir: None,
+            resource_limits: ResourceLimits::default(),
+            elicitation: None,
+            tool_cache: Arc::new(ToolCache::new()),
         }
     }
 
     /// 初始化客户端
+    ///
+    /// 连接期间持有管理器，只用于测试；应用通过 [`AppState::initialize_client`] 在锁外连接。
+    #[cfg(test)]
     pub async fn initialize_client(
         &mut self,
         request: InitializeClientRequest,
+    ) -> Result<ClientStatusResponse, String> {
+        let started = Instant::now();
+        let client_id = request.id.clone();
+        let span = logging::request_span("initialize", &client_id);
+        let result = self
+            .initialize_client_inner(request)
+            .instrument(span.clone())
+            .await;
+        logging::record_duration(&span, started);
+        self.audit(AuditEntry::new(&client_id, "initialize", started).with_result(&result));
+        result
+    }
+
+    /// 初始化客户端的实际实现
+    #[cfg(test)]
+    async fn initialize_client_inner(
+        &mut self,
+        request: InitializeClientRequest,
     ) -> Result<ClientStatusResponse, String> {
         info!(
             "[MCP] 开始初始化客户端 ID: {}, 传输类型: {:?}",
             request.id, request.transport_type
         );
 
-        // 检查客户端ID是否已存在
-        if self.clients.contains_key(&request.id) {
-            error!("[MCP] 客户端 ID: {} 已存在", request.id);
+        let connector = self.prepare_initialize(&request.id)?;
+        let instance = connector.connect(request).await?;
+        self.insert_instance(instance)
+    }
+
+    /// 检查客户端ID是否可用，返回建立连接所需的组件
+    fn prepare_initialize(&self, client_id: &str) -> Result<Connector, String> {
+        if let Some(instance) = self.clients.get(client_id) {
+            error!("[MCP] 客户端 ID: {} 已存在", client_id);
 
             // 添加更详细的日志，显示现有客户端的状态
-            if let Some(instance) = self.clients.get(&request.id) {
-                error!(
-                    "[MCP] 现有客户端状态: ID={}, 状态={:?}, 连接时间={:?}",
-                    instance.id, instance.status, instance.connected_at
-                );
-            }
+            error!(
+                "[MCP] 现有客户端状态: ID={}, 状态={:?}, 连接时间={:?}",
+                instance.id, instance.status, instance.connected_at
+            );
 
-            return Err(format!("Client with ID '{}' already exists", request.id));
+            return Err(format!("Client with ID '{}' already exists", client_id));
         }
+        Ok(self.connector())
+    }
+
+    /// 建立连接所需的共享组件
+    fn connector(&self) -> Connector {
+        Connector {
+            resolver: self.resolver.clone(),
+            oauth: self.oauth.clone(),
+            elicitation: self.elicitation.clone(),
+        }
+    }
+
+    /// 建立连接并创建客户端实例
+    ///
+    /// 不需要访问管理器，自动连接时可以在不持有管理器锁的情况下并行执行。
+    async fn connect(
+        request: InitializeClientRequest,
+        resolver: Arc<PlaceholderResolver>,
+        oauth: Arc<OAuthManager>,
+        elicitation: Option<Arc<ElicitationManager>>,
+    ) -> Result<ClientInstance, String> {
+        // 保存原始请求，重连时不需要前端再次提供配置
+        let config = request.clone();
+        let recorder = Arc::new(RecorderSlot::default());
+        let inspector = Arc::new(InspectorSlot::new(&request.id));
+        let raw: Arc<dyn RawSender>;
 
         // 创建客户端
         let mut client = match request.transport_type {
@@ -84,23 +252,63 @@ impl McpClientManager {
                     .ok_or_else(|| "URL is required for SSE transport".to_string())?;
 
                 info!("[MCP] 创建 SSE 传输, URL: {}", url);
-                debug!("[MCP] SSE 请求头: {:?}", request.headers);
+                debug!(
+                    "[MCP] SSE 请求头: {:?}",
+                    redact::redact_opt_map(&request.headers)
+                );
 
-                let headers = request.headers.unwrap_or_default();
-                let transport = SseTransport::new(&url, headers);
+                if request.sandbox.is_some() {
+                    warn!("[MCP] SSE 传输不支持沙箱配置，已忽略");
+                }
+
+                // 占位符在创建传输前才解析，解析后的值不写入日志
+                let url = resolver.resolve(&request.id, &url).await?;
+                let headers = resolver
+                    .resolve_map(&request.id, request.headers.unwrap_or_default())
+                    .await?;
+
+                // OAuth 授权和 SSE 传输使用相同的 TLS 和代理配置
+                let http_client = build_http_client(request.tls.as_ref(), request.proxy.as_ref())?;
 
                 info!("[MCP] 启动 SSE 传输...");
-                let handle = match transport.start().await {
+                // 始终使用自定义传输，TLS、代理、服务器请求和收到的消息都在这里处理；
+                // 未显式配置 Authorization 时，服务器返回 401 后由 OAuth 管理器授权并重试
+                let build = |headers: HashMap<String, String>| {
+                    let mut transport = HttpSseTransport::new(http_client.clone(), &url, headers);
+                    if let Some(elicitation) = &elicitation {
+                        transport =
+                            transport.with_request_handler(elicitation.handler(&request.id));
+                    }
+                    transport
+                        .with_observer(recorder.clone())
+                        .with_observer(inspector.clone())
+                };
+                let handle = match oauth
+                    .start_transport(
+                        &http_client,
+                        &request.id,
+                        &url,
+                        headers,
+                        request.oauth.as_ref(),
+                        build,
+                    )
+                    .await
+                {
                     Ok(h) => {
                         info!("[MCP] SSE 传输启动成功");
                         h
                     }
                     Err(e) => {
                         error!("[MCP] SSE 传输启动失败: {}", e);
-                        return Err(e.to_string());
+                        return Err(e);
                     }
                 };
 
+                let handle = InspectingHandle::new(
+                    RecordingHandle::new(handle, recorder.clone()),
+                    inspector.clone(),
+                );
+                raw = Arc::new(handle.clone());
                 let service = McpService::new(handle);
                 info!("[MCP] 创建 SSE 客户端");
                 McpClientEnum::Sse(McpClient::new(service))
@@ -113,21 +321,43 @@ impl McpClientManager {
 
                 info!("[MCP] 创建 Stdio 传输, 命令: {}", command);
                 let args = request.args.clone().unwrap_or_default();
-                debug!("[MCP] Stdio 参数: {:?}", args);
-                debug!("[MCP] Stdio 环境变量: {:?}", request.headers);
+                debug!(
+                    "[MCP] Stdio 参数: {}",
+                    redact::redact_text(&format!("{:?}", args))
+                );
+                debug!(
+                    "[MCP] Stdio 环境变量: {:?}",
+                    redact::redact_opt_map(&request.headers)
+                );
+
+                // 占位符在查找命令和应用沙箱之前解析，沙箱工作目录和规格使用解析后的值
+                let command = resolver.resolve(&request.id, &command).await?;
+                let args = resolver.resolve_all(&request.id, args).await?;
+                let sandbox_profile = match request.sandbox.clone() {
+                    Some(mut profile) => {
+                        sandbox::validate_profile(&profile)?;
+                        if let Some(dir) = &profile.working_dir {
+                            profile.working_dir = Some(resolver.resolve(&request.id, dir).await?);
+                        }
+                        Some(profile)
+                    }
+                    None => None,
+                };
 
                 // 获取并合并环境变量
-                let mut env_vars = request.headers.unwrap_or_default();
+                let mut env_vars = resolver
+                    .resolve_map(&request.id, request.headers.unwrap_or_default())
+                    .await?;
 
                 // 获取系统 PATH 环境变量
                 if let Ok(path) = std::env::var("PATH") {
-                    info!("[MCP] 系统 PATH: {}", path);
+                    debug!("[MCP] 系统 PATH: {}", path);
 
                     // 如果用户已经提供了 PATH，则合并而不是覆盖
                     if let Some(existing_path) = env_vars.get("PATH") {
                         let merged_path = format!("{};{}", existing_path, path);
                         env_vars.insert("PATH".to_string(), merged_path);
-                        info!("[MCP] 合并 PATH: {}", env_vars.get("PATH").unwrap());
+                        debug!("[MCP] 合并 PATH: {}", env_vars.get("PATH").unwrap());
                     } else {
                         env_vars.insert("PATH".to_string(), path);
                         info!("[MCP] 添加系统 PATH 到环境变量");
@@ -234,10 +464,36 @@ impl McpClientManager {
                 #[cfg(not(target_os = "windows"))]
                 let (command_to_use, args_to_use) = (command.clone(), args.clone());
 
-                info!("[MCP] 最终使用的命令: {}", command_to_use);
-                info!("[MCP] 最终使用的参数: {:?}", args_to_use);
+                // 如果配置了沙箱，则通过沙箱启动器执行
+                let (command_to_use, args_to_use, env_vars) = match &sandbox_profile {
+                    Some(profile) => sandbox::wrap_command(
+                        &request.id,
+                        profile,
+                        command_to_use,
+                        args_to_use,
+                        env_vars,
+                    )?,
+                    None => (command_to_use, args_to_use, env_vars),
+                };
+
+                // 解析出的密钥和输入值已登记，日志中会被遮蔽
+                info!(
+                    "[MCP] 最终使用的命令: {}",
+                    redact::redact_text(&command_to_use)
+                );
+                info!(
+                    "[MCP] 最终使用的参数: {}",
+                    redact::redact_text(&format!("{:?}", args_to_use))
+                );
 
-                let transport = StdioTransport::new(&command_to_use, args_to_use, env_vars);
+                let mut transport =
+                    StdioProcessTransport::new(&command_to_use, args_to_use, env_vars);
+                if let Some(elicitation) = &elicitation {
+                    transport = transport.with_request_handler(elicitation.handler(&request.id));
+                }
+                let transport = transport
+                    .with_observer(recorder.clone())
+                    .with_observer(inspector.clone());
 
                 info!("[MCP] 启动 Stdio 传输...");
                 let handle = match transport.start().await {
@@ -251,10 +507,30 @@ impl McpClientManager {
                     }
                 };
 
+                let handle = InspectingHandle::new(
+                    RecordingHandle::new(handle, recorder.clone()),
+                    inspector.clone(),
+                );
+                raw = Arc::new(handle.clone());
                 let service = McpService::new(handle);
                 info!("[MCP] 创建 Stdio 客户端");
                 McpClientEnum::Stdio(McpClient::new(service))
             }
+            TransportType::Replay => {
+                let path = request
+                    .replay_path
+                    .clone()
+                    .ok_or_else(|| "Recording path is required for Replay transport".to_string())?;
+
+                info!("[MCP] 创建回放传输, 录制文件: {}", path);
+                let handle = ReplayTransport::new(&path)
+                    .start()
+                    .await
+                    .map_err(|e| format!("Failed to load recording {}: {}", path, e))?;
+                let handle = InspectingHandle::new(handle, inspector.clone());
+                raw = Arc::new(handle.clone());
+                McpClientEnum::Replay(McpClient::new(McpService::new(handle)))
+            }
         };
 
         // 初始化连接
@@ -294,6 +570,18 @@ impl McpClientManager {
                 client = McpClientEnum::Stdio(c);
                 result
             }
+            McpClientEnum::Replay(mut c) => {
+                info!("[MCP] 初始化回放客户端连接...");
+                let client_info = ClientInfo {
+                    name: request.client_name.clone(),
+                    version: request.client_version.clone(),
+                };
+                let result = c
+                    .initialize(client_info, ClientCapabilities::default())
+                    .await;
+                client = McpClientEnum::Replay(c);
+                result
+            }
         };
 
         let server_info = match server_info {
@@ -309,9 +597,7 @@ impl McpClientManager {
                     version: info.server_info.version.clone(),
                     capabilities: serde_json::to_value(info.capabilities)
                         .map(|v| match v {
-                            serde_json::Value::Object(map) => {
-                                map.into_iter().map(|(k, v)| (k, v)).collect()
-                            }
+                            serde_json::Value::Object(map) => map.into_iter().collect(),
                             _ => HashMap::new(),
                         })
                         .unwrap_or_default(),
@@ -328,32 +614,67 @@ impl McpClientManager {
         info!("[MCP] 客户端连接成功, 时间: {}", connected_at);
 
         // 创建客户端实例
-        let instance = ClientInstance {
+        Ok(ClientInstance {
             id: request.id.clone(),
-            client: client,
+            client: Arc::new(client),
             status: ClientStatus::Connected,
             connected_at: Some(connected_at),
-            server_info: Some(server_info.clone()),
-        };
+            server_info: Some(server_info),
+            sandbox: request.sandbox.clone(),
+            limiter: Arc::new(ClientLimiter::new(
+                &request.limits.clone().unwrap_or_default(),
+            )),
+            breaker: Arc::new(CircuitBreaker::new(
+                request.circuit_breaker.clone().unwrap_or_default(),
+            )),
+            config,
+            recorder,
+            inspector,
+            raw,
+            prompt_arguments: std::sync::Mutex::new(HashMap::new()),
+            metrics: Arc::new(CallMetrics::new()),
+        })
+    }
+
+    /// 将已连接的客户端实例加入管理器
+    fn insert_instance(
+        &mut self,
+        instance: ClientInstance,
+    ) -> Result<ClientStatusResponse, String> {
+        if self.clients.contains_key(&instance.id) {
+            error!("[MCP] 客户端 ID: {} 已存在", instance.id);
+            return Err(format!("Client with ID '{}' already exists", instance.id));
+        }
 
         // 添加到客户端列表
-        info!("[MCP] 添加客户端到管理器, ID: {}", request.id);
-        self.clients.insert(request.id.clone(), instance);
+        info!("[MCP] 添加客户端到管理器, ID: {}", instance.id);
+        let status = instance.status_response();
+        self.clients.insert(instance.id.clone(), instance);
 
         // 返回客户端状态
-        Ok(ClientStatusResponse {
-            id: request.id,
-            status: ClientStatus::Connected,
-            error: None,
-            connected_at: Some(connected_at),
-            server_info: Some(server_info),
-        })
+        Ok(status)
     }
 
     /// 断开客户端连接
     pub async fn disconnect_client(
         &mut self,
         client_id: &str,
+    ) -> Result<ClientStatusResponse, String> {
+        let started = Instant::now();
+        let span = logging::request_span("disconnect", client_id);
+        let result = self
+            .disconnect_client_inner(client_id)
+            .instrument(span.clone())
+            .await;
+        logging::record_duration(&span, started);
+        self.audit(AuditEntry::new(client_id, "disconnect", started).with_result(&result));
+        result
+    }
+
+    /// 断开客户端连接的实际实现
+    async fn disconnect_client_inner(
+        &mut self,
+        client_id: &str,
     ) -> Result<ClientStatusResponse, String> {
         info!("[MCP] 断开客户端连接, ID: {}", client_id);
 
@@ -366,6 +687,7 @@ impl McpClientManager {
         info!("[MCP] 更新客户端状态为断开连接, ID: {}", client_id);
         instance.status = ClientStatus::Disconnected;
         instance.connected_at = None;
+        self.tool_cache.remove_client(client_id);
 
         // 返回状态
         Ok(ClientStatusResponse {
@@ -374,11 +696,35 @@ impl McpClientManager {
             error: None,
             connected_at: None,
             server_info: instance.server_info.clone(),
+            sandbox: instance.sandbox.clone(),
+            circuit_breaker: Some(instance.breaker.status()),
         })
     }
 
     /// 删除客户端
     pub async fn delete_client(&mut self, client_id: &str) -> Result<(), String> {
+        let started = Instant::now();
+        // 删除后无法再获取服务器信息，提前记录
+        let server_info = self
+            .clients
+            .get(client_id)
+            .and_then(|instance| instance.server_info.clone());
+        let span = logging::request_span("delete", client_id);
+        let result = self
+            .delete_client_inner(client_id)
+            .instrument(span.clone())
+            .await;
+        logging::record_duration(&span, started);
+        self.audit(
+            AuditEntry::new(client_id, "delete", started)
+                .with_server(server_info.as_ref())
+                .with_result(&result),
+        );
+        result
+    }
+
+    /// 删除客户端的实际实现
+    async fn delete_client_inner(&mut self, client_id: &str) -> Result<(), String> {
         info!("[MCP] 删除客户端, ID: {}", client_id);
 
         if !self.clients.contains_key(client_id) {
@@ -389,6 +735,7 @@ impl McpClientManager {
         // 移除客户端
         info!("[MCP] 从管理器中移除客户端, ID: {}", client_id);
         self.clients.remove(client_id);
+        self.tool_cache.remove_client(client_id);
         Ok(())
     }
 
@@ -429,6 +776,8 @@ impl McpClientManager {
             },
             connected_at: instance.connected_at,
             server_info: instance.server_info.clone(),
+            sandbox: instance.sandbox.clone(),
+            circuit_breaker: Some(instance.breaker.status()),
         };
 
         debug!(
@@ -454,6 +803,8 @@ impl McpClientManager {
                 },
                 connected_at: instance.connected_at,
                 server_info: instance.server_info.clone(),
+                sandbox: instance.sandbox.clone(),
+                circuit_breaker: Some(instance.breaker.status()),
             })
             .collect();
 
@@ -461,10 +812,11 @@ impl McpClientManager {
         statuses
     }
 
-    /// 修复客户端连接
-    pub async fn repair_client(&mut self, client_id: &str) -> Result<ClientStatusResponse, String> {
+    /// 开始修复客户端，未连接时标记为连接中并返回重连使用的配置
+    fn begin_repair(&mut self, client_id: &str) -> Result<RepairStep, String> {
         info!("[MCP] 尝试修复客户端连接, ID: {}", client_id);
 
+        let connector = self.connector();
         let instance = self.clients.get_mut(client_id).ok_or_else(|| {
             error!("[MCP] 客户端不存在, ID: {}", client_id);
             format!("Client with ID '{}' not found", client_id)
@@ -479,60 +831,271 @@ impl McpClientManager {
         // 如果客户端已经连接，则无需修复
         if matches!(instance.status, ClientStatus::Connected) {
             info!("[MCP] 客户端已连接，无需修复, ID: {}", client_id);
-            return Ok(ClientStatusResponse {
-                id: instance.id.clone(),
-                status: ClientStatus::Connected,
-                error: None,
-                connected_at: instance.connected_at,
-                server_info: instance.server_info.clone(),
-            });
+            return Ok(RepairStep::Connected(Box::new(instance.status_response())));
         }
 
-        // 尝试重新初始化连接
-        info!("[MCP] 尝试重新初始化客户端连接, ID: {}", client_id);
-
         // 更新状态为连接中
         instance.status = ClientStatus::Connecting;
         info!("[MCP] 客户端状态更新为 Connecting, ID: {}", client_id);
 
-        // 根据客户端类型执行不同的重连逻辑
-        match &mut instance.client {
-            McpClientEnum::Sse(client) => {
-                // 对于SSE客户端，可能需要重新建立连接
-                // 这里简化处理，仅更新状态
-                info!("[MCP] 修复 SSE 客户端连接, ID: {}", client_id);
-                instance.status = ClientStatus::Connected;
-                instance.connected_at = Some(Utc::now());
+        // 使用保存的初始化请求重新建立连接
+        Ok(RepairStep::Reconnect(
+            Box::new(instance.config.clone()),
+            connector,
+        ))
+    }
+
+    /// 完成修复，重连成功后替换旧实例，失败时标记为错误状态
+    fn finish_repair(
+        &mut self,
+        client_id: &str,
+        result: Result<ClientInstance, String>,
+    ) -> Result<ClientStatusResponse, String> {
+        // 重连期间客户端可能已被删除
+        if !self.clients.contains_key(client_id) {
+            warn!("[MCP] 客户端在重连期间被删除, ID: {}", client_id);
+            return Err(format!("Client with ID '{}' not found", client_id));
+        }
+
+        match result {
+            Ok(new_instance) => {
+                info!(
+                    "[MCP] 修复后客户端状态: ID={}, 状态={:?}, 连接时间={:?}",
+                    new_instance.id, new_instance.status, new_instance.connected_at
+                );
+                info!("[MCP] 客户端连接修复成功, ID: {}", client_id);
+
+                let status = new_instance.status_response();
+                self.clients.insert(client_id.to_string(), new_instance);
+                Ok(status)
             }
-            McpClientEnum::Stdio(client) => {
-                // 对于Stdio客户端，可能需要重新启动进程
-                // 这里简化处理，仅更新状态
-                info!("[MCP] 修复 Stdio 客户端连接, ID: {}", client_id);
-                instance.status = ClientStatus::Connected;
-                instance.connected_at = Some(Utc::now());
+            Err(e) => {
+                error!("[MCP] 客户端连接修复失败, ID: {}, 错误: {}", client_id, e);
+                if let Some(instance) = self.clients.get_mut(client_id) {
+                    instance.status = ClientStatus::Error(e.clone());
+                }
+                Err(e)
             }
         }
+    }
 
-        // 记录修复后的状态
-        info!(
-            "[MCP] 修复后客户端状态: ID={}, 状态={:?}, 连接时间={:?}",
-            instance.id, instance.status, instance.connected_at
-        );
+    /// 设置审计日志
+    pub fn set_audit_log(&mut self, audit_log: Arc<AuditLog>) {
+        self.audit_log = Some(audit_log);
+    }
+
+    /// 获取审计日志
+    pub fn audit_log(&self) -> Option<Arc<AuditLog>> {
+        self.audit_log.clone()
+    }
 
-        info!("[MCP] 客户端连接修复成功, ID: {}", client_id);
+    /// 设置配置占位符解析器
+    pub fn set_resolver(&mut self, resolver: Arc<PlaceholderResolver>) {
+        self.resolver = resolver;
+    }
 
-        // 返回更新后的状态
-        Ok(ClientStatusResponse {
-            id: instance.id.clone(),
-            status: instance.status.clone(),
-            error: None,
-            connected_at: instance.connected_at,
-            server_info: instance.server_info.clone(),
-        })
+    /// 设置 OAuth 授权管理器
+    pub fn set_oauth(&mut self, oauth: Arc<OAuthManager>) {
+        self.oauth = oauth;
+    }
+
+    /// 设置未指定路径时保存会话录制的目录
+    pub fn set_recordings_dir(&mut self, dir: PathBuf) {
+        self.recordings_dir = Some(dir);
+    }
+
+    /// 当前的资源读取限制
+    pub fn resource_limits(&self) -> ResourceLimits {
+        self.resource_limits.clone()
+    }
+
+    /// 设置资源读取的内联阈值、大小上限和缓存清理策略，缓存目录保持不变
+    ///
+    /// 设置后立即按新的策略清理缓存目录。
+    pub fn set_resource_limits(&mut self, limits: ResourceLimits) {
+        self.resource_limits = ResourceLimits {
+            cache_dir: self.resource_limits.cache_dir.clone(),
+            ..limits
+        };
+        resources::prune_cache(&self.resource_limits, None);
+    }
+
+    /// 设置资源缓存文件目录
+    pub fn set_resource_cache_dir(&mut self, dir: PathBuf) {
+        self.resource_limits.cache_dir = dir;
+        resources::prune_cache(&self.resource_limits, None);
+    }
+
+    /// 设置结构化输入请求管理器，之后连接的 SSE 和 Stdio 客户端声明 elicitation 能力
+    pub fn set_elicitation(&mut self, elicitation: Arc<ElicitationManager>) {
+        self.elicitation = Some(elicitation);
+    }
+
+    /// 获取工具结果缓存
+    pub fn tool_cache(&self) -> Arc<ToolCache> {
+        self.tool_cache.clone()
+    }
+
+    /// 开始录制客户端会话，返回录制文件路径
+    ///
+    /// 未指定路径时保存到录制目录下；已在录制时切换到新的文件。
+    pub fn start_recording(&self, client_id: &str, path: Option<String>) -> Result<String, String> {
+        let instance = self
+            .clients
+            .get(client_id)
+            .ok_or_else(|| format!("Client with ID '{}' not found", client_id))?;
+        if matches!(instance.client.as_ref(), McpClientEnum::Replay(_)) {
+            return Err("Replayed sessions cannot be recorded".to_string());
+        }
+
+        let path = match path {
+            Some(path) => PathBuf::from(path),
+            None => {
+                let dir = self
+                    .recordings_dir
+                    .as_ref()
+                    .ok_or_else(|| "Recording path is required".to_string())?;
+                let name: String = client_id
+                    .chars()
+                    .map(|c| {
+                        if c.is_ascii_alphanumeric() || c == '-' {
+                            c
+                        } else {
+                            '_'
+                        }
+                    })
+                    .collect();
+                dir.join(format!(
+                    "{}-{}.jsonl",
+                    name,
+                    Utc::now().format("%Y%m%d-%H%M%S")
+                ))
+            }
+        };
+        instance.recorder.start(path.clone())?;
+        Ok(path.display().to_string())
+    }
+
+    /// 停止录制客户端会话，返回录制文件路径，未在录制时返回 None
+    pub fn stop_recording(&self, client_id: &str) -> Result<Option<String>, String> {
+        let instance = self
+            .clients
+            .get(client_id)
+            .ok_or_else(|| format!("Client with ID '{}' not found", client_id))?;
+        Ok(instance
+            .recorder
+            .stop()
+            .map(|path| path.display().to_string()))
+    }
+
+    /// 开启客户端的协议检查器，返回线上消息事件的接收端
+    pub fn start_inspector(
+        &self,
+        client_id: &str,
+    ) -> Result<tokio::sync::mpsc::UnboundedReceiver<InspectorEvent>, String> {
+        let instance = self
+            .clients
+            .get(client_id)
+            .ok_or_else(|| format!("Client with ID '{}' not found", client_id))?;
+        Ok(instance.inspector.start())
+    }
+
+    /// 关闭客户端的协议检查器，未开启时返回 false
+    pub fn stop_inspector(&self, client_id: &str) -> Result<bool, String> {
+        let instance = self
+            .clients
+            .get(client_id)
+            .ok_or_else(|| format!("Client with ID '{}' not found", client_id))?;
+        Ok(instance.inspector.stop())
+    }
+
+    /// 获取直接发送消息的传输句柄，发送时不需要持有管理器锁
+    pub fn raw_sender(&self, client_id: &str) -> Result<Arc<dyn RawSender>, String> {
+        Ok(self.get_connected_instance(client_id)?.raw.clone())
+    }
+
+    /// 写入审计记录，未设置服务器信息时从客户端实例补充
+    fn audit(&self, mut entry: AuditEntry) {
+        if self.audit_log.is_none() {
+            return;
+        }
+
+        if entry.server_name.is_none() {
+            let server_info = self
+                .clients
+                .get(&entry.client_id)
+                .and_then(|instance| instance.server_info.as_ref());
+            entry = entry.with_server(server_info);
+        }
+
+        append_audit(self.audit_log.as_ref(), &entry);
+    }
+
+    /// 设置客户端限流配置，新的配置只对之后的调用生效
+    pub fn set_client_limits(
+        &mut self,
+        client_id: &str,
+        limits: LimitConfig,
+    ) -> Result<(), String> {
+        let instance = self.clients.get_mut(client_id).ok_or_else(|| {
+            warn!("[MCP] 客户端不存在, ID: {}", client_id);
+            format!("Client with ID '{}' not found", client_id)
+        })?;
+
+        info!("[MCP] 更新客户端限流配置, ID: {}", client_id);
+        instance.limiter = Arc::new(ClientLimiter::new(&limits));
+        // 重连时使用新的配置
+        instance.config.limits = Some(limits);
+        Ok(())
+    }
+
+    /// 手动重置客户端熔断器
+    pub fn reset_circuit_breaker(&self, client_id: &str) -> Result<BreakerStatus, String> {
+        let instance = self
+            .clients
+            .get(client_id)
+            .ok_or_else(|| format!("Client with ID '{}' not found", client_id))?;
+
+        info!("[MCP] 重置客户端熔断器, ID: {}", client_id);
+        instance.breaker.reset();
+        Ok(instance.breaker.status())
+    }
+
+    /// 获取工具调用统计，未指定客户端时返回所有客户端，按客户端ID排序
+    pub fn metrics(&self, client_id: Option<&str>) -> Result<Vec<ClientMetrics>, String> {
+        if let Some(client_id) = client_id {
+            let instance = self
+                .clients
+                .get(client_id)
+                .ok_or_else(|| format!("Client with ID '{}' not found", client_id))?;
+            return Ok(vec![instance.metrics.snapshot(client_id)]);
+        }
+
+        let mut metrics: Vec<ClientMetrics> = self
+            .clients
+            .values()
+            .map(|instance| instance.metrics.snapshot(&instance.id))
+            .collect();
+        metrics.sort_by(|a, b| a.client_id.cmp(&b.client_id));
+        Ok(metrics)
+    }
+
+    /// 获取客户端限流配置
+    pub fn get_client_limits(&self, client_id: &str) -> Result<LimitConfig, String> {
+        self.clients
+            .get(client_id)
+            .map(|instance| instance.limiter.config().clone())
+            .ok_or_else(|| format!("Client with ID '{}' not found", client_id))
     }
 
     /// 获取客户端
     fn get_client(&self, client_id: &str) -> Result<&McpClientEnum, String> {
+        self.get_connected_instance(client_id)
+            .map(|instance| instance.client.as_ref())
+    }
+
+    /// 获取已连接的客户端实例
+    fn get_connected_instance(&self, client_id: &str) -> Result<&ClientInstance, String> {
         debug!("[MCP] 获取客户端实例, ID: {}", client_id);
 
         let instance = self.clients.get(client_id).ok_or_else(|| {
@@ -548,43 +1111,81 @@ impl McpClientManager {
             return Err(format!("Client with ID '{}' is not connected", client_id));
         }
 
-        Ok(&instance.client)
+        Ok(instance)
     }
 
     /// 列出工具
     pub async fn list_tools(
         &self,
         request: FilterRequest,
+    ) -> Result<McpResponse<Vec<ToolInfo>>, String> {
+        let started = Instant::now();
+        let client_id = request.client_id.clone();
+        let span = logging::request_span("list_tools", &client_id);
+        let result = self
+            .list_tools_inner(request)
+            .instrument(span.clone())
+            .await;
+        logging::record_duration(&span, started);
+        self.audit(AuditEntry::new(&client_id, "list_tools", started).with_response(&result));
+        result
+    }
+
+    /// 列出工具的实际实现
+    async fn list_tools_inner(
+        &self,
+        request: FilterRequest,
     ) -> Result<McpResponse<Vec<ToolInfo>>, String> {
         info!("[MCP] 列出工具, 客户端ID: {}", request.client_id);
         debug!("[MCP] 过滤条件: {:?}", request.filter);
 
-        let client = self.get_client(&request.client_id)?;
-
-        let result = match client {
-            McpClientEnum::Sse(client) => client.list_tools(request.filter.clone()).await,
-            McpClientEnum::Stdio(client) => client.list_tools(request.filter.clone()).await,
-        };
+        // 直接发送 tools/list，保留客户端库未解析的字段（如 annotations）
+        let instance = self.get_connected_instance(&request.client_id)?;
+        let sender = instance.raw.clone();
+        let timeout = instance
+            .config
+            .timeout_secs
+            .map_or(DEFAULT_REQUEST_TIMEOUT, Duration::from_secs);
+        let params = request
+            .cursor
+            .as_ref()
+            .map(|cursor| serde_json::json!({ "cursor": cursor }));
+        let result =
+            match tokio::time::timeout(timeout, sender.send_raw(raw_message("tools/list", params)))
+                .await
+            {
+                Ok(Ok(response)) => rpc_result(response),
+                Ok(Err(e)) => Err(e.to_string()),
+                Err(_) => Err("Request 'tools/list' timed out".to_string()),
+            };
 
         match result {
-            Ok(tools) => {
-                info!("[MCP] 成功获取工具列表, 数量: {}", tools.tools.len());
-                debug!(
-                    "[MCP] 工具列表: {:?}",
-                    tools.tools.iter().map(|t| &t.name).collect::<Vec<_>>()
+            Ok(result) => {
+                let tools: Vec<serde_json::Value> = result
+                    .get("tools")
+                    .and_then(|tools| tools.as_array())
+                    .cloned()
+                    .unwrap_or_default();
+                info!("[MCP] 成功获取工具列表, 数量: {}", tools.len());
+
+                // 记录工具标注，用于判断结果是否可以缓存
+                self.tool_cache.set_hints(
+                    &request.client_id,
+                    tools
+                        .iter()
+                        .filter_map(|tool| {
+                            let name = tool.get("name")?.as_str()?;
+                            Some((name.to_string(), ToolHints::from_tool(tool)))
+                        })
+                        .collect(),
                 );
 
                 // 转换为 ToolInfo 类型
-                let tool_infos = tools
-                    .tools
-                    .into_iter()
-                    .map(|t| ToolInfo {
-                        name: t.name,
-                        description: t.description,
-                        parameters_schema: Some(t.input_schema.clone()),
-                        result_schema: None,
-                    })
-                    .collect();
+                let tool_infos: Vec<ToolInfo> = tools.iter().filter_map(parse_tool_info).collect();
+                debug!(
+                    "[MCP] 工具列表: {:?}",
+                    tool_infos.iter().map(|t| &t.name).collect::<Vec<_>>()
+                );
 
                 Ok(McpResponse {
                     success: true,
@@ -597,293 +1198,95 @@ impl McpClientManager {
                 Ok(McpResponse {
                     success: false,
                     data: None,
-                    error: Some(e.to_string()),
+                    error: Some(e),
                 })
             }
         }
     }
 
-    /// 调用工具
+    /// 不经过审批直接调用工具，应用中的调用都经过 `AppState::call_tool_checked`
+    #[cfg(test)]
     pub async fn call_tool(
         &self,
         request: ToolCallRequest,
     ) -> Result<McpResponse<serde_json::Value>, String> {
-        info!(
-            "[MCP] 调用工具: {}, 客户端ID: {}",
-            request.tool_name, request.client_id
-        );
-        debug!("[MCP] 工具参数: {:?}", request.params);
+        self.prepare_tool_call(request, None)?.execute().await
+    }
 
-        // 添加标准输出，确保能看到
-        println!(
-            "=== [MCP] 调用工具开始: {}, 客户端ID: {} ===",
-            request.tool_name, request.client_id
-        );
-        println!("=== [MCP] 工具参数: {:?} ===", request.params);
-
-        // 获取客户端
-        let client = match self.get_client(&request.client_id) {
-            Ok(client) => {
-                info!("[MCP] 成功获取客户端实例");
-                println!("=== [MCP] 成功获取客户端实例 ===");
-                client
-            }
-            Err(e) => {
+    /// 准备工具调用
+    ///
+    /// 返回的调用持有客户端的共享引用，可以在释放管理器锁之后执行，
+    /// 这样限流排队和慢速工具不会阻塞其他客户端的操作。
+    pub fn prepare_tool_call(
+        &self,
+        request: ToolCallRequest,
+        approved_by: Option<String>,
+    ) -> Result<PreparedToolCall, String> {
+        let instance = self
+            .get_connected_instance(&request.client_id)
+            .map_err(|e| {
                 error!("[MCP] 获取客户端实例失败: {}", e);
-                println!("=== [MCP] 获取客户端实例失败: {} ===", e);
-                return Err(format!("获取客户端实例失败: {}", e));
-            }
-        };
-
-        // 调用工具
-        info!("[MCP] 准备调用客户端的 call_tool 方法");
-        println!("=== [MCP] 准备调用客户端的 call_tool 方法 ===");
-
-        // 检查工具名称
-        if request.tool_name.is_empty() {
-            let error_msg = "工具名称不能为空".to_string();
-            error!("[MCP] {}", error_msg);
-            println!("=== [MCP] {} ===", error_msg);
-            return Ok(McpResponse {
-                success: false,
-                data: None,
-                error: Some(error_msg),
-            });
-        }
+                let error_msg = format!("获取客户端实例失败: {}", e);
+                self.audit(
+                    AuditEntry::new(&request.client_id, "call_tool", Instant::now())
+                        .with_target(&request.tool_name)
+                        .with_arguments(&parse_tool_arguments(&request.params))
+                        .with_approver(approved_by.clone())
+                        .with_outcome(AuditOutcome::Error, Some(&error_msg)),
+                );
+                error_msg
+            })?;
+
+        Ok(PreparedToolCall {
+            request,
+            approved_by,
+            client: instance.client.clone(),
+            limiter: instance.limiter.clone(),
+            breaker: instance.breaker.clone(),
+            server_info: instance.server_info.clone(),
+            audit_log: self.audit_log.clone(),
+            cache: self.tool_cache.clone(),
+            metrics: instance.metrics.clone(),
+            timeout: instance
+                .config
+                .timeout_secs
+                .map_or(DEFAULT_REQUEST_TIMEOUT, Duration::from_secs),
+            elicitation: self.elicitation.clone(),
+        })
+    }
 
-        // 检查参数格式
-        println!("=== [MCP] 检查参数格式 ===");
-        println!("=== [MCP] 工具名称: {} ===", request.tool_name);
-        println!(
-            "=== [MCP] 参数类型: {} ===",
-            std::any::type_name::<serde_json::Value>()
+    /// 记录被审批拒绝的工具调用
+    pub fn audit_denied_tool_call(&self, request: &ToolCallRequest, reason: &str) {
+        let arguments = parse_tool_arguments(&request.params);
+        self.audit(
+            AuditEntry::new(&request.client_id, "call_tool", Instant::now())
+                .with_target(&request.tool_name)
+                .with_arguments(&arguments)
+                .with_outcome(AuditOutcome::Denied, Some(&reason.to_string())),
         );
-        println!("=== [MCP] 原始参数值: {:?} ===", request.params);
-
-        // 尝试解析参数
-        let arguments = if let serde_json::Value::String(param_str) = &request.params {
-            // 如果参数是字符串，尝试解析为JSON对象
-            println!("=== [MCP] 参数是字符串，尝试解析为JSON对象 ===");
-            match serde_json::from_str::<serde_json::Value>(param_str) {
-                Ok(parsed) => {
-                    println!("=== [MCP] 参数解析成功: {:?} ===", parsed);
-
-                    // 检查是否包含name和arguments字段
-                    if let serde_json::Value::Object(map) = &parsed {
-                        if map.contains_key("name") && map.contains_key("arguments") {
-                            // 提取arguments字段
-                            if let Some(serde_json::Value::Object(args)) = map.get("arguments") {
-                                let args_value = serde_json::Value::Object(args.clone());
-                                println!("=== [MCP] 提取arguments字段: {:?} ===", args_value);
-                                args_value
-                            } else {
-                                println!("=== [MCP] 使用原始解析结果 ===");
-                                parsed
-                            }
-                        } else {
-                            println!("=== [MCP] 使用原始解析结果 ===");
-                            parsed
-                        }
-                    } else {
-                        println!("=== [MCP] 使用原始解析结果 ===");
-                        parsed
-                    }
-                }
-                Err(e) => {
-                    println!("=== [MCP] 参数解析失败: {} ===", e);
-                    println!("=== [MCP] 使用原始参数 ===");
-                    request.params.clone()
-                }
-            }
-        } else {
-            // 如果参数不是字符串，直接使用
-            println!("=== [MCP] 参数不是字符串，直接使用 ===");
-            request.params.clone()
-        };
-
-        println!("=== [MCP] 最终参数: {:?} ===", arguments);
-
-        let result = match client {
-            McpClientEnum::Sse(client) => {
-                info!("[MCP] 使用 SSE 客户端调用工具");
-                println!("=== [MCP] 使用 SSE 客户端调用工具 ===");
-                match tokio::time::timeout(
-                    std::time::Duration::from_secs(30), // 30秒超时
-                    client.call_tool(&request.tool_name, arguments.clone()),
-                )
-                .await
-                {
-                    Ok(result) => match result {
-                        Ok(r) => {
-                            info!("[MCP] SSE 客户端工具调用成功");
-                            println!("=== [MCP] SSE 客户端工具调用成功 ===");
-                            Ok(r)
-                        }
-                        Err(e) => {
-                            error!("[MCP] SSE 客户端工具调用失败: {}", e);
-                            println!("=== [MCP] SSE 客户端工具调用失败: {} ===", e);
-                            Err(e)
-                        }
-                    },
-                    Err(_) => {
-                        error!("[MCP] SSE 客户端工具调用超时");
-                        println!("=== [MCP] SSE 客户端工具调用超时 ===");
-                        Err(mcp_client_fishcode2025::Error::NotReady)
-                    }
-                }
-            }
-            McpClientEnum::Stdio(client) => {
-                info!("[MCP] 使用 Stdio 客户端调用工具");
-                println!("=== [MCP] 使用 Stdio 客户端调用工具 ===");
-
-                // 检查子进程状态
-                println!("=== [MCP] 准备调用 Stdio 客户端的 call_tool 方法 ===");
-
-                // 添加超时机制
-                match tokio::time::timeout(
-                    std::time::Duration::from_secs(30), // 30秒超时
-                    client.call_tool(&request.tool_name, arguments.clone()),
-                )
-                .await
-                {
-                    Ok(result) => match result {
-                        Ok(r) => {
-                            info!("[MCP] Stdio 客户端工具调用成功");
-                            println!("=== [MCP] Stdio 客户端工具调用成功 ===");
-                            println!("=== [MCP] 调用结果: {:?} ===", r);
-                            Ok(r)
-                        }
-                        Err(e) => {
-                            error!("[MCP] Stdio 客户端工具调用失败: {}", e);
-                            println!("=== [MCP] Stdio 客户端工具调用失败: {} ===", e);
-                            println!("=== [MCP] 错误详情: {:?} ===", e);
-                            Err(e)
-                        }
-                    },
-                    Err(_) => {
-                        error!("[MCP] Stdio 客户端工具调用超时");
-                        println!("=== [MCP] Stdio 客户端工具调用超时 ===");
-                        Err(mcp_client_fishcode2025::Error::NotReady)
-                    }
-                }
-            }
-        };
-
-        // 处理结果
-        println!("=== [MCP] 处理调用结果 ===");
-        match result {
-            Ok(result) => {
-                info!("[MCP] 工具调用成功: {}", request.tool_name);
-                println!("=== [MCP] 工具调用成功: {} ===", request.tool_name);
-
-                // 尝试序列化结果
-                let serialized_result = match serde_json::to_value(&result) {
-                    Ok(value) => {
-                        debug!("[MCP] 结果序列化成功");
-                        println!("=== [MCP] 结果序列化成功 ===");
-                        println!(
-                            "=== [MCP] 结果类型: {} ===",
-                            std::any::type_name::<serde_json::Value>()
-                        );
-                        println!("=== [MCP] 结果值: {:?} ===", value);
-                        value
-                    }
-                    Err(e) => {
-                        error!("[MCP] 结果序列化失败: {}", e);
-                        println!("=== [MCP] 结果序列化失败: {} ===", e);
-                        println!("=== [MCP] 原始结果: {:?} ===", result);
-                        serde_json::Value::Null
-                    }
-                };
-
-                debug!("[MCP] 工具调用结果: {:?}", serialized_result);
-                println!("=== [MCP] 工具调用结果: {:?} ===", serialized_result);
-
-                Ok(McpResponse {
-                    success: true,
-                    data: Some(serialized_result),
-                    error: None,
-                })
-            }
-            Err(e) => {
-                error!("[MCP] 工具调用失败: {}, 错误: {}", request.tool_name, e);
-                println!(
-                    "=== [MCP] 工具调用失败: {}, 错误: {} ===",
-                    request.tool_name, e
-                );
-                println!(
-                    "=== [MCP] 错误类型: {} ===",
-                    std::any::type_name::<mcp_client_fishcode2025::Error>()
-                );
-                println!("=== [MCP] 错误详情: {:?} ===", e);
-
-                // 尝试获取更多错误信息
-                let error_message = match e {
-                    mcp_client_fishcode2025::Error::Transport(transport_error) => {
-                        println!("=== [MCP] 传输错误: {:?} ===", transport_error);
-                        format!("传输错误: {}", transport_error)
-                    }
-                    mcp_client_fishcode2025::Error::RpcError { code, message } => {
-                        println!("=== [MCP] RPC错误: 代码={}, 消息={} ===", code, message);
-                        format!("RPC错误: 代码={}, 消息={}", code, message)
-                    }
-                    mcp_client_fishcode2025::Error::Serialization(ser_error) => {
-                        println!("=== [MCP] 序列化错误: {:?} ===", ser_error);
-                        format!("序列化错误: {}", ser_error)
-                    }
-                    mcp_client_fishcode2025::Error::UnexpectedResponse(msg) => {
-                        println!("=== [MCP] 意外响应: {} ===", msg);
-                        format!("意外响应: {}", msg)
-                    }
-                    mcp_client_fishcode2025::Error::NotInitialized => {
-                        println!("=== [MCP] 客户端未初始化 ===");
-                        "客户端未初始化".to_string()
-                    }
-                    mcp_client_fishcode2025::Error::NotReady => {
-                        println!("=== [MCP] 服务未就绪或超时 ===");
-                        "服务未就绪或超时".to_string()
-                    }
-                    mcp_client_fishcode2025::Error::Timeout(_) => {
-                        println!("=== [MCP] 请求超时 ===");
-                        "请求超时".to_string()
-                    }
-                    mcp_client_fishcode2025::Error::ServerBoxError(box_error) => {
-                        println!("=== [MCP] 服务器错误: {:?} ===", box_error);
-                        format!("服务器错误: {}", box_error)
-                    }
-                    mcp_client_fishcode2025::Error::McpServerError {
-                        method,
-                        server,
-                        source,
-                    } => {
-                        println!(
-                            "=== [MCP] MCP服务器错误: 方法={}, 服务器={}, 源={:?} ===",
-                            method, server, source
-                        );
-                        format!(
-                            "MCP服务器错误: 方法={}, 服务器={}, 源={}",
-                            method, server, source
-                        )
-                    }
-                    _ => {
-                        println!("=== [MCP] 未知错误类型 ===");
-                        format!("未知错误: {}", e)
-                    }
-                };
-
-                Ok(McpResponse {
-                    success: false,
-                    data: None,
-                    error: Some(error_message),
-                })
-            }
-        }
     }
 
     /// 列出资源
     pub async fn list_resources(
         &self,
         request: FilterRequest,
+    ) -> Result<McpResponse<Vec<ResourceInfo>>, String> {
+        let started = Instant::now();
+        let client_id = request.client_id.clone();
+        let span = logging::request_span("list_resources", &client_id);
+        let result = self
+            .list_resources_inner(request)
+            .instrument(span.clone())
+            .await;
+        logging::record_duration(&span, started);
+        self.audit(AuditEntry::new(&client_id, "list_resources", started).with_response(&result));
+        result
+    }
+
+    /// 列出资源的实际实现
+    async fn list_resources_inner(
+        &self,
+        request: FilterRequest,
     ) -> Result<McpResponse<Vec<ResourceInfo>>, String> {
         info!("[MCP] 列出资源, 客户端ID: {}", request.client_id);
         debug!("[MCP] 过滤条件: {:?}", request.filter);
@@ -893,6 +1296,7 @@ impl McpClientManager {
         let result = match client {
             McpClientEnum::Sse(client) => client.list_resources(request.filter.clone()).await,
             McpClientEnum::Stdio(client) => client.list_resources(request.filter.clone()).await,
+            McpClientEnum::Replay(client) => client.list_resources(request.filter.clone()).await,
         };
 
         match result {
@@ -942,7 +1346,29 @@ impl McpClientManager {
     pub async fn read_resource(
         &self,
         request: ResourceReadRequest,
-    ) -> Result<McpResponse<serde_json::Value>, String> {
+    ) -> Result<McpResponse<Vec<ResourceContent>>, String> {
+        let started = Instant::now();
+        let client_id = request.client_id.clone();
+        let uri = request.resource_uri.clone();
+        let span = logging::request_span("read_resource", &client_id);
+        let result = self
+            .read_resource_inner(request)
+            .instrument(span.clone())
+            .await;
+        logging::record_duration(&span, started);
+        self.audit(
+            AuditEntry::new(&client_id, "read_resource", started)
+                .with_target(&uri)
+                .with_response(&result),
+        );
+        result
+    }
+
+    /// 读取资源的实际实现
+    async fn read_resource_inner(
+        &self,
+        request: ResourceReadRequest,
+    ) -> Result<McpResponse<Vec<ResourceContent>>, String> {
         info!(
             "[MCP] 读取资源: {}, 客户端ID: {}",
             request.resource_uri, request.client_id
@@ -953,16 +1379,23 @@ impl McpClientManager {
         let result = match client {
             McpClientEnum::Sse(client) => client.read_resource(&request.resource_uri).await,
             McpClientEnum::Stdio(client) => client.read_resource(&request.resource_uri).await,
+            McpClientEnum::Replay(client) => client.read_resource(&request.resource_uri).await,
         };
 
-        match result {
-            Ok(resource) => {
+        let contents = result.map_err(|e| e.to_string()).and_then(|resource| {
+            resources::convert_contents(
+                &serde_json::to_value(resource).unwrap_or_default(),
+                &self.resource_limits,
+            )
+        });
+        match contents {
+            Ok(contents) => {
                 info!("[MCP] 资源读取成功: {}", request.resource_uri);
-                debug!("[MCP] 资源内容已获取");
+                debug!("[MCP] 资源内容数量: {}", contents.len());
 
                 Ok(McpResponse {
                     success: true,
-                    data: Some(serde_json::to_value(resource).unwrap_or_default()),
+                    data: Some(contents),
                     error: None,
                 })
             }
@@ -981,6 +1414,23 @@ impl McpClientManager {
     pub async fn list_prompts(
         &self,
         request: FilterRequest,
+    ) -> Result<McpResponse<Vec<PromptInfo>>, String> {
+        let started = Instant::now();
+        let client_id = request.client_id.clone();
+        let span = logging::request_span("list_prompts", &client_id);
+        let result = self
+            .list_prompts_inner(request)
+            .instrument(span.clone())
+            .await;
+        logging::record_duration(&span, started);
+        self.audit(AuditEntry::new(&client_id, "list_prompts", started).with_response(&result));
+        result
+    }
+
+    /// 列出提示的实际实现
+    async fn list_prompts_inner(
+        &self,
+        request: FilterRequest,
     ) -> Result<McpResponse<Vec<PromptInfo>>, String> {
         info!("[MCP] 列出提示, 客户端ID: {}", request.client_id);
         debug!("[MCP] 过滤条件: {:?}", request.filter);
@@ -990,6 +1440,7 @@ impl McpClientManager {
         let result = match client {
             McpClientEnum::Sse(client) => client.list_prompts(request.filter.clone()).await,
             McpClientEnum::Stdio(client) => client.list_prompts(request.filter.clone()).await,
+            McpClientEnum::Replay(client) => client.list_prompts(request.filter.clone()).await,
         };
 
         match result {
@@ -1001,19 +1452,25 @@ impl McpClientManager {
                 );
 
                 // 转换为 PromptInfo 类型
-                let prompt_infos = prompts
+                let prompt_infos: Vec<PromptInfo> = prompts
                     .prompts
                     .into_iter()
                     .map(|p| PromptInfo {
+                        arguments: prompts::parse_arguments(
+                            &serde_json::to_value(&p.arguments).unwrap_or_default(),
+                        ),
                         name: p.name,
                         description: p.description.unwrap_or_default(),
-                        parameters_schema: Some(
-                            serde_json::to_value(p.arguments.clone())
-                                .unwrap_or(serde_json::Value::Null),
-                        ),
                     })
                     .collect();
 
+                if let Some(instance) = self.clients.get(&request.client_id) {
+                    let mut cached = instance.prompt_arguments.lock().unwrap();
+                    for prompt in &prompt_infos {
+                        cached.insert(prompt.name.clone(), prompt.arguments.clone());
+                    }
+                }
+
                 Ok(McpResponse {
                     success: true,
                     data: Some(prompt_infos),
@@ -1035,36 +1492,86 @@ impl McpClientManager {
     pub async fn get_prompt(
         &self,
         request: PromptRequest,
-    ) -> Result<McpResponse<serde_json::Value>, String> {
+    ) -> Result<McpResponse<PromptResult>, String> {
+        let started = Instant::now();
+        let client_id = request.client_id.clone();
+        let prompt_name = request.prompt_name.clone();
+        let arguments = request.params.clone();
+        let span = logging::request_span("get_prompt", &client_id);
+        let result = self
+            .get_prompt_inner(request)
+            .instrument(span.clone())
+            .await;
+        logging::record_duration(&span, started);
+        self.audit(
+            AuditEntry::new(&client_id, "get_prompt", started)
+                .with_target(&prompt_name)
+                .with_arguments(&arguments)
+                .with_response(&result),
+        );
+        result
+    }
+
+    /// 获取提示的实际实现
+    async fn get_prompt_inner(
+        &self,
+        request: PromptRequest,
+    ) -> Result<McpResponse<PromptResult>, String> {
         info!(
             "[MCP] 获取提示: {}, 客户端ID: {}",
             request.prompt_name, request.client_id
         );
-        debug!("[MCP] 提示参数: {:?}", request.params);
+        debug!(
+            "[MCP] 提示参数: {:?}",
+            redact::redact_value(&request.params)
+        );
 
         let client = self.get_client(&request.client_id)?;
 
-        let result = match client {
-            McpClientEnum::Sse(client) => {
-                client
-                    .get_prompt(&request.prompt_name, request.params)
-                    .await
+        // 缺少必填参数时不发送请求；找不到提示定义时交给服务器校验
+        let params = match self
+            .prompt_arguments(&request.client_id, &request.prompt_name)
+            .await
+        {
+            Some(arguments) => {
+                prompts::validate_arguments(&request.prompt_name, &arguments, &request.params)
             }
-            McpClientEnum::Stdio(client) => {
-                client
-                    .get_prompt(&request.prompt_name, request.params)
-                    .await
+            None => {
+                debug!(
+                    "[MCP] 未找到提示定义，跳过参数校验: {}",
+                    request.prompt_name
+                );
+                Ok(request.params.clone())
+            }
+        };
+        let params = match params {
+            Ok(params) => params,
+            Err(e) => {
+                warn!("[MCP] 提示参数校验失败: {}", e);
+                return Ok(McpResponse {
+                    success: false,
+                    data: None,
+                    error: Some(e),
+                });
             }
         };
 
-        match result {
+        let result = match client {
+            McpClientEnum::Sse(client) => client.get_prompt(&request.prompt_name, params).await,
+            McpClientEnum::Stdio(client) => client.get_prompt(&request.prompt_name, params).await,
+            McpClientEnum::Replay(client) => client.get_prompt(&request.prompt_name, params).await,
+        };
+
+        match result.map_err(|e| e.to_string()).and_then(|prompt| {
+            prompts::normalize_prompt(&serde_json::to_value(prompt).unwrap_or_default())
+        }) {
             Ok(prompt) => {
                 info!("[MCP] 提示获取成功: {}", request.prompt_name);
                 debug!("[MCP] 提示内容: {:?}", prompt);
 
                 Ok(McpResponse {
                     success: true,
-                    data: Some(serde_json::to_value(prompt).unwrap_or_default()),
+                    data: Some(prompt),
                     error: None,
                 })
             }
@@ -1078,18 +1585,883 @@ impl McpClientManager {
             }
         }
     }
+
+    /// 查找提示的参数定义，未缓存时重新列出提示
+    async fn prompt_arguments(
+        &self,
+        client_id: &str,
+        prompt_name: &str,
+    ) -> Option<Vec<PromptArgument>> {
+        let cached = |manager: &Self| {
+            let instance = manager.clients.get(client_id)?;
+            let arguments = instance.prompt_arguments.lock().unwrap();
+            arguments.get(prompt_name).cloned()
+        };
+        if let Some(arguments) = cached(self) {
+            return Some(arguments);
+        }
+        let _ = self
+            .list_prompts_inner(FilterRequest {
+                client_id: client_id.to_string(),
+                filter: None,
+                cursor: None,
+            })
+            .await;
+        cached(self)
+    }
+}
+
+/// 已准备好的工具调用，执行时不需要持有管理器锁
+pub struct PreparedToolCall {
+    request: ToolCallRequest,
+    approved_by: Option<String>,
+    client: Arc<McpClientEnum>,
+    limiter: Arc<ClientLimiter>,
+    breaker: Arc<CircuitBreaker>,
+    server_info: Option<ServerInfo>,
+    audit_log: Option<Arc<AuditLog>>,
+    cache: Arc<ToolCache>,
+    metrics: Arc<CallMetrics>,
+    timeout: Duration,
+    elicitation: Option<Arc<ElicitationManager>>,
+}
+
+impl PreparedToolCall {
+    /// 执行工具调用并写入审计日志
+    pub async fn execute(self) -> Result<McpResponse<serde_json::Value>, String> {
+        let started = Instant::now();
+        let arguments = parse_tool_arguments(&self.request.params);
+        let span = logging::request_span("call_tool", &self.request.client_id);
+        span.record("tool_name", self.request.tool_name.as_str());
+        let result = match self.cached(&arguments) {
+            Some(response) => Ok(response),
+            None => {
+                let result = self.execute_inner().instrument(span.clone()).await;
+                self.store(&arguments, &result);
+                result
+            }
+        };
+        logging::record_duration(&span, started);
+        append_audit(
+            self.audit_log.as_ref(),
+            &AuditEntry::new(&self.request.client_id, "call_tool", started)
+                .with_server(self.server_info.as_ref())
+                .with_target(&self.request.tool_name)
+                .with_arguments(&arguments)
+                .with_approver(self.approved_by.clone())
+                .with_response(&result),
+        );
+        result
+    }
+
+    /// 查找缓存的结果，命中时不再调用服务器
+    fn cached(&self, arguments: &serde_json::Value) -> Option<McpResponse<serde_json::Value>> {
+        let data = self
+            .cache
+            .get(&self.request.client_id, &self.request.tool_name, arguments)?;
+        info!(
+            "[MCP] 使用缓存的工具结果: {}, 客户端ID: {}",
+            self.request.tool_name, self.request.client_id
+        );
+        Some(McpResponse {
+            success: true,
+            data: Some(data),
+            error: None,
+        })
+    }
+
+    /// 缓存成功的结果，工具返回错误时不缓存
+    fn store(
+        &self,
+        arguments: &serde_json::Value,
+        result: &Result<McpResponse<serde_json::Value>, String>,
+    ) {
+        let Ok(McpResponse {
+            success: true,
+            data: Some(data),
+            ..
+        }) = result
+        else {
+            return;
+        };
+        if data.get("isError").and_then(|v| v.as_bool()) == Some(true) {
+            return;
+        }
+        self.cache.insert(
+            &self.request.client_id,
+            &self.request.tool_name,
+            arguments,
+            data,
+        );
+    }
+
+    /// 被限流或熔断拒绝的调用计为错误
+    fn record_rejection(&self, error: &str) {
+        self.metrics.record(
+            &self.request.tool_name,
+            Duration::ZERO,
+            CallOutcome::Rejected(error.to_string()),
+            0,
+            0,
+        );
+    }
+
+    /// 执行工具调用的实际实现
+    async fn execute_inner(&self) -> Result<McpResponse<serde_json::Value>, String> {
+        let request = &self.request;
+        info!(
+            "[MCP] 调用工具: {}, 客户端ID: {}",
+            request.tool_name, request.client_id
+        );
+        debug!(
+            "[MCP] 工具参数: {:?}",
+            redact::redact_value(&request.params)
+        );
+
+        // 检查工具名称
+        if request.tool_name.is_empty() {
+            let error_msg = "工具名称不能为空".to_string();
+            error!("[MCP] {}", error_msg);
+            return Ok(McpResponse {
+                success: false,
+                data: None,
+                error: Some(error_msg),
+            });
+        }
+
+        // 超出限流配置时排队，排队超时则提示调用方退避
+        let _permit = match self
+            .limiter
+            .acquire(&request.client_id, &request.tool_name)
+            .await
+        {
+            Ok(permit) => permit,
+            Err(e) => {
+                self.record_rejection(&e);
+                return Ok(McpResponse {
+                    success: false,
+                    data: None,
+                    error: Some(e),
+                });
+            }
+        };
+
+        // 服务器持续失败时快速失败，不再等待超时
+        // 调用被取消时许可随之释放，半开状态的探测不会一直占用
+        let breaker_permit = match self.breaker.check(&request.client_id) {
+            Ok(permit) => permit,
+            Err(e) => {
+                warn!("[MCP] 熔断中，拒绝工具调用: {}", request.tool_name);
+                self.record_rejection(&e);
+                return Ok(McpResponse {
+                    success: false,
+                    data: None,
+                    error: Some(e),
+                });
+            }
+        };
+
+        // 尝试解析参数
+        let arguments = parse_tool_arguments(&request.params);
+        debug!("[MCP] 最终参数: {:?}", redact::redact_value(&arguments));
+
+        // 统计从发送请求开始，不含限流排队时间
+        let call_started = Instant::now();
+        let bytes_out = arguments.to_string().len() as u64;
+        let call = async {
+            match self.client.as_ref() {
+                McpClientEnum::Sse(client) => {
+                    debug!("[MCP] 使用 SSE 客户端调用工具");
+                    client.call_tool(&request.tool_name, arguments).await
+                }
+                McpClientEnum::Stdio(client) => {
+                    debug!("[MCP] 使用 Stdio 客户端调用工具");
+                    client.call_tool(&request.tool_name, arguments).await
+                }
+                McpClientEnum::Replay(client) => {
+                    debug!("[MCP] 使用回放客户端调用工具");
+                    client.call_tool(&request.tool_name, arguments).await
+                }
+            }
+        };
+
+        // 添加超时机制，等待用户输入期间不计时
+        let mut timed_out = false;
+        let result = match with_call_timeout(
+            call,
+            self.timeout,
+            self.elicitation.as_deref(),
+            &request.client_id,
+        )
+        .await
+        {
+            Some(result) => result,
+            None => {
+                error!("[MCP] 工具调用超时: {}", request.tool_name);
+                timed_out = true;
+                Err(McpError::NotReady)
+            }
+        };
+
+        // 处理结果
+        match result {
+            Ok(result) => {
+                info!("[MCP] 工具调用成功: {}", request.tool_name);
+                breaker_permit.record_success();
+
+                let serialized_result = serde_json::to_value(&result).unwrap_or_else(|e| {
+                    error!("[MCP] 结果序列化失败: {}", e);
+                    serde_json::Value::Null
+                });
+                debug!("[MCP] 工具调用结果: {:?}", serialized_result);
+                self.metrics.record(
+                    &request.tool_name,
+                    call_started.elapsed(),
+                    tool_outcome(&serialized_result),
+                    bytes_out,
+                    serialized_result.to_string().len() as u64,
+                );
+
+                Ok(McpResponse {
+                    success: true,
+                    data: Some(serialized_result),
+                    error: None,
+                })
+            }
+            Err(e) => {
+                error!("[MCP] 工具调用失败: {}, 错误: {}", request.tool_name, e);
+                debug!("[MCP] 错误详情: {:?}", e);
+
+                // 服务器返回的 RPC 错误说明连接正常，不计入熔断
+                let counts_as_failure = !matches!(e, McpError::RpcError { .. });
+                let error_msg = describe_call_error(e);
+                if counts_as_failure {
+                    breaker_permit.record_failure(&redact::redact_text(&error_msg));
+                } else {
+                    breaker_permit.record_success();
+                }
+                let outcome = if timed_out {
+                    CallOutcome::Timeout
+                } else {
+                    CallOutcome::Error(redact::redact_text(&error_msg))
+                };
+                self.metrics.record(
+                    &request.tool_name,
+                    call_started.elapsed(),
+                    outcome,
+                    bytes_out,
+                    0,
+                );
+
+                Ok(McpResponse {
+                    success: false,
+                    data: None,
+                    error: Some(error_msg),
+                })
+            }
+        }
+    }
+}
+
+/// 等待工具调用完成，超时返回 None
+///
+/// 服务器在调用过程中请求用户输入（`elicitation/create`）时，对话框打开期间暂停计时，
+/// 输入结束后重新计时，避免用户操作较慢时调用超时并计入熔断。
+async fn with_call_timeout<F: std::future::Future>(
+    call: F,
+    timeout: Duration,
+    elicitation: Option<&ElicitationManager>,
+    client_id: &str,
+) -> Option<F::Output> {
+    tokio::pin!(call);
+    let mut started = Instant::now();
+    loop {
+        let deadline = tokio::time::Instant::from_std(started + timeout);
+        if let Ok(output) = tokio::time::timeout_at(deadline, &mut call).await {
+            return Some(output);
+        }
+        let elicitation = elicitation?;
+        if elicitation.is_waiting(client_id) {
+            tokio::select! {
+                output = &mut call => return Some(output),
+                _ = elicitation.wait_idle(client_id) => {}
+            }
+        }
+        match elicitation.last_finished(client_id) {
+            Some(finished) if finished > started => started = finished,
+            _ => return None,
+        }
+    }
+}
+
+/// 根据工具结果判断调用是否成功，isError 的结果取第一段文本作为错误信息
+fn tool_outcome(result: &serde_json::Value) -> CallOutcome {
+    if result.get("isError").and_then(|v| v.as_bool()) != Some(true) {
+        return CallOutcome::Success;
+    }
+    let message = result
+        .get("content")
+        .and_then(|content| content.as_array())
+        .into_iter()
+        .flatten()
+        .find_map(|item| item.get("text").and_then(|t| t.as_str()))
+        .unwrap_or("Tool returned an error");
+    CallOutcome::Error(redact::redact_text(message))
+}
+
+/// 取出 JSON-RPC 响应的结果，服务器返回错误时转换为错误信息
+fn rpc_result(response: JsonRpcMessage) -> Result<serde_json::Value, String> {
+    let mut response = serde_json::to_value(response).map_err(|e| e.to_string())?;
+    if let Some(error) = response.get("error") {
+        return Err(error
+            .get("message")
+            .and_then(|m| m.as_str())
+            .map_or_else(|| error.to_string(), str::to_string));
+    }
+    Ok(response
+        .get_mut("result")
+        .map(serde_json::Value::take)
+        .unwrap_or_default())
+}
+
+/// 写入审计记录
+fn append_audit(audit_log: Option<&Arc<AuditLog>>, entry: &AuditEntry) {
+    let Some(audit_log) = audit_log else {
+        return;
+    };
+
+    if let Err(e) = audit_log.append(entry) {
+        error!("[MCP] 写入审计日志失败: {}", e);
+    }
+}
+
+/// 将 `tools/list` 返回的工具定义转换为 ToolInfo，缺少名称时返回 None
+pub fn parse_tool_info(tool: &serde_json::Value) -> Option<ToolInfo> {
+    let text =
+        |value: Option<&serde_json::Value>| value.and_then(|v| v.as_str()).map(str::to_string);
+    let annotations = tool
+        .get("annotations")
+        .filter(|annotations| annotations.is_object())
+        .map(|annotations| {
+            let hint = |name: &str| annotations.get(name).and_then(|v| v.as_bool());
+            ToolAnnotations {
+                title: text(annotations.get("title")),
+                read_only_hint: hint("readOnlyHint"),
+                destructive_hint: hint("destructiveHint"),
+                idempotent_hint: hint("idempotentHint"),
+                open_world_hint: hint("openWorldHint"),
+            }
+        });
+    let icons = tool
+        .get("icons")
+        .and_then(|icons| icons.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|icon| {
+            Some(ToolIcon {
+                src: text(icon.get("src"))?,
+                mime_type: text(icon.get("mimeType")),
+                sizes: match icon.get("sizes") {
+                    // 旧版本规范中 sizes 是空格分隔的字符串
+                    Some(serde_json::Value::String(sizes)) => {
+                        sizes.split_whitespace().map(str::to_string).collect()
+                    }
+                    Some(serde_json::Value::Array(sizes)) => {
+                        sizes.iter().filter_map(|s| text(Some(s))).collect()
+                    }
+                    _ => Vec::new(),
+                },
+            })
+        })
+        .collect();
+
+    Some(ToolInfo {
+        name: text(tool.get("name"))?,
+        description: text(tool.get("description")).unwrap_or_default(),
+        parameters_schema: tool.get("inputSchema").cloned(),
+        result_schema: tool.get("outputSchema").cloned(),
+        title: text(tool.get("title"))
+            .or_else(|| annotations.as_ref().and_then(|a| a.title.clone())),
+        annotations,
+        icons,
+    })
+}
+
+/// 解析工具参数
+///
+/// 前端可能以 JSON 字符串传入参数，或传入包含 name/arguments 的完整调用对象，
+/// 这里统一转换为工具实际需要的参数对象。
+pub fn parse_tool_arguments(params: &serde_json::Value) -> serde_json::Value {
+    // 如果参数不是字符串，直接使用
+    let serde_json::Value::String(param_str) = params else {
+        return params.clone();
+    };
+
+    // 如果参数是字符串，尝试解析为JSON对象
+    let parsed = match serde_json::from_str::<serde_json::Value>(param_str) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            debug!("[MCP] 参数解析失败，使用原始参数: {}", e);
+            return params.clone();
+        }
+    };
+
+    // 如果包含name和arguments字段，提取arguments字段
+    match &parsed {
+        serde_json::Value::Object(map) if map.contains_key("name") => match map.get("arguments") {
+            Some(args @ serde_json::Value::Object(_)) => args.clone(),
+            _ => parsed,
+        },
+        _ => parsed,
+    }
+}
+
+/// 将工具调用错误转换为可读的错误信息
+fn describe_call_error(e: McpError) -> String {
+    match e {
+        McpError::Transport(transport_error) => format!("传输错误: {}", transport_error),
+        McpError::RpcError { code, message } => {
+            format!("RPC错误: 代码={}, 消息={}", code, message)
+        }
+        McpError::Serialization(ser_error) => format!("序列化错误: {}", ser_error),
+        McpError::UnexpectedResponse(msg) => format!("意外响应: {}", msg),
+        McpError::NotInitialized => "客户端未初始化".to_string(),
+        McpError::NotReady => "服务未就绪或超时".to_string(),
+        McpError::Timeout(_) => "请求超时".to_string(),
+        McpError::ServerBoxError(box_error) => format!("服务器错误: {}", box_error),
+        McpError::McpServerError {
+            method,
+            server,
+            source,
+        } => format!(
+            "MCP服务器错误: 方法={}, 服务器={}, 源={}",
+            method, server, source
+        ),
+        #[allow(unreachable_patterns)]
+        other => format!("未知错误: {}", other),
+    }
 }
 
 /// 应用状态
 pub struct AppState {
     pub mcp_client_manager: Mutex<McpClientManager>,
+    pub approval_manager: ApprovalManager,
+    pub server_registry: ServerRegistry,
+    pub placeholder_resolver: Arc<PlaceholderResolver>,
+    pub oauth_manager: Arc<OAuthManager>,
+    pub history_store: Arc<HistoryStore>,
+    /// 运行中的 fishmind 本机 SSE MCP 服务
+    pub fishmind_server: std::sync::Mutex<Option<SseServer>>,
+    /// 运行中的 MCP 网关 SSE 服务
+    pub mcp_gateway: std::sync::Mutex<Option<SseServer>>,
+    /// 参数补全的防抖和缓存
+    pub completions: CompletionCache,
+    /// 服务器的结构化输入请求
+    pub elicitation_manager: Arc<ElicitationManager>,
 }
 
 impl AppState {
     pub fn new() -> Self {
         info!("[MCP] 创建应用状态");
+        let mut manager = McpClientManager::new();
+        let elicitation_manager = Arc::new(ElicitationManager::default());
+        manager.set_elicitation(elicitation_manager.clone());
+        Self {
+            placeholder_resolver: manager.resolver.clone(),
+            oauth_manager: manager.oauth.clone(),
+            mcp_client_manager: Mutex::new(manager),
+            approval_manager: ApprovalManager::new(),
+            server_registry: ServerRegistry::in_memory(),
+            history_store: Arc::new(HistoryStore::unavailable()),
+            fishmind_server: std::sync::Mutex::new(None),
+            mcp_gateway: std::sync::Mutex::new(None),
+            completions: CompletionCache::default(),
+            elicitation_manager,
+        }
+    }
+
+    /// 使用应用数据目录创建应用状态，启用审计日志和服务器配置持久化
+    pub fn with_data_dir(data_dir: PathBuf) -> Self {
+        info!("[MCP] 应用数据目录: {}", data_dir.display());
+        let mut manager = McpClientManager::new();
+        manager.set_recordings_dir(data_dir.join("recordings"));
+        manager.set_resource_cache_dir(data_dir.join("cache").join("resources"));
+
+        match AuditLog::open(data_dir.join("logs").join("mcp_audit.jsonl")) {
+            Ok(audit_log) => manager.set_audit_log(Arc::new(audit_log)),
+            Err(e) => error!("[MCP] 无法打开审计日志: {}", e),
+        }
+
+        let server_registry = ServerRegistry::open(
+            data_dir.join("config").join("mcp_servers.json"),
+        )
+        .unwrap_or_else(|e| {
+            error!("[MCP] 无法加载服务器配置: {}", e);
+            ServerRegistry::in_memory()
+        });
+
+        // 数据密钥由系统钥匙串中的主密钥加密，钥匙串不可用时只在内存中保存密钥
+        let secrets = KeyStore::from_keychain()
+            .and_then(|key_store| SecretStore::open(&data_dir.join("config"), &key_store))
+            .unwrap_or_else(|e| {
+                error!("[MCP] 无法打开密钥存储: {}", e);
+                SecretStore::in_memory()
+            });
+        let secrets = Arc::new(secrets);
+        let placeholder_resolver = Arc::new(PlaceholderResolver::new(secrets.clone()));
+        manager.set_resolver(placeholder_resolver.clone());
+        let oauth_manager = Arc::new(OAuthManager::new(secrets));
+        manager.set_oauth(oauth_manager.clone());
+        let elicitation_manager = Arc::new(ElicitationManager::default());
+        manager.set_elicitation(elicitation_manager.clone());
+
         Self {
-            mcp_client_manager: Mutex::new(McpClientManager::new()),
+            mcp_client_manager: Mutex::new(manager),
+            server_registry,
+            placeholder_resolver,
+            oauth_manager,
+            history_store: Arc::new(HistoryStore::open(HistoryStore::default_path(&data_dir))),
+            elicitation_manager,
+            ..Self::new()
+        }
+    }
+
+    /// 初始化客户端
+    ///
+    /// 只在检查客户端ID和加入管理器时持有管理器锁，连接期间（包括 OAuth 授权和
+    /// 输入占位符等待用户）不阻塞其他操作。
+    pub async fn initialize_client(
+        &self,
+        request: InitializeClientRequest,
+    ) -> Result<ClientStatusResponse, String> {
+        let started = Instant::now();
+        let client_id = request.id.clone();
+        let span = logging::request_span("initialize", &client_id);
+        let result = async {
+            info!(
+                "[MCP] 开始初始化客户端 ID: {}, 传输类型: {:?}",
+                request.id, request.transport_type
+            );
+            let connector = self
+                .mcp_client_manager
+                .lock()
+                .await
+                .prepare_initialize(&request.id)?;
+            let instance = connector.connect(request).await?;
+            self.mcp_client_manager
+                .lock()
+                .await
+                .insert_instance(instance)
+        }
+        .instrument(span.clone())
+        .await;
+        logging::record_duration(&span, started);
+        self.mcp_client_manager
+            .lock()
+            .await
+            .audit(AuditEntry::new(&client_id, "initialize", started).with_result(&result));
+        result
+    }
+
+    /// 修复客户端连接
+    ///
+    /// 重连期间不持有管理器锁，完成后再替换旧实例。
+    pub async fn repair_client(&self, client_id: &str) -> Result<ClientStatusResponse, String> {
+        let started = Instant::now();
+        let span = logging::request_span("repair", client_id);
+        let result = async {
+            let step = self
+                .mcp_client_manager
+                .lock()
+                .await
+                .begin_repair(client_id)?;
+            match step {
+                RepairStep::Connected(status) => Ok(*status),
+                RepairStep::Reconnect(request, connector) => {
+                    let result = connector.connect(*request).await;
+                    self.mcp_client_manager
+                        .lock()
+                        .await
+                        .finish_repair(client_id, result)
+                }
+            }
+        }
+        .instrument(span.clone())
+        .await;
+        logging::record_duration(&span, started);
+        self.mcp_client_manager
+            .lock()
+            .await
+            .audit(AuditEntry::new(client_id, "repair", started).with_result(&result));
+        result
+    }
+
+    /// 更新客户端限流配置
+    ///
+    /// 客户端来自注册表时同时写回注册表，重启后仍然生效。
+    pub async fn set_client_limits(
+        &self,
+        client_id: &str,
+        limits: LimitConfig,
+    ) -> Result<(), String> {
+        self.mcp_client_manager
+            .lock()
+            .await
+            .set_client_limits(client_id, limits.clone())?;
+        if let Some(mut server) = self.server_registry.get(client_id) {
+            server.request.limits = Some(limits);
+            self.server_registry.save(server)?;
+        }
+        Ok(())
+    }
+
+    /// 经过审批后调用工具
+    ///
+    /// 审批检查在获取管理器锁之前进行，避免等待用户时阻塞其他操作；
+    /// 只在查找客户端时持有管理器锁，限流排队和工具执行期间不阻塞其他操作。
+    /// 未通过审批时返回失败响应并写入审计日志。
+    pub async fn call_tool_checked(
+        &self,
+        app: Option<&AppHandle>,
+        request: ToolCallRequest,
+    ) -> Result<McpResponse<serde_json::Value>, String> {
+        let arguments = parse_tool_arguments(&request.params);
+        let approval_span = tracing::info_span!(
+            "mcp_approval",
+            client_id = %request.client_id,
+            tool_name = %request.tool_name,
+        );
+        let approval = match self
+            .approval_manager
+            .check(app, &request.client_id, &request.tool_name, &arguments)
+            .instrument(approval_span)
+            .await
+        {
+            Ok(approval) => approval,
+            Err(e) => {
+                error!("[MCP] 工具调用未通过审批: {}", e);
+                self.mcp_client_manager
+                    .lock()
+                    .await
+                    .audit_denied_tool_call(&request, &e);
+                return Ok(McpResponse {
+                    success: false,
+                    data: None,
+                    error: Some(e),
+                });
+            }
+        };
+
+        let prepared = self
+            .mcp_client_manager
+            .lock()
+            .await
+            .prepare_tool_call(request, approval.approved_by);
+        debug!("[MCP] 已释放客户端管理器锁，准备调用工具");
+        prepared?.execute().await
+    }
+
+    /// 向服务器发送任意方法的 JSON-RPC 消息，用于调试服务器
+    ///
+    /// 绕过类型化的客户端接口，但 `tools/call` 仍然经过审批。
+    /// 返回服务器的原始响应，通知返回空数据。
+    pub async fn send_raw_request(
+        &self,
+        app: Option<&AppHandle>,
+        client_id: &str,
+        method: &str,
+        params: Option<serde_json::Value>,
+        timeout: Duration,
+    ) -> Result<McpResponse<serde_json::Value>, String> {
+        let started = Instant::now();
+        info!("[MCP] 发送原始请求: {}, 客户端ID: {}", method, client_id);
+
+        if method == "tools/call" {
+            let params = params.clone().unwrap_or_default();
+            let tool_name = params
+                .get("name")
+                .and_then(serde_json::Value::as_str)
+                .unwrap_or_default();
+            let arguments = params.get("arguments").cloned().unwrap_or_default();
+            if let Err(e) = self
+                .approval_manager
+                .check(app, client_id, tool_name, &arguments)
+                .await
+            {
+                error!("[MCP] 原始工具调用未通过审批: {}", e);
+                return Ok(McpResponse {
+                    success: false,
+                    data: None,
+                    error: Some(e),
+                });
+            }
+        }
+
+        let audit_log = self.mcp_client_manager.lock().await.audit_log();
+        let result = self
+            .send_raw_message(client_id, method, params.clone(), timeout)
+            .await;
+        append_audit(
+            audit_log.as_ref(),
+            &AuditEntry::new(client_id, "raw_request", started)
+                .with_target(method)
+                .with_arguments(&params.unwrap_or_default())
+                .with_response(&result),
+        );
+        result
+    }
+
+    /// 发送原始消息，只在获取传输句柄时持有管理器锁
+    async fn send_raw_message(
+        &self,
+        client_id: &str,
+        method: &str,
+        params: Option<serde_json::Value>,
+        timeout: Duration,
+    ) -> Result<McpResponse<serde_json::Value>, String> {
+        let sender = self.mcp_client_manager.lock().await.raw_sender(client_id)?;
+        let message = raw_message(method, params);
+        match tokio::time::timeout(timeout, sender.send_raw(message)).await {
+            Ok(Ok(JsonRpcMessage::Nil)) => Ok(McpResponse {
+                success: true,
+                data: None,
+                error: None,
+            }),
+            Ok(Ok(response)) => {
+                let response = serde_json::to_value(response).map_err(|e| e.to_string())?;
+                let error = response.get("error").map(|e| {
+                    e.get("message")
+                        .and_then(|m| m.as_str())
+                        .map_or_else(|| e.to_string(), str::to_string)
+                });
+                Ok(McpResponse {
+                    success: error.is_none(),
+                    data: Some(response),
+                    error,
+                })
+            }
+            Ok(Err(e)) => Ok(McpResponse {
+                success: false,
+                data: None,
+                error: Some(e.to_string()),
+            }),
+            Err(_) => Ok(McpResponse {
+                success: false,
+                data: None,
+                error: Some(format!("Request '{}' timed out", method)),
+            }),
+        }
+    }
+
+    /// 补全提示参数或资源模板变量
+    ///
+    /// 同一输入框的连续请求经过防抖，只发送最后一次；结果按客户端缓存。
+    pub async fn complete_argument(
+        &self,
+        request: CompletionRequest,
+    ) -> Result<McpResponse<CompletionResult>, String> {
+        debug!(
+            "[MCP] 补全参数: {}, 客户端ID: {}",
+            request.argument_name, request.client_id
+        );
+        if let Some(result) = self.completions.get(&request) {
+            return Ok(McpResponse {
+                success: true,
+                data: Some(result),
+                error: None,
+            });
+        }
+
+        if !self.completions.debounce(&request).await {
+            return Ok(McpResponse {
+                success: true,
+                data: Some(CompletionResult {
+                    superseded: true,
+                    ..CompletionResult::default()
+                }),
+                error: None,
+            });
+        }
+
+        let response = self
+            .send_raw_message(
+                &request.client_id,
+                "completion/complete",
+                Some(completion::completion_params(&request)),
+                completion::REQUEST_TIMEOUT,
+            )
+            .await?;
+        let result = match response.data {
+            Some(data) if response.success => {
+                completion::parse_completion(&data["result"], &request.value)
+            }
+            _ => Err(response
+                .error
+                .unwrap_or_else(|| "Empty completion response".to_string())),
+        };
+        match result {
+            Ok(result) => {
+                self.completions.insert(&request, &result);
+                Ok(McpResponse {
+                    success: true,
+                    data: Some(result),
+                    error: None,
+                })
+            }
+            Err(e) => {
+                warn!("[MCP] 参数补全失败: {}", e);
+                Ok(McpResponse {
+                    success: false,
+                    data: None,
+                    error: Some(e),
+                })
+            }
+        }
+    }
+
+    /// 并行连接注册表中所有启用的服务器
+    ///
+    /// 连接过程不持有管理器锁，只在加入管理器时短暂加锁，单个服务器失败不影响其他服务器。
+    pub async fn auto_connect_servers(&self) {
+        let requests = self.server_registry.enabled_requests();
+        if requests.is_empty() {
+            return;
+        }
+        info!("[MCP] 自动连接已启用的服务器, 数量: {}", requests.len());
+
+        let mut tasks = JoinSet::new();
+        for request in requests {
+            let resolver = self.placeholder_resolver.clone();
+            let oauth = self.oauth_manager.clone();
+            let elicitation = Some(self.elicitation_manager.clone());
+            tasks.spawn(async move {
+                let started = Instant::now();
+                let client_id = request.id.clone();
+                let span = logging::request_span("initialize", &client_id);
+                let result = McpClientManager::connect(request, resolver, oauth, elicitation)
+                    .instrument(span.clone())
+                    .await;
+                logging::record_duration(&span, started);
+                (client_id, started, result)
+            });
+        }
+
+        while let Some(joined) = tasks.join_next().await {
+            let (client_id, started, result) = match joined {
+                Ok(output) => output,
+                Err(e) => {
+                    error!("[MCP] 自动连接任务异常退出: {}", e);
+                    continue;
+                }
+            };
+
+            let mut manager = self.mcp_client_manager.lock().await;
+            let result = result.and_then(|instance| manager.insert_instance(instance));
+            manager.audit(AuditEntry::new(&client_id, "initialize", started).with_result(&result));
+            match result {
+                Ok(_) => info!("[MCP] 自动连接成功, ID: {}", client_id),
+                Err(e) => error!("[MCP] 自动连接失败, ID: {}, 错误: {}", client_id, e),
+            }
         }
     }
 }
@@ -1,15 +1,10 @@
 #[cfg(test)]
 mod tests {
-    use super::*;
-    use crate::mcp::client::McpClientManager;
-    use crate::mcp::types::{
-        ClientStatus, FilterRequest, InitializeClientRequest, PromptRequest, ResourceReadRequest,
-        ToolCallRequest, TransportType,
-    };
-    use mcp_client_fishcode2025::{McpClient, McpService};
+    use crate::mcp::client::{parse_tool_info, McpClientManager};
+    use crate::mcp::mock_server::{start_sse, MockMcpServer};
+    use crate::mcp::types::{ClientStatus, FilterRequest, InitializeClientRequest, TransportType};
     use mockall::predicate::*;
     use mockall::*;
-    use serde_json;
     use std::collections::HashMap;
 
     // 创建 MockMcpClient 用于测试
@@ -25,6 +20,13 @@ mod tests {
         }
     }
 
+    // 启动内置的模拟 MCP 服务器，返回 SSE 地址
+    async fn mock_sse_url() -> String {
+        start_sse(MockMcpServer::new(), "127.0.0.1:0")
+            .await
+            .unwrap()
+    }
+
     // 测试 initialize_client 方法
     #[tokio::test]
     async fn test_initialize_client() {
@@ -32,14 +34,13 @@ mod tests {
         let mut manager = McpClientManager::new();
         let request = InitializeClientRequest {
             id: "test-client".to_string(),
-            transport_type: TransportType::Stdio,
-            sse_url: None,
+            transport_type: TransportType::SSE,
+            sse_url: Some(mock_sse_url().await),
             headers: Some(HashMap::new()),
-            command: Some("./src/mcp/mcp-sqlite.exe".to_string()),
-            args: Some(vec![]),
             timeout_secs: Some(30),
             client_name: "test-client".to_string(),
             client_version: "1.0.0".to_string(),
+            ..Default::default()
         };
 
         // 执行测试
@@ -54,10 +55,11 @@ mod tests {
         let status = result.unwrap();
         assert_eq!(status.id, "test-client");
         // 使用模式匹配检查状态
-        match status.status {
-            ClientStatus::Connected => assert!(true),
-            _ => assert!(false, "Expected Connected status, got {:?}", status.status),
-        }
+        assert!(
+            matches!(status.status, ClientStatus::Connected),
+            "Expected Connected status, got {:?}",
+            status.status
+        );
 
         assert!(status.connected_at.is_some());
         assert!(status.server_info.is_some());
@@ -71,14 +73,13 @@ mod tests {
         // 首先初始化一个客户端
         let init_request = InitializeClientRequest {
             id: "test-client".to_string(),
-            transport_type: TransportType::Stdio,
-            sse_url: None,
+            transport_type: TransportType::SSE,
+            sse_url: Some(mock_sse_url().await),
             headers: Some(HashMap::new()),
-            command: Some("./src/mcp/mcp-sqlite.exe".to_string()),
-            args: Some(vec![]),
             timeout_secs: Some(30),
             client_name: "test-client".to_string(),
             client_version: "1.0.0".to_string(),
+            ..Default::default()
         };
         let init_result = manager.initialize_client(init_request).await;
         assert!(
@@ -100,14 +101,11 @@ mod tests {
         assert_eq!(status.id, "test-client");
 
         // 使用模式匹配检查状态
-        match status.status {
-            ClientStatus::Disconnected => assert!(true),
-            _ => assert!(
-                false,
-                "Expected Disconnected status, got {:?}",
-                status.status
-            ),
-        }
+        assert!(
+            matches!(status.status, ClientStatus::Disconnected),
+            "Expected Disconnected status, got {:?}",
+            status.status
+        );
     }
 
     #[tokio::test]
@@ -117,14 +115,13 @@ mod tests {
         // 首先初始化一个客户端
         let init_request = InitializeClientRequest {
             id: "test-client".to_string(),
-            transport_type: TransportType::Stdio,
-            sse_url: None,
+            transport_type: TransportType::SSE,
+            sse_url: Some(mock_sse_url().await),
             headers: Some(HashMap::new()),
-            command: Some("./src/mcp/mcp-sqlite.exe".to_string()),
-            args: Some(vec![]),
             timeout_secs: Some(30),
             client_name: "test-client".to_string(),
             client_version: "1.0.0".to_string(),
+            ..Default::default()
         };
         let init_result = manager.initialize_client(init_request).await;
         assert!(
@@ -154,14 +151,13 @@ mod tests {
         // 首先初始化一个客户端
         let init_request = InitializeClientRequest {
             id: "test-client".to_string(),
-            transport_type: TransportType::Stdio,
-            sse_url: None,
+            transport_type: TransportType::SSE,
+            sse_url: Some(mock_sse_url().await),
             headers: Some(HashMap::new()),
-            command: Some("./src/mcp/mcp-sqlite.exe".to_string()),
-            args: Some(vec![]),
             timeout_secs: Some(30),
             client_name: "test-client".to_string(),
             client_version: "1.0.0".to_string(),
+            ..Default::default()
         };
         let init_result = manager.initialize_client(init_request).await;
         assert!(
@@ -182,10 +178,11 @@ mod tests {
         let status = result.unwrap();
         assert_eq!(status.id, "test-client");
         // 使用模式匹配检查状态
-        match status.status {
-            ClientStatus::Connected => assert!(true),
-            _ => assert!(false, "Expected Connected status, got {:?}", status.status),
-        }
+        assert!(
+            matches!(status.status, ClientStatus::Connected),
+            "Expected Connected status, got {:?}",
+            status.status
+        );
     }
 
     #[tokio::test]
@@ -196,14 +193,13 @@ mod tests {
         // 初始化第一个客户端
         let init_request1 = InitializeClientRequest {
             id: "client1".to_string(),
-            transport_type: TransportType::Stdio,
-            sse_url: None,
+            transport_type: TransportType::SSE,
+            sse_url: Some(mock_sse_url().await),
             headers: Some(HashMap::new()),
-            command: Some("./src/mcp/mcp-sqlite.exe".to_string()),
-            args: Some(vec![]),
             timeout_secs: Some(30),
             client_name: "client1".to_string(),
             client_version: "1.0.0".to_string(),
+            ..Default::default()
         };
         let init_result1 = manager.initialize_client(init_request1).await;
         assert!(
@@ -215,14 +211,13 @@ mod tests {
         // 初始化第二个客户端
         let init_request2 = InitializeClientRequest {
             id: "client2".to_string(),
-            transport_type: TransportType::Stdio,
-            sse_url: None,
+            transport_type: TransportType::SSE,
+            sse_url: Some(mock_sse_url().await),
             headers: Some(HashMap::new()),
-            command: Some("./src/mcp/mcp-sqlite.exe".to_string()),
-            args: Some(vec![]),
             timeout_secs: Some(30),
             client_name: "client2".to_string(),
             client_version: "1.0.0".to_string(),
+            ..Default::default()
         };
         let init_result2 = manager.initialize_client(init_request2).await;
         assert!(
@@ -258,14 +253,13 @@ mod tests {
         // 首先初始化一个客户端
         let init_request = InitializeClientRequest {
             id: "test-client".to_string(),
-            transport_type: TransportType::Stdio,
-            sse_url: None,
+            transport_type: TransportType::SSE,
+            sse_url: Some(mock_sse_url().await),
             headers: Some(HashMap::new()),
-            command: Some("./src/mcp/mcp-sqlite.exe".to_string()),
-            args: Some(vec![]),
             timeout_secs: Some(30),
             client_name: "test-client".to_string(),
             client_version: "1.0.0".to_string(),
+            ..Default::default()
         };
         let init_result = manager.initialize_client(init_request).await;
         assert!(
@@ -278,6 +272,7 @@ mod tests {
         let request = FilterRequest {
             client_id: "test-client".to_string(),
             filter: None,
+            cursor: None,
         };
         let result = manager.list_tools(request).await;
 
@@ -295,6 +290,106 @@ mod tests {
         }
     }
 
+    // 测试工具的标题、标注、结果格式和图标
+    #[tokio::test]
+    async fn test_tool_metadata() {
+        let tool = parse_tool_info(&serde_json::json!({
+            "name": "delete_file",
+            "inputSchema": { "type": "object" },
+            "outputSchema": { "type": "object", "properties": { "deleted": { "type": "boolean" } } },
+            "annotations": { "title": "Delete file", "destructiveHint": true, "readOnlyHint": false },
+            "icons": [
+                { "src": "https://example.com/icon.png", "mimeType": "image/png", "sizes": ["48x48"] },
+                { "src": "data:image/svg+xml;base64,PHN2Zz4=", "sizes": "any" },
+                { "mimeType": "image/png" },
+            ],
+        }))
+        .unwrap();
+        assert_eq!(tool.description, "");
+        assert_eq!(tool.title.as_deref(), Some("Delete file"));
+        assert_eq!(
+            tool.result_schema.unwrap()["properties"]["deleted"]["type"],
+            "boolean"
+        );
+        let annotations = tool.annotations.unwrap();
+        assert_eq!(annotations.destructive_hint, Some(true));
+        assert_eq!(annotations.read_only_hint, Some(false));
+        assert_eq!(annotations.idempotent_hint, None);
+        assert_eq!(tool.icons.len(), 2);
+        assert_eq!(tool.icons[0].mime_type.as_deref(), Some("image/png"));
+        assert_eq!(tool.icons[1].sizes, vec!["any"]);
+        assert!(parse_tool_info(&serde_json::json!({ "description": "no name" })).is_none());
+
+        // 通过模拟服务器列出工具
+        let mut manager = McpClientManager::new();
+        let init_request = InitializeClientRequest {
+            id: "test-client".to_string(),
+            transport_type: TransportType::SSE,
+            sse_url: Some(mock_sse_url().await),
+            headers: Some(HashMap::new()),
+            timeout_secs: Some(30),
+            client_name: "test-client".to_string(),
+            client_version: "1.0.0".to_string(),
+            ..Default::default()
+        };
+        manager.initialize_client(init_request).await.unwrap();
+        let tools = manager
+            .list_tools(FilterRequest {
+                client_id: "test-client".to_string(),
+                filter: None,
+                cursor: None,
+            })
+            .await
+            .unwrap()
+            .data
+            .unwrap();
+        let echo = tools.iter().find(|tool| tool.name == "echo").unwrap();
+        assert_eq!(echo.title.as_deref(), Some("Echo"));
+        assert_eq!(
+            echo.annotations.as_ref().unwrap().read_only_hint,
+            Some(true)
+        );
+        assert!(echo.parameters_schema.is_some());
+        let slow = tools.iter().find(|tool| tool.name == "slow").unwrap();
+        assert!(slow.annotations.is_none() && slow.title.is_none());
+    }
+
+    // 测试过滤条件不作为分页游标发送，游标单独传给服务器
+    #[tokio::test]
+    async fn test_list_tools_cursor() {
+        let mut manager = McpClientManager::new();
+        let init_request = InitializeClientRequest {
+            id: "test-client".to_string(),
+            transport_type: TransportType::SSE,
+            sse_url: Some(mock_sse_url().await),
+            client_name: "test-client".to_string(),
+            client_version: "1.0.0".to_string(),
+            ..Default::default()
+        };
+        manager.initialize_client(init_request).await.unwrap();
+
+        let response = manager
+            .list_tools(FilterRequest {
+                client_id: "test-client".to_string(),
+                filter: Some(String::new()),
+                cursor: None,
+            })
+            .await
+            .unwrap();
+        assert!(response.success, "{:?}", response.error);
+
+        let response = manager
+            .list_tools(FilterRequest {
+                client_id: "test-client".to_string(),
+                filter: None,
+                cursor: Some("page-2".to_string()),
+            })
+            .await
+            .unwrap();
+        assert!(!response.success);
+        assert!(response.error.unwrap().contains("Invalid cursor"));
+    }
+
     // 更多测试用例...
     // 可以添加对 call_tool, list_resources, read_resource, list_prompts, get_prompt 等方法的测试
 }
@@ -1,7 +1,20 @@
-use crate::mcp::{client::AppState, types::*};
+use crate::mcp::{
+    batch,
+    client::AppState,
+    config_io, gateway,
+    inspector::{self, INSPECTOR_EVENT},
+    logging::{self, LoggingConfig},
+    metrics,
+    redact::{self, RedactionConfig},
+    resources::ResourceLimits,
+    server,
+    types::*,
+};
 use log;
+use std::path::PathBuf;
 use std::sync::Arc;
-use tauri::{command, State};
+use std::time::Duration;
+use tauri::{async_runtime, command, AppHandle, Emitter, State};
 
 /// 初始化 MCP 客户端
 #[command]
@@ -9,38 +22,38 @@ pub async fn initialize_mcp_client(
     state: State<'_, Arc<AppState>>,
     request: InitializeClientRequest,
 ) -> Result<ClientStatusResponse, String> {
-    let mut manager = state.mcp_client_manager.lock().await;
-    manager.initialize_client(request).await
+    state.initialize_client(request).await
 }
 
 /// 断开 MCP 客户端连接
 #[command]
 pub async fn disconnect_mcp_client(
     state: State<'_, Arc<AppState>>,
-    clientId: String,
+    client_id: String,
 ) -> Result<ClientStatusResponse, String> {
+    state.completions.clear_client(&client_id);
     let mut manager = state.mcp_client_manager.lock().await;
-    manager.disconnect_client(&clientId).await
+    manager.disconnect_client(&client_id).await
 }
 
 /// 删除 MCP 客户端
 #[command]
 pub async fn delete_mcp_client(
     state: State<'_, Arc<AppState>>,
-    clientId: String,
+    client_id: String,
 ) -> Result<(), String> {
     let mut manager = state.mcp_client_manager.lock().await;
-    manager.delete_client(&clientId).await
+    manager.delete_client(&client_id).await
 }
 
 /// 获取 MCP 客户端状态
 #[command]
 pub async fn get_mcp_client_status(
     state: State<'_, Arc<AppState>>,
-    clientId: String,
+    client_id: String,
 ) -> Result<ClientStatusResponse, String> {
     let manager = state.mcp_client_manager.lock().await;
-    manager.get_client_status(&clientId)
+    manager.get_client_status(&client_id)
 }
 
 /// 获取所有 MCP 客户端状态
@@ -56,10 +69,9 @@ pub async fn get_all_mcp_client_statuses(
 #[command]
 pub async fn mcp_repair_client(
     state: State<'_, Arc<AppState>>,
-    clientId: String,
+    client_id: String,
 ) -> Result<ClientStatusResponse, String> {
-    let mut manager = state.mcp_client_manager.lock().await;
-    manager.repair_client(&clientId).await
+    state.repair_client(&client_id).await
 }
 
 /// 列出工具
@@ -75,66 +87,459 @@ pub async fn list_mcp_tools(
 /// 调用工具
 #[command]
 pub async fn call_mcp_tool(
+    app: AppHandle,
     state: State<'_, Arc<AppState>>,
     request: ToolCallRequest,
 ) -> Result<McpResponse<serde_json::Value>, String> {
     use log::{debug, error, info};
 
-    // 添加标准输出，确保能看到
-    println!(
-        "=== [MCP Command] 接收到工具调用请求: {}, 客户端ID: {} ===",
-        request.tool_name, request.client_id
-    );
-
     info!(
         "[MCP Command] 接收到工具调用请求: {}, 客户端ID: {}",
         request.tool_name, request.client_id
     );
-    debug!("[MCP Command] 工具参数: {:?}", request.params);
+    debug!(
+        "[MCP Command] 工具参数: {:?}",
+        redact::redact_value(&request.params)
+    );
+
+    let result = state.call_tool_checked(Some(&app), request).await;
+    if let Err(err) = &result {
+        error!("[MCP Command] 工具调用过程出错: {}", err);
+    }
+    result
+}
+
+/// 批量调用工具
+///
+/// 调用可以属于不同的客户端并行执行，同一客户端同时执行的调用数受限。
+/// 每个调用完成时发送 `mcp-tool-batch-result` 事件，返回按请求顺序排列的结果。
+#[command]
+pub async fn call_mcp_tools_batch(
+    app: AppHandle,
+    state: State<'_, Arc<AppState>>,
+    request: ToolBatchRequest,
+) -> Result<ToolBatchResult, String> {
+    log::info!(
+        "[MCP Command] 接收到批量工具调用请求, 调用数: {}",
+        request.calls.len()
+    );
+    batch::call_tools(state.inner().clone(), Some(app), request).await
+}
+
+/// 列出已保存的服务器配置
+#[command]
+pub async fn list_mcp_servers(
+    state: State<'_, Arc<AppState>>,
+) -> Result<Vec<McpServerConfig>, String> {
+    Ok(state.server_registry.list())
+}
+
+/// 新增或更新服务器配置
+#[command]
+pub async fn save_mcp_server(
+    state: State<'_, Arc<AppState>>,
+    config: McpServerConfig,
+) -> Result<(), String> {
+    state.server_registry.save(config)
+}
+
+/// 删除服务器配置，不会断开已连接的客户端
+#[command]
+pub async fn delete_mcp_server(
+    state: State<'_, Arc<AppState>>,
+    server_id: String,
+) -> Result<(), String> {
+    state.server_registry.delete(&server_id)
+}
+
+/// 启用或禁用服务器的自动连接
+#[command]
+pub async fn set_mcp_server_enabled(
+    state: State<'_, Arc<AppState>>,
+    server_id: String,
+    enabled: bool,
+) -> Result<(), String> {
+    state.server_registry.set_enabled(&server_id, enabled)
+}
+
+/// 使用已保存的配置连接服务器
+#[command]
+pub async fn connect_mcp_server(
+    state: State<'_, Arc<AppState>>,
+    server_id: String,
+) -> Result<ClientStatusResponse, String> {
+    let config = state
+        .server_registry
+        .get(&server_id)
+        .ok_or_else(|| format!("Server with ID '{}' not found", server_id))?;
+    state.initialize_client(config.request).await
+}
+
+/// 从 Claude Desktop、Cursor 或 VS Code 的配置文件导入服务器
+///
+/// 未指定格式时根据文件路径推断；`save` 为 true 时将不重复的服务器保存到注册表。
+#[command]
+pub async fn import_mcp_servers(
+    state: State<'_, Arc<AppState>>,
+    path: String,
+    format: Option<McpConfigFormat>,
+    save: Option<bool>,
+) -> Result<McpImportResult, String> {
+    let path = PathBuf::from(path);
+    let format = format
+        .or_else(|| config_io::detect_format(&path))
+        .ok_or_else(|| "Cannot detect config format, please specify it".to_string())?;
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+    let servers = config_io::parse_servers(&content, format, &state.server_registry.list())?;
+    let mut saved = 0;
+    if save.unwrap_or(false) {
+        for server in servers
+            .iter()
+            .filter(|server| server.duplicate_of.is_none())
+        {
+            state.server_registry.save(server.config.clone())?;
+            saved += 1;
+        }
+    }
 
-    // 添加标准输出
-    println!("=== [MCP Command] 准备获取客户端管理器锁 ===");
+    Ok(McpImportResult {
+        format,
+        servers,
+        saved,
+    })
+}
+
+/// 将已保存的服务器导出为 Claude Desktop、Cursor 或 VS Code 的配置格式
+///
+/// 目标文件已存在时只写入导出的服务器，保留其中的其他服务器和设置。返回写入的服务器数量。
+#[command]
+pub async fn export_mcp_servers(
+    state: State<'_, Arc<AppState>>,
+    path: String,
+    format: Option<McpConfigFormat>,
+    server_ids: Option<Vec<String>>,
+) -> Result<usize, String> {
+    let path = PathBuf::from(path);
+    let format = format
+        .or_else(|| config_io::detect_format(&path))
+        .ok_or_else(|| "Cannot detect config format, please specify it".to_string())?;
+
+    let servers: Vec<McpServerConfig> = state
+        .server_registry
+        .list()
+        .into_iter()
+        .filter(|server| {
+            server_ids
+                .as_ref()
+                .is_none_or(|ids| ids.contains(&server.request.id))
+        })
+        .collect();
+
+    let existing = std::fs::read_to_string(&path).ok();
+    let (content, count) = config_io::render_servers(existing.as_deref(), &servers, format)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    std::fs::write(&path, content)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(count)
+}
 
+/// 响应服务器配置中 `${input:...}` 占位符的输入请求
+#[command]
+pub async fn respond_mcp_config_input(
+    state: State<'_, Arc<AppState>>,
+    response: InputResponse,
+) -> Result<(), String> {
+    state.placeholder_resolver.respond(response)
+}
+
+/// 清除会话内缓存的配置输入
+#[command]
+pub async fn clear_mcp_config_inputs(state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    state.placeholder_resolver.clear_inputs();
+    Ok(())
+}
+
+/// 保存密钥，可在服务器配置中以 `${secret:name}` 引用
+#[command]
+pub async fn set_mcp_secret(
+    state: State<'_, Arc<AppState>>,
+    name: String,
+    value: String,
+) -> Result<(), String> {
+    state.placeholder_resolver.secrets().set(&name, &value)
+}
+
+/// 删除密钥
+#[command]
+pub async fn delete_mcp_secret(
+    state: State<'_, Arc<AppState>>,
+    name: String,
+) -> Result<bool, String> {
+    state.placeholder_resolver.secrets().delete(&name)
+}
+
+/// 列出密钥名称，不返回密钥值
+#[command]
+pub async fn list_mcp_secrets(state: State<'_, Arc<AppState>>) -> Result<Vec<String>, String> {
+    Ok(state.placeholder_resolver.secrets().names())
+}
+
+/// 获取服务器已保存的 OAuth 令牌状态，未授权时返回 None
+#[command]
+pub async fn get_mcp_oauth_status(
+    state: State<'_, Arc<AppState>>,
+    server_id: String,
+) -> Result<Option<OAuthTokenStatus>, String> {
+    state.oauth_manager.token_status(&server_id)
+}
+
+/// 删除服务器已保存的 OAuth 令牌，下次连接时重新授权
+#[command]
+pub async fn logout_mcp_oauth(
+    state: State<'_, Arc<AppState>>,
+    server_id: String,
+) -> Result<bool, String> {
+    state.oauth_manager.logout(&server_id)
+}
+
+/// 在本机端口上启动 fishmind MCP 服务，端口未指定时随机选择
+#[command]
+pub async fn start_fishmind_mcp_server(
+    state: State<'_, Arc<AppState>>,
+    port: Option<u16>,
+) -> Result<FishmindServerStatus, String> {
+    server::start_sse_server(state.inner().clone(), port.unwrap_or(0)).await
+}
+
+/// 停止 fishmind MCP 服务
+#[command]
+pub async fn stop_fishmind_mcp_server(state: State<'_, Arc<AppState>>) -> Result<bool, String> {
+    Ok(server::stop_sse_server(&state))
+}
+
+/// 获取 fishmind MCP 服务状态，包含连接地址和访问令牌
+#[command]
+pub async fn get_fishmind_mcp_server_status(
+    state: State<'_, Arc<AppState>>,
+) -> Result<FishmindServerStatus, String> {
+    server::sse_server_status(&state)
+}
+
+/// 在本机端口上启动 MCP 网关，统一提供所有已连接服务器的工具、资源和提示
+#[command]
+pub async fn start_mcp_gateway(
+    app: AppHandle,
+    state: State<'_, Arc<AppState>>,
+    port: Option<u16>,
+) -> Result<FishmindServerStatus, String> {
+    gateway::start_gateway(state.inner().clone(), Some(app), port.unwrap_or(0)).await
+}
+
+/// 停止 MCP 网关
+#[command]
+pub async fn stop_mcp_gateway(state: State<'_, Arc<AppState>>) -> Result<bool, String> {
+    Ok(gateway::stop_gateway(&state))
+}
+
+/// 获取 MCP 网关状态，包含连接地址和访问令牌
+#[command]
+pub async fn get_mcp_gateway_status(
+    state: State<'_, Arc<AppState>>,
+) -> Result<FishmindServerStatus, String> {
+    gateway::gateway_status(&state)
+}
+
+/// 开始录制客户端会话，返回录制文件路径
+///
+/// 未指定路径时保存到应用数据目录的 recordings 目录下。服务器主动发送的请求和通知同样会被记录。
+#[command]
+pub async fn start_mcp_recording(
+    state: State<'_, Arc<AppState>>,
+    client_id: String,
+    path: Option<String>,
+) -> Result<String, String> {
     let manager = state.mcp_client_manager.lock().await;
+    manager.start_recording(&client_id, path)
+}
+
+/// 停止录制客户端会话，返回录制文件路径
+#[command]
+pub async fn stop_mcp_recording(
+    state: State<'_, Arc<AppState>>,
+    client_id: String,
+) -> Result<Option<String>, String> {
+    let manager = state.mcp_client_manager.lock().await;
+    manager.stop_recording(&client_id)
+}
+
+/// 补全提示参数或资源模板变量
+///
+/// 连续输入时只发送最后一次请求，被取代的请求返回 `superseded`。
+#[command]
+pub async fn complete_mcp_argument(
+    state: State<'_, Arc<AppState>>,
+    request: CompletionRequest,
+) -> Result<McpResponse<CompletionResult>, String> {
+    state.complete_argument(request).await
+}
 
-    // 添加标准输出
-    println!("=== [MCP Command] 已获取客户端管理器锁，准备调用工具 ===");
-    info!("[MCP Command] 已获取客户端管理器锁，准备调用工具");
-
-    // 添加标准输出
-    println!("=== [MCP Command] 调用 manager.call_tool 开始 ===");
-    let result = manager.call_tool(request).await;
-    println!("=== [MCP Command] 调用 manager.call_tool 完成 ===");
-
-    match &result {
-        Ok(response) => {
-            if response.success {
-                // 添加标准输出
-                println!("=== [MCP Command] 工具调用成功 ===");
-                info!("[MCP Command] 工具调用成功");
-                debug!("[MCP Command] 工具调用结果: {:?}", response.data);
-            } else {
-                // 添加标准输出
-                println!(
-                    "=== [MCP Command] 工具调用失败: {} ===",
-                    response.error.as_deref().unwrap_or("未知错误")
-                );
-                error!(
-                    "[MCP Command] 工具调用失败: {}",
-                    response.error.as_deref().unwrap_or("未知错误")
-                );
+/// 开启客户端的协议检查器
+///
+/// 每条线上消息以 `mcp-inspector-message` 事件发送给前端，直到关闭检查器。
+/// Stdio 连接上服务器主动发送的请求和通知不在追踪范围内。
+#[command]
+pub async fn start_mcp_inspector(
+    app: AppHandle,
+    state: State<'_, Arc<AppState>>,
+    client_id: String,
+) -> Result<(), String> {
+    let mut events = {
+        let manager = state.mcp_client_manager.lock().await;
+        manager.start_inspector(&client_id)?
+    };
+    async_runtime::spawn(async move {
+        while let Some(event) = events.recv().await {
+            if let Err(e) = app.emit(INSPECTOR_EVENT, &event) {
+                log::error!("[MCP Command] 发送协议检查器事件失败: {}", e);
+                break;
             }
         }
-        Err(err) => {
-            // 添加标准输出
-            println!("=== [MCP Command] 工具调用过程出错: {} ===", err);
-            error!("[MCP Command] 工具调用过程出错: {}", err);
-        }
-    }
+    });
+    Ok(())
+}
 
-    // 添加标准输出
-    println!("=== [MCP Command] 返回结果 ===");
-    result
+/// 关闭客户端的协议检查器
+#[command]
+pub async fn stop_mcp_inspector(
+    state: State<'_, Arc<AppState>>,
+    client_id: String,
+) -> Result<bool, String> {
+    let manager = state.mcp_client_manager.lock().await;
+    manager.stop_inspector(&client_id)
+}
+
+/// 向服务器发送任意方法的 JSON-RPC 请求，返回原始响应
+#[command]
+pub async fn send_raw_mcp_request(
+    app: AppHandle,
+    state: State<'_, Arc<AppState>>,
+    client_id: String,
+    method: String,
+    params: Option<serde_json::Value>,
+    timeout_secs: Option<u64>,
+) -> Result<McpResponse<serde_json::Value>, String> {
+    let timeout = Duration::from_secs(timeout_secs.unwrap_or(inspector::DEFAULT_RAW_TIMEOUT_SECS));
+    state
+        .send_raw_request(Some(&app), &client_id, &method, params, timeout)
+        .await
+}
+
+/// 获取客户端限流配置
+#[command]
+pub async fn get_mcp_client_limits(
+    state: State<'_, Arc<AppState>>,
+    client_id: String,
+) -> Result<LimitConfig, String> {
+    let manager = state.mcp_client_manager.lock().await;
+    manager.get_client_limits(&client_id)
+}
+
+/// 设置客户端限流配置，已保存的服务器同时更新注册表
+#[command]
+pub async fn set_mcp_client_limits(
+    state: State<'_, Arc<AppState>>,
+    client_id: String,
+    limits: LimitConfig,
+) -> Result<(), String> {
+    state.set_client_limits(&client_id, limits).await
+}
+
+/// 重置客户端熔断器
+#[command]
+pub async fn reset_mcp_circuit_breaker(
+    state: State<'_, Arc<AppState>>,
+    client_id: String,
+) -> Result<BreakerStatus, String> {
+    let manager = state.mcp_client_manager.lock().await;
+    manager.reset_circuit_breaker(&client_id)
+}
+
+/// 获取工具调用统计，未指定客户端时返回所有客户端
+#[command]
+pub async fn get_mcp_metrics(
+    state: State<'_, Arc<AppState>>,
+    client_id: Option<String>,
+) -> Result<Vec<ClientMetrics>, String> {
+    let manager = state.mcp_client_manager.lock().await;
+    manager.metrics(client_id.as_deref())
+}
+
+/// 将所有客户端的工具调用统计以 Prometheus 文本格式写入文件
+#[command]
+pub async fn export_mcp_metrics(
+    state: State<'_, Arc<AppState>>,
+    path: String,
+) -> Result<(), String> {
+    let clients = state.mcp_client_manager.lock().await.metrics(None)?;
+    metrics::write_prometheus(&PathBuf::from(path), &clients)
+}
+
+/// 获取工具结果缓存的配置、缓存条目和命中统计
+#[command]
+pub async fn get_mcp_tool_cache(
+    state: State<'_, Arc<AppState>>,
+) -> Result<ToolCacheSnapshot, String> {
+    let cache = state.mcp_client_manager.lock().await.tool_cache();
+    Ok(cache.snapshot())
+}
+
+/// 设置工具结果缓存配置
+#[command]
+pub async fn set_mcp_tool_cache_config(
+    state: State<'_, Arc<AppState>>,
+    config: ToolCacheConfig,
+) -> Result<(), String> {
+    let cache = state.mcp_client_manager.lock().await.tool_cache();
+    cache.set_config(config);
+    Ok(())
+}
+
+/// 获取资源读取限制和缓存清理策略
+#[command]
+pub async fn get_mcp_resource_limits(
+    state: State<'_, Arc<AppState>>,
+) -> Result<ResourceLimits, String> {
+    Ok(state.mcp_client_manager.lock().await.resource_limits())
+}
+
+/// 设置资源读取限制和缓存清理策略
+#[command]
+pub async fn set_mcp_resource_limits(
+    state: State<'_, Arc<AppState>>,
+    limits: ResourceLimits,
+) -> Result<(), String> {
+    state
+        .mcp_client_manager
+        .lock()
+        .await
+        .set_resource_limits(limits);
+    Ok(())
+}
+
+/// 清除缓存的工具结果，可以只清除某个客户端或某个工具，返回清除的数量
+#[command]
+pub async fn clear_mcp_tool_cache(
+    state: State<'_, Arc<AppState>>,
+    client_id: Option<String>,
+    tool_name: Option<String>,
+) -> Result<usize, String> {
+    let cache = state.mcp_client_manager.lock().await.tool_cache();
+    Ok(cache.clear(client_id.as_deref(), tool_name.as_deref()))
 }
 
 /// 列出资源
@@ -152,7 +557,7 @@ pub async fn list_mcp_resources(
 pub async fn read_mcp_resource(
     state: State<'_, Arc<AppState>>,
     request: ResourceReadRequest,
-) -> Result<McpResponse<serde_json::Value>, String> {
+) -> Result<McpResponse<Vec<ResourceContent>>, String> {
     let manager = state.mcp_client_manager.lock().await;
     manager.read_resource(request).await
 }
@@ -172,7 +577,139 @@ pub async fn list_mcp_prompts(
 pub async fn get_mcp_prompt(
     state: State<'_, Arc<AppState>>,
     request: PromptRequest,
-) -> Result<McpResponse<serde_json::Value>, String> {
+) -> Result<McpResponse<PromptResult>, String> {
     let manager = state.mcp_client_manager.lock().await;
     manager.get_prompt(request).await
 }
+
+/// 响应工具调用审批请求
+#[command]
+pub async fn respond_mcp_tool_approval(
+    state: State<'_, Arc<AppState>>,
+    response: ApprovalResponse,
+) -> Result<(), String> {
+    state.approval_manager.respond(response)
+}
+
+/// 响应服务器的结构化输入请求（`elicitation/create`）
+///
+/// 接受时内容不符合请求的格式会返回错误，请求继续等待修正后的输入。
+#[command]
+pub async fn respond_mcp_elicitation(
+    state: State<'_, Arc<AppState>>,
+    response: ElicitationResponse,
+) -> Result<(), String> {
+    state.elicitation_manager.respond(response)
+}
+
+/// 获取等待用户处理的结构化输入请求
+#[command]
+pub async fn list_mcp_elicitations(
+    state: State<'_, Arc<AppState>>,
+) -> Result<Vec<ElicitationRequestEvent>, String> {
+    Ok(state.elicitation_manager.pending())
+}
+
+/// 获取工具审批策略
+#[command]
+pub async fn get_mcp_approval_policy(
+    state: State<'_, Arc<AppState>>,
+) -> Result<ApprovalPolicy, String> {
+    Ok(state.approval_manager.get_policy())
+}
+
+/// 设置工具审批策略
+#[command]
+pub async fn set_mcp_approval_policy(
+    state: State<'_, Arc<AppState>>,
+    policy: ApprovalPolicy,
+) -> Result<(), String> {
+    state.approval_manager.set_policy(policy)
+}
+
+/// 清除会话内记住的审批决定
+#[command]
+pub async fn clear_mcp_approval_decisions(state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    state.approval_manager.clear_remembered();
+    Ok(())
+}
+
+/// 查询审计日志
+#[command]
+pub async fn query_mcp_audit_log(
+    state: State<'_, Arc<AppState>>,
+    query: AuditQuery,
+) -> Result<Vec<AuditEntry>, String> {
+    let audit_log = state.mcp_client_manager.lock().await.audit_log();
+    match audit_log {
+        Some(audit_log) => audit_log.query(&query),
+        None => Err("Audit log is not enabled".to_string()),
+    }
+}
+
+/// 导出审计日志到 JSONL 文件
+#[command]
+pub async fn export_mcp_audit_log(
+    state: State<'_, Arc<AppState>>,
+    path: String,
+    query: Option<AuditQuery>,
+) -> Result<usize, String> {
+    let audit_log = state.mcp_client_manager.lock().await.audit_log();
+    match audit_log {
+        Some(audit_log) => audit_log.export(&query.unwrap_or_default(), &PathBuf::from(path)),
+        None => Err("Audit log is not enabled".to_string()),
+    }
+}
+
+/// 获取审计日志保留策略
+#[command]
+pub async fn get_mcp_audit_retention(
+    state: State<'_, Arc<AppState>>,
+) -> Result<AuditRetention, String> {
+    let audit_log = state.mcp_client_manager.lock().await.audit_log();
+    match audit_log {
+        Some(audit_log) => Ok(audit_log.retention()),
+        None => Err("Audit log is not enabled".to_string()),
+    }
+}
+
+/// 设置审计日志保留策略
+#[command]
+pub async fn set_mcp_audit_retention(
+    state: State<'_, Arc<AppState>>,
+    retention: AuditRetention,
+) -> Result<(), String> {
+    let audit_log = state.mcp_client_manager.lock().await.audit_log();
+    match audit_log {
+        Some(audit_log) => audit_log.set_retention(retention),
+        None => Err("Audit log is not enabled".to_string()),
+    }
+}
+
+/// 获取日志脱敏配置
+#[command]
+pub async fn get_mcp_redaction_config() -> Result<RedactionConfig, String> {
+    Ok(redact::get_config())
+}
+
+/// 设置日志脱敏配置
+#[command]
+pub async fn set_mcp_redaction_config(config: RedactionConfig) -> Result<(), String> {
+    redact::set_config(config);
+    Ok(())
+}
+
+/// 获取日志配置，`json` 表示是否以 JSON 格式输出
+#[command]
+pub async fn get_mcp_logging_config() -> Result<LoggingConfig, String> {
+    Ok(logging::get_config())
+}
+
+/// 运行时调整日志级别
+///
+/// 输出格式不能在运行时切换：启动前设置环境变量 `FISHMIND_LOG_JSON=1`（或 `true`）
+/// 时以 JSON 格式输出，每行带有请求 span 的调用ID、客户端ID和 JSON-RPC 请求ID。
+#[command]
+pub async fn set_mcp_log_level(level: String) -> Result<(), String> {
+    logging::set_level(&level)
+}
@@ -1,28 +1,24 @@
 #[cfg(test)]
 mod tests {
-    use super::*;
-
-    use crate::mcp::client::{AppState, McpClientManager};
-    use crate::mcp::commands::{
-        call_mcp_tool, delete_mcp_client, disconnect_mcp_client, get_all_mcp_client_statuses,
-        get_mcp_client_status, initialize_mcp_client, list_mcp_prompts, list_mcp_resources,
-        list_mcp_tools, read_mcp_resource,
-    };
+    use crate::mcp::client::AppState;
+    use crate::mcp::mock_server::{start_sse, MockMcpServer};
     use crate::mcp::types::{
-        ClientStatus, ClientStatusResponse, FilterRequest, InitializeClientRequest, McpResponse,
-        PromptInfo, PromptRequest, ResourceInfo, ResourceReadRequest, ServerInfo, ToolCallRequest,
-        ToolInfo, TransportType,
+        ClientStatus, FilterRequest, InitializeClientRequest, LimitConfig, McpServerConfig,
+        ResourceReadRequest, ToolCallRequest, TransportType,
     };
     use std::collections::HashMap;
     use std::sync::Arc;
-    use tauri::State;
-    use tokio::sync::Mutex;
 
     // 创建测试用的 AppState
     fn create_test_app_state() -> Arc<AppState> {
-        Arc::new(AppState {
-            mcp_client_manager: Mutex::new(McpClientManager::new()),
-        })
+        Arc::new(AppState::new())
+    }
+
+    // 启动内置的模拟 MCP 服务器，返回 SSE 地址
+    async fn mock_sse_url() -> String {
+        start_sse(MockMcpServer::new(), "127.0.0.1:0")
+            .await
+            .unwrap()
     }
 
     // 测试 initialize_mcp_client 方法
@@ -34,14 +30,13 @@ mod tests {
         // 准备测试数据
         let request = InitializeClientRequest {
             id: client_id.clone(),
-            transport_type: TransportType::Stdio,
-            sse_url: None,
+            transport_type: TransportType::SSE,
+            sse_url: Some(mock_sse_url().await),
             headers: Some(HashMap::new()),
-            command: Some("./src/mcp/mcp-sqlite.exe".to_string()),
-            args: Some(vec![]),
             timeout_secs: Some(30),
             client_name: "test-client".to_string(),
             client_version: "1.0.0".to_string(),
+            ..Default::default()
         };
 
         // 执行测试
@@ -59,10 +54,11 @@ mod tests {
         let status = result.unwrap();
         assert_eq!(status.id, client_id);
         // 使用模式匹配检查状态
-        match status.status {
-            ClientStatus::Connected => assert!(true),
-            _ => assert!(false, "Expected Connected status, got {:?}", status.status),
-        }
+        assert!(
+            matches!(status.status, ClientStatus::Connected),
+            "Expected Connected status, got {:?}",
+            status.status
+        );
 
         assert!(status.connected_at.is_some());
         assert!(status.server_info.is_some());
@@ -77,14 +73,13 @@ mod tests {
         let client_id = "test-client".to_string();
         let init_request = InitializeClientRequest {
             id: client_id.clone(),
-            transport_type: TransportType::Stdio,
-            sse_url: None,
+            transport_type: TransportType::SSE,
+            sse_url: Some(mock_sse_url().await),
             headers: Some(HashMap::new()),
-            command: Some("./src/mcp/mcp-sqlite.exe".to_string()),
-            args: Some(vec![]),
             timeout_secs: Some(30),
             client_name: "test-client".to_string(),
             client_version: "1.0.0".to_string(),
+            ..Default::default()
         };
 
         {
@@ -106,14 +101,11 @@ mod tests {
             statuses.len()
         );
         assert_eq!(statuses[0].id, client_id);
-        match statuses[0].status {
-            ClientStatus::Connected => assert!(true),
-            _ => assert!(
-                false,
-                "Expected Connected status, got {:?}",
-                statuses[0].status
-            ),
-        }
+        assert!(
+            matches!(statuses[0].status, ClientStatus::Connected),
+            "Expected Connected status, got {:?}",
+            statuses[0].status
+        );
     }
 
     // 测试 disconnect_mcp_client 方法
@@ -125,14 +117,13 @@ mod tests {
         // 先初始化一个客户端
         let init_request = InitializeClientRequest {
             id: client_id.clone(),
-            transport_type: TransportType::Stdio,
-            sse_url: None,
+            transport_type: TransportType::SSE,
+            sse_url: Some(mock_sse_url().await),
             headers: Some(HashMap::new()),
-            command: Some("./src/mcp/mcp-sqlite.exe".to_string()),
-            args: Some(vec![]),
             timeout_secs: Some(30),
             client_name: "test-client".to_string(),
             client_version: "1.0.0".to_string(),
+            ..Default::default()
         };
 
         {
@@ -154,14 +145,11 @@ mod tests {
         );
         let status = result.unwrap();
         assert_eq!(status.id, client_id);
-        match status.status {
-            ClientStatus::Disconnected => assert!(true),
-            _ => assert!(
-                false,
-                "Expected Disconnected status, got {:?}",
-                status.status
-            ),
-        }
+        assert!(
+            matches!(status.status, ClientStatus::Disconnected),
+            "Expected Disconnected status, got {:?}",
+            status.status
+        );
     }
 
     // 测试 delete_mcp_client 方法
@@ -173,14 +161,13 @@ mod tests {
         // 先初始化一个客户端
         let init_request = InitializeClientRequest {
             id: client_id.clone(),
-            transport_type: TransportType::Stdio,
-            sse_url: None,
+            transport_type: TransportType::SSE,
+            sse_url: Some(mock_sse_url().await),
             headers: Some(HashMap::new()),
-            command: Some("./src/mcp/mcp-sqlite.exe".to_string()),
-            args: Some(vec![]),
             timeout_secs: Some(30),
             client_name: "test-client".to_string(),
             client_version: "1.0.0".to_string(),
+            ..Default::default()
         };
 
         {
@@ -218,14 +205,13 @@ mod tests {
         // 先初始化一个客户端
         let init_request = InitializeClientRequest {
             id: client_id.clone(),
-            transport_type: TransportType::Stdio,
-            sse_url: None,
+            transport_type: TransportType::SSE,
+            sse_url: Some(mock_sse_url().await),
             headers: Some(HashMap::new()),
-            command: Some("./src/mcp/mcp-sqlite.exe".to_string()),
-            args: Some(vec![]),
             timeout_secs: Some(30),
             client_name: "test-client".to_string(),
             client_version: "1.0.0".to_string(),
+            ..Default::default()
         };
 
         {
@@ -247,10 +233,11 @@ mod tests {
         );
         let status = result.unwrap();
         assert_eq!(status.id, client_id);
-        match status.status {
-            ClientStatus::Connected => assert!(true),
-            _ => assert!(false, "Expected Connected status, got {:?}", status.status),
-        }
+        assert!(
+            matches!(status.status, ClientStatus::Connected),
+            "Expected Connected status, got {:?}",
+            status.status
+        );
     }
 
     // 测试 list_mcp_tools 方法
@@ -262,14 +249,13 @@ mod tests {
         // 先初始化一个客户端
         let init_request = InitializeClientRequest {
             id: client_id.clone(),
-            transport_type: TransportType::Stdio,
-            sse_url: None,
+            transport_type: TransportType::SSE,
+            sse_url: Some(mock_sse_url().await),
             headers: Some(HashMap::new()),
-            command: Some("./src/mcp/mcp-sqlite.exe".to_string()),
-            args: Some(vec![]),
             timeout_secs: Some(30),
             client_name: "test-client".to_string(),
             client_version: "1.0.0".to_string(),
+            ..Default::default()
         };
 
         {
@@ -281,6 +267,7 @@ mod tests {
         let request = FilterRequest {
             client_id: client_id.clone(),
             filter: None,
+            cursor: None,
         };
 
         let result = {
@@ -301,14 +288,13 @@ mod tests {
         // 先初始化一个客户端
         let init_request = InitializeClientRequest {
             id: client_id.clone(),
-            transport_type: TransportType::Stdio,
-            sse_url: None,
+            transport_type: TransportType::SSE,
+            sse_url: Some(mock_sse_url().await),
             headers: Some(HashMap::new()),
-            command: Some("./src/mcp/mcp-sqlite.exe".to_string()),
-            args: Some(vec![]),
             timeout_secs: Some(30),
             client_name: "test-client".to_string(),
             client_version: "1.0.0".to_string(),
+            ..Default::default()
         };
 
         {
@@ -320,6 +306,7 @@ mod tests {
         let tools_request = FilterRequest {
             client_id: client_id.clone(),
             filter: None,
+            cursor: None,
         };
 
         let tools_result = {
@@ -359,14 +346,13 @@ mod tests {
         // 先初始化一个客户端
         let init_request = InitializeClientRequest {
             id: client_id.clone(),
-            transport_type: TransportType::Stdio,
-            sse_url: None,
+            transport_type: TransportType::SSE,
+            sse_url: Some(mock_sse_url().await),
             headers: Some(HashMap::new()),
-            command: Some("./src/mcp/mcp-sqlite.exe".to_string()),
-            args: Some(vec![]),
             timeout_secs: Some(30),
             client_name: "test-client".to_string(),
             client_version: "1.0.0".to_string(),
+            ..Default::default()
         };
 
         {
@@ -378,6 +364,7 @@ mod tests {
         let request = FilterRequest {
             client_id: client_id.clone(),
             filter: None,
+            cursor: None,
         };
 
         let result = {
@@ -402,14 +389,13 @@ mod tests {
         // 先初始化一个客户端
         let init_request = InitializeClientRequest {
             id: client_id.clone(),
-            transport_type: TransportType::Stdio,
-            sse_url: None,
+            transport_type: TransportType::SSE,
+            sse_url: Some(mock_sse_url().await),
             headers: Some(HashMap::new()),
-            command: Some("./src/mcp/mcp-sqlite.exe".to_string()),
-            args: Some(vec![]),
             timeout_secs: Some(30),
             client_name: "test-client".to_string(),
             client_version: "1.0.0".to_string(),
+            ..Default::default()
         };
 
         {
@@ -421,6 +407,7 @@ mod tests {
         let resources_request = FilterRequest {
             client_id: client_id.clone(),
             filter: None,
+            cursor: None,
         };
 
         let resources_result = {
@@ -463,14 +450,13 @@ mod tests {
         // 先初始化一个客户端
         let init_request = InitializeClientRequest {
             id: client_id.clone(),
-            transport_type: TransportType::Stdio,
-            sse_url: None,
+            transport_type: TransportType::SSE,
+            sse_url: Some(mock_sse_url().await),
             headers: Some(HashMap::new()),
-            command: Some("./src/mcp/mcp-sqlite.exe".to_string()),
-            args: Some(vec![]),
             timeout_secs: Some(30),
             client_name: "test-client".to_string(),
             client_version: "1.0.0".to_string(),
+            ..Default::default()
         };
 
         {
@@ -482,6 +468,7 @@ mod tests {
         let request = FilterRequest {
             client_id: client_id.clone(),
             filter: None,
+            cursor: None,
         };
 
         let result = {
@@ -492,4 +479,128 @@ mod tests {
         // 验证结果
         assert!(result.is_ok(), "Failed to list prompts: {:?}", result.err());
     }
+
+    // 连接过程中不持有管理器锁
+    #[tokio::test]
+    async fn test_initialize_does_not_hold_manager_lock() {
+        let app_state = create_test_app_state();
+
+        // 接受连接但从不响应的服务器
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut sockets = Vec::new();
+            while let Ok((socket, _)) = listener.accept().await {
+                sockets.push(socket);
+            }
+        });
+
+        let request = InitializeClientRequest {
+            id: "hanging".to_string(),
+            transport_type: TransportType::SSE,
+            sse_url: Some(format!("http://{}/sse", addr)),
+            client_name: "test-client".to_string(),
+            client_version: "1.0.0".to_string(),
+            ..Default::default()
+        };
+        let connecting = tokio::spawn({
+            let app_state = app_state.clone();
+            async move { app_state.initialize_client(request).await }
+        });
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+
+        let statuses = tokio::time::timeout(std::time::Duration::from_secs(1), async {
+            app_state
+                .mcp_client_manager
+                .lock()
+                .await
+                .get_all_client_statuses()
+        })
+        .await
+        .expect("manager lock is held while connecting");
+        assert!(statuses.is_empty());
+        assert!(!connecting.is_finished());
+        connecting.abort();
+    }
+
+    // 修复断开的客户端
+    #[tokio::test]
+    async fn test_repair_client_reconnects() {
+        let app_state = create_test_app_state();
+        let request = InitializeClientRequest {
+            id: "test-client".to_string(),
+            transport_type: TransportType::SSE,
+            sse_url: Some(mock_sse_url().await),
+            client_name: "test-client".to_string(),
+            client_version: "1.0.0".to_string(),
+            ..Default::default()
+        };
+        app_state.initialize_client(request.clone()).await.unwrap();
+        assert!(app_state.initialize_client(request).await.is_err());
+
+        app_state
+            .mcp_client_manager
+            .lock()
+            .await
+            .disconnect_client("test-client")
+            .await
+            .unwrap();
+        let status = app_state.repair_client("test-client").await.unwrap();
+        assert!(matches!(status.status, ClientStatus::Connected));
+        assert!(app_state.repair_client("missing").await.is_err());
+    }
+
+    // 测试更新限流配置后重连仍然生效，并写回注册表
+    #[tokio::test]
+    async fn test_set_client_limits_persisted() {
+        let app_state = create_test_app_state();
+        let request = InitializeClientRequest {
+            id: "test-client".to_string(),
+            transport_type: TransportType::SSE,
+            sse_url: Some(mock_sse_url().await),
+            client_name: "test-client".to_string(),
+            client_version: "1.0.0".to_string(),
+            ..Default::default()
+        };
+        app_state
+            .server_registry
+            .save(McpServerConfig {
+                name: None,
+                enabled: true,
+                request: request.clone(),
+            })
+            .unwrap();
+        app_state.initialize_client(request).await.unwrap();
+
+        let limits = LimitConfig {
+            max_concurrent: Some(2),
+            ..Default::default()
+        };
+        app_state
+            .set_client_limits("test-client", limits)
+            .await
+            .unwrap();
+        let saved = app_state.server_registry.get("test-client").unwrap();
+        assert_eq!(saved.request.limits.unwrap().max_concurrent, Some(2));
+
+        app_state
+            .mcp_client_manager
+            .lock()
+            .await
+            .disconnect_client("test-client")
+            .await
+            .unwrap();
+        app_state.repair_client("test-client").await.unwrap();
+        let limits = app_state
+            .mcp_client_manager
+            .lock()
+            .await
+            .get_client_limits("test-client")
+            .unwrap();
+        assert_eq!(limits.max_concurrent, Some(2));
+        assert!(app_state
+            .set_client_limits("missing", LimitConfig::default())
+            .await
+            .is_err());
+    }
 }
@@ -0,0 +1,189 @@
+use crate::mcp::types::{CompletionReference, CompletionRequest, CompletionResult};
+use log::debug;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 补全请求的默认防抖时间
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// 补全结果的默认缓存时间
+pub const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// 补全请求的超时时间
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+// MCP 规定单次补全最多返回 100 个候选值
+const MAX_VALUES: usize = 100;
+
+// 防抖按输入框区分: (客户端ID, 补全目标, 参数名)
+type DebounceKey = (String, CompletionReference, String);
+
+// 缓存还区分输入值和上下文参数
+type CacheKey = (
+    CompletionReference,
+    String,
+    String,
+    BTreeMap<String, String>,
+);
+
+// 单个客户端的缓存: 缓存键 -> (缓存时间, 结果)
+type ClientEntries = HashMap<CacheKey, (Instant, CompletionResult)>;
+
+/// 参数补全的防抖和缓存
+///
+/// 用户连续输入时只发送最后一次请求，之前的请求在防抖结束后返回 `superseded`。
+/// 结果按客户端缓存，断开客户端时清除。
+pub struct CompletionCache {
+    debounce: Duration,
+    ttl: Duration,
+    next_seq: AtomicU64,
+    latest: Mutex<HashMap<DebounceKey, u64>>,
+    entries: Mutex<HashMap<String, ClientEntries>>,
+}
+
+impl CompletionCache {
+    pub fn new(debounce: Duration, ttl: Duration) -> Self {
+        Self {
+            debounce,
+            ttl,
+            next_seq: AtomicU64::new(1),
+            latest: Mutex::new(HashMap::new()),
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 查找未过期的缓存结果
+    pub fn get(&self, request: &CompletionRequest) -> Option<CompletionResult> {
+        let entries = self.entries.lock().unwrap();
+        let (stored_at, result) = entries.get(&request.client_id)?.get(&cache_key(request))?;
+        (stored_at.elapsed() < self.ttl).then(|| CompletionResult {
+            cached: true,
+            ..result.clone()
+        })
+    }
+
+    /// 缓存补全结果
+    pub fn insert(&self, request: &CompletionRequest, result: &CompletionResult) {
+        let mut entries = self.entries.lock().unwrap();
+        let client = entries.entry(request.client_id.clone()).or_default();
+        client.retain(|_, (stored_at, _)| stored_at.elapsed() < self.ttl);
+        client.insert(cache_key(request), (Instant::now(), result.clone()));
+    }
+
+    /// 清除客户端的缓存结果
+    pub fn clear_client(&self, client_id: &str) {
+        self.entries.lock().unwrap().remove(client_id);
+        self.latest
+            .lock()
+            .unwrap()
+            .retain(|(id, _, _), _| id != client_id);
+    }
+
+    /// 等待防抖时间，期间同一输入框有更新的请求时返回 false
+    pub async fn debounce(&self, request: &CompletionRequest) -> bool {
+        let key = (
+            request.client_id.clone(),
+            request.reference.clone(),
+            request.argument_name.clone(),
+        );
+        let seq = self.next_seq.fetch_add(1, Ordering::SeqCst);
+        self.latest.lock().unwrap().insert(key.clone(), seq);
+
+        tokio::time::sleep(self.debounce).await;
+
+        let mut latest = self.latest.lock().unwrap();
+        if latest.get(&key) != Some(&seq) {
+            debug!(
+                "[MCP] 补全请求已被更新的请求取代: {}",
+                request.argument_name
+            );
+            return false;
+        }
+        latest.remove(&key);
+        true
+    }
+}
+
+impl Default for CompletionCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_DEBOUNCE, DEFAULT_CACHE_TTL)
+    }
+}
+
+fn cache_key(request: &CompletionRequest) -> CacheKey {
+    (
+        request.reference.clone(),
+        request.argument_name.clone(),
+        request.value.clone(),
+        request
+            .context
+            .clone()
+            .unwrap_or_default()
+            .into_iter()
+            .collect(),
+    )
+}
+
+/// 生成 `completion/complete` 请求参数
+pub fn completion_params(request: &CompletionRequest) -> Value {
+    let mut params = json!({
+        "ref": request.reference,
+        "argument": { "name": request.argument_name, "value": request.value },
+    });
+    if let Some(context) = &request.context {
+        params["context"] = json!({ "arguments": context });
+    }
+    params
+}
+
+/// 解析 `completion/complete` 响应，候选值按与输入值的匹配程度排序
+pub fn parse_completion(result: &Value, value: &str) -> Result<CompletionResult, String> {
+    let completion = result
+        .get("completion")
+        .ok_or_else(|| "Invalid completion response: missing 'completion'".to_string())?;
+    let values = completion
+        .get("values")
+        .and_then(Value::as_array)
+        .ok_or_else(|| "Invalid completion response: missing 'values'".to_string())?
+        .iter()
+        .filter_map(Value::as_str)
+        .map(str::to_string)
+        .collect();
+    Ok(CompletionResult {
+        values: rank(values, value),
+        total: completion.get("total").and_then(Value::as_u64),
+        has_more: completion
+            .get("hasMore")
+            .and_then(Value::as_bool)
+            .unwrap_or(false),
+        cached: false,
+        superseded: false,
+    })
+}
+
+/// 排序候选值: 前缀匹配优先，其次包含输入值，其余保持服务器返回的顺序
+///
+/// 比较和去重时都忽略大小写，只有大小写不同的候选值只保留第一个。
+pub fn rank(values: Vec<String>, value: &str) -> Vec<String> {
+    let needle = value.to_lowercase();
+    let mut seen = HashSet::new();
+    let mut values: Vec<String> = values
+        .into_iter()
+        .filter(|candidate| seen.insert(candidate.to_lowercase()))
+        .collect();
+    values.sort_by_key(|candidate| {
+        let candidate = candidate.to_lowercase();
+        if candidate.starts_with(&needle) {
+            0
+        } else if candidate.contains(&needle) {
+            1
+        } else {
+            2
+        }
+    });
+    values.truncate(MAX_VALUES);
+    values
+}
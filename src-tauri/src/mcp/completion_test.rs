@@ -0,0 +1,127 @@
+#[cfg(test)]
+mod tests {
+    use crate::mcp::completion::{parse_completion, rank};
+    use crate::mcp::mock_server::{connected_state, MockMcpServer};
+    use crate::mcp::types::{CompletionReference, CompletionRequest};
+    use serde_json::json;
+    use std::time::Duration;
+
+    fn completion(prompt: &str, argument: &str, value: &str) -> CompletionRequest {
+        CompletionRequest {
+            client_id: "mock".to_string(),
+            reference: CompletionReference::Prompt {
+                name: prompt.to_string(),
+            },
+            argument_name: argument.to_string(),
+            value: value.to_string(),
+            context: None,
+        }
+    }
+
+    // 测试候选值排序: 前缀匹配优先，其次包含，忽略大小写并去重
+    #[test]
+    fn test_rank() {
+        let values = ["Grace", "Ada", "Mara", "alan", "Ada", "ADA", "Bob"]
+            .iter()
+            .map(|v| v.to_string())
+            .collect();
+        assert_eq!(
+            rank(values, "A"),
+            vec!["Ada", "alan", "Grace", "Mara", "Bob"]
+        );
+    }
+
+    // 测试解析补全响应
+    #[test]
+    fn test_parse_completion() {
+        let result = parse_completion(
+            &json!({ "completion": { "values": ["casual", "formal"], "total": 10, "hasMore": true } }),
+            "f",
+        )
+        .unwrap();
+        assert_eq!(result.values, vec!["formal", "casual"]);
+        assert_eq!(result.total, Some(10));
+        assert!(result.has_more);
+
+        assert!(parse_completion(&json!({ "values": [] }), "").is_err());
+    }
+
+    // 测试补全结果排序并按客户端缓存
+    #[tokio::test]
+    async fn test_complete_and_cache() {
+        let server = MockMcpServer::new();
+        let state = connected_state(&[("mock", server.clone())]).await;
+
+        let first = state
+            .complete_argument(completion("greeting", "name", "a"))
+            .await
+            .unwrap();
+        assert!(first.success, "completion failed: {:?}", first.error);
+        let first = first.data.unwrap();
+        assert_eq!(first.values, vec!["Ada", "Alan", "Alice", "Grace"]);
+        assert!(!first.cached);
+
+        let second = state
+            .complete_argument(completion("greeting", "name", "a"))
+            .await
+            .unwrap()
+            .data
+            .unwrap();
+        assert!(second.cached);
+        assert_eq!(second.values, first.values);
+        assert_eq!(server.completion_requests(), 1);
+
+        // 清除缓存后重新请求服务器
+        state.completions.clear_client("mock");
+        state
+            .complete_argument(completion("greeting", "name", "a"))
+            .await
+            .unwrap();
+        assert_eq!(server.completion_requests(), 2);
+
+        let unknown = state
+            .complete_argument(completion("missing", "name", "a"))
+            .await
+            .unwrap();
+        assert!(!unknown.success);
+        assert!(unknown.error.is_some());
+    }
+
+    // 测试连续输入时只发送最后一次请求
+    #[tokio::test]
+    async fn test_debounce() {
+        let server = MockMcpServer::new();
+        let state = connected_state(&[("mock", server.clone())]).await;
+
+        let earlier = {
+            let state = state.clone();
+            tokio::spawn(async move {
+                state
+                    .complete_argument(completion("greeting", "name", "al"))
+                    .await
+            })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let latest = state
+            .complete_argument(completion("greeting", "name", "ali"))
+            .await
+            .unwrap()
+            .data
+            .unwrap();
+
+        let earlier = earlier.await.unwrap().unwrap().data.unwrap();
+        assert!(earlier.superseded);
+        assert!(earlier.values.is_empty());
+        assert!(!latest.superseded);
+        assert_eq!(latest.values, vec!["Alice"]);
+        assert_eq!(server.completion_requests(), 1);
+
+        // 不同参数的请求互不取代
+        let (name, style) = tokio::join!(
+            state.complete_argument(completion("greeting", "name", "gr")),
+            state.complete_argument(completion("greeting", "style", "f")),
+        );
+        assert_eq!(name.unwrap().data.unwrap().values, vec!["Grace"]);
+        assert_eq!(style.unwrap().data.unwrap().values, vec!["formal"]);
+    }
+}
@@ -0,0 +1,272 @@
+use crate::mcp::types::*;
+use log::{info, warn};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::path::Path;
+
+// 导入的服务器使用的客户端信息
+const CLIENT_NAME: &str = "fishmind";
+const CLIENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+// 可以转换为初始化请求的字段
+const SUPPORTED_FIELDS: &[&str] = &[
+    "command", "args", "env", "url", "type", "headers", "disabled",
+];
+
+/// 根据文件路径推断配置格式
+pub fn detect_format(path: &Path) -> Option<McpConfigFormat> {
+    let file_name = path.file_name()?.to_str()?;
+    if file_name == "claude_desktop_config.json" {
+        return Some(McpConfigFormat::ClaudeDesktop);
+    }
+
+    let parent = path.parent()?.file_name()?.to_str()?;
+    match (parent, file_name) {
+        (".cursor", "mcp.json") => Some(McpConfigFormat::Cursor),
+        (".vscode", "mcp.json") => Some(McpConfigFormat::Vscode),
+        _ => None,
+    }
+}
+
+// VS Code 使用 servers，其他格式使用 mcpServers
+fn servers_key(format: McpConfigFormat) -> &'static str {
+    match format {
+        McpConfigFormat::Vscode => "servers",
+        McpConfigFormat::ClaudeDesktop | McpConfigFormat::Cursor => "mcpServers",
+    }
+}
+
+/// 解析外部配置文件中的服务器配置
+///
+/// `existing` 为已保存的服务器，用于标记重复项。
+pub fn parse_servers(
+    content: &str,
+    format: McpConfigFormat,
+    existing: &[McpServerConfig],
+) -> Result<Vec<McpServerImport>, String> {
+    let root: Value =
+        serde_json::from_str(content).map_err(|e| format!("Invalid config file: {}", e))?;
+
+    // 容忍使用另一种键名的文件
+    let servers = root
+        .get(servers_key(format))
+        .or_else(|| root.get("mcpServers"))
+        .or_else(|| root.get("servers"))
+        .and_then(Value::as_object)
+        .ok_or_else(|| format!("No '{}' block found", servers_key(format)))?;
+
+    let mut imports: Vec<McpServerImport> = Vec::new();
+    for (name, entry) in servers {
+        let entry = entry
+            .as_object()
+            .ok_or_else(|| format!("Server '{}' is not an object", name))?;
+        let (config, unsupported_fields) = parse_entry(name, entry)?;
+
+        let duplicate_of = existing
+            .iter()
+            .chain(imports.iter().map(|import| &import.config))
+            .find(|other| is_duplicate(&config.request, &other.request))
+            .map(|other| other.request.id.clone());
+        if let Some(other) = &duplicate_of {
+            warn!("[MCP] 导入的服务器 {} 与 {} 重复", name, other);
+        }
+
+        imports.push(McpServerImport {
+            config,
+            duplicate_of,
+            unsupported_fields,
+        });
+    }
+
+    info!("[MCP] 解析服务器配置完成, 数量: {}", imports.len());
+    Ok(imports)
+}
+
+fn parse_entry(
+    name: &str,
+    entry: &Map<String, Value>,
+) -> Result<(McpServerConfig, Vec<String>), String> {
+    let mut unsupported_fields: Vec<String> = entry
+        .keys()
+        .filter(|key| !SUPPORTED_FIELDS.contains(&key.as_str()))
+        .cloned()
+        .collect();
+
+    let command = entry
+        .get("command")
+        .and_then(Value::as_str)
+        .map(String::from);
+    let url = entry.get("url").and_then(Value::as_str).map(String::from);
+    let transport_type = match entry.get("type").and_then(Value::as_str) {
+        Some("stdio") => TransportType::Stdio,
+        Some("sse") => TransportType::SSE,
+        // 目前只支持 SSE 远程传输，其他远程传输按 SSE 导入并提示
+        Some(other) if url.is_some() => {
+            unsupported_fields.push(format!("type={}", other));
+            TransportType::SSE
+        }
+        Some(other) => return Err(format!("Server '{}' has unknown type '{}'", name, other)),
+        None if command.is_some() => TransportType::Stdio,
+        None if url.is_some() => TransportType::SSE,
+        None => return Err(format!("Server '{}' needs either 'command' or 'url'", name)),
+    };
+
+    let args = entry.get("args").and_then(Value::as_array).map(|args| {
+        args.iter()
+            .map(|arg| match arg {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            })
+            .collect()
+    });
+
+    // Stdio 传输的环境变量和 SSE 传输的请求头都保存在 headers 中
+    let headers_field = match transport_type {
+        TransportType::Stdio => "env",
+        TransportType::SSE | TransportType::Replay => "headers",
+    };
+    for (field, present) in [
+        ("env", entry.contains_key("env")),
+        ("headers", entry.contains_key("headers")),
+    ] {
+        if present && field != headers_field {
+            unsupported_fields.push(field.to_string());
+        }
+    }
+    let headers = entry
+        .get(headers_field)
+        .and_then(Value::as_object)
+        .map(|map| {
+            map.iter()
+                .map(|(k, v)| {
+                    let v = match v {
+                        Value::String(s) => s.clone(),
+                        other => other.to_string(),
+                    };
+                    (k.clone(), v)
+                })
+                .collect::<HashMap<String, String>>()
+        });
+
+    let request = InitializeClientRequest {
+        id: name.to_string(),
+        transport_type,
+        sse_url: url,
+        command,
+        args,
+        headers,
+        client_name: CLIENT_NAME.to_string(),
+        client_version: CLIENT_VERSION.to_string(),
+        ..Default::default()
+    };
+
+    Ok((
+        McpServerConfig {
+            name: Some(name.to_string()),
+            enabled: !entry
+                .get("disabled")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+            request,
+        },
+        unsupported_fields,
+    ))
+}
+
+// ID 相同，或指向同一个命令/URL 的服务器视为重复
+fn is_duplicate(a: &InitializeClientRequest, b: &InitializeClientRequest) -> bool {
+    if a.id == b.id {
+        return true;
+    }
+    match (&a.transport_type, &b.transport_type) {
+        (TransportType::Stdio, TransportType::Stdio) => {
+            a.command == b.command
+                && a.args.clone().unwrap_or_default() == b.args.clone().unwrap_or_default()
+        }
+        (TransportType::SSE, TransportType::SSE) => a.sse_url == b.sse_url,
+        _ => false,
+    }
+}
+
+/// 将服务器配置写入外部配置格式
+///
+/// `existing` 为目标文件的原有内容，导出的服务器按名称写入服务器配置块，
+/// 同名的服务器被覆盖，块中的其他服务器和文件中的其他设置保持不变。
+/// 返回新的文件内容和写入的服务器数量。
+pub fn render_servers(
+    existing: Option<&str>,
+    servers: &[McpServerConfig],
+    format: McpConfigFormat,
+) -> Result<(String, usize), String> {
+    let mut root = match existing.map(str::trim).filter(|s| !s.is_empty()) {
+        Some(content) => serde_json::from_str::<Value>(content)
+            .map_err(|e| format!("Existing config file is not valid JSON: {}", e))?,
+        None => json!({}),
+    };
+    let root_map = root
+        .as_object_mut()
+        .ok_or_else(|| "Existing config file is not a JSON object".to_string())?;
+    let block = root_map
+        .entry(servers_key(format))
+        .or_insert_with(|| Value::Object(Map::new()))
+        .as_object_mut()
+        .ok_or_else(|| {
+            format!(
+                "'{}' in existing config is not an object",
+                servers_key(format)
+            )
+        })?;
+
+    let mut count = 0;
+    for server in servers {
+        let request = &server.request;
+        let mut entry = Map::new();
+
+        match request.transport_type {
+            TransportType::Stdio => {
+                if format == McpConfigFormat::Vscode {
+                    entry.insert("type".to_string(), json!("stdio"));
+                }
+                entry.insert("command".to_string(), json!(request.command));
+                entry.insert(
+                    "args".to_string(),
+                    json!(request.args.clone().unwrap_or_default()),
+                );
+                if let Some(env) = &request.headers {
+                    entry.insert("env".to_string(), json!(env));
+                }
+            }
+            TransportType::Replay => {
+                warn!("[MCP] 回放会话不能导出，跳过: {}", request.id);
+                continue;
+            }
+            TransportType::SSE => {
+                // Claude Desktop 的配置文件只支持本地命令
+                if format == McpConfigFormat::ClaudeDesktop {
+                    warn!(
+                        "[MCP] Claude Desktop 配置不支持远程服务器，跳过: {}",
+                        request.id
+                    );
+                    continue;
+                }
+                if format == McpConfigFormat::Vscode {
+                    entry.insert("type".to_string(), json!("sse"));
+                }
+                entry.insert("url".to_string(), json!(request.sse_url));
+                if let Some(headers) = &request.headers {
+                    entry.insert("headers".to_string(), json!(headers));
+                }
+            }
+        }
+
+        if !server.enabled && format != McpConfigFormat::Vscode {
+            entry.insert("disabled".to_string(), json!(true));
+        }
+        block.insert(request.id.clone(), Value::Object(entry));
+        count += 1;
+    }
+
+    let content = serde_json::to_string_pretty(&root)
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    Ok((content, count))
+}
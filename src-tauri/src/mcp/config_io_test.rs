@@ -0,0 +1,190 @@
+#[cfg(test)]
+mod tests {
+    use crate::mcp::config_io::{detect_format, parse_servers, render_servers};
+    use crate::mcp::types::{McpConfigFormat, TransportType};
+    use serde_json::Value;
+    use std::path::Path;
+
+    const CLAUDE_CONFIG: &str = r#"{
+        "globalShortcut": "Ctrl+Space",
+        "mcpServers": {
+            "sqlite": {
+                "command": "uvx",
+                "args": ["mcp-server-sqlite", "--db-path", "test.db"],
+                "env": { "LOG_LEVEL": "debug" }
+            },
+            "sqlite-copy": {
+                "command": "uvx",
+                "args": ["mcp-server-sqlite", "--db-path", "test.db"],
+                "cwd": "/tmp"
+            }
+        }
+    }"#;
+
+    const VSCODE_CONFIG: &str = r#"{
+        "inputs": [],
+        "servers": {
+            "github": {
+                "type": "http",
+                "url": "https://api.example.com/mcp",
+                "headers": { "Authorization": "Bearer ${input:token}" }
+            },
+            "local": {
+                "type": "sse",
+                "url": "http://localhost:8080/sse",
+                "env": { "IGNORED": "1" }
+            }
+        }
+    }"#;
+
+    // 测试根据路径推断格式
+    #[test]
+    fn test_detect_format() {
+        assert_eq!(
+            detect_format(Path::new(
+                "/home/u/.config/Claude/claude_desktop_config.json"
+            )),
+            Some(McpConfigFormat::ClaudeDesktop)
+        );
+        assert_eq!(
+            detect_format(Path::new("/work/.cursor/mcp.json")),
+            Some(McpConfigFormat::Cursor)
+        );
+        assert_eq!(
+            detect_format(Path::new("/work/.vscode/mcp.json")),
+            Some(McpConfigFormat::Vscode)
+        );
+        assert_eq!(detect_format(Path::new("/work/mcp.json")), None);
+    }
+
+    // 测试解析 Claude Desktop 配置，标记重复项和不支持的字段
+    #[test]
+    fn test_parse_claude_desktop() {
+        let servers = parse_servers(CLAUDE_CONFIG, McpConfigFormat::ClaudeDesktop, &[]).unwrap();
+        assert_eq!(servers.len(), 2);
+
+        let sqlite = servers
+            .iter()
+            .find(|s| s.config.request.id == "sqlite")
+            .unwrap();
+        assert!(matches!(
+            sqlite.config.request.transport_type,
+            TransportType::Stdio
+        ));
+        assert_eq!(sqlite.config.request.command.as_deref(), Some("uvx"));
+        assert_eq!(sqlite.config.request.args.as_ref().unwrap().len(), 3);
+        assert_eq!(
+            sqlite.config.request.headers.as_ref().unwrap()["LOG_LEVEL"],
+            "debug"
+        );
+        assert!(sqlite.unsupported_fields.is_empty());
+
+        let copy = servers
+            .iter()
+            .find(|s| s.config.request.id == "sqlite-copy")
+            .unwrap();
+        assert_eq!(copy.unsupported_fields, vec!["cwd".to_string()]);
+
+        // 两项中后解析的一项被标记为重复
+        assert_eq!(
+            servers.iter().filter(|s| s.duplicate_of.is_some()).count(),
+            1
+        );
+    }
+
+    // 测试解析 VS Code 配置中的远程服务器
+    #[test]
+    fn test_parse_vscode() {
+        let servers = parse_servers(VSCODE_CONFIG, McpConfigFormat::Vscode, &[]).unwrap();
+
+        let github = servers
+            .iter()
+            .find(|s| s.config.request.id == "github")
+            .unwrap();
+        assert!(matches!(
+            github.config.request.transport_type,
+            TransportType::SSE
+        ));
+        assert_eq!(
+            github.config.request.headers.as_ref().unwrap()["Authorization"],
+            "Bearer ${input:token}"
+        );
+        assert_eq!(github.unsupported_fields, vec!["type=http".to_string()]);
+
+        let local = servers
+            .iter()
+            .find(|s| s.config.request.id == "local")
+            .unwrap();
+        assert_eq!(local.unsupported_fields, vec!["env".to_string()]);
+    }
+
+    // 测试与已保存服务器重复
+    #[test]
+    fn test_duplicate_of_existing() {
+        let existing = parse_servers(CLAUDE_CONFIG, McpConfigFormat::ClaudeDesktop, &[])
+            .unwrap()
+            .into_iter()
+            .map(|s| s.config)
+            .collect::<Vec<_>>();
+
+        let servers =
+            parse_servers(CLAUDE_CONFIG, McpConfigFormat::Cursor, &existing[..1]).unwrap();
+        assert!(servers.iter().all(|s| s.duplicate_of.is_some()));
+    }
+
+    // 测试导出时保留原文件的其他设置
+    #[test]
+    fn test_render_round_trip() {
+        let servers = parse_servers(VSCODE_CONFIG, McpConfigFormat::Vscode, &[])
+            .unwrap()
+            .into_iter()
+            .map(|s| s.config)
+            .collect::<Vec<_>>();
+
+        let (content, count) =
+            render_servers(Some(CLAUDE_CONFIG), &servers, McpConfigFormat::Cursor).unwrap();
+        assert_eq!(count, 2);
+        let root: Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(root["globalShortcut"], "Ctrl+Space");
+        assert_eq!(
+            root["mcpServers"]["local"]["url"],
+            "http://localhost:8080/sse"
+        );
+
+        // Claude Desktop 不支持远程服务器
+        let (_, count) = render_servers(None, &servers, McpConfigFormat::ClaudeDesktop).unwrap();
+        assert_eq!(count, 0);
+
+        let (content, _) = render_servers(None, &servers, McpConfigFormat::Vscode).unwrap();
+        let reparsed = parse_servers(&content, McpConfigFormat::Vscode, &[]).unwrap();
+        assert_eq!(reparsed.len(), 2);
+    }
+
+    // 测试导出时保留目标文件中的其他服务器
+    #[test]
+    fn test_render_keeps_foreign_servers() {
+        let servers = parse_servers(CLAUDE_CONFIG, McpConfigFormat::ClaudeDesktop, &[])
+            .unwrap()
+            .into_iter()
+            .map(|s| s.config)
+            .filter(|config| config.request.id == "sqlite")
+            .collect::<Vec<_>>();
+        let existing = r#"{
+            "mcpServers": {
+                "foreign": { "command": "node", "args": ["server.js"] },
+                "sqlite": { "command": "old" }
+            }
+        }"#;
+
+        let (content, count) =
+            render_servers(Some(existing), &servers, McpConfigFormat::ClaudeDesktop).unwrap();
+        assert_eq!(count, 1);
+        let root: Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(root["mcpServers"]["foreign"]["command"], "node");
+        assert_eq!(root["mcpServers"]["foreign"]["args"][0], "server.js");
+        assert_eq!(root["mcpServers"]["sqlite"]["command"], "uvx");
+
+        let invalid = r#"{ "mcpServers": [] }"#;
+        assert!(render_servers(Some(invalid), &servers, McpConfigFormat::ClaudeDesktop).is_err());
+    }
+}
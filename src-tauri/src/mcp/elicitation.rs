@@ -0,0 +1,327 @@
+use crate::mcp::http_sse::ServerRequestHandler;
+use crate::mcp::server_transport::{RpcResult, INTERNAL_ERROR, INVALID_PARAMS, METHOD_NOT_FOUND};
+use crate::mcp::types::*;
+use async_trait::async_trait;
+use log::{info, warn};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::{oneshot, Notify};
+
+/// 结构化输入请求事件名
+pub const ELICITATION_REQUEST_EVENT: &str = "mcp-elicitation-request";
+
+/// 等待用户输入的默认超时时间
+pub const DEFAULT_ELICITATION_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// 等待用户超时的 JSON-RPC 错误码
+pub const REQUEST_TIMEOUT: i64 = -32001;
+
+struct PendingElicitation {
+    event: ElicitationRequestEvent,
+    sender: oneshot::Sender<ElicitationResponse>,
+}
+
+// 客户端等待用户输入的状态，工具调用据此暂停超时计时
+#[derive(Default)]
+struct UserWait {
+    pending: usize,
+    finished_at: Option<Instant>,
+}
+
+/// 服务器结构化输入请求（`elicitation/create`）管理器
+///
+/// 请求以事件发送给前端并挂起，直到前端通过 `respond_mcp_elicitation` 返回结果或等待超时，
+/// 超时后向服务器返回错误。
+pub struct ElicitationManager {
+    app: OnceLock<AppHandle>,
+    pending: Mutex<HashMap<String, PendingElicitation>>,
+    waits: Mutex<HashMap<String, UserWait>>,
+    // 客户端的等待状态变化时通知
+    waits_changed: Notify,
+    next_id: AtomicU64,
+    timeout: Duration,
+}
+
+impl ElicitationManager {
+    /// 创建管理器
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            app: OnceLock::new(),
+            pending: Mutex::new(HashMap::new()),
+            waits: Mutex::new(HashMap::new()),
+            waits_changed: Notify::new(),
+            next_id: AtomicU64::new(1),
+            timeout,
+        }
+    }
+
+    /// 设置应用句柄，用于向前端发送请求事件
+    pub fn set_app_handle(&self, app: AppHandle) {
+        let _ = self.app.set(app);
+    }
+
+    /// 为客户端创建传输层使用的服务器请求处理器
+    pub fn handler(self: &Arc<Self>, client_id: &str) -> Arc<dyn ServerRequestHandler> {
+        Arc::new(ElicitationHandler {
+            client_id: client_id.to_string(),
+            manager: self.clone(),
+        })
+    }
+
+    /// 等待用户处理的请求，前端重新加载后用于恢复输入对话框
+    pub fn pending(&self) -> Vec<ElicitationRequestEvent> {
+        let mut pending: Vec<ElicitationRequestEvent> = self
+            .pending
+            .lock()
+            .unwrap()
+            .values()
+            .map(|pending| pending.event.clone())
+            .collect();
+        pending.sort_by(|a, b| a.request_id.cmp(&b.request_id));
+        pending
+    }
+
+    /// 客户端是否有等待用户处理的请求
+    pub fn is_waiting(&self, client_id: &str) -> bool {
+        self.waits
+            .lock()
+            .unwrap()
+            .get(client_id)
+            .is_some_and(|wait| wait.pending > 0)
+    }
+
+    /// 客户端最近一次等待用户输入结束的时间
+    pub fn last_finished(&self, client_id: &str) -> Option<Instant> {
+        self.waits.lock().unwrap().get(client_id)?.finished_at
+    }
+
+    /// 等待客户端的请求全部处理完毕
+    pub async fn wait_idle(&self, client_id: &str) {
+        loop {
+            let changed = self.waits_changed.notified();
+            if !self.is_waiting(client_id) {
+                return;
+            }
+            changed.await;
+        }
+    }
+
+    /// 向用户请求结构化输入，返回 `elicitation/create` 的结果
+    pub async fn request(&self, client_id: &str, params: &Value) -> RpcResult {
+        let message = params
+            .get("message")
+            .and_then(Value::as_str)
+            .ok_or((INVALID_PARAMS, "Missing elicitation message".to_string()))?;
+        let requested_schema = params
+            .get("requestedSchema")
+            .filter(|schema| schema.get("type").and_then(Value::as_str) == Some("object"))
+            .ok_or((
+                INVALID_PARAMS,
+                "Requested schema must be an object schema".to_string(),
+            ))?;
+
+        let request_id = format!(
+            "elicitation-{}",
+            self.next_id.fetch_add(1, Ordering::SeqCst)
+        );
+        let event = ElicitationRequestEvent {
+            request_id: request_id.clone(),
+            client_id: client_id.to_string(),
+            message: message.to_string(),
+            requested_schema: requested_schema.clone(),
+        };
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(
+            request_id.clone(),
+            PendingElicitation {
+                event: event.clone(),
+                sender: tx,
+            },
+        );
+        self.waits
+            .lock()
+            .unwrap()
+            .entry(client_id.to_string())
+            .or_default()
+            .pending += 1;
+        // 传输关闭或超时时移除请求
+        let _guard = PendingGuard {
+            manager: self,
+            client_id,
+            request_id: &request_id,
+        };
+
+        info!(
+            "[MCP] 等待用户输入结构化数据, 请求ID: {}, 客户端ID: {}",
+            request_id, client_id
+        );
+        if let Some(app) = self.app.get() {
+            app.emit(ELICITATION_REQUEST_EVENT, &event).map_err(|e| {
+                (
+                    INTERNAL_ERROR,
+                    format!("Failed to emit elicitation request: {}", e),
+                )
+            })?;
+        }
+
+        let response = match tokio::time::timeout(self.timeout, rx).await {
+            Ok(Ok(response)) => response,
+            Ok(Err(_)) => {
+                return Err((
+                    INTERNAL_ERROR,
+                    format!("Elicitation request '{}' was cancelled", request_id),
+                ))
+            }
+            Err(_) => {
+                warn!("[MCP] 等待用户输入超时, 请求ID: {}", request_id);
+                return Err((
+                    REQUEST_TIMEOUT,
+                    "Elicitation request timed out waiting for the user".to_string(),
+                ));
+            }
+        };
+
+        info!(
+            "[MCP] 用户处理结构化输入请求: {:?}, 请求ID: {}",
+            response.action, request_id
+        );
+        Ok(match response.action {
+            ElicitationAction::Accept => json!({
+                "action": response.action,
+                "content": response.content.unwrap_or_else(|| json!({})),
+            }),
+            action => json!({ "action": action }),
+        })
+    }
+
+    /// 处理前端返回的结果
+    ///
+    /// 接受时按请求的格式校验内容，校验失败时请求继续等待，前端可以修正后重新提交。
+    pub fn respond(&self, mut response: ElicitationResponse) -> Result<(), String> {
+        let mut pending = self.pending.lock().unwrap();
+        let entry = pending.get(&response.request_id).ok_or_else(|| {
+            format!(
+                "Elicitation request '{}' not found or already handled",
+                response.request_id
+            )
+        })?;
+
+        if response.action == ElicitationAction::Accept {
+            let content =
+                validate_content(&entry.event.requested_schema, response.content.as_ref())?;
+            response.content = Some(content);
+        } else {
+            response.content = None;
+        }
+
+        let entry = pending.remove(&response.request_id).unwrap();
+        entry.sender.send(response).map_err(|r| {
+            format!(
+                "Elicitation request '{}' is no longer waiting",
+                r.request_id
+            )
+        })
+    }
+}
+
+impl Default for ElicitationManager {
+    fn default() -> Self {
+        Self::new(DEFAULT_ELICITATION_TIMEOUT)
+    }
+}
+
+struct PendingGuard<'a> {
+    manager: &'a ElicitationManager,
+    client_id: &'a str,
+    request_id: &'a str,
+}
+
+impl Drop for PendingGuard<'_> {
+    fn drop(&mut self) {
+        self.manager.pending.lock().unwrap().remove(self.request_id);
+        if let Some(wait) = self.manager.waits.lock().unwrap().get_mut(self.client_id) {
+            wait.pending -= 1;
+            wait.finished_at = Some(Instant::now());
+        }
+        self.manager.waits_changed.notify_waiters();
+    }
+}
+
+/// 按请求的格式校验用户输入
+///
+/// MCP 只允许扁平的对象，属性为字符串（可带 enum）、数字、整数或布尔值；
+/// 不允许格式中未声明的属性，必填属性不能为空。
+pub fn validate_content(schema: &Value, content: Option<&Value>) -> Result<Value, String> {
+    let content = content
+        .and_then(Value::as_object)
+        .ok_or_else(|| "Accepted elicitation content must be an object".to_string())?;
+    let empty = Map::new();
+    let properties = schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .unwrap_or(&empty);
+
+    let mut validated = Map::new();
+    for (name, value) in content {
+        if value.is_null() {
+            continue;
+        }
+        let property = properties
+            .get(name)
+            .ok_or_else(|| format!("Unknown field '{}'", name))?;
+        let valid = match property.get("type").and_then(Value::as_str) {
+            Some("string") => {
+                value.is_string()
+                    && property
+                        .get("enum")
+                        .and_then(Value::as_array)
+                        .is_none_or(|options| options.contains(value))
+            }
+            Some("number") => value.is_number(),
+            Some("integer") => value.is_i64() || value.is_u64(),
+            Some("boolean") => value.is_boolean(),
+            _ => true,
+        };
+        if !valid {
+            return Err(format!("Invalid value for field '{}'", name));
+        }
+        validated.insert(name.clone(), value.clone());
+    }
+
+    let missing: Vec<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .filter(|name| !validated.contains_key(*name))
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!("Missing required fields: {}", missing.join(", ")));
+    }
+    Ok(Value::Object(validated))
+}
+
+// 单个客户端的服务器请求处理器
+struct ElicitationHandler {
+    client_id: String,
+    manager: Arc<ElicitationManager>,
+}
+
+#[async_trait]
+impl ServerRequestHandler for ElicitationHandler {
+    fn capabilities(&self) -> Value {
+        json!({ "elicitation": {} })
+    }
+
+    async fn handle(&self, method: &str, params: Value) -> RpcResult {
+        match method {
+            "elicitation/create" => self.manager.request(&self.client_id, &params).await,
+            other => Err((METHOD_NOT_FOUND, format!("Method not found: {}", other))),
+        }
+    }
+}
@@ -0,0 +1,337 @@
+#[cfg(test)]
+mod tests {
+    use crate::mcp::client::McpClientManager;
+    use crate::mcp::elicitation::{validate_content, ElicitationManager, REQUEST_TIMEOUT};
+    #[cfg(unix)]
+    use crate::mcp::mock_server::stdio_bridge;
+    use crate::mcp::mock_server::{start_sse, MockMcpServer};
+    use crate::mcp::types::{
+        ElicitationAction, ElicitationRequestEvent, ElicitationResponse, InitializeClientRequest,
+        ToolCallRequest, TransportType,
+    };
+    use serde_json::{json, Value};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": { "type": "string" },
+                "format": { "type": "string", "enum": ["json", "csv"] },
+                "retries": { "type": "integer" },
+                "overwrite": { "type": "boolean" },
+            },
+            "required": ["path"],
+        })
+    }
+
+    fn params() -> Value {
+        json!({ "message": "Where should the file be saved?", "requestedSchema": schema() })
+    }
+
+    fn response(
+        request_id: &str,
+        action: ElicitationAction,
+        content: Option<Value>,
+    ) -> ElicitationResponse {
+        ElicitationResponse {
+            request_id: request_id.to_string(),
+            action,
+            content,
+        }
+    }
+
+    // 等待请求出现在待处理列表中
+    async fn wait_pending(manager: &ElicitationManager) -> ElicitationRequestEvent {
+        for _ in 0..200 {
+            if let Some(event) = manager.pending().into_iter().next() {
+                return event;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("elicitation request was not received");
+    }
+
+    // 测试按请求的格式校验输入
+    #[test]
+    fn test_validate_content() {
+        let schema = schema();
+        let content =
+            json!({ "path": "/tmp/a.json", "format": "csv", "retries": 3, "overwrite": null });
+        assert_eq!(
+            validate_content(&schema, Some(&content)).unwrap(),
+            json!({ "path": "/tmp/a.json", "format": "csv", "retries": 3 })
+        );
+
+        let invalid = [
+            (json!({ "format": "json" }), "Missing required fields: path"),
+            (
+                json!({ "path": "a", "format": "xml" }),
+                "Invalid value for field 'format'",
+            ),
+            (
+                json!({ "path": "a", "retries": 1.5 }),
+                "Invalid value for field 'retries'",
+            ),
+            (
+                json!({ "path": "a", "mode": "fast" }),
+                "Unknown field 'mode'",
+            ),
+            (
+                json!(["a"]),
+                "Accepted elicitation content must be an object",
+            ),
+        ];
+        for (content, expected) in invalid {
+            assert_eq!(
+                validate_content(&schema, Some(&content)).unwrap_err(),
+                expected
+            );
+        }
+        assert!(validate_content(&schema, None).is_err());
+    }
+
+    // 测试接受、拒绝和校验失败后重新提交
+    #[tokio::test]
+    async fn test_request_and_respond() {
+        let manager = Arc::new(ElicitationManager::default());
+
+        let waiting = manager.clone();
+        let handle = tokio::spawn(async move { waiting.request("mock", &params()).await });
+        let event = wait_pending(&manager).await;
+        assert_eq!(event.client_id, "mock");
+        assert_eq!(event.message, "Where should the file be saved?");
+        assert_eq!(event.requested_schema, schema());
+
+        // 校验失败时请求继续等待
+        let invalid = manager.respond(response(
+            &event.request_id,
+            ElicitationAction::Accept,
+            Some(json!({ "format": "json" })),
+        ));
+        assert!(invalid.is_err());
+        assert_eq!(manager.pending().len(), 1);
+
+        manager
+            .respond(response(
+                &event.request_id,
+                ElicitationAction::Accept,
+                Some(json!({ "path": "/tmp/a.json" })),
+            ))
+            .unwrap();
+        assert_eq!(
+            handle.await.unwrap().unwrap(),
+            json!({ "action": "accept", "content": { "path": "/tmp/a.json" } })
+        );
+        assert!(manager.pending().is_empty());
+
+        // 拒绝时不返回内容
+        let waiting = manager.clone();
+        let handle = tokio::spawn(async move { waiting.request("mock", &params()).await });
+        let event = wait_pending(&manager).await;
+        manager
+            .respond(response(
+                &event.request_id,
+                ElicitationAction::Decline,
+                Some(json!({ "path": "ignored" })),
+            ))
+            .unwrap();
+        assert_eq!(
+            handle.await.unwrap().unwrap(),
+            json!({ "action": "decline" })
+        );
+
+        // 无效的请求参数
+        let err = manager
+            .request(
+                "mock",
+                &json!({ "message": "hi", "requestedSchema": { "type": "string" } }),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(err.0, -32602);
+    }
+
+    // 测试等待超时后向服务器返回错误并移除请求
+    #[tokio::test]
+    async fn test_request_timeout() {
+        let manager = ElicitationManager::new(Duration::from_millis(50));
+        let (code, message) = manager.request("mock", &params()).await.unwrap_err();
+        assert_eq!(code, REQUEST_TIMEOUT);
+        assert!(message.contains("timed out"));
+        assert!(manager.pending().is_empty());
+
+        let late = manager.respond(response("elicitation-1", ElicitationAction::Cancel, None));
+        assert!(late.is_err());
+    }
+
+    // 测试工具调用过程中服务器向用户请求输入
+    #[tokio::test]
+    async fn test_elicitation_during_tool_call() {
+        let elicitation = Arc::new(ElicitationManager::default());
+        let url = start_sse(MockMcpServer::new(), "127.0.0.1:0")
+            .await
+            .unwrap();
+
+        let mut manager = McpClientManager::new();
+        let request = |id: &str| InitializeClientRequest {
+            id: id.to_string(),
+            transport_type: TransportType::SSE,
+            sse_url: Some(url.clone()),
+            headers: Some(HashMap::new()),
+            timeout_secs: Some(30),
+            client_name: "elicitation-test".to_string(),
+            client_version: "1.0.0".to_string(),
+            ..Default::default()
+        };
+        let confirm = |id: &str| ToolCallRequest {
+            client_id: id.to_string(),
+            tool_name: "confirm".to_string(),
+            params: json!({}),
+        };
+
+        // 未设置管理器时不声明 elicitation 能力
+        manager.initialize_client(request("plain")).await.unwrap();
+        let unsupported = manager.call_tool(confirm("plain")).await.unwrap();
+        assert_eq!(unsupported.data.unwrap()["isError"], true);
+
+        manager.set_elicitation(elicitation.clone());
+        manager.initialize_client(request("mock")).await.unwrap();
+
+        let user = async {
+            let event = wait_pending(&elicitation).await;
+            assert_eq!(event.client_id, "mock");
+            elicitation
+                .respond(response(
+                    &event.request_id,
+                    ElicitationAction::Accept,
+                    Some(json!({ "path": "/tmp/out.json", "format": "json" })),
+                ))
+                .unwrap();
+        };
+        let (result, _) = tokio::join!(manager.call_tool(confirm("mock")), user);
+        let result = result.unwrap();
+        assert!(result.success, "confirm failed: {:?}", result.error);
+        let data = result.data.unwrap();
+        assert_eq!(data["isError"], false);
+        assert_eq!(data["content"][0]["text"], "saved to /tmp/out.json");
+
+        let user = async {
+            let event = wait_pending(&elicitation).await;
+            elicitation
+                .respond(response(&event.request_id, ElicitationAction::Cancel, None))
+                .unwrap();
+        };
+        let (result, _) = tokio::join!(manager.call_tool(confirm("mock")), user);
+        assert_eq!(
+            result.unwrap().data.unwrap()["content"][0]["text"],
+            "cancel"
+        );
+    }
+
+    // 测试工具调用按配置超时，等待用户输入期间不计时
+    #[tokio::test]
+    async fn test_call_timeout_paused_during_elicitation() {
+        let elicitation = Arc::new(ElicitationManager::default());
+        let mut manager = McpClientManager::new();
+        manager.set_elicitation(elicitation.clone());
+        let request = InitializeClientRequest {
+            id: "mock".to_string(),
+            transport_type: TransportType::SSE,
+            sse_url: Some(
+                start_sse(MockMcpServer::new(), "127.0.0.1:0")
+                    .await
+                    .unwrap(),
+            ),
+            headers: Some(HashMap::new()),
+            timeout_secs: Some(1),
+            client_name: "elicitation-test".to_string(),
+            client_version: "1.0.0".to_string(),
+            ..Default::default()
+        };
+        manager.initialize_client(request).await.unwrap();
+
+        let slow = manager
+            .call_tool(ToolCallRequest {
+                client_id: "mock".to_string(),
+                tool_name: "slow".to_string(),
+                params: json!({ "ms": 1500 }),
+            })
+            .await
+            .unwrap();
+        assert!(!slow.success);
+
+        // 用户在超时时间之后才提交输入
+        let user = async {
+            let event = wait_pending(&elicitation).await;
+            tokio::time::sleep(Duration::from_millis(1500)).await;
+            elicitation
+                .respond(response(
+                    &event.request_id,
+                    ElicitationAction::Accept,
+                    Some(json!({ "path": "/tmp/late.json" })),
+                ))
+                .unwrap();
+        };
+        let confirm = ToolCallRequest {
+            client_id: "mock".to_string(),
+            tool_name: "confirm".to_string(),
+            params: json!({}),
+        };
+        let (result, _) = tokio::join!(manager.call_tool(confirm), user);
+        let result = result.unwrap();
+        assert!(result.success, "confirm failed: {:?}", result.error);
+        assert_eq!(
+            result.data.unwrap()["content"][0]["text"],
+            "saved to /tmp/late.json"
+        );
+        assert!(!elicitation.is_waiting("mock"));
+    }
+
+    // 测试 Stdio 服务器同样可以请求用户输入
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_elicitation_over_stdio() {
+        let elicitation = Arc::new(ElicitationManager::default());
+        let mut manager = McpClientManager::new();
+        manager.set_elicitation(elicitation.clone());
+        let (command, args) = stdio_bridge(MockMcpServer::new()).await;
+        let request = InitializeClientRequest {
+            id: "stdio".to_string(),
+            transport_type: TransportType::Stdio,
+            command: Some(command),
+            args: Some(args),
+            timeout_secs: Some(30),
+            client_name: "elicitation-test".to_string(),
+            client_version: "1.0.0".to_string(),
+            ..Default::default()
+        };
+        manager.initialize_client(request).await.unwrap();
+
+        let user = async {
+            let event = wait_pending(&elicitation).await;
+            assert_eq!(event.client_id, "stdio");
+            elicitation
+                .respond(response(
+                    &event.request_id,
+                    ElicitationAction::Accept,
+                    Some(json!({ "path": "/tmp/stdio.json" })),
+                ))
+                .unwrap();
+        };
+        let confirm = ToolCallRequest {
+            client_id: "stdio".to_string(),
+            tool_name: "confirm".to_string(),
+            params: json!({}),
+        };
+        let (result, _) = tokio::join!(manager.call_tool(confirm), user);
+        let result = result.unwrap();
+        assert!(result.success, "confirm failed: {:?}", result.error);
+        assert_eq!(
+            result.data.unwrap()["content"][0]["text"],
+            "saved to /tmp/stdio.json"
+        );
+    }
+}
@@ -0,0 +1,456 @@
+use crate::mcp::client::AppState;
+use crate::mcp::prompts;
+use crate::mcp::resources;
+use crate::mcp::server::{local_service_status, start_local_service, stop_local_service};
+use crate::mcp::server_transport::{
+    self, rpc_response, McpMessageHandler, RpcResult, INTERNAL_ERROR, INVALID_PARAMS,
+    METHOD_NOT_FOUND, PROTOCOL_VERSION, RESOURCE_NOT_FOUND,
+};
+use crate::mcp::types::{
+    ClientStatus, FilterRequest, FishmindServerStatus, McpResponse, PromptRequest,
+    ResourceReadRequest, ToolCallRequest, ToolInfo,
+};
+use async_trait::async_trait;
+use log::{debug, info, warn};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::AppHandle;
+use tokio::sync::mpsc;
+
+/// 以网关模式启动的命令行参数
+///
+/// `fishmind --mcp-gateway` 连接所有已启用的服务器，并通过 stdio 统一对外提供，
+/// `--data-dir <目录>` 指定应用数据目录。该模式下没有界面，需要询问的工具调用会在审批超时后被拒绝。
+pub const GATEWAY_FLAG: &str = "--mcp-gateway";
+
+/// 网关访问令牌在密钥存储中的名称
+pub const ACCESS_TOKEN_SECRET: &str = "fishmind_mcp_gateway_token";
+
+/// 工具和提示名称中客户端ID与原名称之间的分隔符
+pub const NAME_SEPARATOR: &str = "__";
+
+/// 网关资源 URI 前缀，完整格式为 `fishmind-gateway://<客户端ID>/<原 URI>`
+pub const RESOURCE_PREFIX: &str = "fishmind-gateway://";
+
+/// 聚合所有已连接服务器的 MCP 网关
+///
+/// 工具和提示以 `<客户端ID>__<原名称>` 命名，资源 URI 加上客户端前缀，
+/// 调用按名称转发到对应的客户端。工具调用经过与界面调用相同的审批、限流、熔断和审计。
+pub struct McpGateway {
+    state: Arc<AppState>,
+    // 用于发送审批询问事件，未设置时需要询问的调用会在审批超时后被拒绝
+    app: Option<AppHandle>,
+}
+
+impl McpGateway {
+    pub fn new(state: Arc<AppState>, app: Option<AppHandle>) -> Arc<Self> {
+        Arc::new(Self { state, app })
+    }
+
+    // 已连接的客户端ID，按ID排序保证列表顺序稳定
+    async fn connected_clients(&self) -> Vec<String> {
+        let manager = self.state.mcp_client_manager.lock().await;
+        let mut ids: Vec<String> = manager
+            .get_all_client_statuses()
+            .into_iter()
+            .filter(|status| matches!(status.status, ClientStatus::Connected))
+            .map(|status| status.id)
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    async fn list_tools(&self) -> RpcResult {
+        let mut tools = Vec::new();
+        for client_id in self.connected_clients().await {
+            let response = self
+                .state
+                .mcp_client_manager
+                .lock()
+                .await
+                .list_tools(filter(&client_id))
+                .await;
+            // 单个服务器失败不影响其他服务器
+            let Some(client_tools) = successful(&client_id, "tools", response) else {
+                continue;
+            };
+            tools.extend(
+                client_tools
+                    .into_iter()
+                    .map(|tool| gateway_tool(&client_id, tool)),
+            );
+        }
+        Ok(json!({ "tools": tools }))
+    }
+
+    async fn call_tool(&self, params: &Value) -> RpcResult {
+        let name = params
+            .get("name")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let (client_id, tool_name) = self.resolve_name(name).await?;
+        debug!("[MCP] 网关转发工具调用: {} -> {}", name, client_id);
+
+        let request = ToolCallRequest {
+            client_id,
+            tool_name,
+            params: params.get("arguments").cloned().unwrap_or(json!({})),
+        };
+        let response = self
+            .state
+            .call_tool_checked(self.app.as_ref(), request)
+            .await;
+
+        // 审批拒绝、熔断等失败作为工具错误返回，由调用方的模型处理
+        Ok(match response {
+            Ok(McpResponse {
+                success: true,
+                data: Some(result),
+                ..
+            }) => result,
+            Ok(response) => tool_error(&response.error.unwrap_or_default()),
+            Err(e) => tool_error(&e),
+        })
+    }
+
+    async fn list_resources(&self) -> RpcResult {
+        let mut resources = Vec::new();
+        for client_id in self.connected_clients().await {
+            let response = self
+                .state
+                .mcp_client_manager
+                .lock()
+                .await
+                .list_resources(filter(&client_id))
+                .await;
+            let Some(client_resources) = successful(&client_id, "resources", response) else {
+                continue;
+            };
+            resources.extend(client_resources.into_iter().map(|resource| {
+                json!({
+                    "uri": gateway_uri(&client_id, &resource.uri),
+                    "name": format!("{}: {}", client_id, resource.uri),
+                    "description": resource.description,
+                    "mimeType": resource.content_type,
+                })
+            }));
+        }
+        Ok(json!({ "resources": resources }))
+    }
+
+    async fn read_resource(&self, params: &Value) -> RpcResult {
+        let uri = params
+            .get("uri")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let (client_id, resource_uri) = self.resolve_uri(uri).await?;
+
+        let response = self
+            .state
+            .mcp_client_manager
+            .lock()
+            .await
+            .read_resource(ResourceReadRequest {
+                client_id: client_id.clone(),
+                resource_uri,
+            })
+            .await;
+        let mut result =
+            resources::to_mcp_contents(&forwarded(response)?).map_err(|e| (INTERNAL_ERROR, e))?;
+
+        // 返回内容中的 URI 换回网关 URI，与请求保持一致
+        if let Some(contents) = result.get_mut("contents").and_then(Value::as_array_mut) {
+            for content in contents {
+                if let Some(original) = content.get("uri").and_then(Value::as_str) {
+                    content["uri"] = json!(gateway_uri(&client_id, original));
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    async fn list_prompts(&self) -> RpcResult {
+        let mut prompts = Vec::new();
+        for client_id in self.connected_clients().await {
+            let response = self
+                .state
+                .mcp_client_manager
+                .lock()
+                .await
+                .list_prompts(filter(&client_id))
+                .await;
+            let Some(client_prompts) = successful(&client_id, "prompts", response) else {
+                continue;
+            };
+            prompts.extend(client_prompts.into_iter().map(|prompt| {
+                json!({
+                    "name": namespaced(&client_id, &prompt.name),
+                    "description": prompt.description,
+                    "arguments": prompt.arguments,
+                })
+            }));
+        }
+        Ok(json!({ "prompts": prompts }))
+    }
+
+    async fn get_prompt(&self, params: &Value) -> RpcResult {
+        let name = params
+            .get("name")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let (client_id, prompt_name) = self.resolve_name(name).await?;
+
+        let response = self
+            .state
+            .mcp_client_manager
+            .lock()
+            .await
+            .get_prompt(PromptRequest {
+                client_id,
+                prompt_name,
+                params: params.get("arguments").cloned().unwrap_or(json!({})),
+            })
+            .await;
+        Ok(prompts::to_mcp_prompt(&forwarded(response)?))
+    }
+
+    // 把 `<客户端ID>__<原名称>` 拆分为客户端ID和原名称
+    //
+    // 客户端ID本身可能包含分隔符，因此按已连接的客户端匹配，取最长的前缀。
+    async fn resolve_name(&self, name: &str) -> Result<(String, String), (i64, String)> {
+        self.connected_clients()
+            .await
+            .into_iter()
+            .filter_map(|client_id| {
+                let rest = name
+                    .strip_prefix(&client_id)?
+                    .strip_prefix(NAME_SEPARATOR)?;
+                Some((client_id.clone(), rest.to_string()))
+            })
+            .max_by_key(|(client_id, _)| client_id.len())
+            .ok_or_else(|| (INVALID_PARAMS, format!("Unknown tool or prompt: {}", name)))
+    }
+
+    // 把网关资源 URI 拆分为客户端ID和原 URI
+    async fn resolve_uri(&self, uri: &str) -> Result<(String, String), (i64, String)> {
+        let not_found = || (RESOURCE_NOT_FOUND, format!("Resource not found: {}", uri));
+        let rest = uri.strip_prefix(RESOURCE_PREFIX).ok_or_else(not_found)?;
+        self.connected_clients()
+            .await
+            .into_iter()
+            .filter_map(|client_id| {
+                let original = rest.strip_prefix(&client_id)?.strip_prefix('/')?;
+                Some((client_id.clone(), original.to_string()))
+            })
+            .max_by_key(|(client_id, _)| client_id.len())
+            .ok_or_else(not_found)
+    }
+}
+
+#[async_trait]
+impl McpMessageHandler for McpGateway {
+    async fn handle_message(
+        &self,
+        message: Value,
+        _outbound: &mpsc::UnboundedSender<Value>,
+    ) -> Option<Value> {
+        let method = message.get("method")?.as_str()?.to_string();
+        let id = message.get("id").cloned();
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        debug!("[MCP] 网关收到: {}", method);
+
+        let result = match method.as_str() {
+            "initialize" => Ok(json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": {
+                    "tools": { "listChanged": false },
+                    "resources": { "listChanged": false, "subscribe": false },
+                    "prompts": { "listChanged": false },
+                },
+                "serverInfo": { "name": "fishmind-gateway", "version": env!("CARGO_PKG_VERSION") },
+                "instructions": "Tools, resources and prompts of all MCP servers connected in fishmind, prefixed with the server ID",
+            })),
+            "ping" => Ok(json!({})),
+            "tools/list" => self.list_tools().await,
+            "tools/call" => self.call_tool(&params).await,
+            "resources/list" => self.list_resources().await,
+            "resources/read" => self.read_resource(&params).await,
+            "prompts/list" => self.list_prompts().await,
+            "prompts/get" => self.get_prompt(&params).await,
+            _ if id.is_none() => return None,
+            other => Err((METHOD_NOT_FOUND, format!("Method not found: {}", other))),
+        };
+        rpc_response(id, result)
+    }
+}
+
+fn filter(client_id: &str) -> FilterRequest {
+    FilterRequest {
+        client_id: client_id.to_string(),
+        filter: None,
+        cursor: None,
+    }
+}
+
+fn namespaced(client_id: &str, name: &str) -> String {
+    format!("{}{}{}", client_id, NAME_SEPARATOR, name)
+}
+
+/// 转换为网关 `tools/list` 中的工具定义
+///
+/// 保留服务器提供的标题、标注、结果格式和图标，调用方的模型和审批逻辑依赖这些信息。
+pub fn gateway_tool(client_id: &str, tool: ToolInfo) -> Value {
+    let mut definition = json!({
+        "name": namespaced(client_id, &tool.name),
+        "description": tool.description,
+        "inputSchema": tool
+            .parameters_schema
+            .unwrap_or_else(|| json!({ "type": "object" })),
+    });
+    if let Some(title) = tool.title {
+        definition["title"] = json!(title);
+    }
+    if let Some(schema) = tool.result_schema {
+        definition["outputSchema"] = schema;
+    }
+    if let Some(annotations) = tool.annotations {
+        let fields = [
+            ("title", annotations.title.map(Value::from)),
+            ("readOnlyHint", annotations.read_only_hint.map(Value::from)),
+            (
+                "destructiveHint",
+                annotations.destructive_hint.map(Value::from),
+            ),
+            (
+                "idempotentHint",
+                annotations.idempotent_hint.map(Value::from),
+            ),
+            (
+                "openWorldHint",
+                annotations.open_world_hint.map(Value::from),
+            ),
+        ];
+        definition["annotations"] = fields
+            .into_iter()
+            .filter_map(|(name, value)| Some((name.to_string(), value?)))
+            .collect::<serde_json::Map<_, _>>()
+            .into();
+    }
+    if !tool.icons.is_empty() {
+        definition["icons"] = tool
+            .icons
+            .into_iter()
+            .map(|icon| {
+                let mut value = json!({ "src": icon.src });
+                if let Some(mime_type) = icon.mime_type {
+                    value["mimeType"] = json!(mime_type);
+                }
+                if !icon.sizes.is_empty() {
+                    value["sizes"] = json!(icon.sizes);
+                }
+                value
+            })
+            .collect();
+    }
+    definition
+}
+
+fn gateway_uri(client_id: &str, uri: &str) -> String {
+    format!("{}{}/{}", RESOURCE_PREFIX, client_id, uri)
+}
+
+fn tool_error(message: &str) -> Value {
+    json!({ "content": [{ "type": "text", "text": message }], "isError": true })
+}
+
+// 列表请求失败时记录警告并跳过该客户端
+fn successful<T>(
+    client_id: &str,
+    kind: &str,
+    response: Result<McpResponse<Vec<T>>, String>,
+) -> Option<Vec<T>> {
+    let error = match response {
+        Ok(McpResponse {
+            success: true,
+            data,
+            ..
+        }) => return Some(data.unwrap_or_default()),
+        Ok(response) => response.error.unwrap_or_default(),
+        Err(e) => e,
+    };
+    warn!(
+        "[MCP] 网关无法列出 {}, 客户端ID: {}, 错误: {}",
+        kind, client_id, error
+    );
+    None
+}
+
+// 转发请求的失败作为 JSON-RPC 错误返回
+fn forwarded<T>(response: Result<McpResponse<T>, String>) -> Result<T, (i64, String)> {
+    match response {
+        Ok(McpResponse {
+            success: true,
+            data: Some(data),
+            ..
+        }) => Ok(data),
+        Ok(response) => Err((INTERNAL_ERROR, response.error.unwrap_or_default())),
+        Err(e) => Err((INTERNAL_ERROR, e)),
+    }
+}
+
+/// 在本机端口上启动网关 SSE 服务，已在运行时返回当前状态
+pub async fn start_gateway(
+    state: Arc<AppState>,
+    app: Option<AppHandle>,
+    port: u16,
+) -> Result<FishmindServerStatus, String> {
+    let handler = McpGateway::new(state.clone(), app);
+    start_local_service(
+        &state,
+        &state.mcp_gateway,
+        ACCESS_TOKEN_SECRET,
+        handler,
+        port,
+    )
+    .await
+}
+
+/// 停止网关 SSE 服务，未运行时返回 false
+pub fn stop_gateway(state: &AppState) -> bool {
+    stop_local_service(&state.mcp_gateway)
+}
+
+/// 网关 SSE 服务状态
+pub fn gateway_status(state: &AppState) -> Result<FishmindServerStatus, String> {
+    local_service_status(state, &state.mcp_gateway, ACCESS_TOKEN_SECRET)
+}
+
+/// 以网关模式运行进程，参数见 [`GATEWAY_FLAG`]
+///
+/// 标准输出用于协议消息，不能写日志。
+pub fn run_from_args(args: Vec<String>) {
+    let data_dir = args
+        .iter()
+        .position(|arg| arg == "--data-dir")
+        .and_then(|index| args.get(index + 1))
+        .map(PathBuf::from)
+        .or_else(crate::mcp::server::default_data_dir);
+    let Some(data_dir) = data_dir else {
+        eprintln!("Cannot determine the fishmind data directory, use --data-dir");
+        return;
+    };
+
+    let runtime = tokio::runtime::Runtime::new().expect("failed to start tokio runtime");
+    runtime.block_on(async {
+        let state = Arc::new(AppState::with_data_dir(data_dir));
+        state.auto_connect_servers().await;
+        info!("[MCP] 网关通过 stdio 提供服务");
+        server_transport::serve_io(
+            McpGateway::new(state, None),
+            tokio::io::stdin(),
+            tokio::io::stdout(),
+        )
+        .await;
+    });
+}
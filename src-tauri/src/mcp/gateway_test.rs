@@ -0,0 +1,202 @@
+#[cfg(test)]
+mod tests {
+    use crate::mcp::client::parse_tool_info;
+    use crate::mcp::client::AppState;
+    use crate::mcp::gateway::{
+        gateway_status, gateway_tool, start_gateway, stop_gateway, McpGateway,
+    };
+    use crate::mcp::mock_server::{connected_state, MockMcpServer};
+    use crate::mcp::server_transport::McpMessageHandler;
+    use crate::mcp::types::{ApprovalMode, ApprovalPolicy};
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+    use tokio::sync::mpsc;
+
+    // 连接两个模拟服务器，其中一个客户端ID包含名称分隔符
+    async fn connected_gateway_state() -> Arc<AppState> {
+        connected_state(&[
+            ("mock", MockMcpServer::new()),
+            ("mock__two", MockMcpServer::new()),
+        ])
+        .await
+    }
+
+    async fn call(gateway: &McpGateway, method: &str, params: Value) -> Value {
+        let (tx, _rx) = mpsc::unbounded_channel();
+        gateway
+            .handle_message(
+                json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }),
+                &tx,
+            )
+            .await
+            .unwrap()
+    }
+
+    fn names(response: &Value, list: &str) -> Vec<String> {
+        response["result"][list]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| {
+                item.get("name")
+                    .or_else(|| item.get("uri"))
+                    .and_then(Value::as_str)
+                    .unwrap()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    // 测试工具按客户端ID加前缀，并转发到对应的客户端
+    #[tokio::test]
+    async fn test_namespaced_tools() {
+        let gateway = McpGateway::new(connected_gateway_state().await, None);
+
+        let list = call(&gateway, "tools/list", json!({})).await;
+        let tools = names(&list, "tools");
+        assert!(tools.contains(&"mock__echo".to_string()));
+        assert!(tools.contains(&"mock__two__echo".to_string()));
+        // 服务器提供的标题和标注原样转发
+        let echo = list["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|tool| tool["name"] == "mock__echo")
+            .unwrap();
+        assert_eq!(echo["title"], "Echo");
+        assert_eq!(
+            echo["annotations"],
+            json!({ "title": "Echo", "readOnlyHint": true, "openWorldHint": false })
+        );
+
+        for name in ["mock__echo", "mock__two__echo"] {
+            let result = call(
+                &gateway,
+                "tools/call",
+                json!({ "name": name, "arguments": { "text": name } }),
+            )
+            .await;
+            assert_eq!(result["result"]["content"][0]["text"], name);
+        }
+
+        let unknown = call(
+            &gateway,
+            "tools/call",
+            json!({ "name": "missing__echo", "arguments": {} }),
+        )
+        .await;
+        assert_eq!(unknown["error"]["code"], -32602);
+    }
+
+    // 测试审批策略在网关转发时生效
+    #[tokio::test]
+    async fn test_approval_applied() {
+        let state = connected_gateway_state().await;
+        state
+            .approval_manager
+            .set_policy(ApprovalPolicy {
+                default_mode: ApprovalMode::Deny,
+                ..ApprovalPolicy::default()
+            })
+            .unwrap();
+        let gateway = McpGateway::new(state, None);
+
+        let denied = call(
+            &gateway,
+            "tools/call",
+            json!({ "name": "mock__echo", "arguments": { "text": "hi" } }),
+        )
+        .await;
+        assert_eq!(denied["result"]["isError"], true);
+        assert!(denied["result"]["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .contains("denied"));
+    }
+
+    // 测试资源 URI 和提示名称加前缀后可以读取
+    #[tokio::test]
+    async fn test_resources_and_prompts() {
+        let gateway = McpGateway::new(connected_gateway_state().await, None);
+
+        let resources = names(
+            &call(&gateway, "resources/list", json!({})).await,
+            "resources",
+        );
+        assert_eq!(resources.len(), 4);
+
+        let uri = "fishmind-gateway://mock__two/mock://text/hello";
+        let read = call(&gateway, "resources/read", json!({ "uri": uri })).await;
+        assert_eq!(read["result"]["contents"][0]["uri"], uri);
+        assert!(read["result"]["contents"][0]["text"]
+            .as_str()
+            .unwrap()
+            .contains("Hello from the mock server"));
+
+        let missing = call(
+            &gateway,
+            "resources/read",
+            json!({ "uri": "mock://text/hello" }),
+        )
+        .await;
+        assert_eq!(missing["error"]["code"], -32002);
+
+        let prompts = names(&call(&gateway, "prompts/list", json!({})).await, "prompts");
+        assert_eq!(prompts, vec!["mock__greeting", "mock__two__greeting"]);
+
+        let prompt = call(
+            &gateway,
+            "prompts/get",
+            json!({ "name": "mock__greeting", "arguments": { "name": "Ada" } }),
+        )
+        .await;
+        assert!(prompt["result"].to_string().contains("Hello, Ada!"));
+    }
+
+    // 测试网关 SSE 服务的启动和停止
+    #[tokio::test]
+    async fn test_gateway_service() {
+        let state = Arc::new(AppState::new());
+
+        let status = start_gateway(state.clone(), None, 0).await.unwrap();
+        assert!(status.running);
+        assert!(status.access_token.is_some());
+
+        let rejected = reqwest::get(status.url.unwrap()).await.unwrap();
+        assert_eq!(rejected.status(), 401);
+
+        assert!(stop_gateway(&state));
+        assert!(!gateway_status(&state).unwrap().running);
+    }
+
+    // 测试工具定义保留结果格式和图标
+    #[test]
+    fn test_gateway_tool_metadata() {
+        let tool = parse_tool_info(&json!({
+            "name": "delete_file",
+            "description": "Delete a file",
+            "inputSchema": { "type": "object" },
+            "outputSchema": { "type": "object", "properties": { "deleted": { "type": "boolean" } } },
+            "annotations": { "destructiveHint": true, "readOnlyHint": false },
+            "icons": [{ "src": "https://example.com/icon.png", "mimeType": "image/png", "sizes": "48x48" }],
+        }))
+        .unwrap();
+        assert_eq!(
+            gateway_tool("fs", tool),
+            json!({
+                "name": "fs__delete_file",
+                "description": "Delete a file",
+                "inputSchema": { "type": "object" },
+                "outputSchema": { "type": "object", "properties": { "deleted": { "type": "boolean" } } },
+                "annotations": { "destructiveHint": true, "readOnlyHint": false },
+                "icons": [{ "src": "https://example.com/icon.png", "mimeType": "image/png", "sizes": ["48x48"] }],
+            })
+        );
+
+        let plain = parse_tool_info(&json!({ "name": "plain" })).unwrap();
+        assert_eq!(
+            gateway_tool("fs", plain),
+            json!({ "name": "fs__plain", "description": "", "inputSchema": { "type": "object" } })
+        );
+    }
+}
@@ -0,0 +1,325 @@
+use log::info;
+use serde::Serialize;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions, SqliteRow};
+use sqlx::Row;
+use std::path::{Path, PathBuf};
+use tokio::sync::OnceCell;
+
+/// 单次查询返回的最大条数
+pub const MAX_LIMIT: u32 = 100;
+const DEFAULT_LIMIT: u32 = 20;
+
+// 搜索结果摘要在匹配位置前后保留的字符数
+const SNIPPET_CONTEXT: usize = 60;
+
+/// 聊天话题
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatTopic {
+    pub id: String,
+    pub title: String,
+    pub created_at: String,
+    pub updated_at: String,
+    pub message_count: i64,
+    pub preview: Option<String>,
+    pub source_assistant_id: Option<String>,
+}
+
+/// 聊天消息
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatMessage {
+    pub id: String,
+    pub topic_id: String,
+    pub role: String,
+    pub content: String,
+    pub timestamp: String,
+    pub model_id: Option<String>,
+}
+
+/// 聊天记录搜索结果，`snippet` 为匹配位置附近的内容
+#[derive(Debug, Clone, Serialize)]
+pub struct MessageMatch {
+    pub message_id: String,
+    pub topic_id: String,
+    pub topic_title: String,
+    pub role: String,
+    pub timestamp: String,
+    pub snippet: String,
+}
+
+/// 助手
+#[derive(Debug, Clone, Serialize)]
+pub struct Assistant {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub provider_id: Option<String>,
+    pub model_id: Option<String>,
+    pub system_prompt: String,
+    pub is_default: bool,
+    pub tags: Option<String>,
+}
+
+/// 前端聊天数据库的只读访问
+///
+/// 数据库由前端通过 SQL 插件创建和写入，这里只以只读方式打开，首次查询时才连接。
+pub struct HistoryStore {
+    path: Option<PathBuf>,
+    pool: OnceCell<SqlitePool>,
+}
+
+impl HistoryStore {
+    /// 使用前端数据库文件创建
+    pub fn open(path: PathBuf) -> Self {
+        Self {
+            path: Some(path),
+            pool: OnceCell::new(),
+        }
+    }
+
+    /// 不可用的聊天记录，所有查询都返回错误
+    pub fn unavailable() -> Self {
+        Self {
+            path: None,
+            pool: OnceCell::new(),
+        }
+    }
+
+    /// 应用数据目录下前端数据库的路径
+    pub fn default_path(data_dir: &Path) -> PathBuf {
+        data_dir.join("data").join("db").join("fishmind.db")
+    }
+
+    async fn pool(&self) -> Result<&SqlitePool, String> {
+        let path = self
+            .path
+            .as_ref()
+            .ok_or_else(|| "Chat history is not available".to_string())?;
+        self.pool
+            .get_or_try_init(|| async {
+                if !path.exists() {
+                    return Err(format!("Chat database not found: {}", path.display()));
+                }
+                info!("[MCP] 打开聊天数据库: {}", path.display());
+                let options = SqliteConnectOptions::new().filename(path).read_only(true);
+                SqlitePoolOptions::new()
+                    .max_connections(2)
+                    .connect_with(options)
+                    .await
+                    .map_err(|e| format!("Failed to open chat database: {}", e))
+            })
+            .await
+    }
+
+    /// 按关键字搜索消息内容，不区分 ASCII 大小写，可限定话题
+    pub async fn search_messages(
+        &self,
+        query: &str,
+        topic_id: Option<&str>,
+        limit: Option<u32>,
+    ) -> Result<Vec<MessageMatch>, String> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Err("Search query must not be empty".to_string());
+        }
+        let pool = self.pool().await?;
+        let pattern = format!("%{}%", escape_like(query));
+
+        let rows = sqlx::query(
+            "SELECT m.id, m.topic_id, m.role, m.content, m.timestamp, t.title \
+             FROM messages m JOIN topics t ON t.id = m.topic_id \
+             WHERE m.content LIKE ?1 ESCAPE '\\' AND (?2 IS NULL OR m.topic_id = ?2) \
+             ORDER BY m.timestamp DESC LIMIT ?3",
+        )
+        .bind(pattern)
+        .bind(topic_id)
+        .bind(clamp_limit(limit))
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to search chat history: {}", e))?;
+
+        rows.iter()
+            .map(|row| {
+                let content: String = get(row, "content")?;
+                Ok(MessageMatch {
+                    message_id: get(row, "id")?,
+                    topic_id: get(row, "topic_id")?,
+                    topic_title: get(row, "title")?,
+                    role: get(row, "role")?,
+                    timestamp: get(row, "timestamp")?,
+                    snippet: snippet(&content, query),
+                })
+            })
+            .collect()
+    }
+
+    /// 按更新时间倒序列出话题
+    pub async fn list_topics(&self, limit: Option<u32>) -> Result<Vec<ChatTopic>, String> {
+        let pool = self.pool().await?;
+        let rows = sqlx::query(
+            "SELECT id, title, created_at, updated_at, message_count, preview, source_assistant_id \
+             FROM topics ORDER BY updated_at DESC LIMIT ?1",
+        )
+        .bind(clamp_limit(limit))
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to list topics: {}", e))?;
+        rows.iter().map(topic_from_row).collect()
+    }
+
+    /// 获取单个话题
+    pub async fn get_topic(&self, topic_id: &str) -> Result<ChatTopic, String> {
+        let pool = self.pool().await?;
+        let row = sqlx::query(
+            "SELECT id, title, created_at, updated_at, message_count, preview, source_assistant_id \
+             FROM topics WHERE id = ?1",
+        )
+        .bind(topic_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| format!("Failed to get topic: {}", e))?
+        .ok_or_else(|| format!("Topic not found: {}", topic_id))?;
+        topic_from_row(&row)
+    }
+
+    /// 按时间顺序列出话题中的消息，超过上限时返回最早的部分
+    pub async fn topic_messages(
+        &self,
+        topic_id: &str,
+        limit: Option<u32>,
+    ) -> Result<Vec<ChatMessage>, String> {
+        let pool = self.pool().await?;
+        let rows = sqlx::query(
+            "SELECT id, topic_id, role, content, timestamp, model_id \
+             FROM messages WHERE topic_id = ?1 ORDER BY timestamp ASC LIMIT ?2",
+        )
+        .bind(topic_id)
+        .bind(clamp_limit(limit))
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to list topic messages: {}", e))?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(ChatMessage {
+                    id: get(row, "id")?,
+                    topic_id: get(row, "topic_id")?,
+                    role: get(row, "role")?,
+                    content: get(row, "content")?,
+                    timestamp: get(row, "timestamp")?,
+                    model_id: get(row, "model_id")?,
+                })
+            })
+            .collect()
+    }
+
+    /// 列出所有助手，默认助手在前
+    pub async fn list_assistants(&self) -> Result<Vec<Assistant>, String> {
+        let pool = self.pool().await?;
+        let rows = sqlx::query(
+            "SELECT id, name, description, provider_id, model_id, system_prompt, is_default, tags \
+             FROM assistants ORDER BY is_default DESC, name ASC",
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to list assistants: {}", e))?;
+        rows.iter().map(assistant_from_row).collect()
+    }
+
+    /// 获取单个助手
+    pub async fn get_assistant(&self, assistant_id: &str) -> Result<Assistant, String> {
+        let pool = self.pool().await?;
+        let row = sqlx::query(
+            "SELECT id, name, description, provider_id, model_id, system_prompt, is_default, tags \
+             FROM assistants WHERE id = ?1",
+        )
+        .bind(assistant_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| format!("Failed to get assistant: {}", e))?
+        .ok_or_else(|| format!("Assistant not found: {}", assistant_id))?;
+        assistant_from_row(&row)
+    }
+}
+
+fn get<'r, T>(row: &'r SqliteRow, column: &str) -> Result<T, String>
+where
+    T: sqlx::Decode<'r, sqlx::Sqlite> + sqlx::Type<sqlx::Sqlite>,
+{
+    row.try_get(column)
+        .map_err(|e| format!("Failed to read column {}: {}", column, e))
+}
+
+fn topic_from_row(row: &SqliteRow) -> Result<ChatTopic, String> {
+    Ok(ChatTopic {
+        id: get(row, "id")?,
+        title: get(row, "title")?,
+        created_at: get(row, "created_at")?,
+        updated_at: get(row, "updated_at")?,
+        message_count: get::<Option<i64>>(row, "message_count")?.unwrap_or(0),
+        preview: get(row, "preview")?,
+        source_assistant_id: get(row, "source_assistant_id")?,
+    })
+}
+
+fn assistant_from_row(row: &SqliteRow) -> Result<Assistant, String> {
+    Ok(Assistant {
+        id: get(row, "id")?,
+        name: get(row, "name")?,
+        description: get(row, "description")?,
+        provider_id: get(row, "provider_id")?,
+        model_id: get(row, "model_id")?,
+        system_prompt: get(row, "system_prompt")?,
+        is_default: get::<Option<i64>>(row, "is_default")?.unwrap_or(0) != 0,
+        tags: get(row, "tags")?,
+    })
+}
+
+fn clamp_limit(limit: Option<u32>) -> u32 {
+    limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
+}
+
+// 转义 LIKE 通配符，配合 ESCAPE '\' 使用
+fn escape_like(query: &str) -> String {
+    let mut escaped = String::with_capacity(query.len());
+    for c in query.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// 截取匹配位置前后的内容，按字符而不是字节截取，避免切断多字节字符
+pub fn snippet(content: &str, query: &str) -> String {
+    let chars: Vec<char> = content.chars().collect();
+    let lower: Vec<char> = chars.iter().map(|c| c.to_ascii_lowercase()).collect();
+    let needle: Vec<char> = query.chars().map(|c| c.to_ascii_lowercase()).collect();
+
+    let position = if needle.is_empty() || needle.len() > lower.len() {
+        None
+    } else {
+        lower
+            .windows(needle.len())
+            .position(|window| window == needle)
+    };
+    let Some(position) = position else {
+        let head: String = chars.iter().take(SNIPPET_CONTEXT * 2).collect();
+        return if chars.len() > SNIPPET_CONTEXT * 2 {
+            format!("{}…", head)
+        } else {
+            head
+        };
+    };
+
+    let start = position.saturating_sub(SNIPPET_CONTEXT);
+    let end = (position + needle.len() + SNIPPET_CONTEXT).min(chars.len());
+    let mut snippet: String = chars[start..end].iter().collect();
+    if start > 0 {
+        snippet.insert(0, '…');
+    }
+    if end < chars.len() {
+        snippet.push('…');
+    }
+    snippet
+}
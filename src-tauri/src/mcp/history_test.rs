@@ -0,0 +1,148 @@
+#[cfg(test)]
+mod tests {
+    use crate::mcp::history::{snippet, HistoryStore};
+    use sqlx::sqlite::SqliteConnectOptions;
+    use sqlx::{Connection, SqliteConnection};
+    use std::path::PathBuf;
+
+    // 与前端 schema.ts 一致的表结构和测试数据
+    const SEED: &str = "
+        CREATE TABLE topics (
+            id TEXT PRIMARY KEY, title TEXT NOT NULL, created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL, last_model_id TEXT, last_provider_id TEXT,
+            message_count INTEGER DEFAULT 0, preview TEXT, source_assistant_id TEXT,
+            current_config TEXT
+        );
+        CREATE TABLE messages (
+            id TEXT PRIMARY KEY, topic_id TEXT NOT NULL, role TEXT NOT NULL,
+            content TEXT NOT NULL, timestamp TEXT NOT NULL, model_id TEXT, provider_id TEXT
+        );
+        CREATE TABLE assistants (
+            id TEXT PRIMARY KEY, name TEXT NOT NULL, description TEXT, avatar TEXT,
+            provider_id TEXT, model_id TEXT, system_prompt TEXT NOT NULL, temperature REAL,
+            memory_strategy TEXT, context_window_size INTEGER, enabled_tool_ids TEXT,
+            knowledge_base_ids TEXT, created_at TEXT NOT NULL, updated_at TEXT NOT NULL,
+            is_default INTEGER DEFAULT 0, tags TEXT
+        );
+        INSERT INTO topics (id, title, created_at, updated_at, message_count, preview)
+            VALUES ('t1', 'Rust 学习', '2024-01-01', '2024-01-03', 3, '所有权'),
+                   ('t2', 'Travel', '2024-01-02', '2024-01-02', 1, NULL);
+        INSERT INTO messages (id, topic_id, role, content, timestamp) VALUES
+            ('m1', 't1', 'user', '什么是所有权 Ownership？', '2024-01-01T10:00:00'),
+            ('m2', 't1', 'assistant', 'Ownership 是 Rust 管理内存的方式，100% 安全', '2024-01-01T10:00:05'),
+            ('m3', 't1', 'user', 'snake_case naming', '2024-01-01T10:01:00'),
+            ('m4', 't2', 'user', 'Book a flight, mind the ownership of one case', '2024-01-02T09:00:00');
+        INSERT INTO assistants (id, name, system_prompt, created_at, updated_at, is_default)
+            VALUES ('a1', 'Coder', 'You write code', '2024-01-01', '2024-01-01', 0),
+                   ('a2', 'Default', 'You are helpful', '2024-01-01', '2024-01-01', 1);
+    ";
+
+    async fn seeded_store(name: &str) -> HistoryStore {
+        let dir = std::env::temp_dir().join(format!(
+            "fishmind-history-test-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = HistoryStore::default_path(&dir);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+
+        let options = SqliteConnectOptions::new()
+            .filename(&path)
+            .create_if_missing(true);
+        let mut conn = SqliteConnection::connect_with(&options).await.unwrap();
+        sqlx::raw_sql(SEED).execute(&mut conn).await.unwrap();
+        conn.close().await.unwrap();
+
+        HistoryStore::open(path)
+    }
+
+    // 测试关键字搜索不区分大小写，可限定话题和数量
+    #[tokio::test]
+    async fn test_search_messages() {
+        let store = seeded_store("search").await;
+
+        let matches = store
+            .search_messages("ownership", None, None)
+            .await
+            .unwrap();
+        let ids: Vec<&str> = matches.iter().map(|m| m.message_id.as_str()).collect();
+        assert_eq!(ids, vec!["m4", "m2", "m1"]);
+        assert_eq!(matches[1].topic_title, "Rust 学习");
+
+        let in_topic = store
+            .search_messages("ownership", Some("t2"), None)
+            .await
+            .unwrap();
+        assert_eq!(in_topic.len(), 1);
+
+        let limited = store
+            .search_messages("ownership", None, Some(1))
+            .await
+            .unwrap();
+        assert_eq!(limited.len(), 1);
+
+        assert!(store.search_messages("  ", None, None).await.is_err());
+    }
+
+    // 测试 LIKE 通配符按字面匹配
+    #[tokio::test]
+    async fn test_search_escapes_wildcards() {
+        let store = seeded_store("escape").await;
+
+        let underscore = store.search_messages("e_c", None, None).await.unwrap();
+        assert_eq!(underscore.len(), 1);
+        assert_eq!(underscore[0].message_id, "m3");
+
+        let percent = store.search_messages("100%", None, None).await.unwrap();
+        assert_eq!(percent.len(), 1);
+        let literal = store.search_messages("%", None, None).await.unwrap();
+        assert_eq!(literal.len(), 1);
+        assert_eq!(literal[0].message_id, "m2");
+    }
+
+    // 测试话题、消息和助手查询
+    #[tokio::test]
+    async fn test_topics_messages_assistants() {
+        let store = seeded_store("topics").await;
+
+        let topics = store.list_topics(None).await.unwrap();
+        assert_eq!(topics[0].id, "t1");
+        assert_eq!(topics[0].message_count, 3);
+        assert_eq!(topics[1].preview, None);
+
+        let messages = store.topic_messages("t1", Some(2)).await.unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].id, "m1");
+        assert_eq!(messages[1].role, "assistant");
+
+        assert!(store.get_topic("missing").await.is_err());
+
+        let assistants = store.list_assistants().await.unwrap();
+        assert_eq!(assistants[0].name, "Default");
+        assert!(assistants[0].is_default);
+        assert_eq!(store.get_assistant("a1").await.unwrap().name, "Coder");
+    }
+
+    // 测试数据库不存在或不可用时返回错误
+    #[tokio::test]
+    async fn test_unavailable() {
+        let missing = HistoryStore::open(PathBuf::from("/nonexistent/fishmind.db"));
+        assert!(missing.list_topics(None).await.is_err());
+        assert!(HistoryStore::unavailable().list_assistants().await.is_err());
+    }
+
+    // 测试摘要按字符截取且保留匹配内容
+    #[test]
+    fn test_snippet() {
+        let content = format!("{}关键字{}", "前".repeat(100), "后".repeat(100));
+        let result = snippet(&content, "关键字");
+        assert!(result.starts_with('…'));
+        assert!(result.ends_with('…'));
+        assert!(result.contains("关键字"));
+        assert_eq!(result.chars().count(), 60 + 3 + 60 + 2);
+
+        assert_eq!(snippet("short text", "TEXT"), "short text");
+        assert_eq!(snippet("no match here", "xyz"), "no match here");
+    }
+}
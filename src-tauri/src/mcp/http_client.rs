@@ -0,0 +1,85 @@
+use crate::mcp::types::*;
+use log::{info, warn};
+use reqwest::{Certificate, Identity, NoProxy, Proxy};
+use std::fs;
+
+/// 根据 TLS 和代理配置创建 HTTP 客户端
+///
+/// SSE 传输和 OAuth 授权请求共用该客户端，保证访问同一服务器时使用相同的证书和代理。
+/// 未设置代理时沿用 reqwest 的默认行为，读取 `HTTP_PROXY`/`HTTPS_PROXY`/`NO_PROXY` 环境变量。
+pub fn build_http_client(
+    tls: Option<&TlsConfig>,
+    proxy: Option<&ProxyConfig>,
+) -> Result<reqwest::Client, String> {
+    let mut builder = reqwest::Client::builder().use_rustls_tls();
+
+    if let Some(tls) = tls {
+        if let Some(path) = &tls.ca_bundle_path {
+            let pem = fs::read(path)
+                .map_err(|e| format!("Failed to read CA bundle '{}': {}", path, e))?;
+            let certificates = Certificate::from_pem_bundle(&pem)
+                .map_err(|e| format!("Invalid CA bundle '{}': {}", path, e))?;
+            if certificates.is_empty() {
+                return Err(format!("CA bundle '{}' contains no certificates", path));
+            }
+            info!("[MCP] 加载 CA 证书: {}, 数量: {}", path, certificates.len());
+            for certificate in certificates {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+
+        match (&tls.client_cert_path, &tls.client_key_path) {
+            (Some(cert_path), Some(key_path)) => {
+                let mut pem = fs::read(cert_path).map_err(|e| {
+                    format!("Failed to read client certificate '{}': {}", cert_path, e)
+                })?;
+                pem.push(b'\n');
+                pem.extend(
+                    fs::read(key_path)
+                        .map_err(|e| format!("Failed to read client key '{}': {}", key_path, e))?,
+                );
+                let identity = Identity::from_pem(&pem)
+                    .map_err(|e| format!("Invalid client certificate or key: {}", e))?;
+                info!("[MCP] 使用客户端证书: {}", cert_path);
+                builder = builder.identity(identity);
+            }
+            (None, None) => {}
+            _ => {
+                return Err(
+                    "Client certificate and client key must be configured together".to_string(),
+                )
+            }
+        }
+
+        if tls.insecure_skip_verify {
+            warn!("[MCP] 已禁用 TLS 证书校验，连接可能被中间人攻击，仅限开发环境使用");
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+    }
+
+    if let Some(proxy) = proxy {
+        let mut reqwest_proxy = Proxy::all(&proxy.url)
+            .map_err(|e| format!("Invalid proxy URL '{}': {}", display_proxy(&proxy.url), e))?;
+        if !proxy.no_proxy.is_empty() {
+            reqwest_proxy = reqwest_proxy.no_proxy(NoProxy::from_string(&proxy.no_proxy.join(",")));
+        }
+        info!("[MCP] 使用代理: {}", display_proxy(&proxy.url));
+        builder = builder.proxy(reqwest_proxy);
+    }
+
+    builder
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))
+}
+
+// 代理地址可能包含用户名和密码，输出前去掉
+fn display_proxy(url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(mut parsed) => {
+            let _ = parsed.set_username("");
+            let _ = parsed.set_password(None);
+            parsed.to_string()
+        }
+        Err(_) => "<invalid>".to_string(),
+    }
+}
@@ -0,0 +1,476 @@
+use crate::mcp::logging;
+use crate::mcp::recorder::Direction;
+use crate::mcp::server_transport::{rpc_response, RpcResult, METHOD_NOT_FOUND};
+use async_trait::async_trait;
+use log::{debug, info, warn};
+use mcp_client_fishcode2025::transport::{Error as TransportError, Transport, TransportHandle};
+use mcp_core_fishcode2025::protocol::JsonRpcMessage;
+use reqwest::{header, StatusCode, Url};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::{JoinHandle, JoinSet};
+
+// 等待服务器下发消息端点的超时时间
+const ENDPOINT_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub(crate) type PendingRequests =
+    Arc<Mutex<HashMap<u64, oneshot::Sender<Result<JsonRpcMessage, TransportError>>>>>;
+
+/// 服务器发起的请求的处理器
+#[async_trait]
+pub trait ServerRequestHandler: Send + Sync {
+    /// 客户端额外声明的能力，合并到 `initialize` 请求的 `capabilities` 中
+    fn capabilities(&self) -> Value;
+
+    /// 处理一条服务器请求
+    async fn handle(&self, method: &str, params: Value) -> RpcResult;
+}
+
+/// 不经过传输句柄 `send` 的消息的观察者
+///
+/// 包括服务器主动发送的请求和通知，以及客户端对服务器请求的响应，
+/// 会话录制和协议检查器通过它看到完整的双向消息。
+pub trait MessageObserver: Send + Sync {
+    fn observe(&self, direction: Direction, message: &Value);
+}
+
+pub(crate) type Observers = Arc<Vec<Arc<dyn MessageObserver>>>;
+
+fn notify(observers: &Observers, direction: Direction, message: &Value) {
+    for observer in observers.iter() {
+        observer.observe(direction, message);
+    }
+}
+
+/// 使用自定义 HTTP 客户端的 SSE 传输
+///
+/// 协议与库自带的 `SseTransport` 相同：GET 建立事件流，服务器通过 `endpoint` 事件下发消息端点，
+/// 请求通过 POST 发送，响应以 `message` 事件返回。区别在于 HTTP 客户端由调用方创建，
+/// 因此可以使用自定义 CA、客户端证书和代理；服务器发起的请求（如 `elicitation/create`）
+/// 交给请求处理器，结果同样通过 POST 返回。客户端的所有 SSE 连接都使用该传输。
+pub struct HttpSseTransport {
+    client: reqwest::Client,
+    url: String,
+    headers: HashMap<String, String>,
+    handler: Option<Arc<dyn ServerRequestHandler>>,
+    observers: Vec<Arc<dyn MessageObserver>>,
+    // 建立事件流被拒绝（401）时服务器返回的 `WWW-Authenticate` 头
+    challenge: Mutex<Option<String>>,
+}
+
+impl HttpSseTransport {
+    pub fn new(client: reqwest::Client, url: &str, headers: HashMap<String, String>) -> Self {
+        Self {
+            client,
+            url: url.to_string(),
+            headers,
+            handler: None,
+            observers: Vec::new(),
+            challenge: Mutex::new(None),
+        }
+    }
+
+    /// 添加消息观察者
+    pub fn with_observer(mut self, observer: Arc<dyn MessageObserver>) -> Self {
+        self.observers.push(observer);
+        self
+    }
+
+    /// 设置服务器请求处理器，未设置时只响应 `ping`
+    pub fn with_request_handler(mut self, handler: Arc<dyn ServerRequestHandler>) -> Self {
+        self.handler = Some(handler);
+        self
+    }
+
+    /// 启动失败且服务器返回 401 时的 `WWW-Authenticate` 头，用于触发 OAuth 授权
+    pub fn auth_challenge(&self) -> Option<String> {
+        self.challenge.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl Transport for HttpSseTransport {
+    type Handle = HttpSseTransportHandle;
+
+    async fn start(&self) -> Result<Self::Handle, TransportError> {
+        let base = Url::parse(&self.url)
+            .map_err(|e| TransportError::SseConnection(format!("Invalid SSE URL: {}", e)))?;
+
+        let mut request = self
+            .client
+            .get(base.clone())
+            .header(header::ACCEPT, "text/event-stream");
+        for (key, value) in &self.headers {
+            request = request.header(key.as_str(), value.as_str());
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| TransportError::SseConnection(e.to_string()))?;
+        if !response.status().is_success() {
+            if response.status() == StatusCode::UNAUTHORIZED {
+                *self.challenge.lock().unwrap() = response
+                    .headers()
+                    .get(header::WWW_AUTHENTICATE)
+                    .and_then(|value| value.to_str().ok())
+                    .map(str::to_string);
+            }
+            return Err(TransportError::HttpError {
+                status: response.status().as_u16(),
+                message: response.text().await.unwrap_or_default(),
+            });
+        }
+
+        let pending: PendingRequests = Arc::new(Mutex::new(HashMap::new()));
+        let observers: Observers = Arc::new(self.observers.clone());
+        let (endpoint_tx, endpoint_rx) = oneshot::channel();
+        let (request_tx, request_rx) = mpsc::unbounded_channel();
+        let reader = tokio::spawn(read_events(
+            response,
+            base,
+            endpoint_tx,
+            pending.clone(),
+            request_tx,
+            observers.clone(),
+        ));
+
+        let endpoint = match tokio::time::timeout(ENDPOINT_TIMEOUT, endpoint_rx).await {
+            Ok(Ok(Ok(endpoint))) => endpoint,
+            Ok(Ok(Err(e))) => {
+                reader.abort();
+                return Err(TransportError::SseConnection(e));
+            }
+            Ok(Err(_)) | Err(_) => {
+                reader.abort();
+                return Err(TransportError::SseConnection(
+                    "Server did not send a message endpoint".to_string(),
+                ));
+            }
+        };
+        info!("[MCP] SSE 消息端点: {}", endpoint);
+
+        let poster = Poster {
+            client: self.client.clone(),
+            endpoint,
+            headers: self.headers.clone(),
+        };
+        let responder = tokio::spawn(serve_requests(
+            poster.clone(),
+            self.handler.clone(),
+            request_rx,
+            observers,
+        ));
+
+        Ok(HttpSseTransportHandle {
+            inner: Arc::new(HandleInner {
+                poster,
+                capabilities: self.handler.as_ref().map(|handler| handler.capabilities()),
+                pending,
+                reader,
+                responder,
+            }),
+        })
+    }
+
+    async fn close(&self) -> Result<(), TransportError> {
+        // 事件流在最后一个句柄释放时关闭
+        Ok(())
+    }
+}
+
+/// 自定义 SSE 传输句柄
+#[derive(Clone)]
+pub struct HttpSseTransportHandle {
+    inner: Arc<HandleInner>,
+}
+
+struct HandleInner {
+    poster: Poster,
+    // 请求处理器声明的能力
+    capabilities: Option<Value>,
+    pending: PendingRequests,
+    reader: JoinHandle<()>,
+    responder: JoinHandle<()>,
+}
+
+impl Drop for HandleInner {
+    fn drop(&mut self) {
+        self.reader.abort();
+        self.responder.abort();
+    }
+}
+
+/// 向服务器发送消息的通道，服务器请求的响应通过它返回
+#[async_trait]
+pub(crate) trait Outbound: Clone + Send + Sync + 'static {
+    async fn send_value(&self, message: &Value) -> Result<(), TransportError>;
+}
+
+// 向消息端点发送消息
+#[derive(Clone)]
+struct Poster {
+    client: reqwest::Client,
+    endpoint: Url,
+    headers: HashMap<String, String>,
+}
+
+impl Poster {
+    async fn post(&self, message: &impl Serialize) -> Result<(), TransportError> {
+        let mut request = self.client.post(self.endpoint.clone()).json(message);
+        for (key, value) in &self.headers {
+            request = request.header(key.as_str(), value.as_str());
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| TransportError::SseConnection(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(TransportError::HttpError {
+                status: response.status().as_u16(),
+                message: response.text().await.unwrap_or_default(),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Outbound for Poster {
+    async fn send_value(&self, message: &Value) -> Result<(), TransportError> {
+        self.post(message).await
+    }
+}
+
+#[async_trait]
+impl TransportHandle for HttpSseTransportHandle {
+    async fn send(&self, message: JsonRpcMessage) -> Result<JsonRpcMessage, TransportError> {
+        send_message(
+            &self.inner.poster,
+            &self.inner.pending,
+            self.inner.capabilities.as_ref(),
+            message,
+        )
+        .await
+    }
+}
+
+/// 发送客户端消息，请求等待服务器的响应，通知发送后立即返回
+pub(crate) async fn send_message<O: Outbound>(
+    outbound: &O,
+    pending: &PendingRequests,
+    capabilities: Option<&Value>,
+    message: JsonRpcMessage,
+) -> Result<JsonRpcMessage, TransportError> {
+    let message = match capabilities {
+        Some(capabilities) => advertise(message, capabilities),
+        None => message,
+    };
+    let value = serde_json::to_value(&message)?;
+    match &message {
+        JsonRpcMessage::Request(_) => {
+            let id = message_id(&value).ok_or(TransportError::UnsupportedMessage)?;
+            logging::record_rpc_id(value["method"].as_str().unwrap_or_default(), id);
+            let (tx, rx) = oneshot::channel();
+            pending.lock().unwrap().insert(id, tx);
+
+            if let Err(e) = outbound.send_value(&value).await {
+                pending.lock().unwrap().remove(&id);
+                return Err(e);
+            }
+            rx.await.map_err(|_| TransportError::ChannelClosed)?
+        }
+        JsonRpcMessage::Notification(_) => {
+            outbound.send_value(&value).await?;
+            Ok(JsonRpcMessage::Nil)
+        }
+        _ => Err(TransportError::UnsupportedMessage),
+    }
+}
+
+// 把请求处理器声明的能力合并到 `initialize` 请求中
+fn advertise(message: JsonRpcMessage, capabilities: &Value) -> JsonRpcMessage {
+    match message {
+        JsonRpcMessage::Request(mut request) if request.method == "initialize" => {
+            let params = request.params.get_or_insert_with(|| json!({}));
+            if let Some(params) = params.as_object_mut() {
+                let declared = params.entry("capabilities").or_insert_with(|| json!({}));
+                if let (Some(declared), Some(capabilities)) =
+                    (declared.as_object_mut(), capabilities.as_object())
+                {
+                    for (name, capability) in capabilities {
+                        declared.insert(name.clone(), capability.clone());
+                    }
+                }
+            }
+            JsonRpcMessage::Request(request)
+        }
+        other => other,
+    }
+}
+
+// 处理服务器发起的请求，每个请求在独立任务中处理，等待用户输入时不阻塞其他请求
+pub(crate) async fn serve_requests<O: Outbound>(
+    outbound: O,
+    handler: Option<Arc<dyn ServerRequestHandler>>,
+    mut requests: mpsc::UnboundedReceiver<Value>,
+    observers: Observers,
+) {
+    // 传输关闭时中止仍在处理的请求
+    let mut tasks = JoinSet::new();
+    while let Some(request) = requests.recv().await {
+        let outbound = outbound.clone();
+        let handler = handler.clone();
+        let observers = observers.clone();
+        tasks.spawn(async move {
+            let method = request
+                .get("method")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            let params = request.get("params").cloned().unwrap_or(Value::Null);
+            debug!("[MCP] 收到服务器请求: {}", method);
+
+            let result = match (method, &handler) {
+                ("ping", _) => Ok(json!({})),
+                (_, Some(handler)) => handler.handle(method, params).await,
+                (_, None) => Err((METHOD_NOT_FOUND, format!("Method not found: {}", method))),
+            };
+            if let Some(response) = rpc_response(request.get("id").cloned(), result) {
+                notify(&observers, Direction::Outgoing, &response);
+                if let Err(e) = outbound.send_value(&response).await {
+                    warn!("[MCP] 发送服务器请求的响应失败: {}", e);
+                }
+            }
+        });
+        // 回收已完成的任务
+        while tasks.try_join_next().is_some() {}
+    }
+}
+
+// SSE 事件
+struct SseEvent {
+    event: String,
+    data: String,
+}
+
+// 读取事件流，分发响应；流结束时让所有等待中的请求失败
+async fn read_events(
+    mut response: reqwest::Response,
+    base: Url,
+    endpoint_tx: oneshot::Sender<Result<Url, String>>,
+    pending: PendingRequests,
+    requests: mpsc::UnboundedSender<Value>,
+    observers: Observers,
+) {
+    let mut endpoint_tx = Some(endpoint_tx);
+    let mut buffer: Vec<u8> = Vec::new();
+
+    let reason = loop {
+        let chunk = match response.chunk().await {
+            Ok(Some(chunk)) => chunk,
+            Ok(None) => break "SSE stream closed by server".to_string(),
+            Err(e) => break format!("SSE stream error: {}", e),
+        };
+        // JSON 中的换行都经过转义，去掉 \r 后统一按 \n 解析
+        buffer.extend(chunk.iter().filter(|b| **b != b'\r'));
+
+        for event in take_events(&mut buffer) {
+            match event.event.as_str() {
+                "endpoint" => {
+                    let result = base
+                        .join(event.data.trim())
+                        .map_err(|e| format!("Invalid message endpoint: {}", e));
+                    if let Some(tx) = endpoint_tx.take() {
+                        let _ = tx.send(result);
+                    }
+                }
+                "message" | "" => dispatch(&pending, &requests, &observers, &event.data),
+                other => debug!("[MCP] 忽略 SSE 事件: {}", other),
+            }
+        }
+    };
+
+    warn!("[MCP] {}", reason);
+    if let Some(tx) = endpoint_tx.take() {
+        let _ = tx.send(Err(reason.clone()));
+    }
+    for (_, tx) in pending.lock().unwrap().drain() {
+        let _ = tx.send(Err(TransportError::SseConnection(reason.clone())));
+    }
+}
+
+// 从缓冲区取出所有完整的事件，不完整的部分留在缓冲区
+fn take_events(buffer: &mut Vec<u8>) -> Vec<SseEvent> {
+    let mut events = Vec::new();
+    while let Some(end) = buffer.windows(2).position(|w| w == b"\n\n") {
+        let block: Vec<u8> = buffer.drain(..end + 2).collect();
+        let block = String::from_utf8_lossy(&block[..end]);
+
+        let mut event = SseEvent {
+            event: String::new(),
+            data: String::new(),
+        };
+        let mut data_lines = Vec::new();
+        for line in block.lines() {
+            let (field, value) = line.split_once(':').unwrap_or((line, ""));
+            let value = value.strip_prefix(' ').unwrap_or(value);
+            match field {
+                "event" => event.event = value.to_string(),
+                "data" => data_lines.push(value),
+                _ => {}
+            }
+        }
+        if data_lines.is_empty() {
+            continue;
+        }
+        event.data = data_lines.join("\n");
+        events.push(event);
+    }
+    events
+}
+
+// 分发服务器消息：响应交给等待中的请求，服务器请求交给请求处理器
+pub(crate) fn dispatch(
+    pending: &PendingRequests,
+    requests: &mpsc::UnboundedSender<Value>,
+    observers: &Observers,
+    data: &str,
+) {
+    let value: Value = match serde_json::from_str(data) {
+        Ok(value) => value,
+        Err(e) => {
+            warn!("[MCP] 无法解析服务器消息: {}", e);
+            return;
+        }
+    };
+
+    // 服务器发起的请求交给请求处理器，通知只交给观察者
+    if value.get("method").is_some() {
+        notify(observers, Direction::Incoming, &value);
+        if value.get("id").is_some() {
+            let _ = requests.send(value);
+        } else {
+            debug!("[MCP] 收到服务器通知: {}", value["method"]);
+        }
+        return;
+    }
+
+    let is_response = value.get("result").is_some() || value.get("error").is_some();
+    let Some(id) = message_id(&value).filter(|_| is_response) else {
+        debug!("[MCP] 忽略服务器消息: {}", value);
+        return;
+    };
+
+    let Some(tx) = pending.lock().unwrap().remove(&id) else {
+        debug!("[MCP] 收到未知请求的响应, ID: {}", id);
+        return;
+    };
+    let _ = tx.send(serde_json::from_value(value).map_err(TransportError::from));
+}
+
+pub(crate) fn message_id(value: &Value) -> Option<u64> {
+    value.get("id").and_then(Value::as_u64)
+}
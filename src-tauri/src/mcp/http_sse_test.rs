@@ -0,0 +1,162 @@
+#[cfg(test)]
+mod tests {
+    use crate::mcp::http_sse::HttpSseTransport;
+    use mcp_client_fishcode2025::transport::{Transport, TransportHandle};
+    use mcp_core_fishcode2025::protocol::JsonRpcMessage;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::sync::{mpsc, Mutex};
+
+    // 本地模拟的 SSE MCP 服务器，把请求参数原样作为结果返回
+    async fn start_mock_server(require_header: bool) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let origin = format!("http://{}", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::unbounded_channel::<String>();
+        let rx = Arc::new(Mutex::new(rx));
+
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let tx = tx.clone();
+                let rx = rx.clone();
+                tokio::spawn(async move { handle(stream, tx, rx, require_header).await });
+            }
+        });
+        format!("{}/sse", origin)
+    }
+
+    async fn handle(
+        mut stream: TcpStream,
+        tx: mpsc::UnboundedSender<String>,
+        rx: Arc<Mutex<mpsc::UnboundedReceiver<String>>>,
+        require_header: bool,
+    ) {
+        let Some((method, head, body)) = read_request(&mut stream).await else {
+            return;
+        };
+
+        if method == "GET" {
+            if require_header && !head.to_ascii_lowercase().contains("x-api-key: test-key") {
+                let _ = stream
+                    .write_all(b"HTTP/1.1 401 Unauthorized\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+                    .await;
+                return;
+            }
+            let _ = stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\n\r\n")
+                .await;
+            let _ = stream
+                .write_all(
+                    b": keep-alive\r\n\r\nevent: endpoint\r\ndata: /messages?session=1\r\n\r\n",
+                )
+                .await;
+            let mut rx = rx.lock().await;
+            while let Some(message) = rx.recv().await {
+                // 分两次写入，验证跨数据块的事件解析
+                let event = format!("event: message\ndata: {}\n\n", message);
+                let (first, second) = event.split_at(event.len() / 2);
+                if stream.write_all(first.as_bytes()).await.is_err()
+                    || stream.write_all(second.as_bytes()).await.is_err()
+                {
+                    return;
+                }
+            }
+            return;
+        }
+
+        let request: serde_json::Value = serde_json::from_str(&body).unwrap();
+        if let Some(id) = request.get("id") {
+            let response = serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "result": { "echo": request["params"], "text": "你好" },
+            });
+            let _ = tx.send(response.to_string());
+        }
+        let _ = stream
+            .write_all(b"HTTP/1.1 202 Accepted\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+            .await;
+    }
+
+    async fn read_request(stream: &mut TcpStream) -> Option<(String, String, String)> {
+        let mut buf = Vec::new();
+        let mut chunk = [0u8; 4096];
+        loop {
+            let n = stream.read(&mut chunk).await.ok()?;
+            if n == 0 {
+                return None;
+            }
+            buf.extend_from_slice(&chunk[..n]);
+            let Some(end) = buf.windows(4).position(|w| w == b"\r\n\r\n") else {
+                continue;
+            };
+
+            let head = String::from_utf8_lossy(&buf[..end]).to_string();
+            let method = head.split_whitespace().next()?.to_string();
+            let length: usize = head
+                .lines()
+                .filter_map(|line| line.split_once(':'))
+                .find(|(k, _)| k.eq_ignore_ascii_case("content-length"))
+                .and_then(|(_, v)| v.trim().parse().ok())
+                .unwrap_or(0);
+            while buf.len() < end + 4 + length {
+                let n = stream.read(&mut chunk).await.ok()?;
+                if n == 0 {
+                    return None;
+                }
+                buf.extend_from_slice(&chunk[..n]);
+            }
+            let body = String::from_utf8_lossy(&buf[end + 4..end + 4 + length]).to_string();
+            return Some((method, head, body));
+        }
+    }
+
+    fn message(value: serde_json::Value) -> JsonRpcMessage {
+        serde_json::from_value(value).unwrap()
+    }
+
+    // 测试请求和响应按 ID 匹配，通知不等待响应
+    #[tokio::test]
+    async fn test_request_response() {
+        let url = start_mock_server(false).await;
+        let transport = HttpSseTransport::new(reqwest::Client::new(), &url, HashMap::new());
+        let handle = transport.start().await.unwrap();
+
+        let (first, second) = tokio::join!(
+            handle.send(message(serde_json::json!({
+                "jsonrpc": "2.0", "id": 1, "method": "tools/call", "params": { "n": 1 }
+            }))),
+            handle.send(message(serde_json::json!({
+                "jsonrpc": "2.0", "id": 2, "method": "tools/call", "params": { "n": 2 }
+            }))),
+        );
+        let first = serde_json::to_value(first.unwrap()).unwrap();
+        let second = serde_json::to_value(second.unwrap()).unwrap();
+        assert_eq!(first["result"]["echo"]["n"], 1);
+        assert_eq!(second["result"]["echo"]["n"], 2);
+        assert_eq!(first["result"]["text"], "你好");
+
+        let notification = handle
+            .send(message(serde_json::json!({
+                "jsonrpc": "2.0", "method": "notifications/initialized"
+            })))
+            .await
+            .unwrap();
+        assert!(matches!(notification, JsonRpcMessage::Nil));
+    }
+
+    // 测试请求头用于建立事件流，服务器拒绝时启动失败
+    #[tokio::test]
+    async fn test_headers_and_http_error() {
+        let url = start_mock_server(true).await;
+
+        let rejected = HttpSseTransport::new(reqwest::Client::new(), &url, HashMap::new());
+        assert!(rejected.start().await.is_err());
+
+        let mut headers = HashMap::new();
+        headers.insert("X-Api-Key".to_string(), "test-key".to_string());
+        let accepted = HttpSseTransport::new(reqwest::Client::new(), &url, headers);
+        assert!(accepted.start().await.is_ok());
+    }
+}
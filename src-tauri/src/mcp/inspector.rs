@@ -0,0 +1,274 @@
+use crate::mcp::http_sse::MessageObserver;
+use crate::mcp::recorder::Direction;
+use crate::mcp::redact;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use log::info;
+use mcp_client_fishcode2025::transport::{Error as TransportError, TransportHandle};
+use mcp_core_fishcode2025::protocol::{JsonRpcMessage, JsonRpcNotification, JsonRpcRequest};
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
+use tokio::sync::mpsc;
+
+/// 协议检查器事件名
+pub const INSPECTOR_EVENT: &str = "mcp-inspector-message";
+
+/// 直接发送请求的默认超时时间（秒）
+pub const DEFAULT_RAW_TIMEOUT_SECS: u64 = 30;
+
+// 直接发送的请求使用独立的ID段，避免与客户端自身的请求ID冲突
+const RAW_REQUEST_ID_BASE: u64 = 1 << 32;
+
+static NEXT_RAW_ID: AtomicU64 = AtomicU64::new(RAW_REQUEST_ID_BASE);
+
+/// 消息类型
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageKind {
+    Request,
+    Response,
+    Notification,
+    /// JSON-RPC 错误响应或传输错误
+    Error,
+}
+
+/// 协议检查器事件，每条线上消息一个
+#[derive(Debug, Clone, Serialize)]
+pub struct InspectorEvent {
+    pub client_id: String,
+    pub timestamp: DateTime<Utc>,
+    pub direction: Direction,
+    pub kind: MessageKind,
+    pub method: Option<String>,
+    pub id: Option<Value>,
+    /// 响应相对请求的耗时（毫秒），只有响应和错误有值
+    pub latency_ms: Option<u64>,
+    /// 序列化后的消息字节数
+    pub payload_size: usize,
+    /// 脱敏后的消息内容，传输错误时为空
+    pub payload: Option<Value>,
+    pub error: Option<String>,
+}
+
+/// 客户端的协议检查器状态，与传输句柄共享
+///
+/// 同一时间只有一个订阅者，重新开启时替换之前的订阅者。
+pub struct InspectorSlot {
+    client_id: String,
+    sender: RwLock<Option<mpsc::UnboundedSender<InspectorEvent>>>,
+}
+
+impl InspectorSlot {
+    pub fn new(client_id: &str) -> Self {
+        Self {
+            client_id: client_id.to_string(),
+            sender: RwLock::new(None),
+        }
+    }
+
+    /// 开启线上消息追踪，返回事件接收端
+    pub fn start(&self) -> mpsc::UnboundedReceiver<InspectorEvent> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        *self.sender.write().unwrap() = Some(tx);
+        info!("[MCP] 开启协议检查器, 客户端ID: {}", self.client_id);
+        rx
+    }
+
+    /// 关闭线上消息追踪，未开启时返回 false
+    pub fn stop(&self) -> bool {
+        let stopped = self.sender.write().unwrap().take().is_some();
+        if stopped {
+            info!("[MCP] 关闭协议检查器, 客户端ID: {}", self.client_id);
+        }
+        stopped
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.sender.read().unwrap().is_some()
+    }
+
+    // 接收端已关闭时自动停止追踪
+    fn emit(&self, event: InspectorEvent) {
+        let closed = match self.sender.read().unwrap().as_ref() {
+            Some(sender) => sender.send(event).is_err(),
+            None => false,
+        };
+        if closed {
+            self.stop();
+        }
+    }
+
+    fn event(
+        &self,
+        direction: Direction,
+        kind: MessageKind,
+        method: Option<String>,
+        id: Option<Value>,
+        payload: Option<Value>,
+    ) -> InspectorEvent {
+        InspectorEvent {
+            client_id: self.client_id.clone(),
+            timestamp: Utc::now(),
+            direction,
+            kind,
+            method,
+            id,
+            latency_ms: None,
+            payload_size: payload.as_ref().map_or(0, |p| p.to_string().len()),
+            payload: payload.map(|p| redact::redact_value(&p)),
+            error: None,
+        }
+    }
+}
+
+// 服务器主动发送的请求和通知，以及对服务器请求的响应
+impl MessageObserver for InspectorSlot {
+    fn observe(&self, direction: Direction, message: &Value) {
+        if !self.is_active() {
+            return;
+        }
+        let method = message
+            .get("method")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        let id = message.get("id").filter(|id| !id.is_null()).cloned();
+        let error = message.get("error").map(|e| {
+            e.get("message")
+                .and_then(Value::as_str)
+                .map_or_else(|| e.to_string(), str::to_string)
+        });
+        let kind = match (&method, &id, &error) {
+            (Some(_), Some(_), _) => MessageKind::Request,
+            (Some(_), None, _) => MessageKind::Notification,
+            (None, _, Some(_)) => MessageKind::Error,
+            (None, _, None) => MessageKind::Response,
+        };
+        self.emit(InspectorEvent {
+            error,
+            ..self.event(direction, kind, method, id, Some(message.clone()))
+        });
+    }
+}
+
+/// 可追踪的传输句柄
+///
+/// 开启协议检查器后，把每条请求、响应和通知作为事件发送给订阅者；未开启时直接转发。
+/// 服务器主动发送的消息不经过句柄，由 SSE 和 Stdio 传输通过 `MessageObserver` 补充。
+#[derive(Clone)]
+pub struct InspectingHandle<H> {
+    inner: H,
+    slot: Arc<InspectorSlot>,
+}
+
+impl<H> InspectingHandle<H> {
+    pub fn new(inner: H, slot: Arc<InspectorSlot>) -> Self {
+        Self { inner, slot }
+    }
+}
+
+#[async_trait]
+impl<H: TransportHandle> TransportHandle for InspectingHandle<H> {
+    async fn send(&self, message: JsonRpcMessage) -> Result<JsonRpcMessage, TransportError> {
+        if !self.slot.is_active() {
+            return self.inner.send(message).await;
+        }
+
+        let payload = serde_json::to_value(&message).ok();
+        let method = payload
+            .as_ref()
+            .and_then(|p| p.get("method"))
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        let id = payload
+            .as_ref()
+            .and_then(|p| p.get("id"))
+            .filter(|id| !id.is_null())
+            .cloned();
+        let is_notification = matches!(message, JsonRpcMessage::Notification(_));
+        let kind = if is_notification {
+            MessageKind::Notification
+        } else {
+            MessageKind::Request
+        };
+        self.slot.emit(self.slot.event(
+            Direction::Outgoing,
+            kind,
+            method.clone(),
+            id.clone(),
+            payload,
+        ));
+
+        let started = Instant::now();
+        let result = self.inner.send(message).await;
+        let latency_ms = Some(started.elapsed().as_millis() as u64);
+        let event = match &result {
+            // 通知没有响应
+            Ok(_) if is_notification => return result,
+            Ok(response) => {
+                let payload = serde_json::to_value(response).ok();
+                let error = payload.as_ref().and_then(|p| p.get("error")).map(|e| {
+                    e.get("message")
+                        .and_then(Value::as_str)
+                        .map_or_else(|| e.to_string(), str::to_string)
+                });
+                let kind = if error.is_some() {
+                    MessageKind::Error
+                } else {
+                    MessageKind::Response
+                };
+                InspectorEvent {
+                    latency_ms,
+                    error,
+                    ..self
+                        .slot
+                        .event(Direction::Incoming, kind, method, id, payload)
+                }
+            }
+            Err(e) => InspectorEvent {
+                latency_ms,
+                error: Some(e.to_string()),
+                ..self
+                    .slot
+                    .event(Direction::Incoming, MessageKind::Error, method, id, None)
+            },
+        };
+        self.slot.emit(event);
+        result
+    }
+}
+
+/// 直接向服务器发送消息
+///
+/// `TransportHandle` 要求 `Clone`，不能作为 trait 对象保存，因此单独定义。
+#[async_trait]
+pub trait RawSender: Send + Sync {
+    async fn send_raw(&self, message: JsonRpcMessage) -> Result<JsonRpcMessage, TransportError>;
+}
+
+#[async_trait]
+impl<H: TransportHandle> RawSender for H {
+    async fn send_raw(&self, message: JsonRpcMessage) -> Result<JsonRpcMessage, TransportError> {
+        self.send(message).await
+    }
+}
+
+/// 构造任意方法的消息，`notifications/` 开头的方法作为通知发送
+pub fn raw_message(method: &str, params: Option<Value>) -> JsonRpcMessage {
+    if method.starts_with("notifications/") {
+        JsonRpcMessage::Notification(JsonRpcNotification {
+            jsonrpc: "2.0".to_string(),
+            method: method.to_string(),
+            params,
+        })
+    } else {
+        JsonRpcMessage::Request(JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(NEXT_RAW_ID.fetch_add(1, Ordering::SeqCst)),
+            method: method.to_string(),
+            params,
+        })
+    }
+}
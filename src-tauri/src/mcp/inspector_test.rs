@@ -0,0 +1,147 @@
+#[cfg(test)]
+mod tests {
+    use crate::mcp::inspector::{InspectorEvent, MessageKind};
+    use crate::mcp::mock_server::{connected_state, MockMcpServer};
+    use crate::mcp::recorder::Direction;
+    use crate::mcp::types::ToolCallRequest;
+    use serde_json::json;
+    use std::time::Duration;
+    use tokio::sync::mpsc::UnboundedReceiver;
+
+    fn drain(events: &mut UnboundedReceiver<InspectorEvent>) -> Vec<InspectorEvent> {
+        let mut drained = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            drained.push(event);
+        }
+        drained
+    }
+
+    // 测试开启检查器后每个请求和响应都产生事件
+    #[tokio::test]
+    async fn test_inspector_events() {
+        let state = connected_state(&[("mock", MockMcpServer::new())]).await;
+        let mut events = state
+            .mcp_client_manager
+            .lock()
+            .await
+            .start_inspector("mock")
+            .unwrap();
+
+        let result = state
+            .mcp_client_manager
+            .lock()
+            .await
+            .call_tool(ToolCallRequest {
+                client_id: "mock".to_string(),
+                tool_name: "echo".to_string(),
+                params: json!({ "text": "inspect me" }),
+            })
+            .await
+            .unwrap();
+        assert!(result.success);
+
+        let drained = drain(&mut events);
+        assert_eq!(drained.len(), 2);
+        let (request, response) = (&drained[0], &drained[1]);
+        assert_eq!(request.client_id, "mock");
+        assert_eq!(request.direction, Direction::Outgoing);
+        assert_eq!(request.kind, MessageKind::Request);
+        assert_eq!(request.method.as_deref(), Some("tools/call"));
+        assert!(request.latency_ms.is_none());
+        assert!(request.payload_size > 0);
+
+        assert_eq!(response.direction, Direction::Incoming);
+        assert_eq!(response.kind, MessageKind::Response);
+        assert_eq!(response.method.as_deref(), Some("tools/call"));
+        assert_eq!(response.id, request.id);
+        assert!(response.latency_ms.is_some());
+        assert!(response
+            .payload
+            .as_ref()
+            .unwrap()
+            .to_string()
+            .contains("inspect me"));
+
+        // 服务器主动发送的通知同样产生事件
+        state
+            .mcp_client_manager
+            .lock()
+            .await
+            .call_tool(ToolCallRequest {
+                client_id: "mock".to_string(),
+                tool_name: "list_changed".to_string(),
+                params: json!({}),
+            })
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(drain(&mut events).iter().any(|event| {
+            event.direction == Direction::Incoming
+                && event.kind == MessageKind::Notification
+                && event.method.as_deref() == Some("notifications/tools/list_changed")
+        }));
+
+        // 关闭后不再产生事件
+        let manager = state.mcp_client_manager.lock().await;
+        assert!(manager.stop_inspector("mock").unwrap());
+        assert!(!manager.stop_inspector("mock").unwrap());
+        assert!(manager.start_inspector("missing").is_err());
+    }
+
+    // 测试直接发送任意方法的请求和通知
+    #[tokio::test]
+    async fn test_send_raw_request() {
+        let state = connected_state(&[("mock", MockMcpServer::new())]).await;
+        let mut events = state
+            .mcp_client_manager
+            .lock()
+            .await
+            .start_inspector("mock")
+            .unwrap();
+        let timeout = Duration::from_secs(10);
+
+        let tools = state
+            .send_raw_request(None, "mock", "tools/list", Some(json!({})), timeout)
+            .await
+            .unwrap();
+        assert!(tools.success);
+        assert!(tools.data.unwrap()["result"]["tools"].is_array());
+
+        let unknown = state
+            .send_raw_request(None, "mock", "unknown/method", None, timeout)
+            .await
+            .unwrap();
+        assert!(!unknown.success);
+        assert_eq!(unknown.data.unwrap()["error"]["code"], -32601);
+
+        let notification = state
+            .send_raw_request(
+                None,
+                "mock",
+                "notifications/roots/list_changed",
+                None,
+                timeout,
+            )
+            .await
+            .unwrap();
+        assert!(notification.success);
+        assert!(notification.data.is_none());
+
+        let kinds: Vec<MessageKind> = drain(&mut events).iter().map(|event| event.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                MessageKind::Request,
+                MessageKind::Response,
+                MessageKind::Request,
+                MessageKind::Error,
+                MessageKind::Notification,
+            ]
+        );
+
+        assert!(state
+            .send_raw_request(None, "missing", "tools/list", None, timeout)
+            .await
+            .is_err());
+    }
+}
@@ -1,203 +1,250 @@
 #[cfg(test)]
 mod integration_tests {
     use crate::mcp::client::McpClientManager;
+    use crate::mcp::mock_server::{start_sse, MockMcpServer};
     use crate::mcp::types::{
-        ClientStatus, FilterRequest, InitializeClientRequest, PromptRequest, ResourceReadRequest,
-        ToolCallRequest, TransportType,
+        ClientStatus, ContentBlock, FilterRequest, InitializeClientRequest, PromptRequest,
+        ResourceContent, ResourceReadRequest, ToolCallRequest, TransportType,
     };
     use std::collections::HashMap;
 
-    // 注意：这个测试需要一个实际运行的MCP服务器
-    // 可以在测试前启动一个本地服务器，或者使用一个测试环境的服务器
-    // 这里我们假设有一个可用的服务器运行在localhost:8080
+    // 使用内置的模拟 MCP 服务器，不依赖外部服务器或本机路径
 
-    #[tokio::test]
-    async fn test_full_mcp_client_lifecycle() {
-        // 创建客户端管理器
-        let mut manager = McpClientManager::new();
-
-        // 1. 初始化客户端
-        let client_id = "integration-test-client";
-        let init_request = InitializeClientRequest {
+    fn base_request(client_id: &str, transport_type: TransportType) -> InitializeClientRequest {
+        InitializeClientRequest {
             id: client_id.to_string(),
-            transport_type: TransportType::Stdio,
-            sse_url: None,
+            transport_type,
             headers: Some(HashMap::new()),
-            command: Some("./src/mcp/mcp-sqlite.exe".to_string()),
-            args: Some(vec![
-                "--db".to_string(),
-                "C:\\Users\\daiwj\\test.db".to_string(),
-            ]),
             timeout_secs: Some(30),
             client_name: "integration-test".to_string(),
             client_version: "1.0.0".to_string(),
-        };
+            ..Default::default()
+        }
+    }
 
-        let init_result = manager.initialize_client(init_request).await;
-        assert!(
-            init_result.is_ok(),
-            "Failed to initialize client: {:?}",
-            init_result.err()
-        );
+    async fn sse_request(client_id: &str) -> InitializeClientRequest {
+        let url = start_sse(MockMcpServer::new(), "127.0.0.1:0")
+            .await
+            .unwrap();
+        InitializeClientRequest {
+            sse_url: Some(url),
+            ..base_request(client_id, TransportType::SSE)
+        }
+    }
 
-        let status = init_result.unwrap();
-        assert_eq!(status.id, client_id);
-        match status.status {
-            ClientStatus::Connected => assert!(true),
-            _ => assert!(false, "Expected Connected status, got {:?}", status.status),
-        };
-        assert!(status.server_info.is_some());
+    #[cfg(unix)]
+    async fn stdio_request(client_id: &str) -> InitializeClientRequest {
+        let (command, args) = crate::mcp::mock_server::stdio_bridge(MockMcpServer::new()).await;
+        InitializeClientRequest {
+            command: Some(command),
+            args: Some(args),
+            ..base_request(client_id, TransportType::Stdio)
+        }
+    }
 
-        // 2. 列出工具
-        println!("\n===== 开始测试列出工具 =====");
-        let tools_request = FilterRequest {
+    fn tool_call(client_id: &str, tool_name: &str, params: serde_json::Value) -> ToolCallRequest {
+        ToolCallRequest {
             client_id: client_id.to_string(),
-            filter: None,
-        };
+            tool_name: tool_name.to_string(),
+            params,
+        }
+    }
 
-        let tools_result = manager.list_tools(tools_request).await;
+    async fn tool_names(manager: &McpClientManager, client_id: &str) -> Vec<String> {
+        let response = manager
+            .list_tools(FilterRequest {
+                client_id: client_id.to_string(),
+                filter: None,
+                cursor: None,
+            })
+            .await
+            .unwrap();
         assert!(
-            tools_result.is_ok(),
+            response.success,
             "Failed to list tools: {:?}",
-            tools_result.err()
+            response.error
         );
+        response
+            .data
+            .unwrap()
+            .into_iter()
+            .map(|tool| tool.name)
+            .collect()
+    }
 
-        let tools_response = tools_result.unwrap();
-        assert!(tools_response.success);
-        assert!(tools_response.data.is_some());
-        println!(
-            "找到 {} 个工具",
-            tools_response.data.as_ref().map_or(0, |tools| tools.len())
-        );
-        if let Some(tools) = &tools_response.data {
-            for (i, tool) in tools.iter().enumerate() {
-                println!("  工具 {}: {}", i + 1, tool.name);
-            }
-        }
-        println!("===== 工具列出测试完成 =====\n");
-
-        // 3. 调用工具（如果有可用的工具）
-        println!("\n===== 开始测试调用工具 =====");
-        if let Some(tools) = &tools_response.data {
-            if let Some(tool) = tools.first() {
-                println!("尝试调用工具: {}", tool.name);
-                let tool_call_request = ToolCallRequest {
-                    client_id: client_id.to_string(),
-                    tool_name: tool.name.clone(),
-                    params: serde_json::json!({"query":"select * from products"}),
-                };
-
-                let call_result = manager.call_tool(tool_call_request).await;
-                assert!(
-                    call_result.is_ok(),
-                    "Failed to call tool: {:?}",
-                    call_result.err()
-                );
-
-                let call_response = call_result.unwrap();
-                assert!(call_response.success);
-                println!(
-                    "工具调用结果: {}",
-                    serde_json::to_string_pretty(&call_response.data)
-                        .unwrap_or_else(|_| "无法格式化结果".to_string())
-                );
-            }
-        } else {
-            println!("没有可用的工具可以调用");
-        }
-        println!("===== 工具调用测试完成 =====\n");
+    // 完整的客户端生命周期：初始化、工具、资源、提示、断开和删除
+    async fn run_lifecycle(mut manager: McpClientManager, init_request: InitializeClientRequest) {
+        let client_id = init_request.id.clone();
 
-        // 4. 列出资源
-        println!("\n===== 开始测试列出资源 =====");
-        let resources_request = FilterRequest {
-            client_id: client_id.to_string(),
-            filter: None,
-        };
-
-        let resources_result = manager.list_resources(resources_request).await;
+        // 1. 初始化客户端
+        let status = manager
+            .initialize_client(init_request)
+            .await
+            .expect("Failed to initialize client");
+        assert_eq!(status.id, client_id);
         assert!(
-            resources_result.is_ok(),
-            "Failed to list resources: {:?}",
-            resources_result.err()
+            matches!(status.status, ClientStatus::Connected),
+            "Expected Connected status, got {:?}",
+            status.status
         );
+        assert_eq!(status.server_info.unwrap().name, "fishmind-mock");
 
-        let resources_response = resources_result.unwrap();
-        assert!(resources_response.success);
-        assert!(resources_response.data.is_some());
-        println!(
-            "找到 {} 个资源",
-            resources_response
-                .data
-                .as_ref()
-                .map_or(0, |resources| resources.len())
-        );
-        if let Some(resources) = &resources_response.data {
-            for (i, resource) in resources.iter().enumerate() {
-                println!("  资源 {}: {}", i + 1, resource.uri);
-            }
-        }
-        println!("===== 资源列出测试完成 =====\n");
-
-        // 5. 读取资源（如果有可用的资源）
-        println!("\n===== 开始测试读取资源 =====");
-        if let Some(resources) = &resources_response.data {
-            if let Some(resource) = resources.first() {
-                println!("尝试读取资源: {}", resource.uri);
-                let resource_read_request = ResourceReadRequest {
-                    client_id: client_id.to_string(),
-                    resource_uri: resource.uri.clone(),
-                };
-
-                let read_result = manager.read_resource(resource_read_request).await;
-                assert!(
-                    read_result.is_ok(),
-                    "Failed to read resource: {:?}",
-                    read_result.err()
-                );
-
-                let read_response = read_result.unwrap();
-                assert!(read_response.success);
-                println!(
-                    "资源内容: {}",
-                    serde_json::to_string_pretty(&read_response.data)
-                        .unwrap_or_else(|_| "无法格式化内容".to_string())
-                );
-            }
-        } else {
-            println!("没有可用的资源可以读取");
+        // 2. 列出工具
+        let tools = tool_names(&manager, &client_id).await;
+        for name in ["echo", "slow", "failing", "progress", "list_changed"] {
+            assert!(tools.contains(&name.to_string()), "missing tool {}", name);
         }
-        println!("===== 资源读取测试完成 =====\n");
 
-        // 6. 断开客户端连接
-        println!("\n===== 开始测试断开连接 =====");
-        let disconnect_result = manager.disconnect_client(client_id).await;
-        assert!(
-            disconnect_result.is_ok(),
-            "Failed to disconnect client: {:?}",
-            disconnect_result.err()
+        // 3. 调用工具
+        let echo = manager
+            .call_tool(tool_call(
+                &client_id,
+                "echo",
+                serde_json::json!({"text": "你好"}),
+            ))
+            .await
+            .unwrap();
+        assert!(echo.success, "echo failed: {:?}", echo.error);
+        assert_eq!(echo.data.unwrap()["content"][0]["text"], "你好");
+
+        let progress = manager
+            .call_tool(tool_call(
+                &client_id,
+                "progress",
+                serde_json::json!({"steps": 2}),
+            ))
+            .await
+            .unwrap();
+        assert!(progress.success);
+
+        // 工具返回 isError 时调用本身成功，由调用方处理错误内容
+        let failing = manager
+            .call_tool(tool_call(&client_id, "failing", serde_json::json!({})))
+            .await
+            .unwrap();
+        assert!(failing.success);
+        assert_eq!(failing.data.unwrap()["isError"], true);
+
+        let rpc_error = manager
+            .call_tool(tool_call(
+                &client_id,
+                "failing",
+                serde_json::json!({"rpc": true}),
+            ))
+            .await
+            .unwrap();
+        assert!(!rpc_error.success);
+        assert!(rpc_error.error.is_some());
+
+        // 工具列表变更后重新列出可以看到新工具
+        let changed = manager
+            .call_tool(tool_call(&client_id, "list_changed", serde_json::json!({})))
+            .await
+            .unwrap();
+        assert!(changed.success);
+        assert!(tool_names(&manager, &client_id)
+            .await
+            .contains(&"extra".to_string()));
+
+        // 4. 列出和读取资源
+        let resources = manager
+            .list_resources(FilterRequest {
+                client_id: client_id.clone(),
+                filter: None,
+                cursor: None,
+            })
+            .await
+            .unwrap();
+        assert!(resources.success);
+        let resources = resources.data.unwrap();
+        assert_eq!(resources.len(), 2);
+
+        let read = manager
+            .read_resource(ResourceReadRequest {
+                client_id: client_id.clone(),
+                resource_uri: "mock://text/hello".to_string(),
+            })
+            .await
+            .unwrap();
+        assert!(read.success);
+        assert_eq!(
+            read.data.unwrap(),
+            vec![ResourceContent::Text {
+                uri: "mock://text/hello".to_string(),
+                mime_type: "text/plain".to_string(),
+                text: "Hello from the mock server".to_string(),
+            }]
+        );
+
+        // 5. 列出和获取提示
+        let prompts = manager
+            .list_prompts(FilterRequest {
+                client_id: client_id.clone(),
+                filter: None,
+                cursor: None,
+            })
+            .await
+            .unwrap();
+        assert!(prompts.success);
+        assert_eq!(prompts.data.unwrap()[0].name, "greeting");
+
+        let prompt = manager
+            .get_prompt(PromptRequest {
+                client_id: client_id.clone(),
+                prompt_name: "greeting".to_string(),
+                params: serde_json::json!({"name": "Ada"}),
+            })
+            .await
+            .unwrap();
+        assert!(prompt.success);
+        assert_eq!(
+            prompt.data.unwrap().messages[0].content,
+            vec![ContentBlock::Text {
+                text: "Hello, Ada!".to_string()
+            }]
         );
 
-        let disconnect_status = disconnect_result.unwrap();
+        // 6. 断开客户端连接
+        let disconnect_status = manager
+            .disconnect_client(&client_id)
+            .await
+            .expect("Failed to disconnect client");
         assert_eq!(disconnect_status.id, client_id);
-        match disconnect_status.status {
-            ClientStatus::Disconnected => assert!(true),
-            _ => assert!(
-                false,
-                "Expected Disconnected status, got {:?}",
-                disconnect_status.status
-            ),
-        };
+        assert!(matches!(
+            disconnect_status.status,
+            ClientStatus::Disconnected
+        ));
 
         // 7. 删除客户端
-        let delete_result = manager.delete_client(client_id).await;
-        assert!(
-            delete_result.is_ok(),
-            "Failed to delete client: {:?}",
-            delete_result.err()
-        );
+        manager
+            .delete_client(&client_id)
+            .await
+            .expect("Failed to delete client");
+        assert!(manager.get_client_status(&client_id).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_full_mcp_client_lifecycle_sse() {
+        let request = sse_request("integration-sse-client").await;
+        run_lifecycle(McpClientManager::new(), request).await;
+    }
 
-        // 验证客户端已被删除
-        let status_result = manager.get_client_status(client_id);
-        assert!(status_result.is_err());
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_full_mcp_client_lifecycle_stdio() {
+        let request = stdio_request("integration-stdio-client").await;
+        run_lifecycle(McpClientManager::new(), request).await;
+    }
+
+    // 命令中的占位符在查找命令和启动进程之前解析
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_stdio_command_placeholders() {
+        std::env::set_var("FISHMIND_TEST_STDIO_SHELL", "bash");
+        let request = InitializeClientRequest {
+            command: Some("${env:FISHMIND_TEST_STDIO_SHELL}".to_string()),
+            ..stdio_request("integration-stdio-placeholders").await
+        };
+        run_lifecycle(McpClientManager::new(), request).await;
     }
 }
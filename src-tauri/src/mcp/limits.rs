@@ -0,0 +1,200 @@
+use crate::mcp::types::LimitConfig;
+use log::{debug, warn};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// 限流错误前缀，模型和前端可据此识别需要退避重试
+pub const RATE_LIMITED_ERROR: &str = "RATE_LIMITED";
+
+// 未配置时请求排队的最长时间
+const DEFAULT_QUEUE_TIMEOUT_MS: u64 = 10_000;
+
+// 建议的重试等待时间上限，速率极小时等待时间可能超出 Duration 的范围
+const MAX_RETRY_WAIT: Duration = Duration::from_secs(3600);
+
+/// 令牌桶
+struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate_per_sec: f64, burst: Option<u32>) -> Self {
+        let capacity = burst.map(|b| b as f64).unwrap_or(rate_per_sec).max(1.0);
+        Self {
+            capacity,
+            tokens: capacity,
+            refill_per_sec: rate_per_sec,
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// 尝试取出一个令牌，失败时返回需要等待的时间
+    fn try_take(&mut self) -> Result<(), Duration> {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last_refill = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            let wait = Duration::try_from_secs_f64((1.0 - self.tokens) / self.refill_per_sec)
+                .unwrap_or(MAX_RETRY_WAIT);
+            Err(wait.min(MAX_RETRY_WAIT))
+        }
+    }
+
+    /// 归还取出的令牌
+    fn refund(&mut self) {
+        self.tokens = (self.tokens + 1.0).min(self.capacity);
+    }
+}
+
+/// 单个维度（客户端或工具）的限流器
+struct Limiter {
+    bucket: Option<Mutex<TokenBucket>>,
+    semaphore: Option<Arc<Semaphore>>,
+}
+
+impl Limiter {
+    fn new(rate_per_sec: Option<f64>, burst: Option<u32>, max_concurrent: Option<usize>) -> Self {
+        Self {
+            bucket: rate_per_sec
+                .filter(|rate| *rate > 0.0)
+                .map(|rate| Mutex::new(TokenBucket::new(rate, burst))),
+            semaphore: max_concurrent.map(|max| Arc::new(Semaphore::new(max.max(1)))),
+        }
+    }
+
+    /// 在截止时间前获取令牌和并发许可，失败时返回建议的重试等待时间
+    async fn acquire(&self, deadline: Instant) -> Result<Option<OwnedSemaphorePermit>, Duration> {
+        if let Some(bucket) = &self.bucket {
+            loop {
+                let wait = match bucket.lock().unwrap().try_take() {
+                    Ok(()) => break,
+                    Err(wait) => wait,
+                };
+                if Instant::now() + wait > deadline {
+                    return Err(wait);
+                }
+                tokio::time::sleep(wait).await;
+            }
+        }
+
+        match &self.semaphore {
+            Some(semaphore) => {
+                match tokio::time::timeout_at(deadline.into(), semaphore.clone().acquire_owned())
+                    .await
+                {
+                    Ok(Ok(permit)) => Ok(Some(permit)),
+                    // 信号量不会被关闭，超时时建议等待一个排队周期
+                    _ => {
+                        self.refund();
+                        Err(Duration::from_millis(DEFAULT_QUEUE_TIMEOUT_MS / 10))
+                    }
+                }
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// 调用最终没有执行时归还令牌，并发许可随 permit 释放
+    fn refund(&self) {
+        if let Some(bucket) = &self.bucket {
+            bucket.lock().unwrap().refund();
+        }
+    }
+}
+
+/// 持有期间占用并发名额
+pub struct LimitPermit {
+    _permits: Vec<OwnedSemaphorePermit>,
+}
+
+/// 客户端级限流器，包含按工具覆盖的限流配置
+pub struct ClientLimiter {
+    config: LimitConfig,
+    client: Limiter,
+    tools: HashMap<String, Limiter>,
+}
+
+impl ClientLimiter {
+    /// 根据配置创建限流器
+    pub fn new(config: &LimitConfig) -> Self {
+        let tools = config
+            .tools
+            .iter()
+            .map(|(name, tool)| {
+                (
+                    name.clone(),
+                    Limiter::new(tool.rate_per_sec, tool.burst, tool.max_concurrent),
+                )
+            })
+            .collect();
+
+        Self {
+            config: config.clone(),
+            client: Limiter::new(config.rate_per_sec, config.burst, config.max_concurrent),
+            tools,
+        }
+    }
+
+    /// 当前配置
+    pub fn config(&self) -> &LimitConfig {
+        &self.config
+    }
+
+    /// 为一次工具调用获取许可，超过排队时间时返回限流错误
+    pub async fn acquire(&self, client_id: &str, tool_name: &str) -> Result<LimitPermit, String> {
+        let queue_timeout = self
+            .config
+            .queue_timeout_ms
+            .unwrap_or(DEFAULT_QUEUE_TIMEOUT_MS);
+        let deadline = Instant::now() + Duration::from_millis(queue_timeout);
+        let mut permits = Vec::new();
+        let mut acquired: Vec<&Limiter> = Vec::new();
+
+        let limiters = self
+            .tools
+            .get(tool_name)
+            .into_iter()
+            .chain(std::iter::once(&self.client));
+        for limiter in limiters {
+            match limiter.acquire(deadline).await {
+                Ok(permit) => {
+                    permits.extend(permit);
+                    acquired.push(limiter);
+                }
+                Err(retry_after) => {
+                    // 后面的限流器拒绝时，归还前面已经取出的令牌
+                    for limiter in acquired {
+                        limiter.refund();
+                    }
+                    warn!(
+                        "[MCP] 工具调用被限流, 客户端ID: {}, 工具: {}",
+                        client_id, tool_name
+                    );
+                    return Err(format!(
+                        "{}: too many calls to tool '{}' on client '{}', back off and retry after {} ms",
+                        RATE_LIMITED_ERROR,
+                        tool_name,
+                        client_id,
+                        retry_after.as_millis().max(1)
+                    ));
+                }
+            }
+        }
+
+        debug!(
+            "[MCP] 获取限流许可成功, 客户端ID: {}, 工具: {}",
+            client_id, tool_name
+        );
+        Ok(LimitPermit { _permits: permits })
+    }
+}
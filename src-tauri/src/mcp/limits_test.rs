@@ -0,0 +1,114 @@
+#[cfg(test)]
+mod tests {
+    use crate::mcp::limits::{ClientLimiter, RATE_LIMITED_ERROR};
+    use crate::mcp::types::{LimitConfig, ToolLimitConfig};
+    use std::collections::HashMap;
+
+    // 测试未配置限制时总是放行
+    #[tokio::test]
+    async fn test_unlimited() {
+        let limiter = ClientLimiter::new(&LimitConfig::default());
+        for _ in 0..100 {
+            assert!(limiter.acquire("test-client", "echo").await.is_ok());
+        }
+    }
+
+    // 测试令牌耗尽且排队超时后返回限流错误
+    #[tokio::test]
+    async fn test_rate_limit_exceeded() {
+        let limiter = ClientLimiter::new(&LimitConfig {
+            rate_per_sec: Some(1.0),
+            burst: Some(2),
+            queue_timeout_ms: Some(10),
+            ..Default::default()
+        });
+
+        assert!(limiter.acquire("test-client", "echo").await.is_ok());
+        assert!(limiter.acquire("test-client", "echo").await.is_ok());
+
+        let err = limiter.acquire("test-client", "echo").await.err().unwrap();
+        assert!(err.starts_with(RATE_LIMITED_ERROR));
+        assert!(err.contains("retry after"));
+    }
+
+    // 测试排队时间足够时等待令牌补充
+    #[tokio::test]
+    async fn test_rate_limit_queues_until_refill() {
+        let limiter = ClientLimiter::new(&LimitConfig {
+            rate_per_sec: Some(20.0),
+            burst: Some(1),
+            queue_timeout_ms: Some(1_000),
+            ..Default::default()
+        });
+
+        assert!(limiter.acquire("test-client", "echo").await.is_ok());
+        assert!(limiter.acquire("test-client", "echo").await.is_ok());
+    }
+
+    // 测试工具级并发上限，许可释放后可再次获取
+    #[tokio::test]
+    async fn test_tool_concurrency_override() {
+        let mut tools = HashMap::new();
+        tools.insert(
+            "slow".to_string(),
+            ToolLimitConfig {
+                max_concurrent: Some(1),
+                ..Default::default()
+            },
+        );
+        let limiter = ClientLimiter::new(&LimitConfig {
+            queue_timeout_ms: Some(10),
+            tools,
+            ..Default::default()
+        });
+
+        let permit = limiter.acquire("test-client", "slow").await.unwrap();
+        assert!(limiter.acquire("test-client", "slow").await.is_err());
+        // 其他工具不受影响
+        assert!(limiter.acquire("test-client", "echo").await.is_ok());
+
+        drop(permit);
+        assert!(limiter.acquire("test-client", "slow").await.is_ok());
+    }
+
+    // 测试速率极小时建议的等待时间有上限，不会溢出
+    #[tokio::test]
+    async fn test_tiny_rate() {
+        let limiter = ClientLimiter::new(&LimitConfig {
+            rate_per_sec: Some(1e-300),
+            burst: Some(1),
+            queue_timeout_ms: Some(10),
+            ..Default::default()
+        });
+
+        assert!(limiter.acquire("test-client", "echo").await.is_ok());
+        let err = limiter.acquire("test-client", "echo").await.err().unwrap();
+        assert!(err.ends_with("retry after 3600000 ms"), "{}", err);
+    }
+
+    // 测试客户端级限制拒绝调用时归还工具级令牌
+    #[tokio::test]
+    async fn test_refund_tool_token() {
+        let mut tools = HashMap::new();
+        tools.insert(
+            "search".to_string(),
+            ToolLimitConfig {
+                rate_per_sec: Some(0.001),
+                burst: Some(1),
+                ..Default::default()
+            },
+        );
+        let limiter = ClientLimiter::new(&LimitConfig {
+            max_concurrent: Some(1),
+            queue_timeout_ms: Some(10),
+            tools,
+            ..Default::default()
+        });
+
+        let permit = limiter.acquire("test-client", "echo").await.unwrap();
+        assert!(limiter.acquire("test-client", "search").await.is_err());
+
+        drop(permit);
+        assert!(limiter.acquire("test-client", "search").await.is_ok());
+    }
+}
@@ -0,0 +1,132 @@
+use crate::mcp::redact;
+use serde::Serialize;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+use tracing::Span;
+use tracing_subscriber::{
+    fmt::{self, format::FmtSpan},
+    layer::SubscriberExt,
+    reload,
+    util::SubscriberInitExt,
+    EnvFilter, Registry,
+};
+
+/// 设置为 1 或 true 时以 JSON 格式输出日志
+pub const JSON_LOG_ENV: &str = "FISHMIND_LOG_JSON";
+
+/// 当前日志配置
+#[derive(Debug, Clone, Serialize)]
+pub struct LoggingConfig {
+    pub level: String,
+    /// 是否以 JSON 格式输出，只能在启动时通过 [`JSON_LOG_ENV`] 设置
+    pub json: bool,
+}
+
+static FILTER_HANDLE: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+static CONFIG: OnceLock<Mutex<LoggingConfig>> = OnceLock::new();
+static NEXT_CALL_ID: AtomicU64 = AtomicU64::new(1);
+
+/// 输出前对日志行脱敏的写入器
+struct RedactingWriter;
+
+impl Write for RedactingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let text = String::from_utf8_lossy(buf);
+        io::stderr().write_all(redact::redact_text(&text).as_bytes())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stderr().flush()
+    }
+}
+
+/// 初始化日志系统
+///
+/// 使用 `RUST_LOG` 作为初始过滤规则（默认 info），`log` 宏的输出也会经由 tracing 处理，
+/// 因此 MCP 传输层的日志会带上当前请求的 span 字段。
+pub fn init() {
+    let level = std::env::var("RUST_LOG").unwrap_or_else(|_| "info".to_string());
+    let json = matches!(std::env::var(JSON_LOG_ENV).as_deref(), Ok("1") | Ok("true"));
+
+    let filter = EnvFilter::try_new(&level).unwrap_or_else(|_| EnvFilter::new("info"));
+    let (filter, handle) = reload::Layer::new(filter);
+    let fmt_layer = fmt::layer()
+        .with_writer(|| RedactingWriter)
+        .with_span_events(FmtSpan::CLOSE);
+
+    let registry = tracing_subscriber::registry().with(filter);
+    if json {
+        registry
+            .with(
+                fmt_layer
+                    .json()
+                    .with_current_span(true)
+                    .with_span_list(true),
+            )
+            .init();
+    } else {
+        registry.with(fmt_layer).init();
+    }
+
+    let _ = FILTER_HANDLE.set(handle);
+    let _ = CONFIG.set(Mutex::new(LoggingConfig { level, json }));
+}
+
+/// 运行时调整日志级别，支持 `EnvFilter` 语法，例如 `debug` 或 `info,fishmind::mcp=trace`
+pub fn set_level(level: &str) -> Result<(), String> {
+    let filter =
+        EnvFilter::try_new(level).map_err(|e| format!("Invalid log level '{}': {}", level, e))?;
+    let handle = FILTER_HANDLE
+        .get()
+        .ok_or_else(|| "Logging is not initialized".to_string())?;
+    handle
+        .reload(filter)
+        .map_err(|e| format!("Failed to update log level: {}", e))?;
+
+    if let Some(config) = CONFIG.get() {
+        config.lock().unwrap().level = level.to_string();
+    }
+    tracing::info!("[MCP] 日志级别已调整为: {}", level);
+    Ok(())
+}
+
+/// 获取当前日志配置
+pub fn get_config() -> LoggingConfig {
+    CONFIG
+        .get()
+        .map(|config| config.lock().unwrap().clone())
+        .unwrap_or(LoggingConfig {
+            level: "info".to_string(),
+            json: false,
+        })
+}
+
+/// 为一次 MCP 请求创建 span，携带调用ID、客户端ID和操作名
+pub fn request_span(operation: &str, client_id: &str) -> Span {
+    let call_id = NEXT_CALL_ID.fetch_add(1, Ordering::SeqCst);
+    tracing::info_span!(
+        "mcp_request",
+        call_id,
+        client_id = %client_id,
+        operation = %operation,
+        tool_name = tracing::field::Empty,
+        rpc_id = tracing::field::Empty,
+        duration_ms = tracing::field::Empty,
+    )
+}
+
+/// 在当前请求的 span 上记录发出的 JSON-RPC 请求ID，用于与服务器日志对照
+///
+/// 一次操作可能发送多个请求（如分页），span 上保留最后一个，每个请求另有一条 debug 日志。
+pub fn record_rpc_id(method: &str, id: u64) {
+    Span::current().record("rpc_id", id);
+    tracing::debug!("[MCP] 发送请求 {}, JSON-RPC ID: {}", method, id);
+}
+
+/// 在 span 上记录请求耗时
+pub fn record_duration(span: &Span, started: Instant) {
+    span.record("duration_ms", started.elapsed().as_millis() as u64);
+}
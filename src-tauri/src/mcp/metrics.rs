@@ -0,0 +1,315 @@
+use crate::mcp::types::{CallStats, ClientMetrics, LastCallError, LatencySummary, ToolMetrics};
+use chrono::Utc;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// 耗时直方图的桶上限（毫秒），超过最后一个上限的调用计入溢出桶
+pub const LATENCY_BUCKETS_MS: [u64; 14] = [
+    5, 10, 25, 50, 100, 250, 500, 1_000, 2_500, 5_000, 10_000, 20_000, 30_000, 60_000,
+];
+
+/// 单次工具调用的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CallOutcome {
+    Success,
+    Error(String),
+    Timeout,
+    /// 被限流或熔断拒绝，没有发送给服务器
+    Rejected(String),
+}
+
+#[derive(Default)]
+struct Histogram {
+    // 最后一个元素是溢出桶
+    counts: [u64; LATENCY_BUCKETS_MS.len() + 1],
+    sum_ms: u64,
+    max_ms: u64,
+}
+
+impl Histogram {
+    fn observe(&mut self, ms: u64) {
+        let bucket = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|bound| ms <= *bound)
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+        self.counts[bucket] += 1;
+        self.sum_ms += ms;
+        self.max_ms = self.max_ms.max(ms);
+    }
+
+    // 返回分位数所在桶的上限，不超过观察到的最大值
+    fn quantile(&self, q: f64) -> u64 {
+        let total: u64 = self.counts.iter().sum();
+        if total == 0 {
+            return 0;
+        }
+        let rank = ((total as f64) * q).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (bucket, count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return LATENCY_BUCKETS_MS
+                    .get(bucket)
+                    .map_or(self.max_ms, |bound| (*bound).min(self.max_ms));
+            }
+        }
+        self.max_ms
+    }
+
+    fn summary(&self) -> LatencySummary {
+        LatencySummary {
+            p50: self.quantile(0.5),
+            p95: self.quantile(0.95),
+            p99: self.quantile(0.99),
+            max: self.max_ms,
+            sum: self.sum_ms,
+            buckets: self.counts.to_vec(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Stats {
+    calls: u64,
+    successes: u64,
+    errors: u64,
+    timeouts: u64,
+    bytes_out: u64,
+    bytes_in: u64,
+    latency: Histogram,
+    last_error: Option<LastCallError>,
+}
+
+impl Stats {
+    fn record(&mut self, call: &RecordedCall) {
+        self.calls += 1;
+        self.bytes_out += call.bytes_out;
+        self.bytes_in += call.bytes_in;
+        // 被拒绝的调用没有等待服务器，不计入耗时
+        if !matches!(call.outcome, CallOutcome::Rejected(_)) {
+            self.latency.observe(call.duration.as_millis() as u64);
+        }
+        let message = match &call.outcome {
+            CallOutcome::Success => {
+                self.successes += 1;
+                return;
+            }
+            CallOutcome::Error(message) | CallOutcome::Rejected(message) => {
+                self.errors += 1;
+                message.clone()
+            }
+            CallOutcome::Timeout => {
+                self.timeouts += 1;
+                "Tool call timed out".to_string()
+            }
+        };
+        self.last_error = Some(LastCallError {
+            tool_name: call.tool_name.to_string(),
+            message,
+            at: Utc::now(),
+        });
+    }
+
+    fn snapshot(&self) -> CallStats {
+        CallStats {
+            calls: self.calls,
+            successes: self.successes,
+            errors: self.errors,
+            timeouts: self.timeouts,
+            bytes_out: self.bytes_out,
+            bytes_in: self.bytes_in,
+            latency_ms: self.latency.summary(),
+            last_error: self.last_error.clone(),
+        }
+    }
+}
+
+struct RecordedCall<'a> {
+    tool_name: &'a str,
+    duration: Duration,
+    outcome: CallOutcome,
+    bytes_out: u64,
+    bytes_in: u64,
+}
+
+#[derive(Default)]
+struct ClientStats {
+    total: Stats,
+    tools: HashMap<String, Stats>,
+}
+
+/// 单个客户端的工具调用统计
+///
+/// 命中结果缓存的调用不计入；被限流或熔断拒绝的调用计为错误，但不计入耗时。
+#[derive(Default)]
+pub struct CallMetrics {
+    stats: Mutex<ClientStats>,
+}
+
+impl CallMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录一次调用，`bytes_out` 为参数大小，`bytes_in` 为结果大小
+    pub fn record(
+        &self,
+        tool_name: &str,
+        duration: Duration,
+        outcome: CallOutcome,
+        bytes_out: u64,
+        bytes_in: u64,
+    ) {
+        let call = RecordedCall {
+            tool_name,
+            duration,
+            outcome,
+            bytes_out,
+            bytes_in,
+        };
+        let mut stats = self.stats.lock().unwrap();
+        stats.total.record(&call);
+        stats
+            .tools
+            .entry(tool_name.to_string())
+            .or_default()
+            .record(&call);
+    }
+
+    /// 当前统计，工具按名称排序
+    pub fn snapshot(&self, client_id: &str) -> ClientMetrics {
+        let stats = self.stats.lock().unwrap();
+        let mut tools: Vec<ToolMetrics> = stats
+            .tools
+            .iter()
+            .map(|(tool_name, stats)| ToolMetrics {
+                tool_name: tool_name.clone(),
+                stats: stats.snapshot(),
+            })
+            .collect();
+        tools.sort_by(|a, b| a.tool_name.cmp(&b.tool_name));
+        ClientMetrics {
+            client_id: client_id.to_string(),
+            stats: stats.total.snapshot(),
+            tools,
+        }
+    }
+}
+
+/// 生成 Prometheus 文本格式的统计，按客户端和工具分别输出
+pub fn prometheus_text(clients: &[ClientMetrics]) -> String {
+    let tools: Vec<(String, &CallStats)> = clients
+        .iter()
+        .flat_map(|client| {
+            client.tools.iter().map(move |tool| {
+                (
+                    format!(
+                        "client=\"{}\",tool=\"{}\"",
+                        escape_label(&client.client_id),
+                        escape_label(&tool.tool_name)
+                    ),
+                    &tool.stats,
+                )
+            })
+        })
+        .collect();
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "# HELP fishmind_mcp_tool_calls_total MCP tool calls by outcome."
+    );
+    let _ = writeln!(out, "# TYPE fishmind_mcp_tool_calls_total counter");
+    for (labels, stats) in &tools {
+        for (outcome, count) in [
+            ("success", stats.successes),
+            ("error", stats.errors),
+            ("timeout", stats.timeouts),
+        ] {
+            let _ = writeln!(
+                out,
+                "fishmind_mcp_tool_calls_total{{{},outcome=\"{}\"}} {}",
+                labels, outcome, count
+            );
+        }
+    }
+
+    let _ = writeln!(
+        out,
+        "# HELP fishmind_mcp_tool_bytes_total Bytes sent to and received from MCP tools."
+    );
+    let _ = writeln!(out, "# TYPE fishmind_mcp_tool_bytes_total counter");
+    for (labels, stats) in &tools {
+        for (direction, bytes) in [("out", stats.bytes_out), ("in", stats.bytes_in)] {
+            let _ = writeln!(
+                out,
+                "fishmind_mcp_tool_bytes_total{{{},direction=\"{}\"}} {}",
+                labels, direction, bytes
+            );
+        }
+    }
+
+    let _ = writeln!(
+        out,
+        "# HELP fishmind_mcp_tool_latency_seconds MCP tool call latency."
+    );
+    let _ = writeln!(out, "# TYPE fishmind_mcp_tool_latency_seconds histogram");
+    for (labels, stats) in &tools {
+        let latency = &stats.latency_ms;
+        // 桶计数按 Prometheus 的约定累加输出
+        let mut cumulative = 0;
+        for (bound, count) in LATENCY_BUCKETS_MS.iter().zip(&latency.buckets) {
+            cumulative += count;
+            let _ = writeln!(
+                out,
+                "fishmind_mcp_tool_latency_seconds_bucket{{{},le=\"{}\"}} {}",
+                labels,
+                *bound as f64 / 1000.0,
+                cumulative
+            );
+        }
+        let count: u64 = latency.buckets.iter().sum();
+        let _ = writeln!(
+            out,
+            "fishmind_mcp_tool_latency_seconds_bucket{{{},le=\"+Inf\"}} {}",
+            labels, count
+        );
+        let _ = writeln!(
+            out,
+            "fishmind_mcp_tool_latency_seconds_sum{{{}}} {}",
+            labels,
+            latency.sum as f64 / 1000.0
+        );
+        let _ = writeln!(
+            out,
+            "fishmind_mcp_tool_latency_seconds_count{{{}}} {}",
+            labels, count
+        );
+    }
+    out
+}
+
+/// 将 Prometheus 文本格式的统计写入文件
+///
+/// 先写临时文件再替换，采集程序不会读到写了一半的文件。
+pub fn write_prometheus(path: &Path, clients: &[ClientMetrics]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let tmp_path = path.with_extension("prom.tmp");
+    fs::write(&tmp_path, prometheus_text(clients))
+        .and_then(|_| fs::rename(&tmp_path, path))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -0,0 +1,285 @@
+#[cfg(test)]
+mod tests {
+    use crate::mcp::client::McpClientManager;
+    use crate::mcp::metrics::{prometheus_text, write_prometheus, CallMetrics, CallOutcome};
+    use crate::mcp::mock_server::{start_sse, MockMcpServer};
+    use crate::mcp::types::{InitializeClientRequest, LimitConfig, ToolCallRequest, TransportType};
+    use serde_json::json;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    // 测试计数、分位数和最近一次错误
+    #[test]
+    fn test_record_and_snapshot() {
+        let metrics = CallMetrics::new();
+        let latencies = [(60, 8), (35, 200), (4, 3000)];
+        for (count, ms) in latencies {
+            for _ in 0..count {
+                metrics.record(
+                    "search",
+                    Duration::from_millis(ms),
+                    CallOutcome::Success,
+                    10,
+                    100,
+                );
+            }
+        }
+        metrics.record(
+            "search",
+            Duration::from_millis(40_000),
+            CallOutcome::Timeout,
+            10,
+            0,
+        );
+        metrics.record(
+            "write",
+            Duration::from_millis(3),
+            CallOutcome::Error("disk full".to_string()),
+            5,
+            20,
+        );
+
+        let snapshot = metrics.snapshot("mock");
+        assert_eq!(snapshot.client_id, "mock");
+        assert_eq!(snapshot.stats.calls, 101);
+        assert_eq!(snapshot.stats.successes, 99);
+        assert_eq!(snapshot.stats.timeouts, 1);
+        assert_eq!(snapshot.stats.errors, 1);
+        assert_eq!(snapshot.stats.bytes_out, 1005);
+        assert_eq!(snapshot.stats.bytes_in, 9920);
+        let last_error = snapshot.stats.last_error.unwrap();
+        assert_eq!(last_error.tool_name, "write");
+        assert_eq!(last_error.message, "disk full");
+
+        let names: Vec<&str> = snapshot
+            .tools
+            .iter()
+            .map(|tool| tool.tool_name.as_str())
+            .collect();
+        assert_eq!(names, vec!["search", "write"]);
+        let search = &snapshot.tools[0].stats;
+        assert_eq!(search.calls, 100);
+        assert_eq!(search.latency_ms.p50, 10);
+        assert_eq!(search.latency_ms.p95, 250);
+        assert_eq!(search.latency_ms.p99, 5000);
+        assert_eq!(search.latency_ms.max, 40_000);
+        assert_eq!(search.latency_ms.sum, 480 + 7000 + 12_000 + 40_000);
+        assert_eq!(
+            search.latency_ms.buckets,
+            vec![0, 60, 0, 0, 0, 35, 0, 0, 0, 4, 0, 0, 0, 1, 0]
+        );
+        assert_eq!(
+            search.last_error.as_ref().unwrap().message,
+            "Tool call timed out"
+        );
+
+        // 分位数不超过观察到的最大值
+        let write = &snapshot.tools[1].stats;
+        assert_eq!(write.latency_ms.p50, 3);
+        assert!(CallMetrics::new().snapshot("empty").tools.is_empty());
+    }
+
+    // 测试被拒绝的调用计为错误，但不计入耗时
+    #[test]
+    fn test_record_rejection() {
+        let metrics = CallMetrics::new();
+        metrics.record(
+            "search",
+            Duration::from_millis(20),
+            CallOutcome::Success,
+            10,
+            100,
+        );
+        metrics.record(
+            "search",
+            Duration::ZERO,
+            CallOutcome::Rejected("Circuit breaker is open".to_string()),
+            0,
+            0,
+        );
+
+        let stats = metrics.snapshot("mock").stats;
+        assert_eq!(stats.calls, 2);
+        assert_eq!(stats.errors, 1);
+        assert_eq!(stats.latency_ms.buckets.iter().sum::<u64>(), 1);
+        assert_eq!(stats.latency_ms.p50, 20);
+        assert_eq!(stats.last_error.unwrap().message, "Circuit breaker is open");
+    }
+
+    // 测试 Prometheus 文本格式和标签转义
+    #[test]
+    fn test_prometheus_text() {
+        let metrics = CallMetrics::new();
+        metrics.record(
+            "say \"hi\"",
+            Duration::from_millis(20),
+            CallOutcome::Success,
+            7,
+            9,
+        );
+        metrics.record(
+            "say \"hi\"",
+            Duration::from_millis(1_500),
+            CallOutcome::Success,
+            7,
+            9,
+        );
+        let clients = vec![metrics.snapshot("mock")];
+        let text = prometheus_text(&clients);
+        let labels = r#"client="mock",tool="say \"hi\"""#;
+        for line in [
+            "# TYPE fishmind_mcp_tool_calls_total counter".to_string(),
+            format!(
+                "fishmind_mcp_tool_calls_total{{{},outcome=\"success\"}} 2",
+                labels
+            ),
+            format!(
+                "fishmind_mcp_tool_calls_total{{{},outcome=\"timeout\"}} 0",
+                labels
+            ),
+            format!(
+                "fishmind_mcp_tool_bytes_total{{{},direction=\"out\"}} 14",
+                labels
+            ),
+            "# TYPE fishmind_mcp_tool_latency_seconds histogram".to_string(),
+            format!(
+                "fishmind_mcp_tool_latency_seconds_bucket{{{},le=\"0.01\"}} 0",
+                labels
+            ),
+            format!(
+                "fishmind_mcp_tool_latency_seconds_bucket{{{},le=\"0.025\"}} 1",
+                labels
+            ),
+            format!(
+                "fishmind_mcp_tool_latency_seconds_bucket{{{},le=\"1\"}} 1",
+                labels
+            ),
+            format!(
+                "fishmind_mcp_tool_latency_seconds_bucket{{{},le=\"2.5\"}} 2",
+                labels
+            ),
+            format!(
+                "fishmind_mcp_tool_latency_seconds_bucket{{{},le=\"60\"}} 2",
+                labels
+            ),
+            format!(
+                "fishmind_mcp_tool_latency_seconds_bucket{{{},le=\"+Inf\"}} 2",
+                labels
+            ),
+            format!("fishmind_mcp_tool_latency_seconds_sum{{{}}} 1.52", labels),
+            format!("fishmind_mcp_tool_latency_seconds_count{{{}}} 2", labels),
+        ] {
+            assert!(text.lines().any(|l| l == line), "missing line: {}", line);
+        }
+
+        let dir = std::env::temp_dir().join(format!("fishmind-metrics-{}", std::process::id()));
+        let path = dir.join("mcp.prom");
+        write_prometheus(&path, &clients).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), text);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    // 测试通过管理器调用工具时记录统计
+    #[tokio::test]
+    async fn test_manager_records_tool_calls() {
+        let mut manager = McpClientManager::new();
+        let request = InitializeClientRequest {
+            id: "mock".to_string(),
+            transport_type: TransportType::SSE,
+            sse_url: Some(
+                start_sse(MockMcpServer::new(), "127.0.0.1:0")
+                    .await
+                    .unwrap(),
+            ),
+            headers: Some(HashMap::new()),
+            timeout_secs: Some(30),
+            client_name: "metrics-test".to_string(),
+            client_version: "1.0.0".to_string(),
+            ..Default::default()
+        };
+        manager.initialize_client(request).await.unwrap();
+
+        let call = |tool_name: &str, params| ToolCallRequest {
+            client_id: "mock".to_string(),
+            tool_name: tool_name.to_string(),
+            params,
+        };
+        manager
+            .call_tool(call("echo", json!({ "text": "hi" })))
+            .await
+            .unwrap();
+        manager.call_tool(call("failing", json!({}))).await.unwrap();
+        manager
+            .call_tool(call("failing", json!({ "rpc": true })))
+            .await
+            .unwrap();
+
+        let metrics = manager.metrics(Some("mock")).unwrap().remove(0);
+        assert_eq!(metrics.stats.calls, 3);
+        assert_eq!(metrics.stats.successes, 1);
+        assert_eq!(metrics.stats.errors, 2);
+        assert!(metrics.stats.bytes_out > 0 && metrics.stats.bytes_in > 0);
+
+        let failing = metrics
+            .tools
+            .iter()
+            .find(|tool| tool.tool_name == "failing")
+            .unwrap();
+        assert_eq!(failing.stats.errors, 2);
+        assert!(failing
+            .stats
+            .last_error
+            .as_ref()
+            .unwrap()
+            .message
+            .contains("Intentional failure"));
+
+        assert_eq!(manager.metrics(None).unwrap().len(), 1);
+        assert!(manager.metrics(Some("missing")).is_err());
+    }
+
+    // 测试被限流拒绝的调用计为错误
+    #[tokio::test]
+    async fn test_manager_records_rejections() {
+        let mut manager = McpClientManager::new();
+        let request = InitializeClientRequest {
+            id: "mock".to_string(),
+            transport_type: TransportType::SSE,
+            sse_url: Some(
+                start_sse(MockMcpServer::new(), "127.0.0.1:0")
+                    .await
+                    .unwrap(),
+            ),
+            headers: Some(HashMap::new()),
+            timeout_secs: Some(30),
+            limits: Some(LimitConfig {
+                rate_per_sec: Some(0.1),
+                burst: Some(1),
+                queue_timeout_ms: Some(10),
+                ..Default::default()
+            }),
+            client_name: "metrics-test".to_string(),
+            client_version: "1.0.0".to_string(),
+            ..Default::default()
+        };
+        manager.initialize_client(request).await.unwrap();
+
+        for expected in [true, false] {
+            let result = manager
+                .call_tool(ToolCallRequest {
+                    client_id: "mock".to_string(),
+                    tool_name: "echo".to_string(),
+                    params: json!({ "text": "hi" }),
+                })
+                .await
+                .unwrap();
+            assert_eq!(result.success, expected);
+        }
+
+        let metrics = manager.metrics(Some("mock")).unwrap().remove(0);
+        assert_eq!(metrics.stats.calls, 2);
+        assert_eq!(metrics.stats.successes, 1);
+        assert_eq!(metrics.stats.errors, 1);
+        assert_eq!(metrics.stats.latency_ms.buckets.iter().sum::<u64>(), 1);
+    }
+}
@@ -0,0 +1,498 @@
+use crate::mcp::server_transport::{
+    self, rpc_response, McpMessageHandler, INTERNAL_ERROR, INVALID_PARAMS, METHOD_NOT_FOUND,
+    PROTOCOL_VERSION, RESOURCE_NOT_FOUND,
+};
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use log::debug;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+
+pub use crate::mcp::server_transport::serve_io;
+
+/// 以模拟服务器模式启动的命令行参数
+///
+/// `fishmind --mcp-mock-server` 通过 stdio 提供服务，
+/// `fishmind --mcp-mock-server --sse 127.0.0.1:3001` 在本机端口上提供 SSE 服务。
+pub const MOCK_SERVER_FLAG: &str = "--mcp-mock-server";
+
+/// 用于测试和离线开发的模拟 MCP 服务器
+///
+/// 提供以下工具：
+/// - `echo`：原样返回 `text` 参数
+/// - `slow`：等待 `ms` 毫秒后返回
+/// - `failing`：返回 `isError` 结果，`rpc` 为 true 时返回 JSON-RPC 错误
+/// - `progress`：按 `steps` 发送进度通知后返回
+/// - `list_changed`：切换额外的 `extra` 工具并发送 `notifications/tools/list_changed`
+/// - `confirm`：通过 `elicitation/create` 向用户询问保存路径，客户端未声明该能力时返回 `isError`
+///
+/// 以及两个文本资源和一个带必填参数的 `greeting` 提示，`greeting` 的参数支持补全。
+/// 未列出的 `mock://binary/<字节数>.<扩展名>` 资源返回指定大小的二进制内容，不带 MIME 类型。
+pub struct MockMcpServer {
+    extra_tool: AtomicBool,
+    completion_requests: AtomicUsize,
+    // 客户端是否声明了 elicitation 能力
+    elicitation: AtomicBool,
+    // 等待客户端响应的服务器请求
+    server_requests: Mutex<HashMap<u64, oneshot::Sender<Value>>>,
+    next_request_id: AtomicU64,
+}
+
+impl MockMcpServer {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            extra_tool: AtomicBool::new(false),
+            completion_requests: AtomicUsize::new(0),
+            elicitation: AtomicBool::new(false),
+            server_requests: Mutex::new(HashMap::new()),
+            next_request_id: AtomicU64::new(1),
+        })
+    }
+
+    /// 收到的补全请求数，用于验证客户端的防抖和缓存
+    #[cfg(test)]
+    pub fn completion_requests(&self) -> usize {
+        self.completion_requests.load(Ordering::SeqCst)
+    }
+
+    /// 处理一条客户端消息，请求返回响应，通知返回 None
+    ///
+    /// 进度和列表变更等服务器通知通过 `outbound` 发送。
+    pub async fn handle_message(
+        &self,
+        message: Value,
+        outbound: &mpsc::UnboundedSender<Value>,
+    ) -> Option<Value> {
+        // 客户端对服务器请求的响应
+        if message.get("method").is_none() {
+            let id = message.get("id").and_then(Value::as_u64)?;
+            if let Some(tx) = self.server_requests.lock().unwrap().remove(&id) {
+                let _ = tx.send(message);
+            }
+            return None;
+        }
+
+        let method = message.get("method")?.as_str()?.to_string();
+        let id = message.get("id").cloned();
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        debug!("[MCP] 模拟服务器收到: {}", method);
+
+        let result = match method.as_str() {
+            "initialize" => {
+                let elicitation = params
+                    .get("capabilities")
+                    .and_then(|capabilities| capabilities.get("elicitation"))
+                    .is_some();
+                self.elicitation.store(elicitation, Ordering::SeqCst);
+                Ok(json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": {
+                    "tools": { "listChanged": true },
+                    "resources": { "listChanged": false, "subscribe": false },
+                    "prompts": { "listChanged": false },
+                    "completions": {},
+                },
+                "serverInfo": { "name": "fishmind-mock", "version": env!("CARGO_PKG_VERSION") },
+                "instructions": "Mock MCP server for tests and offline development",
+                }))
+            }
+            "ping" => Ok(json!({})),
+            // 工具只有一页，任何游标都无效
+            "tools/list" if params.get("cursor").is_some() => {
+                Err((INVALID_PARAMS, "Invalid cursor".to_string()))
+            }
+            "tools/list" => Ok(json!({ "tools": self.tools() })),
+            "tools/call" => self.call_tool(&params, outbound).await,
+            "resources/list" => Ok(json!({ "resources": resources() })),
+            "resources/read" => read_resource(&params),
+            "prompts/list" => Ok(json!({ "prompts": prompts() })),
+            "prompts/get" => get_prompt(&params),
+            "completion/complete" => {
+                self.completion_requests.fetch_add(1, Ordering::SeqCst);
+                complete(&params)
+            }
+            _ if id.is_none() => return None,
+            other => Err((METHOD_NOT_FOUND, format!("Method not found: {}", other))),
+        };
+
+        // 通知不需要响应
+        rpc_response(id, result)
+    }
+
+    fn tools(&self) -> Vec<Value> {
+        let mut tools = vec![
+            annotate(
+                tool(
+                    "echo",
+                    "Echo the given text",
+                    json!({ "text": { "type": "string" } }),
+                    &["text"],
+                ),
+                json!({ "title": "Echo", "readOnlyHint": true, "openWorldHint": false }),
+            ),
+            tool(
+                "slow",
+                "Wait for the given number of milliseconds",
+                json!({ "ms": { "type": "integer" } }),
+                &[],
+            ),
+            tool(
+                "failing",
+                "Always fail",
+                json!({ "rpc": { "type": "boolean" } }),
+                &[],
+            ),
+            tool(
+                "progress",
+                "Report progress before returning",
+                json!({ "steps": { "type": "integer" } }),
+                &[],
+            ),
+            annotate(
+                tool(
+                    "list_changed",
+                    "Toggle the extra tool and notify the client",
+                    json!({}),
+                    &[],
+                ),
+                json!({ "readOnlyHint": false, "idempotentHint": false }),
+            ),
+            tool(
+                "confirm",
+                "Ask the user where to save a file",
+                json!({}),
+                &[],
+            ),
+        ];
+        if self.extra_tool.load(Ordering::SeqCst) {
+            tools.push(tool("extra", "Added by list_changed", json!({}), &[]));
+        }
+        tools
+    }
+
+    async fn call_tool(
+        &self,
+        params: &Value,
+        outbound: &mpsc::UnboundedSender<Value>,
+    ) -> Result<Value, (i64, String)> {
+        let name = params
+            .get("name")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let arguments = params.get("arguments").cloned().unwrap_or(json!({}));
+
+        match name {
+            "echo" => {
+                let text = arguments
+                    .get("text")
+                    .and_then(Value::as_str)
+                    .ok_or((INVALID_PARAMS, "Missing argument: text".to_string()))?;
+                Ok(text_result(text, false))
+            }
+            "slow" => {
+                let ms = arguments.get("ms").and_then(Value::as_u64).unwrap_or(1000);
+                tokio::time::sleep(Duration::from_millis(ms)).await;
+                Ok(text_result(&format!("waited {} ms", ms), false))
+            }
+            "failing" => {
+                if arguments.get("rpc").and_then(Value::as_bool) == Some(true) {
+                    Err((INTERNAL_ERROR, "Intentional failure".to_string()))
+                } else {
+                    Ok(text_result("Intentional failure", true))
+                }
+            }
+            "progress" => {
+                let steps = arguments.get("steps").and_then(Value::as_u64).unwrap_or(3);
+                let token = params
+                    .get("_meta")
+                    .and_then(|meta| meta.get("progressToken"));
+                for step in 1..=steps {
+                    if let Some(token) = token {
+                        let _ = outbound.send(json!({
+                            "jsonrpc": "2.0",
+                            "method": "notifications/progress",
+                            "params": { "progressToken": token, "progress": step, "total": steps },
+                        }));
+                    }
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                }
+                Ok(text_result(&format!("completed {} steps", steps), false))
+            }
+            "list_changed" => {
+                let enabled = !self.extra_tool.fetch_xor(true, Ordering::SeqCst);
+                let _ = outbound.send(json!({
+                    "jsonrpc": "2.0",
+                    "method": "notifications/tools/list_changed",
+                }));
+                Ok(text_result(
+                    &format!("extra tool enabled: {}", enabled),
+                    false,
+                ))
+            }
+            "confirm" => self.confirm(outbound).await,
+            "extra" if self.extra_tool.load(Ordering::SeqCst) => Ok(text_result("extra", false)),
+            other => Err((INVALID_PARAMS, format!("Unknown tool: {}", other))),
+        }
+    }
+
+    async fn confirm(
+        &self,
+        outbound: &mpsc::UnboundedSender<Value>,
+    ) -> Result<Value, (i64, String)> {
+        if !self.elicitation.load(Ordering::SeqCst) {
+            return Ok(text_result("Client does not support elicitation", true));
+        }
+
+        let id = self.next_request_id.fetch_add(1, Ordering::SeqCst);
+        let (tx, rx) = oneshot::channel();
+        self.server_requests.lock().unwrap().insert(id, tx);
+        let _ = outbound.send(json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "elicitation/create",
+            "params": {
+                "message": "Where should the file be saved?",
+                "requestedSchema": {
+                    "type": "object",
+                    "properties": {
+                        "path": { "type": "string" },
+                        "format": { "type": "string", "enum": ["json", "csv"] },
+                        "overwrite": { "type": "boolean" },
+                    },
+                    "required": ["path"],
+                },
+            },
+        }));
+
+        let response = rx
+            .await
+            .map_err(|_| (INTERNAL_ERROR, "Elicitation was cancelled".to_string()))?;
+        if let Some(error) = response.get("error") {
+            let message = error.get("message").and_then(Value::as_str);
+            return Ok(text_result(
+                &format!("elicitation failed: {}", message.unwrap_or_default()),
+                true,
+            ));
+        }
+        let result = response.get("result").cloned().unwrap_or(Value::Null);
+        let text = match result.get("action").and_then(Value::as_str) {
+            Some("accept") => format!(
+                "saved to {}",
+                result["content"]["path"].as_str().unwrap_or_default()
+            ),
+            Some(action) => action.to_string(),
+            None => "unknown".to_string(),
+        };
+        Ok(text_result(&text, false))
+    }
+}
+
+fn tool(name: &str, description: &str, properties: Value, required: &[&str]) -> Value {
+    json!({
+        "name": name,
+        "description": description,
+        "inputSchema": { "type": "object", "properties": properties, "required": required },
+    })
+}
+
+fn annotate(mut tool: Value, annotations: Value) -> Value {
+    tool["annotations"] = annotations;
+    tool
+}
+
+fn text_result(text: &str, is_error: bool) -> Value {
+    json!({ "content": [{ "type": "text", "text": text }], "isError": is_error })
+}
+
+fn resources() -> Vec<Value> {
+    vec![
+        json!({ "uri": "mock://text/hello", "name": "hello", "mimeType": "text/plain" }),
+        json!({ "uri": "mock://json/config", "name": "config", "mimeType": "application/json" }),
+    ]
+}
+
+fn read_resource(params: &Value) -> Result<Value, (i64, String)> {
+    let uri = params
+        .get("uri")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let (mime_type, text) = match uri {
+        "mock://text/hello" => ("text/plain", "Hello from the mock server".to_string()),
+        "mock://json/config" => ("application/json", json!({ "mode": "mock" }).to_string()),
+        _ => return read_binary(uri),
+    };
+    Ok(json!({ "contents": [{ "uri": uri, "mimeType": mime_type, "text": text }] }))
+}
+
+fn read_binary(uri: &str) -> Result<Value, (i64, String)> {
+    let size: usize = uri
+        .strip_prefix("mock://binary/")
+        .and_then(|name| name.split('.').next())
+        .and_then(|size| size.parse().ok())
+        .ok_or_else(|| (RESOURCE_NOT_FOUND, format!("Resource not found: {}", uri)))?;
+    let bytes: Vec<u8> = (0..size).map(|i| (i % 256) as u8).collect();
+    Ok(json!({ "contents": [{ "uri": uri, "blob": BASE64.encode(bytes) }] }))
+}
+
+fn prompts() -> Vec<Value> {
+    vec![json!({
+        "name": "greeting",
+        "description": "Greet someone by name",
+        "arguments": [
+            { "name": "name", "description": "Who to greet", "required": true },
+            { "name": "style", "description": "formal or casual", "required": false },
+        ],
+    })]
+}
+
+fn get_prompt(params: &Value) -> Result<Value, (i64, String)> {
+    let name = params
+        .get("name")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    if name != "greeting" {
+        return Err((INVALID_PARAMS, format!("Unknown prompt: {}", name)));
+    }
+    let arguments = params.get("arguments").cloned().unwrap_or(json!({}));
+    let who = arguments.get("name").and_then(Value::as_str).ok_or((
+        INVALID_PARAMS,
+        "Missing required argument: name".to_string(),
+    ))?;
+    let text = match arguments.get("style").and_then(Value::as_str) {
+        Some("formal") => format!("Good day, {}.", who),
+        _ => format!("Hello, {}!", who),
+    };
+    Ok(json!({
+        "description": "Greet someone by name",
+        "messages": [{ "role": "user", "content": { "type": "text", "text": text } }],
+    }))
+}
+
+// 候选值按固定顺序返回，不做排序
+fn complete(params: &Value) -> Result<Value, (i64, String)> {
+    let prompt = params
+        .get("ref")
+        .filter(|r| r.get("type").and_then(Value::as_str) == Some("ref/prompt"))
+        .and_then(|r| r.get("name"))
+        .and_then(Value::as_str);
+    if prompt != Some("greeting") {
+        return Err((INVALID_PARAMS, "Unknown completion reference".to_string()));
+    }
+    let argument = params.get("argument").cloned().unwrap_or(json!({}));
+    let candidates: &[&str] = match argument.get("name").and_then(Value::as_str) {
+        Some("name") => &["Grace", "Ada", "Alan", "Alice", "Linus"],
+        Some("style") => &["formal", "casual"],
+        _ => &[],
+    };
+    let value = argument
+        .get("value")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_lowercase();
+    let values: Vec<&str> = candidates
+        .iter()
+        .copied()
+        .filter(|candidate| candidate.to_lowercase().contains(&value))
+        .collect();
+    Ok(json!({
+        "completion": { "values": values, "total": values.len(), "hasMore": false },
+    }))
+}
+
+#[async_trait]
+impl McpMessageHandler for MockMcpServer {
+    async fn handle_message(
+        &self,
+        message: Value,
+        outbound: &mpsc::UnboundedSender<Value>,
+    ) -> Option<Value> {
+        MockMcpServer::handle_message(self, message, outbound).await
+    }
+}
+
+/// 在本机端口上启动不需要访问令牌的 SSE 服务，返回事件流地址
+///
+/// 地址中端口为 0 时随机选择端口。
+pub async fn start_sse(server: Arc<MockMcpServer>, addr: &str) -> Result<String, String> {
+    let server = server_transport::start_sse(server, addr, None).await?;
+    Ok(server.url)
+}
+
+/// 启动通过 stdio 连接模拟服务器的命令，返回命令和参数
+///
+/// 子进程用 bash 的 /dev/tcp 把标准输入输出桥接到进程内的模拟服务器，
+/// 测试因此完整经过 Stdio 传输的进程启动和管道读写。
+#[cfg(all(test, unix))]
+pub async fn stdio_bridge(server: Arc<MockMcpServer>) -> (String, Vec<String>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        if let Ok((stream, _)) = listener.accept().await {
+            let (reader, writer) = stream.into_split();
+            serve_io(server, reader, writer).await;
+        }
+    });
+
+    (
+        "bash".to_string(),
+        vec![
+            "-c".to_string(),
+            format!(
+                "exec 3<>/dev/tcp/127.0.0.1/{}; cat <&3 & exec cat >&3",
+                port
+            ),
+        ],
+    )
+}
+
+/// 创建应用状态，并为每个 (客户端ID, 模拟服务器) 通过 SSE 连接一个客户端
+#[cfg(test)]
+pub async fn connected_state(
+    servers: &[(&str, Arc<MockMcpServer>)],
+) -> Arc<crate::mcp::client::AppState> {
+    use crate::mcp::types::{InitializeClientRequest, TransportType};
+
+    let state = Arc::new(crate::mcp::client::AppState::new());
+    for (client_id, server) in servers {
+        let request = InitializeClientRequest {
+            id: client_id.to_string(),
+            transport_type: TransportType::SSE,
+            sse_url: Some(start_sse(server.clone(), "127.0.0.1:0").await.unwrap()),
+            headers: Some(HashMap::new()),
+            timeout_secs: Some(30),
+            client_name: "mock-test".to_string(),
+            client_version: "1.0.0".to_string(),
+            ..Default::default()
+        };
+        state.initialize_client(request).await.unwrap();
+    }
+    state
+}
+
+/// 以模拟服务器模式运行进程，参数见 [`MOCK_SERVER_FLAG`]
+pub fn run_from_args(args: Vec<String>) {
+    let runtime = tokio::runtime::Runtime::new().expect("failed to start tokio runtime");
+    runtime.block_on(async {
+        let server = MockMcpServer::new();
+        match args.iter().position(|arg| arg == "--sse") {
+            Some(index) => {
+                let addr = args
+                    .get(index + 1)
+                    .map(String::as_str)
+                    .unwrap_or("127.0.0.1:0");
+                match start_sse(server, addr).await {
+                    Ok(url) => {
+                        eprintln!("{}", url);
+                        std::future::pending::<()>().await;
+                    }
+                    Err(e) => eprintln!("{}", e),
+                }
+            }
+            None => serve_io(server, tokio::io::stdin(), tokio::io::stdout()).await,
+        }
+    });
+}
@@ -0,0 +1,161 @@
+#[cfg(test)]
+mod tests {
+    use crate::mcp::mock_server::{serve_io, MockMcpServer};
+    use serde_json::{json, Value};
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::sync::mpsc;
+
+    fn request(id: u64, method: &str, params: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+    }
+
+    // 测试进度工具按进度令牌发送通知
+    #[tokio::test]
+    async fn test_progress_notifications() {
+        let server = MockMcpServer::new();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+
+        let response = server
+            .handle_message(
+                request(
+                    1,
+                    "tools/call",
+                    json!({ "name": "progress", "arguments": { "steps": 2 }, "_meta": { "progressToken": "p1" } }),
+                ),
+                &tx,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            response["result"]["content"][0]["text"],
+            "completed 2 steps"
+        );
+
+        let first = rx.try_recv().unwrap();
+        let second = rx.try_recv().unwrap();
+        assert_eq!(first["method"], "notifications/progress");
+        assert_eq!(first["params"]["progressToken"], "p1");
+        assert_eq!(second["params"]["progress"], 2);
+        assert_eq!(second["params"]["total"], 2);
+        assert!(rx.try_recv().is_err());
+    }
+
+    // 测试 list_changed 工具切换额外工具并发送通知
+    #[tokio::test]
+    async fn test_list_changed() {
+        let server = MockMcpServer::new();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let tool_names = |response: Value| -> Vec<String> {
+            response["result"]["tools"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|tool| tool["name"].as_str().unwrap().to_string())
+                .collect()
+        };
+
+        let before = server
+            .handle_message(request(1, "tools/list", json!({})), &tx)
+            .await
+            .unwrap();
+        assert!(!tool_names(before).contains(&"extra".to_string()));
+
+        server
+            .handle_message(
+                request(2, "tools/call", json!({ "name": "list_changed" })),
+                &tx,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            rx.try_recv().unwrap()["method"],
+            "notifications/tools/list_changed"
+        );
+
+        let after = server
+            .handle_message(request(3, "tools/list", json!({})), &tx)
+            .await
+            .unwrap();
+        assert!(tool_names(after).contains(&"extra".to_string()));
+    }
+
+    // 测试错误响应和通知
+    #[tokio::test]
+    async fn test_errors_and_notifications() {
+        let server = MockMcpServer::new();
+        let (tx, _rx) = mpsc::unbounded_channel();
+
+        let failing = server
+            .handle_message(request(1, "tools/call", json!({ "name": "failing" })), &tx)
+            .await
+            .unwrap();
+        assert_eq!(failing["result"]["isError"], true);
+
+        let rpc_error = server
+            .handle_message(
+                request(
+                    2,
+                    "tools/call",
+                    json!({ "name": "failing", "arguments": { "rpc": true } }),
+                ),
+                &tx,
+            )
+            .await
+            .unwrap();
+        assert_eq!(rpc_error["error"]["code"], -32603);
+
+        let unknown = server
+            .handle_message(request(3, "unknown/method", json!({})), &tx)
+            .await
+            .unwrap();
+        assert_eq!(unknown["error"]["code"], -32601);
+
+        let missing_argument = server
+            .handle_message(
+                request(4, "prompts/get", json!({ "name": "greeting" })),
+                &tx,
+            )
+            .await
+            .unwrap();
+        assert_eq!(missing_argument["error"]["code"], -32602);
+
+        let notification = json!({ "jsonrpc": "2.0", "method": "notifications/initialized" });
+        assert!(server.handle_message(notification, &tx).await.is_none());
+    }
+
+    // 测试按行分隔的 stdio 服务，慢请求不阻塞后续请求
+    #[tokio::test]
+    async fn test_serve_io() {
+        let (client, server_side) = tokio::io::duplex(64 * 1024);
+        let (server_read, server_write) = tokio::io::split(server_side);
+        tokio::spawn(serve_io(MockMcpServer::new(), server_read, server_write));
+
+        let (client_read, mut client_write) = tokio::io::split(client);
+        for message in [
+            request(
+                1,
+                "tools/call",
+                json!({ "name": "slow", "arguments": { "ms": 200 } }),
+            ),
+            request(
+                2,
+                "tools/call",
+                json!({ "name": "echo", "arguments": { "text": "hi" } }),
+            ),
+        ] {
+            client_write
+                .write_all(format!("{}\n", message).as_bytes())
+                .await
+                .unwrap();
+        }
+
+        let mut lines = BufReader::new(client_read).lines();
+        let first: Value =
+            serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        let second: Value =
+            serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        assert_eq!(first["id"], 2);
+        assert_eq!(first["result"]["content"][0]["text"], "hi");
+        assert_eq!(second["id"], 1);
+    }
+}
@@ -1,10 +1,91 @@
+pub mod approval;
+pub mod audit;
+pub mod batch;
+pub mod breaker;
 pub mod client;
 pub mod commands;
+pub mod completion;
+pub mod config_io;
+pub mod elicitation;
+pub mod gateway;
+pub mod history;
+pub mod http_client;
+pub mod http_sse;
+pub mod inspector;
+pub mod limits;
+pub mod logging;
+pub mod metrics;
+pub mod mock_server;
+pub mod oauth;
+pub mod placeholders;
+pub mod prompts;
+pub mod recorder;
+pub mod redact;
+pub mod registry;
+pub mod resources;
+pub mod sandbox;
+pub mod secrets;
+pub mod server;
+pub mod server_transport;
+pub mod stdio;
+pub mod tool_cache;
 pub mod types;
 
+#[cfg(test)]
+mod approval_test;
+#[cfg(test)]
+mod audit_test;
+#[cfg(test)]
+mod batch_test;
+#[cfg(test)]
+mod breaker_test;
 #[cfg(test)]
 mod client_test;
 #[cfg(test)]
 mod commands_test;
 #[cfg(test)]
+mod completion_test;
+#[cfg(test)]
+mod config_io_test;
+#[cfg(test)]
+mod elicitation_test;
+#[cfg(test)]
+mod gateway_test;
+#[cfg(test)]
+mod history_test;
+#[cfg(test)]
+mod http_client_test;
+#[cfg(test)]
+mod http_sse_test;
+#[cfg(test)]
+mod inspector_test;
+#[cfg(test)]
 mod integration_test;
+#[cfg(test)]
+mod limits_test;
+#[cfg(test)]
+mod metrics_test;
+#[cfg(test)]
+mod mock_server_test;
+#[cfg(test)]
+mod oauth_test;
+#[cfg(test)]
+mod placeholders_test;
+#[cfg(test)]
+mod prompts_test;
+#[cfg(test)]
+mod recorder_test;
+#[cfg(test)]
+mod redact_test;
+#[cfg(test)]
+mod registry_test;
+#[cfg(test)]
+mod resources_test;
+#[cfg(test)]
+mod sandbox_test;
+#[cfg(test)]
+mod secrets_test;
+#[cfg(test)]
+mod server_test;
+#[cfg(test)]
+mod tool_cache_test;
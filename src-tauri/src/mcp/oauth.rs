@@ -0,0 +1,684 @@
+use crate::mcp::{
+    http_sse::{HttpSseTransport, HttpSseTransportHandle},
+    redact,
+    secrets::SecretStore,
+    types::*,
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use chrono::{DateTime, Utc};
+use log::{debug, info, warn};
+use mcp_client_fishcode2025::transport::Transport;
+use regex::Regex;
+use reqwest::{header, Url};
+use ring::digest;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// 授权开始时发送给前端的事件名
+pub const OAUTH_AUTHORIZE_EVENT: &str = "mcp-oauth-authorize";
+
+// 本地回调路径
+const CALLBACK_PATH: &str = "/callback";
+// 等待用户在浏览器中完成授权的超时时间
+const AUTHORIZE_TIMEOUT: Duration = Duration::from_secs(300);
+// 元数据和令牌请求的超时时间
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+// 令牌在过期前多久视为需要刷新
+const REFRESH_MARGIN_SECS: i64 = 60;
+
+/// 打开授权链接的回调
+pub type UrlOpener = dyn Fn(&str) -> Result<(), String> + Send + Sync;
+
+// `WWW-Authenticate` 中的 Bearer 质询参数
+#[derive(Debug, Default)]
+struct BearerChallenge {
+    resource_metadata: Option<String>,
+    scope: Option<String>,
+}
+
+// RFC 9728 受保护资源元数据
+#[derive(Debug, Deserialize)]
+struct ProtectedResourceMetadata {
+    resource: Option<String>,
+    #[serde(default)]
+    authorization_servers: Vec<String>,
+    #[serde(default)]
+    scopes_supported: Vec<String>,
+}
+
+// RFC 8414 授权服务器元数据
+#[derive(Debug, Deserialize)]
+struct AuthorizationServerMetadata {
+    authorization_endpoint: String,
+    token_endpoint: String,
+    registration_endpoint: Option<String>,
+}
+
+// RFC 7591 动态注册结果
+#[derive(Debug, Deserialize)]
+struct ClientRegistration {
+    client_id: String,
+    client_secret: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: Option<i64>,
+    refresh_token: Option<String>,
+}
+
+// 加密保存在密钥存储中的令牌
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredToken {
+    resource: String,
+    token_endpoint: String,
+    client_id: String,
+    client_secret: Option<String>,
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_at: Option<DateTime<Utc>>,
+}
+
+impl StoredToken {
+    fn is_fresh(&self) -> bool {
+        self.expires_at.is_none_or(|expires_at| {
+            expires_at
+                .checked_sub_signed(chrono::Duration::seconds(REFRESH_MARGIN_SECS))
+                .is_some_and(|refresh_at| refresh_at > Utc::now())
+        })
+    }
+}
+
+/// 远程 MCP 服务器的 OAuth 2.1 授权管理器
+///
+/// 令牌以 `oauth:<服务器ID>` 为名加密保存在密钥存储中，连接前自动刷新。
+pub struct OAuthManager {
+    secrets: Arc<SecretStore>,
+    app: OnceLock<AppHandle>,
+}
+
+impl OAuthManager {
+    /// 创建授权管理器
+    pub fn new(secrets: Arc<SecretStore>) -> Self {
+        Self {
+            secrets,
+            app: OnceLock::new(),
+        }
+    }
+
+    /// 设置应用句柄，用于通知前端并打开浏览器
+    pub fn set_app_handle(&self, app: AppHandle) {
+        let _ = self.app.set(app);
+    }
+
+    /// 启动 SSE 传输，服务器需要授权时执行 OAuth 授权
+    ///
+    /// 先带上已保存的令牌（即将过期时先刷新）启动传输；服务器以 401 和 Bearer 质询拒绝时，
+    /// 刷新令牌或在浏览器中重新授权，然后重试一次，因此已吊销的令牌也会被替换。
+    /// 请求头中已有 `Authorization` 或禁用 OAuth 时直接启动。
+    /// 所有请求使用调用方提供的 HTTP 客户端，与连接服务器时的 TLS 和代理配置一致，
+    /// `build` 根据请求头创建传输。
+    pub async fn start_transport<B>(
+        &self,
+        http: &reqwest::Client,
+        server_id: &str,
+        url: &str,
+        headers: HashMap<String, String>,
+        config: Option<&OAuthConfig>,
+        build: B,
+    ) -> Result<HttpSseTransportHandle, String>
+    where
+        B: Fn(HashMap<String, String>) -> HttpSseTransport,
+    {
+        let app = self.app.get().cloned();
+        let owner = server_id.to_string();
+        let opener = move |authorization_url: &str| -> Result<(), String> {
+            let app = app.as_ref().ok_or_else(|| {
+                format!(
+                    "Authorization for '{}' requires user interaction, which is not available",
+                    owner
+                )
+            })?;
+            let event = OAuthAuthorizeEvent {
+                server_id: owner.clone(),
+                authorization_url: authorization_url.to_string(),
+            };
+            app.emit(OAUTH_AUTHORIZE_EVENT, &event)
+                .map_err(|e| format!("Failed to emit authorization event: {}", e))?;
+            // 浏览器打开失败时前端仍可通过事件中的链接手动打开
+            if let Err(e) = tauri_plugin_opener::open_url(authorization_url, None::<&str>) {
+                warn!("[MCP] 打开浏览器失败: {}", e);
+            }
+            Ok(())
+        };
+        self.start_transport_with(http, server_id, url, headers, config, &opener, build)
+            .await
+    }
+
+    /// 使用指定的链接打开方式启动 SSE 传输
+    #[allow(clippy::too_many_arguments)]
+    pub async fn start_transport_with<B>(
+        &self,
+        http: &reqwest::Client,
+        server_id: &str,
+        url: &str,
+        mut headers: HashMap<String, String>,
+        config: Option<&OAuthConfig>,
+        opener: &UrlOpener,
+        build: B,
+    ) -> Result<HttpSseTransportHandle, String>
+    where
+        B: Fn(HashMap<String, String>) -> HttpSseTransport,
+    {
+        let config = config.cloned().unwrap_or_default();
+        let managed = !config.disabled
+            && !headers
+                .keys()
+                .any(|key| key.eq_ignore_ascii_case("authorization"));
+        if managed {
+            if let Some(authorization) = self.stored_authorization(http, server_id, url).await? {
+                headers.insert("Authorization".to_string(), authorization);
+            }
+        }
+
+        let transport = build(headers.clone());
+        let error = match transport.start().await {
+            Ok(handle) => return Ok(handle),
+            Err(e) => e,
+        };
+        let challenge = transport
+            .auth_challenge()
+            .filter(|_| managed)
+            .and_then(|value| parse_bearer_challenge(&value));
+        let Some(challenge) = challenge else {
+            return Err(error.to_string());
+        };
+        info!("[MCP] 服务器需要 OAuth 授权: {}", server_id);
+
+        let authorization = self
+            .reauthorize(http, server_id, url, &challenge, &config, opener)
+            .await?;
+        headers.insert("Authorization".to_string(), authorization);
+        build(headers).start().await.map_err(|e| e.to_string())
+    }
+
+    /// 获取已保存令牌的状态
+    pub fn token_status(&self, server_id: &str) -> Result<Option<OAuthTokenStatus>, String> {
+        Ok(self.load_token(server_id)?.map(|token| OAuthTokenStatus {
+            server_id: server_id.to_string(),
+            resource: token.resource,
+            expires_at: token.expires_at,
+            has_refresh_token: token.refresh_token.is_some(),
+        }))
+    }
+
+    /// 删除已保存的令牌，下次连接时重新授权
+    pub fn logout(&self, server_id: &str) -> Result<bool, String> {
+        self.secrets.delete(&secret_name(server_id))
+    }
+
+    // 已保存的令牌，即将过期时先刷新；没有可用令牌时返回 None
+    async fn stored_authorization(
+        &self,
+        http: &reqwest::Client,
+        server_id: &str,
+        url: &str,
+    ) -> Result<Option<String>, String> {
+        let Some(token) = self.load_token(server_id)? else {
+            return Ok(None);
+        };
+        if token.resource != url {
+            return Ok(None);
+        }
+        if token.is_fresh() {
+            debug!("[MCP] 使用已保存的 OAuth 令牌, 服务器: {}", server_id);
+            return Ok(Some(bearer(&token.access_token)));
+        }
+        match self.refresh(http, server_id, token).await {
+            Ok(token) => Ok(Some(bearer(&token.access_token))),
+            Err(e) => {
+                warn!("[MCP] 刷新 OAuth 令牌失败: {}", e);
+                Ok(None)
+            }
+        }
+    }
+
+    // 服务器拒绝连接后获取新令牌：有刷新令牌时先刷新，失败后在浏览器中重新授权
+    async fn reauthorize(
+        &self,
+        http: &reqwest::Client,
+        server_id: &str,
+        url: &str,
+        challenge: &BearerChallenge,
+        config: &OAuthConfig,
+        opener: &UrlOpener,
+    ) -> Result<String, String> {
+        let stored = self
+            .load_token(server_id)?
+            .filter(|token| token.resource == url && token.refresh_token.is_some());
+        if let Some(token) = stored {
+            match self.refresh(http, server_id, token).await {
+                Ok(token) => return Ok(bearer(&token.access_token)),
+                Err(e) => warn!("[MCP] 刷新 OAuth 令牌失败，重新授权: {}", e),
+            }
+        }
+        let token = self
+            .run_authorization(http, server_id, url, challenge, config, opener)
+            .await?;
+        Ok(bearer(&token.access_token))
+    }
+
+    fn load_token(&self, server_id: &str) -> Result<Option<StoredToken>, String> {
+        let Some(content) = self.secrets.get(&secret_name(server_id))? else {
+            return Ok(None);
+        };
+        let token: StoredToken = serde_json::from_str(&content)
+            .map_err(|e| format!("Stored OAuth token is corrupted: {}", e))?;
+        redact::register_secret(&token.access_token);
+        Ok(Some(token))
+    }
+
+    fn save_token(&self, server_id: &str, token: &StoredToken) -> Result<(), String> {
+        redact::register_secret(&token.access_token);
+        if let Some(refresh_token) = &token.refresh_token {
+            redact::register_secret(refresh_token);
+        }
+        let content = serde_json::to_string(token)
+            .map_err(|e| format!("Failed to serialize OAuth token: {}", e))?;
+        self.secrets.set(&secret_name(server_id), &content)
+    }
+
+    async fn run_authorization(
+        &self,
+        http: &reqwest::Client,
+        server_id: &str,
+        url: &str,
+        challenge: &BearerChallenge,
+        config: &OAuthConfig,
+        opener: &UrlOpener,
+    ) -> Result<StoredToken, String> {
+        let resource_url = Url::parse(url).map_err(|e| format!("Invalid server URL: {}", e))?;
+        let resource_metadata = self.discover_resource(http, &resource_url, challenge).await;
+        let issuer = resource_metadata
+            .as_ref()
+            .and_then(|metadata| metadata.authorization_servers.first().cloned())
+            .unwrap_or_else(|| resource_url.origin().ascii_serialization());
+        let resource = resource_metadata
+            .as_ref()
+            .and_then(|metadata| metadata.resource.clone())
+            .unwrap_or_else(|| url.to_string());
+        let metadata = self.discover_authorization_server(http, &issuer).await?;
+        info!("[MCP] OAuth 授权服务器: {}, 服务器: {}", issuer, server_id);
+
+        // 先绑定回调端口，注册和授权请求都需要完整的回调地址
+        let listener = TcpListener::bind(("127.0.0.1", config.redirect_port.unwrap_or(0)))
+            .await
+            .map_err(|e| format!("Failed to start OAuth callback listener: {}", e))?;
+        let port = listener
+            .local_addr()
+            .map_err(|e| format!("Failed to start OAuth callback listener: {}", e))?
+            .port();
+        let redirect_uri = format!("http://127.0.0.1:{}{}", port, CALLBACK_PATH);
+
+        let registration = match &config.client_id {
+            Some(client_id) => ClientRegistration {
+                client_id: client_id.clone(),
+                client_secret: config.client_secret.clone(),
+            },
+            None => self.register_client(http, &metadata, &redirect_uri).await?,
+        };
+
+        let scope = if !config.scopes.is_empty() {
+            Some(config.scopes.join(" "))
+        } else {
+            challenge.scope.clone().or_else(|| {
+                resource_metadata
+                    .as_ref()
+                    .filter(|metadata| !metadata.scopes_supported.is_empty())
+                    .map(|metadata| metadata.scopes_supported.join(" "))
+            })
+        };
+
+        let verifier = random_token()?;
+        let state = random_token()?;
+        let challenge_value =
+            URL_SAFE_NO_PAD.encode(digest::digest(&digest::SHA256, verifier.as_bytes()));
+        let mut params = vec![
+            ("response_type", "code".to_string()),
+            ("client_id", registration.client_id.clone()),
+            ("redirect_uri", redirect_uri.clone()),
+            ("code_challenge", challenge_value),
+            ("code_challenge_method", "S256".to_string()),
+            ("state", state.clone()),
+            ("resource", resource.clone()),
+        ];
+        if let Some(scope) = scope {
+            params.push(("scope", scope));
+        }
+        let authorization_url = Url::parse_with_params(&metadata.authorization_endpoint, &params)
+            .map_err(|e| format!("Invalid authorization endpoint: {}", e))?;
+
+        info!("[MCP] 等待用户在浏览器中完成授权, 服务器: {}", server_id);
+        opener(authorization_url.as_str())?;
+        let code = tokio::time::timeout(AUTHORIZE_TIMEOUT, wait_for_callback(listener, &state))
+            .await
+            .map_err(|_| "Authorization timed out".to_string())??;
+
+        let mut form = vec![
+            ("grant_type", "authorization_code".to_string()),
+            ("code", code),
+            ("redirect_uri", redirect_uri),
+            ("client_id", registration.client_id.clone()),
+            ("code_verifier", verifier),
+            ("resource", resource),
+        ];
+        if let Some(secret) = &registration.client_secret {
+            form.push(("client_secret", secret.clone()));
+        }
+        let response = self
+            .request_token(http, &metadata.token_endpoint, &form)
+            .await?;
+
+        let token = StoredToken {
+            resource: url.to_string(),
+            token_endpoint: metadata.token_endpoint,
+            client_id: registration.client_id,
+            client_secret: registration.client_secret,
+            access_token: response.access_token,
+            refresh_token: response.refresh_token,
+            expires_at: expires_at(response.expires_in),
+        };
+        self.save_token(server_id, &token)?;
+        info!("[MCP] OAuth 授权成功, 服务器: {}", server_id);
+        Ok(token)
+    }
+
+    async fn refresh(
+        &self,
+        http: &reqwest::Client,
+        server_id: &str,
+        token: StoredToken,
+    ) -> Result<StoredToken, String> {
+        let refresh_token = token
+            .refresh_token
+            .clone()
+            .ok_or_else(|| "No refresh token".to_string())?;
+        info!("[MCP] 刷新 OAuth 令牌, 服务器: {}", server_id);
+
+        let mut form = vec![
+            ("grant_type", "refresh_token".to_string()),
+            ("refresh_token", refresh_token.clone()),
+            ("client_id", token.client_id.clone()),
+            ("resource", token.resource.clone()),
+        ];
+        if let Some(secret) = &token.client_secret {
+            form.push(("client_secret", secret.clone()));
+        }
+        let response = self
+            .request_token(http, &token.token_endpoint, &form)
+            .await?;
+
+        let token = StoredToken {
+            access_token: response.access_token,
+            // 授权服务器可能不轮换刷新令牌
+            refresh_token: response.refresh_token.or(Some(refresh_token)),
+            expires_at: expires_at(response.expires_in),
+            ..token
+        };
+        self.save_token(server_id, &token)?;
+        Ok(token)
+    }
+
+    // 发现受保护资源元数据，失败时返回 None 并回退到服务器源站
+    async fn discover_resource(
+        &self,
+        http: &reqwest::Client,
+        resource_url: &Url,
+        challenge: &BearerChallenge,
+    ) -> Option<ProtectedResourceMetadata> {
+        let origin = resource_url.origin().ascii_serialization();
+        let path = resource_url.path().trim_end_matches('/');
+        let mut candidates = Vec::new();
+        if let Some(url) = &challenge.resource_metadata {
+            candidates.push(url.clone());
+        }
+        if !path.is_empty() {
+            candidates.push(format!(
+                "{}/.well-known/oauth-protected-resource{}",
+                origin, path
+            ));
+        }
+        candidates.push(format!("{}/.well-known/oauth-protected-resource", origin));
+
+        for candidate in candidates {
+            if let Some(metadata) = self.get_json(http, &candidate).await {
+                return Some(metadata);
+            }
+        }
+        None
+    }
+
+    // 发现授权服务器元数据，均不可用时使用规范中的默认端点
+    async fn discover_authorization_server(
+        &self,
+        http: &reqwest::Client,
+        issuer: &str,
+    ) -> Result<AuthorizationServerMetadata, String> {
+        let issuer_url =
+            Url::parse(issuer).map_err(|e| format!("Invalid authorization server: {}", e))?;
+        let origin = issuer_url.origin().ascii_serialization();
+        let path = issuer_url.path().trim_end_matches('/');
+        let candidates = [
+            format!("{}/.well-known/oauth-authorization-server{}", origin, path),
+            format!("{}/.well-known/openid-configuration{}", origin, path),
+            format!("{}{}/.well-known/openid-configuration", origin, path),
+        ];
+
+        for candidate in candidates {
+            if let Some(metadata) = self.get_json(http, &candidate).await {
+                return Ok(metadata);
+            }
+        }
+
+        warn!("[MCP] 未找到授权服务器元数据，使用默认端点: {}", origin);
+        Ok(AuthorizationServerMetadata {
+            authorization_endpoint: format!("{}/authorize", origin),
+            token_endpoint: format!("{}/token", origin),
+            registration_endpoint: Some(format!("{}/register", origin)),
+        })
+    }
+
+    async fn register_client(
+        &self,
+        http: &reqwest::Client,
+        metadata: &AuthorizationServerMetadata,
+        redirect_uri: &str,
+    ) -> Result<ClientRegistration, String> {
+        let endpoint = metadata.registration_endpoint.as_ref().ok_or_else(|| {
+            "Authorization server does not support dynamic client registration, please configure a client ID"
+                .to_string()
+        })?;
+        info!("[MCP] 动态注册 OAuth 客户端: {}", endpoint);
+
+        let body = serde_json::json!({
+            "client_name": "fishmind",
+            "redirect_uris": [redirect_uri],
+            "grant_types": ["authorization_code", "refresh_token"],
+            "response_types": ["code"],
+            "token_endpoint_auth_method": "none",
+        });
+        let response = http
+            .post(endpoint)
+            .json(&body)
+            .timeout(REQUEST_TIMEOUT)
+            .send()
+            .await
+            .map_err(|e| format!("Client registration failed: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!(
+                "Client registration failed with status {}",
+                response.status()
+            ));
+        }
+        response
+            .json()
+            .await
+            .map_err(|e| format!("Invalid client registration response: {}", e))
+    }
+
+    async fn request_token(
+        &self,
+        http: &reqwest::Client,
+        endpoint: &str,
+        form: &[(&str, String)],
+    ) -> Result<TokenResponse, String> {
+        let response = http
+            .post(endpoint)
+            .header(header::ACCEPT, "application/json")
+            .form(form)
+            .timeout(REQUEST_TIMEOUT)
+            .send()
+            .await
+            .map_err(|e| format!("Token request failed: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!(
+                "Token request failed with status {}",
+                response.status()
+            ));
+        }
+        response
+            .json()
+            .await
+            .map_err(|e| format!("Invalid token response: {}", e))
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, http: &reqwest::Client, url: &str) -> Option<T> {
+        let response = http
+            .get(url)
+            .header(header::ACCEPT, "application/json")
+            .timeout(REQUEST_TIMEOUT)
+            .send()
+            .await
+            .ok()?;
+        if !response.status().is_success() {
+            return None;
+        }
+        response.json().await.ok()
+    }
+}
+
+// 根据 expires_in 计算过期时间，超出日期范围时视为不过期
+fn expires_at(expires_in: Option<i64>) -> Option<DateTime<Utc>> {
+    let expires_in = chrono::Duration::try_seconds(expires_in?)?;
+    Utc::now().checked_add_signed(expires_in)
+}
+
+fn secret_name(server_id: &str) -> String {
+    format!("oauth:{}", server_id)
+}
+
+fn bearer(access_token: &str) -> String {
+    format!("Bearer {}", access_token)
+}
+
+// 生成 43 个字符的随机 URL 安全字符串，用于 PKCE verifier 和 state
+fn random_token() -> Result<String, String> {
+    let mut bytes = [0u8; 32];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| "Failed to generate random value".to_string())?;
+    Ok(URL_SAFE_NO_PAD.encode(bytes))
+}
+
+fn parse_bearer_challenge(value: &str) -> Option<BearerChallenge> {
+    let value = value.trim();
+    if !value.get(..6)?.eq_ignore_ascii_case("bearer") {
+        return None;
+    }
+    let params = &value[6..];
+
+    static PARAM: OnceLock<Regex> = OnceLock::new();
+    let regex = PARAM.get_or_init(|| Regex::new(r#"(\w+)\s*=\s*(?:"([^"]*)"|([^\s,]+))"#).unwrap());
+    let mut challenge = BearerChallenge::default();
+    for captures in regex.captures_iter(params) {
+        let value = captures
+            .get(2)
+            .or_else(|| captures.get(3))
+            .map(|m| m.as_str().to_string());
+        match &captures[1] {
+            "resource_metadata" => challenge.resource_metadata = value,
+            "scope" => challenge.scope = value,
+            _ => {}
+        }
+    }
+    Some(challenge)
+}
+
+// 等待浏览器重定向到本地回调，返回授权码
+async fn wait_for_callback(listener: TcpListener, expected_state: &str) -> Result<String, String> {
+    loop {
+        let (mut stream, _) = listener
+            .accept()
+            .await
+            .map_err(|e| format!("OAuth callback listener failed: {}", e))?;
+
+        let mut buf = vec![0u8; 8192];
+        let n = match stream.read(&mut buf).await {
+            Ok(n) => n,
+            Err(_) => continue,
+        };
+        let request = String::from_utf8_lossy(&buf[..n]);
+        let Some(target) = request
+            .lines()
+            .next()
+            .and_then(|line| line.split_whitespace().nth(1))
+        else {
+            continue;
+        };
+        let Ok(url) = Url::parse(&format!("http://127.0.0.1{}", target)) else {
+            continue;
+        };
+        if url.path() != CALLBACK_PATH {
+            let _ = stream
+                .write_all(
+                    b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                )
+                .await;
+            continue;
+        }
+
+        let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+        let result = if let Some(error) = params.get("error") {
+            Err(format!(
+                "Authorization was denied: {}",
+                params.get("error_description").unwrap_or(error)
+            ))
+        } else if params.get("state").map(String::as_str) != Some(expected_state) {
+            Err("Authorization callback state does not match".to_string())
+        } else {
+            params
+                .get("code")
+                .cloned()
+                .ok_or_else(|| "Authorization callback has no code".to_string())
+        };
+
+        let message = match &result {
+            Ok(_) => "Authorization complete, you can close this window and return to fishmind.",
+            Err(_) => "Authorization failed, please return to fishmind and try again.",
+        };
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            message.len(),
+            message
+        );
+        let _ = stream.write_all(response.as_bytes()).await;
+        return result;
+    }
+}
@@ -0,0 +1,480 @@
+#[cfg(test)]
+mod tests {
+    use crate::encryption::KeyStore;
+    use crate::mcp::http_sse::HttpSseTransport;
+    use crate::mcp::oauth::{OAuthManager, UrlOpener};
+    use crate::mcp::secrets::SecretStore;
+    use crate::mcp::types::OAuthConfig;
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+    use reqwest::Url;
+    use ring::digest;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    const ACCESS_TOKEN: &str = "mock-access-token-1";
+    const REFRESHED_TOKEN: &str = "mock-access-token-2";
+    const REFRESH_TOKEN: &str = "mock-refresh-token";
+    const ENDPOINT_EVENT: &str = "event: endpoint\ndata: /mcp/message\n\n";
+
+    // 本地模拟的受保护 MCP 服务器和授权服务器
+    struct MockServer {
+        origin: String,
+        expires_in: i64,
+        challenge: Mutex<Option<String>>,
+        token_requests: Mutex<Vec<String>>,
+        // 建立事件流的请求数
+        sse_requests: Mutex<usize>,
+        // 已吊销的访问令牌
+        revoked: Mutex<Vec<String>>,
+        // 最近一次被接受的访问令牌
+        accepted: Mutex<Option<String>>,
+    }
+
+    impl MockServer {
+        fn sse_url(&self) -> String {
+            format!("{}/mcp/sse", self.origin)
+        }
+    }
+
+    async fn start_mock_server(expires_in: i64) -> Arc<MockServer> {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let server = Arc::new(MockServer {
+            origin: format!("http://{}", listener.local_addr().unwrap()),
+            expires_in,
+            challenge: Mutex::new(None),
+            token_requests: Mutex::new(Vec::new()),
+            sse_requests: Mutex::new(0),
+            revoked: Mutex::new(Vec::new()),
+            accepted: Mutex::new(None),
+        });
+
+        let state = server.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let state = state.clone();
+                tokio::spawn(async move { handle(stream, state).await });
+            }
+        });
+        server
+    }
+
+    async fn handle(mut stream: TcpStream, server: Arc<MockServer>) {
+        let Some((method, target, headers, body)) = read_request(&mut stream).await else {
+            return;
+        };
+        let path = target.split('?').next().unwrap_or_default();
+        let json = |value: serde_json::Value| ("200 OK", String::new(), value.to_string());
+
+        let (status, extra, body) = match (method.as_str(), path) {
+            ("GET", "/open/sse") => ("200 OK", String::new(), ENDPOINT_EVENT.to_string()),
+            ("GET", "/mcp/sse") => {
+                *server.sse_requests.lock().unwrap() += 1;
+                let token = headers
+                    .get("authorization")
+                    .and_then(|value| value.strip_prefix("Bearer "))
+                    .filter(|token| [ACCESS_TOKEN, REFRESHED_TOKEN].contains(token))
+                    .filter(|token| !server.revoked.lock().unwrap().iter().any(|t| t == token));
+                if let Some(token) = token {
+                    *server.accepted.lock().unwrap() = Some(token.to_string());
+                    ("200 OK", String::new(), ENDPOINT_EVENT.to_string())
+                } else {
+                    (
+                        "401 Unauthorized",
+                        format!(
+                            "WWW-Authenticate: Bearer realm=\"mcp\", resource_metadata=\"{}/.well-known/oauth-protected-resource/mcp/sse\"\r\n",
+                            server.origin
+                        ),
+                        String::new(),
+                    )
+                }
+            }
+            ("GET", "/.well-known/oauth-protected-resource/mcp/sse") => json(serde_json::json!({
+                "resource": server.sse_url(),
+                "authorization_servers": [server.origin],
+                "scopes_supported": ["mcp:tools"],
+            })),
+            ("GET", "/.well-known/oauth-authorization-server") => json(serde_json::json!({
+                "issuer": server.origin,
+                "authorization_endpoint": format!("{}/authorize", server.origin),
+                "token_endpoint": format!("{}/token", server.origin),
+                "registration_endpoint": format!("{}/register", server.origin),
+                "code_challenge_methods_supported": ["S256"],
+            })),
+            ("POST", "/register") => {
+                let request: serde_json::Value = serde_json::from_str(&body).unwrap();
+                assert_eq!(request["token_endpoint_auth_method"], "none");
+                json(serde_json::json!({ "client_id": "dyn-client" }))
+            }
+            ("POST", "/token") => match token_response(&server, &body) {
+                Some(value) => json(value),
+                None => (
+                    "400 Bad Request",
+                    String::new(),
+                    r#"{"error":"invalid_grant"}"#.to_string(),
+                ),
+            },
+            _ => ("404 Not Found", String::new(), String::new()),
+        };
+
+        let response = format!(
+            "HTTP/1.1 {}\r\n{}Content-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            extra,
+            body.len(),
+            body
+        );
+        let _ = stream.write_all(response.as_bytes()).await;
+    }
+
+    // 校验授权码和 PKCE verifier，或刷新令牌
+    fn token_response(server: &MockServer, body: &str) -> Option<serde_json::Value> {
+        let form: HashMap<String, String> = Url::parse(&format!("http://form/?{}", body))
+            .unwrap()
+            .query_pairs()
+            .into_owned()
+            .collect();
+        server
+            .token_requests
+            .lock()
+            .unwrap()
+            .push(form.get("grant_type").cloned().unwrap_or_default());
+        if form.get("client_id").map(String::as_str) != Some("dyn-client")
+            || form.get("resource") != Some(&server.sse_url())
+        {
+            return None;
+        }
+
+        match form.get("grant_type")?.as_str() {
+            "authorization_code" => {
+                let verifier = form.get("code_verifier")?;
+                let expected =
+                    URL_SAFE_NO_PAD.encode(digest::digest(&digest::SHA256, verifier.as_bytes()));
+                if form.get("code")? != "code-1"
+                    || server.challenge.lock().unwrap().as_deref() != Some(expected.as_str())
+                {
+                    return None;
+                }
+                Some(serde_json::json!({
+                    "access_token": ACCESS_TOKEN,
+                    "token_type": "Bearer",
+                    "expires_in": server.expires_in,
+                    "refresh_token": REFRESH_TOKEN,
+                }))
+            }
+            "refresh_token" if form.get("refresh_token")? == REFRESH_TOKEN => {
+                Some(serde_json::json!({
+                    "access_token": REFRESHED_TOKEN,
+                    "token_type": "Bearer",
+                    "expires_in": 3600,
+                }))
+            }
+            _ => None,
+        }
+    }
+
+    async fn read_request(
+        stream: &mut TcpStream,
+    ) -> Option<(String, String, HashMap<String, String>, String)> {
+        let mut buf = Vec::new();
+        let mut chunk = [0u8; 4096];
+        loop {
+            let n = stream.read(&mut chunk).await.ok()?;
+            if n == 0 {
+                return None;
+            }
+            buf.extend_from_slice(&chunk[..n]);
+            let Some(end) = buf.windows(4).position(|w| w == b"\r\n\r\n") else {
+                continue;
+            };
+
+            let head = String::from_utf8_lossy(&buf[..end]).to_string();
+            let mut lines = head.lines();
+            let mut request_line = lines.next()?.split_whitespace();
+            let method = request_line.next()?.to_string();
+            let target = request_line.next()?.to_string();
+            let headers: HashMap<String, String> = lines
+                .filter_map(|line| line.split_once(':'))
+                .map(|(k, v)| (k.trim().to_ascii_lowercase(), v.trim().to_string()))
+                .collect();
+
+            let length: usize = headers
+                .get("content-length")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0);
+            while buf.len() < end + 4 + length {
+                let n = stream.read(&mut chunk).await.ok()?;
+                if n == 0 {
+                    return None;
+                }
+                buf.extend_from_slice(&chunk[..n]);
+            }
+            let body = String::from_utf8_lossy(&buf[end + 4..end + 4 + length]).to_string();
+            return Some((method, target, headers, body));
+        }
+    }
+
+    // 模拟浏览器：记录 PKCE challenge 后重定向到本地回调
+    fn browser(
+        server: Arc<MockServer>,
+        forge_state: bool,
+    ) -> impl Fn(&str) -> Result<(), String> + Send + Sync {
+        move |authorization_url| {
+            let url = Url::parse(authorization_url).map_err(|e| e.to_string())?;
+            let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+            assert_eq!(params["code_challenge_method"], "S256");
+            assert_eq!(params["client_id"], "dyn-client");
+            assert_eq!(params["scope"], "mcp:tools");
+            assert_eq!(params["resource"], server.sse_url());
+            *server.challenge.lock().unwrap() = params.get("code_challenge").cloned();
+
+            let state = if forge_state {
+                "forged".to_string()
+            } else {
+                params["state"].clone()
+            };
+            let callback = format!("{}?code=code-1&state={}", params["redirect_uri"], state);
+            tokio::spawn(async move {
+                let _ = reqwest::get(callback).await;
+            });
+            Ok(())
+        }
+    }
+
+    fn no_browser(_: &str) -> Result<(), String> {
+        Err("browser should not be opened".to_string())
+    }
+
+    // 与客户端连接时相同，通过 SSE 传输建立连接
+    async fn connect(
+        oauth: &OAuthManager,
+        url: &str,
+        config: Option<&OAuthConfig>,
+        opener: &UrlOpener,
+    ) -> Result<(), String> {
+        let http = reqwest::Client::new();
+        oauth
+            .start_transport_with(
+                &http,
+                "remote",
+                url,
+                HashMap::new(),
+                config,
+                opener,
+                |headers| HttpSseTransport::new(http.clone(), url, headers),
+            )
+            .await
+            .map(|_| ())
+    }
+
+    fn accepted(server: &MockServer) -> Option<String> {
+        server.accepted.lock().unwrap().clone()
+    }
+
+    // 测试完整授权流程：发现、动态注册、PKCE、换取令牌，之后复用已保存的令牌
+    #[tokio::test]
+    async fn test_authorization_code_flow() {
+        let server = start_mock_server(3600).await;
+        let oauth = OAuthManager::new(Arc::new(SecretStore::in_memory()));
+
+        connect(
+            &oauth,
+            &server.sse_url(),
+            None,
+            &browser(server.clone(), false),
+        )
+        .await
+        .unwrap();
+        assert_eq!(accepted(&server).as_deref(), Some(ACCESS_TOKEN));
+
+        let status = oauth.token_status("remote").unwrap().unwrap();
+        assert_eq!(status.resource, server.sse_url());
+        assert!(status.has_refresh_token);
+        assert!(status.expires_at.is_some());
+
+        // 已保存的令牌直接用于建立事件流，不再额外探测
+        *server.sse_requests.lock().unwrap() = 0;
+        connect(&oauth, &server.sse_url(), None, &no_browser)
+            .await
+            .unwrap();
+        assert_eq!(*server.sse_requests.lock().unwrap(), 1);
+        assert_eq!(
+            *server.token_requests.lock().unwrap(),
+            vec!["authorization_code".to_string()]
+        );
+    }
+
+    // 测试即将过期的令牌在连接前刷新
+    #[tokio::test]
+    async fn test_refresh_expiring_token() {
+        let server = start_mock_server(10).await;
+        let oauth = OAuthManager::new(Arc::new(SecretStore::in_memory()));
+
+        connect(
+            &oauth,
+            &server.sse_url(),
+            None,
+            &browser(server.clone(), false),
+        )
+        .await
+        .unwrap();
+
+        connect(&oauth, &server.sse_url(), None, &no_browser)
+            .await
+            .unwrap();
+        assert_eq!(accepted(&server).as_deref(), Some(REFRESHED_TOKEN));
+        // 服务器未轮换刷新令牌时保留原刷新令牌
+        assert!(
+            oauth
+                .token_status("remote")
+                .unwrap()
+                .unwrap()
+                .has_refresh_token
+        );
+    }
+
+    // 测试未过期但已被吊销的令牌在服务器返回 401 后刷新并重试
+    #[tokio::test]
+    async fn test_revoked_token_refreshed() {
+        let server = start_mock_server(3600).await;
+        let oauth = OAuthManager::new(Arc::new(SecretStore::in_memory()));
+
+        connect(
+            &oauth,
+            &server.sse_url(),
+            None,
+            &browser(server.clone(), false),
+        )
+        .await
+        .unwrap();
+        server
+            .revoked
+            .lock()
+            .unwrap()
+            .push(ACCESS_TOKEN.to_string());
+
+        connect(&oauth, &server.sse_url(), None, &no_browser)
+            .await
+            .unwrap();
+        assert_eq!(accepted(&server).as_deref(), Some(REFRESHED_TOKEN));
+        assert_eq!(
+            *server.token_requests.lock().unwrap(),
+            vec![
+                "authorization_code".to_string(),
+                "refresh_token".to_string()
+            ]
+        );
+    }
+
+    // 测试刷新后的令牌也被吊销时只重试一次，下次连接重新授权
+    #[tokio::test]
+    async fn test_revoked_token_retried_once() {
+        let server = start_mock_server(3600).await;
+        let oauth = OAuthManager::new(Arc::new(SecretStore::in_memory()));
+
+        connect(
+            &oauth,
+            &server.sse_url(),
+            None,
+            &browser(server.clone(), false),
+        )
+        .await
+        .unwrap();
+        server
+            .revoked
+            .lock()
+            .unwrap()
+            .extend([ACCESS_TOKEN.to_string(), REFRESHED_TOKEN.to_string()]);
+
+        *server.sse_requests.lock().unwrap() = 0;
+        let result = connect(&oauth, &server.sse_url(), None, &no_browser).await;
+        assert!(result.unwrap_err().contains("401"));
+        assert_eq!(*server.sse_requests.lock().unwrap(), 2);
+    }
+
+    // 测试 expires_in 超出日期范围时视为不过期，不会导致授权失败
+    #[tokio::test]
+    async fn test_huge_expires_in() {
+        let server = start_mock_server(i64::MAX).await;
+        let oauth = OAuthManager::new(Arc::new(SecretStore::in_memory()));
+
+        connect(
+            &oauth,
+            &server.sse_url(),
+            None,
+            &browser(server.clone(), false),
+        )
+        .await
+        .unwrap();
+        assert_eq!(accepted(&server).as_deref(), Some(ACCESS_TOKEN));
+        assert!(oauth
+            .token_status("remote")
+            .unwrap()
+            .unwrap()
+            .expires_at
+            .is_none());
+    }
+
+    // 测试回调 state 不匹配时授权失败且不保存令牌
+    #[tokio::test]
+    async fn test_reject_forged_state() {
+        let server = start_mock_server(3600).await;
+        let oauth = OAuthManager::new(Arc::new(SecretStore::in_memory()));
+
+        let result = connect(
+            &oauth,
+            &server.sse_url(),
+            None,
+            &browser(server.clone(), true),
+        )
+        .await;
+        assert!(result.unwrap_err().contains("state"));
+        assert!(oauth.token_status("remote").unwrap().is_none());
+    }
+
+    // 测试不需要授权或禁用授权时不打开浏览器
+    #[tokio::test]
+    async fn test_no_authorization_needed() {
+        let server = start_mock_server(3600).await;
+        let oauth = OAuthManager::new(Arc::new(SecretStore::in_memory()));
+
+        let open_url = format!("{}/open/sse", server.origin);
+        connect(&oauth, &open_url, None, &no_browser).await.unwrap();
+
+        let disabled = OAuthConfig {
+            disabled: true,
+            ..Default::default()
+        };
+        let result = connect(&oauth, &server.sse_url(), Some(&disabled), &no_browser).await;
+        assert!(result.unwrap_err().contains("401"));
+        assert!(oauth.token_status("remote").unwrap().is_none());
+    }
+
+    // 测试令牌加密保存，注销后删除
+    #[tokio::test]
+    async fn test_token_encrypted_and_logout() {
+        let server = start_mock_server(3600).await;
+        let dir = std::env::temp_dir().join(format!("fishmind-oauth-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let key_store = KeyStore::with_master_key(vec![7; 32]);
+        let oauth = OAuthManager::new(Arc::new(SecretStore::open(&dir, &key_store).unwrap()));
+
+        connect(
+            &oauth,
+            &server.sse_url(),
+            None,
+            &browser(server.clone(), false),
+        )
+        .await
+        .unwrap();
+
+        let content = std::fs::read_to_string(dir.join("mcp_secrets.json")).unwrap();
+        assert!(content.contains("oauth:remote"));
+        assert!(!content.contains(ACCESS_TOKEN));
+        assert!(!content.contains(REFRESH_TOKEN));
+
+        assert!(oauth.logout("remote").unwrap());
+        assert!(oauth.token_status("remote").unwrap().is_none());
+    }
+}
@@ -0,0 +1,219 @@
+use crate::mcp::{redact, secrets::SecretStore, types::*};
+use log::{info, warn};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::oneshot;
+
+/// 配置输入请求事件名
+pub const INPUT_REQUEST_EVENT: &str = "mcp-config-input-request";
+
+// 等待用户输入的超时时间
+const INPUT_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// 服务器配置占位符解析器
+///
+/// 支持以下占位符：
+/// - `${env:VAR}` 或 `${VAR}`：进程环境变量
+/// - `${workspaceFolder}`：当前工作目录
+/// - `${secret:name}`：加密密钥存储中的值
+/// - `${input:prompt}`：通过前端向用户询问，会话内按客户端缓存
+///
+/// 密钥和用户输入的值解析后会登记到脱敏模块，不会出现在日志中。
+pub struct PlaceholderResolver {
+    secrets: Arc<SecretStore>,
+    app: OnceLock<AppHandle>,
+    pending: Mutex<HashMap<String, oneshot::Sender<Option<String>>>>,
+    // 会话内缓存的用户输入: (客户端ID, 提示) -> 值
+    inputs: Mutex<HashMap<(String, String), String>>,
+    next_id: AtomicU64,
+}
+
+impl PlaceholderResolver {
+    /// 创建解析器
+    pub fn new(secrets: Arc<SecretStore>) -> Self {
+        Self {
+            secrets,
+            app: OnceLock::new(),
+            pending: Mutex::new(HashMap::new()),
+            inputs: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// 设置应用句柄，用于向前端请求 `${input:...}` 的值
+    pub fn set_app_handle(&self, app: AppHandle) {
+        let _ = self.app.set(app);
+    }
+
+    /// 加密密钥存储
+    pub fn secrets(&self) -> &Arc<SecretStore> {
+        &self.secrets
+    }
+
+    /// 解析字符串中的所有占位符
+    pub async fn resolve(&self, client_id: &str, value: &str) -> Result<String, String> {
+        let mut result = String::with_capacity(value.len());
+        let mut rest = value;
+
+        while let Some(start) = rest.find("${") {
+            result.push_str(&rest[..start]);
+            let after = &rest[start + 2..];
+            let end = after
+                .find('}')
+                .ok_or_else(|| format!("Unclosed placeholder in config of '{}'", client_id))?;
+            result.push_str(&self.resolve_placeholder(client_id, &after[..end]).await?);
+            rest = &after[end + 1..];
+        }
+        result.push_str(rest);
+        Ok(result)
+    }
+
+    /// 解析列表中的所有占位符
+    pub async fn resolve_all(
+        &self,
+        client_id: &str,
+        values: Vec<String>,
+    ) -> Result<Vec<String>, String> {
+        let mut resolved = Vec::with_capacity(values.len());
+        for value in values {
+            resolved.push(self.resolve(client_id, &value).await?);
+        }
+        Ok(resolved)
+    }
+
+    /// 解析映射值中的所有占位符，键保持不变
+    pub async fn resolve_map(
+        &self,
+        client_id: &str,
+        map: HashMap<String, String>,
+    ) -> Result<HashMap<String, String>, String> {
+        let mut resolved = HashMap::with_capacity(map.len());
+        for (key, value) in map {
+            let value = self.resolve(client_id, &value).await?;
+            // 敏感键名对应的值（如环境变量中的令牌）同样不能出现在日志中
+            if redact::is_sensitive_key(&key) {
+                redact::register_secret(&value);
+            }
+            resolved.insert(key, value);
+        }
+        Ok(resolved)
+    }
+
+    async fn resolve_placeholder(
+        &self,
+        client_id: &str,
+        placeholder: &str,
+    ) -> Result<String, String> {
+        let (kind, name) = placeholder.split_once(':').unwrap_or(("env", placeholder));
+        let name = name.trim();
+
+        match kind {
+            "env" if name == "workspaceFolder" => std::env::current_dir()
+                .map(|dir| dir.to_string_lossy().to_string())
+                .map_err(|e| format!("Failed to resolve ${{workspaceFolder}}: {}", e)),
+            "env" => std::env::var(name).map_err(|_| {
+                format!(
+                    "Environment variable '{}' referenced by '{}' is not set",
+                    name, client_id
+                )
+            }),
+            "secret" => {
+                let value = self.secrets.get(name)?.ok_or_else(|| {
+                    format!("Secret '{}' referenced by '{}' not found", name, client_id)
+                })?;
+                redact::register_secret(&value);
+                Ok(value)
+            }
+            "input" => {
+                let value = self.request_input(client_id, name).await?;
+                redact::register_secret(&value);
+                Ok(value)
+            }
+            other => Err(format!(
+                "Unknown placeholder type '{}' in config of '{}'",
+                other, client_id
+            )),
+        }
+    }
+
+    /// 向前端请求输入，同一客户端的同一提示在会话内只询问一次
+    async fn request_input(&self, client_id: &str, prompt: &str) -> Result<String, String> {
+        let key = (client_id.to_string(), prompt.to_string());
+        if let Some(value) = self.inputs.lock().unwrap().get(&key) {
+            return Ok(value.clone());
+        }
+
+        let app = self.app.get().ok_or_else(|| {
+            format!(
+                "Input '{}' for '{}' requires user interaction, which is not available",
+                prompt, client_id
+            )
+        })?;
+
+        let request_id = format!("input-{}", self.next_id.fetch_add(1, Ordering::SeqCst));
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(request_id.clone(), tx);
+
+        let event = InputRequestEvent {
+            request_id: request_id.clone(),
+            client_id: client_id.to_string(),
+            prompt: prompt.to_string(),
+        };
+        info!(
+            "[MCP] 等待用户输入配置值, 请求ID: {}, 客户端ID: {}",
+            request_id, client_id
+        );
+        if let Err(e) = app.emit(INPUT_REQUEST_EVENT, &event) {
+            self.pending.lock().unwrap().remove(&request_id);
+            return Err(format!("Failed to emit input request: {}", e));
+        }
+
+        let value = match tokio::time::timeout(INPUT_TIMEOUT, rx).await {
+            Ok(Ok(Some(value))) => value,
+            Ok(Ok(None)) | Ok(Err(_)) => {
+                return Err(format!(
+                    "Input '{}' for '{}' was cancelled",
+                    prompt, client_id
+                ))
+            }
+            Err(_) => {
+                self.pending.lock().unwrap().remove(&request_id);
+                warn!("[MCP] 等待用户输入超时, 请求ID: {}", request_id);
+                return Err(format!("Input '{}' for '{}' timed out", prompt, client_id));
+            }
+        };
+
+        self.inputs.lock().unwrap().insert(key, value.clone());
+        Ok(value)
+    }
+
+    /// 处理前端返回的输入
+    pub fn respond(&self, response: InputResponse) -> Result<(), String> {
+        let sender = self
+            .pending
+            .lock()
+            .unwrap()
+            .remove(&response.request_id)
+            .ok_or_else(|| {
+                format!(
+                    "Input request '{}' not found or already handled",
+                    response.request_id
+                )
+            })?;
+
+        sender.send(response.value).map_err(|_| {
+            format!(
+                "Input request '{}' is no longer waiting",
+                response.request_id
+            )
+        })
+    }
+
+    /// 清除会话内缓存的用户输入
+    pub fn clear_inputs(&self) {
+        self.inputs.lock().unwrap().clear();
+    }
+}
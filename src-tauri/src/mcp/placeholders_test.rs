@@ -0,0 +1,100 @@
+#[cfg(test)]
+mod tests {
+    use crate::mcp::placeholders::PlaceholderResolver;
+    use crate::mcp::redact::redact_text;
+    use crate::mcp::secrets::SecretStore;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    fn resolver() -> PlaceholderResolver {
+        let secrets = SecretStore::in_memory();
+        secrets.set("api", "sk-placeholder-test").unwrap();
+        PlaceholderResolver::new(Arc::new(secrets))
+    }
+
+    // 测试环境变量占位符
+    #[tokio::test]
+    async fn test_resolve_env() {
+        std::env::set_var("FISHMIND_PLACEHOLDER_TEST", "value-1");
+        let resolver = resolver();
+
+        assert_eq!(
+            resolver
+                .resolve("test-client", "a=${env:FISHMIND_PLACEHOLDER_TEST}")
+                .await
+                .unwrap(),
+            "a=value-1"
+        );
+        assert_eq!(
+            resolver
+                .resolve("test-client", "${FISHMIND_PLACEHOLDER_TEST}/b")
+                .await
+                .unwrap(),
+            "value-1/b"
+        );
+        assert!(resolver
+            .resolve("test-client", "${env:FISHMIND_PLACEHOLDER_MISSING}")
+            .await
+            .is_err());
+    }
+
+    // 测试密钥占位符，解析后的值在日志中被脱敏
+    #[tokio::test]
+    async fn test_resolve_secret() {
+        let resolver = resolver();
+
+        let value = resolver
+            .resolve("test-client", "Bearer-less ${secret:api}")
+            .await
+            .unwrap();
+        assert_eq!(value, "Bearer-less sk-placeholder-test");
+        assert!(!redact_text(&format!("connect failed: {}", value)).contains("sk-placeholder-test"));
+
+        assert!(resolver
+            .resolve("test-client", "${secret:missing}")
+            .await
+            .is_err());
+    }
+
+    // 测试映射和列表解析
+    #[tokio::test]
+    async fn test_resolve_map_and_list() {
+        let resolver = resolver();
+        let mut env = HashMap::new();
+        env.insert("API_KEY".to_string(), "${secret:api}".to_string());
+        env.insert("MODE".to_string(), "plain".to_string());
+
+        let env = resolver.resolve_map("test-client", env).await.unwrap();
+        assert_eq!(env["API_KEY"], "sk-placeholder-test");
+        assert_eq!(env["MODE"], "plain");
+
+        let args = resolver
+            .resolve_all("test-client", vec!["--key=${secret:api}".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(args, vec!["--key=sk-placeholder-test".to_string()]);
+    }
+
+    // 测试无法交互时输入占位符失败，以及无效占位符
+    #[tokio::test]
+    async fn test_invalid_placeholders() {
+        let resolver = resolver();
+
+        assert!(resolver
+            .resolve("test-client", "${input:Token}")
+            .await
+            .is_err());
+        assert!(resolver
+            .resolve("test-client", "${unknown:x}")
+            .await
+            .is_err());
+        assert!(resolver.resolve("test-client", "${env:HOME").await.is_err());
+        assert_eq!(
+            resolver
+                .resolve("test-client", "no placeholders")
+                .await
+                .unwrap(),
+            "no placeholders"
+        );
+    }
+}
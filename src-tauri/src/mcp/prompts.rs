@@ -0,0 +1,182 @@
+use crate::mcp::types::{ContentBlock, MessageRole, PromptArgument, PromptMessage, PromptResult};
+use serde_json::{json, Map, Value};
+
+/// 解析提示定义中的参数列表，缺少名称的参数会被忽略
+pub fn parse_arguments(arguments: &Value) -> Vec<PromptArgument> {
+    arguments
+        .as_array()
+        .map(|arguments| {
+            arguments
+                .iter()
+                .filter_map(|argument| {
+                    Some(PromptArgument {
+                        name: argument.get("name")?.as_str()?.to_string(),
+                        description: argument
+                            .get("description")
+                            .and_then(Value::as_str)
+                            .map(str::to_string),
+                        required: argument
+                            .get("required")
+                            .and_then(Value::as_bool)
+                            .unwrap_or(false),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 校验提示参数，返回发送给服务器的参数
+///
+/// MCP 的提示参数都是字符串，数字和布尔值转换为字符串；
+/// 缺少必填参数（或为空字符串）时返回错误，不发送请求。
+pub fn validate_arguments(
+    prompt_name: &str,
+    arguments: &[PromptArgument],
+    params: &Value,
+) -> Result<Value, String> {
+    let params = match params {
+        Value::Null => Map::new(),
+        Value::Object(params) => params.clone(),
+        _ => return Err("Prompt arguments must be an object".to_string()),
+    };
+
+    let mut normalized = Map::new();
+    for (name, value) in params {
+        let value = match value {
+            Value::Null => continue,
+            Value::String(value) => value,
+            Value::Number(_) | Value::Bool(_) => value.to_string(),
+            _ => return Err(format!("Prompt argument '{}' must be a string", name)),
+        };
+        normalized.insert(name, Value::String(value));
+    }
+
+    let missing: Vec<&str> = arguments
+        .iter()
+        .filter(|argument| argument.required)
+        .filter(|argument| {
+            normalized
+                .get(&argument.name)
+                .and_then(Value::as_str)
+                .is_none_or(|value| value.trim().is_empty())
+        })
+        .map(|argument| argument.name.as_str())
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!(
+            "Missing required arguments for prompt '{}': {}",
+            prompt_name,
+            missing.join(", ")
+        ));
+    }
+    Ok(Value::Object(normalized))
+}
+
+/// 把 `prompts/get` 的结果转换为对话消息
+///
+/// 相邻的同角色消息合并为一条，每条 MCP 消息的内容成为其中的一个内容块。
+pub fn normalize_prompt(result: &Value) -> Result<PromptResult, String> {
+    let messages = result
+        .get("messages")
+        .and_then(Value::as_array)
+        .ok_or_else(|| "Invalid prompt response: missing 'messages'".to_string())?;
+
+    let mut normalized: Vec<PromptMessage> = Vec::new();
+    for message in messages {
+        let role = match message.get("role").and_then(Value::as_str) {
+            Some("user") => MessageRole::User,
+            Some("assistant") => MessageRole::Assistant,
+            other => return Err(format!("Unsupported prompt message role: {:?}", other)),
+        };
+        let content = content_block(message.get("content").unwrap_or(&Value::Null))?;
+        match normalized.last_mut() {
+            Some(last) if last.role == role => last.content.push(content),
+            _ => normalized.push(PromptMessage {
+                role,
+                content: vec![content],
+            }),
+        }
+    }
+
+    Ok(PromptResult {
+        description: result
+            .get("description")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        messages: normalized,
+    })
+}
+
+// 兼容规范格式和把图片内容嵌套在 `image` 字段中的格式
+fn content_block(content: &Value) -> Result<ContentBlock, String> {
+    let text =
+        |value: &Value, key: &str| value.get(key).and_then(Value::as_str).map(str::to_string);
+    match content.get("type").and_then(Value::as_str) {
+        Some("text") => Ok(ContentBlock::Text {
+            text: text(content, "text").unwrap_or_default(),
+        }),
+        Some("image") => {
+            let image = content.get("image").unwrap_or(content);
+            Ok(ContentBlock::Image {
+                data: text(image, "data")
+                    .ok_or_else(|| "Invalid image content: missing 'data'".to_string())?,
+                mime_type: text(image, "mimeType").unwrap_or_else(|| "image/png".to_string()),
+            })
+        }
+        Some("resource") => {
+            let resource = content
+                .get("resource")
+                .ok_or_else(|| "Invalid resource content: missing 'resource'".to_string())?;
+            Ok(ContentBlock::Resource {
+                uri: text(resource, "uri")
+                    .ok_or_else(|| "Invalid resource content: missing 'uri'".to_string())?,
+                mime_type: text(resource, "mimeType"),
+                text: text(resource, "text"),
+                blob: text(resource, "blob"),
+            })
+        }
+        other => Err(format!("Unsupported prompt content type: {:?}", other)),
+    }
+}
+
+/// 把对话消息转换回 MCP 的 `prompts/get` 结果，用于网关转发
+pub fn to_mcp_prompt(prompt: &PromptResult) -> Value {
+    let messages: Vec<Value> = prompt
+        .messages
+        .iter()
+        .flat_map(|message| {
+            message.content.iter().map(move |content| {
+                let content = match content {
+                    ContentBlock::Text { text } => json!({ "type": "text", "text": text }),
+                    ContentBlock::Image { data, mime_type } => {
+                        json!({ "type": "image", "data": data, "mimeType": mime_type })
+                    }
+                    ContentBlock::Resource {
+                        uri,
+                        mime_type,
+                        text,
+                        blob,
+                    } => {
+                        let mut resource = json!({ "uri": uri });
+                        for (key, value) in
+                            [("mimeType", mime_type), ("text", text), ("blob", blob)]
+                        {
+                            if let Some(value) = value {
+                                resource[key] = json!(value);
+                            }
+                        }
+                        json!({ "type": "resource", "resource": resource })
+                    }
+                };
+                json!({ "role": message.role, "content": content })
+            })
+        })
+        .collect();
+
+    let mut result = json!({ "messages": messages });
+    if let Some(description) = &prompt.description {
+        result["description"] = json!(description);
+    }
+    result
+}
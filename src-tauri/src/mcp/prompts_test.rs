@@ -0,0 +1,197 @@
+#[cfg(test)]
+mod tests {
+    use crate::mcp::client::McpClientManager;
+    use crate::mcp::mock_server::{start_sse, MockMcpServer};
+    use crate::mcp::prompts::{
+        normalize_prompt, parse_arguments, to_mcp_prompt, validate_arguments,
+    };
+    use crate::mcp::types::{
+        ContentBlock, FilterRequest, InitializeClientRequest, MessageRole, PromptArgument,
+        PromptRequest, TransportType,
+    };
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    fn greeting_arguments() -> Vec<PromptArgument> {
+        parse_arguments(&json!([
+            { "name": "name", "description": "Who to greet", "required": true },
+            { "name": "style", "required": null },
+            { "description": "ignored without a name" },
+        ]))
+    }
+
+    // 测试解析参数定义
+    #[test]
+    fn test_parse_arguments() {
+        assert_eq!(
+            greeting_arguments(),
+            vec![
+                PromptArgument {
+                    name: "name".to_string(),
+                    description: Some("Who to greet".to_string()),
+                    required: true,
+                },
+                PromptArgument {
+                    name: "style".to_string(),
+                    description: None,
+                    required: false,
+                },
+            ]
+        );
+        assert!(parse_arguments(&json!(null)).is_empty());
+    }
+
+    // 测试必填参数校验和参数值转换
+    #[test]
+    fn test_validate_arguments() {
+        let arguments = greeting_arguments();
+
+        let params = validate_arguments(
+            "greeting",
+            &arguments,
+            &json!({ "name": 42, "style": null, "extra": true }),
+        )
+        .unwrap();
+        assert_eq!(params, json!({ "name": "42", "extra": "true" }));
+
+        for params in [json!({}), json!(null), json!({ "name": "  " })] {
+            let err = validate_arguments("greeting", &arguments, &params).unwrap_err();
+            assert_eq!(
+                err,
+                "Missing required arguments for prompt 'greeting': name"
+            );
+        }
+
+        assert!(validate_arguments("greeting", &arguments, &json!(["Ada"])).is_err());
+        assert!(validate_arguments(
+            "greeting",
+            &arguments,
+            &json!({ "name": { "first": "Ada" } })
+        )
+        .is_err());
+    }
+
+    // 测试相邻同角色消息合并，内容块类型化
+    #[test]
+    fn test_normalize_prompt() {
+        let result = json!({
+            "description": "Review a file",
+            "messages": [
+                { "role": "user", "content": { "type": "text", "text": "Please review" } },
+                { "role": "user", "content": {
+                    "type": "resource",
+                    "resource": { "uri": "file:///a.rs", "mimeType": "text/x-rust", "text": "fn main() {}" },
+                } },
+                { "role": "assistant", "content": { "type": "image", "data": "aGk=", "mimeType": "image/jpeg" } },
+                { "role": "assistant", "content": { "type": "image", "image": { "data": "aGk=", "mimeType": "image/png" } } },
+            ],
+        });
+
+        let prompt = normalize_prompt(&result).unwrap();
+        assert_eq!(prompt.description.as_deref(), Some("Review a file"));
+        assert_eq!(prompt.messages.len(), 2);
+        assert_eq!(prompt.messages[0].role, MessageRole::User);
+        assert_eq!(
+            prompt.messages[0].content,
+            vec![
+                ContentBlock::Text {
+                    text: "Please review".to_string()
+                },
+                ContentBlock::Resource {
+                    uri: "file:///a.rs".to_string(),
+                    mime_type: Some("text/x-rust".to_string()),
+                    text: Some("fn main() {}".to_string()),
+                    blob: None,
+                },
+            ]
+        );
+        assert_eq!(prompt.messages[1].role, MessageRole::Assistant);
+        assert_eq!(
+            prompt.messages[1].content[1],
+            ContentBlock::Image {
+                data: "aGk=".to_string(),
+                mime_type: "image/png".to_string()
+            }
+        );
+
+        // 转换回 MCP 格式时每个内容块一条消息
+        let mcp = to_mcp_prompt(&prompt);
+        assert_eq!(mcp["messages"].as_array().unwrap().len(), 4);
+        assert_eq!(
+            mcp["messages"][1]["content"]["resource"]["mimeType"],
+            "text/x-rust"
+        );
+        assert_eq!(normalize_prompt(&mcp).unwrap().messages, prompt.messages);
+
+        let unsupported = json!({
+            "messages": [{ "role": "user", "content": { "type": "audio", "data": "" } }],
+        });
+        assert!(normalize_prompt(&unsupported).is_err());
+    }
+
+    // 测试缺少必填参数时不发送请求
+    #[tokio::test]
+    async fn test_get_prompt_validation() {
+        let mut manager = McpClientManager::new();
+        let request = InitializeClientRequest {
+            id: "mock".to_string(),
+            transport_type: TransportType::SSE,
+            sse_url: Some(
+                start_sse(MockMcpServer::new(), "127.0.0.1:0")
+                    .await
+                    .unwrap(),
+            ),
+            headers: Some(HashMap::new()),
+            timeout_secs: Some(30),
+            client_name: "prompts-test".to_string(),
+            client_version: "1.0.0".to_string(),
+            ..Default::default()
+        };
+        manager.initialize_client(request).await.unwrap();
+
+        let prompts = manager
+            .list_prompts(FilterRequest {
+                client_id: "mock".to_string(),
+                filter: None,
+                cursor: None,
+            })
+            .await
+            .unwrap()
+            .data
+            .unwrap();
+        assert_eq!(prompts[0].arguments.len(), 2);
+        assert!(prompts[0].arguments[0].required);
+
+        let missing = manager
+            .get_prompt(PromptRequest {
+                client_id: "mock".to_string(),
+                prompt_name: "greeting".to_string(),
+                params: json!({ "style": "formal" }),
+            })
+            .await
+            .unwrap();
+        assert!(!missing.success);
+        assert_eq!(
+            missing.error.as_deref(),
+            Some("Missing required arguments for prompt 'greeting': name")
+        );
+
+        let prompt = manager
+            .get_prompt(PromptRequest {
+                client_id: "mock".to_string(),
+                prompt_name: "greeting".to_string(),
+                params: json!({ "name": "Ada", "style": "formal" }),
+            })
+            .await
+            .unwrap()
+            .data
+            .unwrap();
+        assert_eq!(prompt.messages[0].role, MessageRole::User);
+        assert_eq!(
+            prompt.messages[0].content,
+            vec![ContentBlock::Text {
+                text: "Good day, Ada.".to_string()
+            }]
+        );
+    }
+}
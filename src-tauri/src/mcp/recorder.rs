@@ -0,0 +1,404 @@
+use crate::mcp::http_sse::MessageObserver;
+use crate::mcp::redact;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use log::{debug, error, info, warn};
+use mcp_client_fishcode2025::transport::{Error as TransportError, Transport, TransportHandle};
+use mcp_core_fishcode2025::protocol::JsonRpcMessage;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+
+/// 消息方向
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    /// 客户端发送给服务器
+    Outgoing,
+    /// 服务器返回给客户端
+    Incoming,
+}
+
+/// 录制文件中的一行
+///
+/// 传输失败时 `message` 为空，`error` 为错误信息。
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RecordedMessage {
+    pub timestamp: DateTime<Utc>,
+    pub direction: Direction,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// MCP 会话录制器
+///
+/// 以 JSON Lines 格式追加写入双向的 JSON-RPC 消息。录制文件通常会附在问题报告中，
+/// 因此写入前按日志脱敏规则处理敏感参数。
+pub struct SessionRecorder {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl SessionRecorder {
+    /// 创建录制文件，已存在时覆盖
+    pub fn create(path: PathBuf) -> Result<Self, String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create recording directory: {}", e))?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(&path)
+            .map_err(|e| format!("Failed to create recording {}: {}", path.display(), e))?;
+        info!("[MCP] 开始录制会话: {}", path.display());
+        Ok(Self {
+            path,
+            file: Mutex::new(file),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 写入一条记录，写入失败只记录日志，不影响请求
+    pub fn write(&self, entry: &RecordedMessage) {
+        let Ok(line) = serde_json::to_string(entry) else {
+            return;
+        };
+        let mut file = self.file.lock().unwrap();
+        if let Err(e) = writeln!(file, "{}", line).and_then(|_| file.flush()) {
+            error!(
+                "[MCP] 写入会话录制失败: {}, 错误: {}",
+                self.path.display(),
+                e
+            );
+        }
+    }
+}
+
+impl RecordedMessage {
+    fn new(direction: Direction, result: Result<&JsonRpcMessage, String>) -> Self {
+        let (message, error) = match result {
+            Ok(message) => match serde_json::to_value(message) {
+                Ok(value) => (Some(redact::redact_value(&value)), None),
+                Err(e) => (None, Some(format!("Failed to serialize message: {}", e))),
+            },
+            Err(e) => (None, Some(e)),
+        };
+        Self {
+            timestamp: Utc::now(),
+            direction,
+            message,
+            error,
+        }
+    }
+}
+
+// 握手阶段的方法，始终保留在内存中
+const HANDSHAKE_METHODS: [&str; 2] = ["initialize", "notifications/initialized"];
+
+/// 客户端的会话录制状态，与传输句柄共享
+///
+/// 握手消息始终保留在内存中，开始录制时先写入录制文件，
+/// 因此连接之后才开始的录制也可以直接回放。
+#[derive(Default)]
+pub struct RecorderSlot {
+    recorder: RwLock<Option<Arc<SessionRecorder>>>,
+    handshake: Mutex<Vec<RecordedMessage>>,
+}
+
+impl RecorderSlot {
+    /// 开始录制到指定文件，已在录制时切换到新的文件
+    pub fn start(&self, path: PathBuf) -> Result<(), String> {
+        let recorder = SessionRecorder::create(path)?;
+        for entry in self.handshake.lock().unwrap().iter() {
+            recorder.write(entry);
+        }
+        *self.recorder.write().unwrap() = Some(Arc::new(recorder));
+        Ok(())
+    }
+
+    /// 停止录制，返回录制文件路径
+    pub fn stop(&self) -> Option<PathBuf> {
+        let recorder = self.recorder.write().unwrap().take()?;
+        info!("[MCP] 停止录制会话: {}", recorder.path().display());
+        Some(recorder.path().to_path_buf())
+    }
+
+    fn record(&self, entry: RecordedMessage, handshake: bool) {
+        if let Some(recorder) = self.recorder.read().unwrap().as_ref() {
+            recorder.write(&entry);
+        }
+        if handshake {
+            self.handshake.lock().unwrap().push(entry);
+        }
+    }
+}
+
+// 服务器主动发送的请求和通知，以及对服务器请求的响应，只在录制中时写入
+impl MessageObserver for RecorderSlot {
+    fn observe(&self, direction: Direction, message: &Value) {
+        if self.recorder.read().unwrap().is_none() {
+            return;
+        }
+        self.record(
+            RecordedMessage {
+                timestamp: Utc::now(),
+                direction,
+                message: Some(redact::redact_value(message)),
+                error: None,
+            },
+            false,
+        );
+    }
+}
+
+/// 可录制的传输句柄
+///
+/// 包装任意传输句柄，开始录制后记录每次发送的消息和对应的响应。
+/// 服务器主动发送的消息不经过句柄，由 SSE 和 Stdio 传输通过 `MessageObserver` 补充。
+#[derive(Clone)]
+pub struct RecordingHandle<H> {
+    inner: H,
+    slot: Arc<RecorderSlot>,
+}
+
+impl<H> RecordingHandle<H> {
+    pub fn new(inner: H, slot: Arc<RecorderSlot>) -> Self {
+        Self { inner, slot }
+    }
+}
+
+#[async_trait]
+impl<H: TransportHandle> TransportHandle for RecordingHandle<H> {
+    async fn send(&self, message: JsonRpcMessage) -> Result<JsonRpcMessage, TransportError> {
+        let method = serde_json::to_value(&message).ok().and_then(|value| {
+            value
+                .get("method")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        });
+        let handshake = method
+            .as_deref()
+            .is_some_and(|method| HANDSHAKE_METHODS.contains(&method));
+        if !handshake && self.slot.recorder.read().unwrap().is_none() {
+            return self.inner.send(message).await;
+        }
+
+        let is_notification = matches!(message, JsonRpcMessage::Notification(_));
+        self.slot.record(
+            RecordedMessage::new(Direction::Outgoing, Ok(&message)),
+            handshake,
+        );
+        let result = self.inner.send(message).await;
+        match &result {
+            // 通知没有响应
+            Ok(_) if is_notification => {}
+            Ok(response) => self.slot.record(
+                RecordedMessage::new(Direction::Incoming, Ok(response)),
+                handshake,
+            ),
+            Err(e) => self.slot.record(
+                RecordedMessage::new(Direction::Incoming, Err(e.to_string())),
+                handshake,
+            ),
+        }
+        result
+    }
+}
+
+/// 读取录制文件
+pub fn load_session(path: &Path) -> Result<Vec<RecordedMessage>, String> {
+    let file = File::open(path)
+        .map_err(|e| format!("Failed to open recording {}: {}", path.display(), e))?;
+    BufReader::new(file)
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, Ok(line) if line.trim().is_empty()))
+        .map(|(index, line)| {
+            let line = line.map_err(|e| format!("Failed to read recording: {}", e))?;
+            serde_json::from_str(&line)
+                .map_err(|e| format!("Invalid recording at line {}: {}", index + 1, e))
+        })
+        .collect()
+}
+
+// 一次录制的请求及其响应
+#[derive(Debug)]
+struct Exchange {
+    method: String,
+    // 请求的目标，见 request_target
+    target: Option<String>,
+    // 通知没有响应
+    response: Option<Result<Value, String>>,
+}
+
+/// 回放录制会话的传输
+///
+/// 不连接任何服务器，按方法名和请求目标（工具或提示名称、资源URI）返回录制的响应，
+/// 并把响应ID替换为当前请求的ID。方法和目标都相同的多次请求按录制顺序回放，
+/// 录制中没有对应的请求时调用失败，不会返回其他目标的响应。
+/// 其余参数不参与匹配，录制文件中的参数已经过脱敏处理。
+pub struct ReplayTransport {
+    path: PathBuf,
+}
+
+impl ReplayTransport {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+#[async_trait]
+impl Transport for ReplayTransport {
+    type Handle = ReplayTransportHandle;
+
+    async fn start(&self) -> Result<Self::Handle, TransportError> {
+        let entries = load_session(&self.path).map_err(|e| {
+            error!("[MCP] 无法加载录制会话: {}", e);
+            TransportError::ChannelClosed
+        })?;
+        let exchanges = pair_exchanges(entries);
+        info!(
+            "[MCP] 回放录制会话: {}, 请求数: {}",
+            self.path.display(),
+            exchanges.len()
+        );
+        Ok(ReplayTransportHandle {
+            exchanges: Arc::new(Mutex::new(exchanges)),
+        })
+    }
+
+    async fn close(&self) -> Result<(), TransportError> {
+        // 回放不持有连接，没有需要关闭的资源
+        Ok(())
+    }
+}
+
+/// 回放传输句柄
+#[derive(Clone)]
+pub struct ReplayTransportHandle {
+    exchanges: Arc<Mutex<VecDeque<Exchange>>>,
+}
+
+#[async_trait]
+impl TransportHandle for ReplayTransportHandle {
+    async fn send(&self, message: JsonRpcMessage) -> Result<JsonRpcMessage, TransportError> {
+        let request = serde_json::to_value(&message)?;
+        let method = request
+            .get("method")
+            .and_then(Value::as_str)
+            .ok_or(TransportError::UnsupportedMessage)?;
+
+        let target = request_target(&request);
+
+        let exchange = {
+            let mut exchanges = self.exchanges.lock().unwrap();
+            let index = exchanges
+                .iter()
+                .position(|exchange| exchange.method == method && exchange.target == target);
+            index.and_then(|index| exchanges.remove(index))
+        };
+        let Some(exchange) = exchange else {
+            warn!(
+                "[MCP] 录制会话中没有匹配的请求: {}, 目标: {}",
+                method,
+                target.as_deref().unwrap_or("-")
+            );
+            return Err(TransportError::UnsupportedMessage);
+        };
+        debug!("[MCP] 回放请求: {}", method);
+
+        match exchange.response {
+            None => Ok(JsonRpcMessage::Nil),
+            Some(Ok(mut response)) => {
+                if let (Some(id), Some(object)) = (request.get("id"), response.as_object_mut()) {
+                    object.insert("id".to_string(), id.clone());
+                }
+                Ok(serde_json::from_value(response)?)
+            }
+            Some(Err(e)) => {
+                warn!("[MCP] 回放录制的传输错误: {}, 错误: {}", method, e);
+                Err(TransportError::ChannelClosed)
+            }
+        }
+    }
+}
+
+// 请求的目标：工具或提示名称、资源URI，补全请求取引用的名称或URI
+fn request_target(request: &Value) -> Option<String> {
+    let params = request.get("params")?;
+    let target = params.get("ref").unwrap_or(params);
+    target
+        .get("name")
+        .or_else(|| target.get("uri"))
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
+
+// 按录制顺序把请求和响应配对
+//
+// 录制器在请求返回后才写入响应，并发请求的响应不一定紧跟请求，因此按ID匹配。
+fn pair_exchanges(entries: Vec<RecordedMessage>) -> VecDeque<Exchange> {
+    let mut exchanges: Vec<(Option<Value>, Exchange)> = Vec::new();
+    for entry in entries {
+        match entry.direction {
+            Direction::Outgoing => {
+                let Some(message) = entry.message else {
+                    continue;
+                };
+                let Some(method) = message.get("method").and_then(Value::as_str) else {
+                    continue;
+                };
+                let id = message.get("id").cloned();
+                exchanges.push((
+                    id.clone(),
+                    Exchange {
+                        method: method.to_string(),
+                        target: request_target(&message),
+                        response: None,
+                    },
+                ));
+            }
+            // 服务器主动发送的请求和通知不是响应
+            Direction::Incoming
+                if entry
+                    .message
+                    .as_ref()
+                    .is_some_and(|message| message.get("method").is_some()) => {}
+            Direction::Incoming => {
+                let id = entry
+                    .message
+                    .as_ref()
+                    .and_then(|message| message.get("id"))
+                    .cloned();
+                // 错误没有ID时属于最早一个尚未收到响应的请求
+                let pending = exchanges.iter_mut().find(|(request_id, exchange)| {
+                    request_id.is_some()
+                        && exchange.response.is_none()
+                        && (id.is_none() || *request_id == id)
+                });
+                if let Some((_, exchange)) = pending {
+                    exchange.response = Some(match entry.message {
+                        Some(message) => Ok(message),
+                        None => Err(entry.error.unwrap_or_default()),
+                    });
+                }
+            }
+        }
+    }
+    exchanges
+        .into_iter()
+        .filter(|(id, exchange)| id.is_none() || exchange.response.is_some())
+        .map(|(_, exchange)| exchange)
+        .collect()
+}
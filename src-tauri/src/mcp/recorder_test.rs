@@ -0,0 +1,231 @@
+#[cfg(test)]
+mod tests {
+    use crate::mcp::client::McpClientManager;
+    use crate::mcp::mock_server::{start_sse, MockMcpServer};
+    use crate::mcp::recorder::{load_session, Direction, ReplayTransport};
+    use crate::mcp::types::{
+        FilterRequest, InitializeClientRequest, ToolCallRequest, TransportType,
+    };
+    use mcp_client_fishcode2025::transport::{Transport, TransportHandle};
+    use mcp_core_fishcode2025::protocol::JsonRpcMessage;
+    use serde_json::{json, Value};
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    fn temp_file(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("fishmind-recorder-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join(name)
+    }
+
+    fn client_request(id: &str, transport_type: TransportType) -> InitializeClientRequest {
+        InitializeClientRequest {
+            id: id.to_string(),
+            transport_type,
+            headers: Some(HashMap::new()),
+            timeout_secs: Some(30),
+            client_name: "recorder-test".to_string(),
+            client_version: "1.0.0".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn echo(client_id: &str, text: &str) -> ToolCallRequest {
+        ToolCallRequest {
+            client_id: client_id.to_string(),
+            tool_name: "echo".to_string(),
+            params: json!({ "text": text }),
+        }
+    }
+
+    fn message(value: Value) -> JsonRpcMessage {
+        serde_json::from_value(value).unwrap()
+    }
+
+    // 测试录制的会话可以在没有服务器的情况下回放
+    #[tokio::test]
+    async fn test_record_and_replay() {
+        let path = temp_file("record-and-replay.jsonl");
+        let mut manager = McpClientManager::new();
+        let mut request = client_request("live", TransportType::SSE);
+        request.sse_url = Some(
+            start_sse(MockMcpServer::new(), "127.0.0.1:0")
+                .await
+                .unwrap(),
+        );
+        manager.initialize_client(request).await.unwrap();
+
+        // 录制开始前的调用不写入文件
+        manager.call_tool(echo("live", "before")).await.unwrap();
+        let recorded = manager
+            .start_recording("live", Some(path.display().to_string()))
+            .unwrap();
+        assert_eq!(recorded, path.display().to_string());
+        manager
+            .list_tools(FilterRequest {
+                client_id: "live".to_string(),
+                filter: None,
+                cursor: None,
+            })
+            .await
+            .unwrap();
+        manager.call_tool(echo("live", "first")).await.unwrap();
+        manager.call_tool(echo("live", "second")).await.unwrap();
+        // 服务器主动发送的通知也写入录制文件
+        manager
+            .call_tool(ToolCallRequest {
+                client_id: "live".to_string(),
+                tool_name: "list_changed".to_string(),
+                params: json!({}),
+            })
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(
+            manager.stop_recording("live").unwrap(),
+            Some(path.display().to_string())
+        );
+        assert_eq!(manager.stop_recording("live").unwrap(), None);
+
+        // 握手消息在录制开始时补写，之后按时间顺序记录
+        let entries = load_session(&path).unwrap();
+        let methods: Vec<&str> = entries
+            .iter()
+            .filter(|entry| entry.direction == Direction::Outgoing)
+            .filter_map(|entry| entry.message.as_ref()?.get("method")?.as_str())
+            .collect();
+        assert_eq!(
+            methods,
+            vec![
+                "initialize",
+                "notifications/initialized",
+                "tools/list",
+                "tools/call",
+                "tools/call",
+                "tools/call"
+            ]
+        );
+        assert!(entries
+            .iter()
+            .any(|entry| entry.direction == Direction::Incoming
+                && entry.message.as_ref().unwrap()["method"]
+                    == "notifications/tools/list_changed"));
+        assert!(!entries.iter().any(|entry| entry
+            .message
+            .as_ref()
+            .unwrap()
+            .to_string()
+            .contains(r#""text":"before""#)));
+        assert!(entries
+            .windows(2)
+            .all(|pair| pair[0].timestamp <= pair[1].timestamp));
+
+        let mut replay = client_request("replay", TransportType::Replay);
+        replay.replay_path = Some(path.display().to_string());
+        let status = manager.initialize_client(replay).await.unwrap();
+        assert!(status.server_info.is_some());
+
+        let tools = manager
+            .list_tools(FilterRequest {
+                client_id: "replay".to_string(),
+                filter: None,
+                cursor: None,
+            })
+            .await
+            .unwrap();
+        assert!(tools.data.unwrap().iter().any(|tool| tool.name == "echo"));
+
+        // 同一工具的请求按录制顺序回放，与本次调用的其余参数无关
+        for expected in ["first", "second"] {
+            let result = manager.call_tool(echo("replay", "ignored")).await.unwrap();
+            assert!(result.success, "replay failed: {:?}", result.error);
+            assert_eq!(result.data.unwrap()["content"][0]["text"], expected);
+        }
+
+        // 录制中没有更多的响应
+        let exhausted = manager.call_tool(echo("replay", "third")).await.unwrap();
+        assert!(!exhausted.success);
+
+        assert!(manager.start_recording("replay", None).is_err());
+    }
+
+    // 测试响应乱序写入时按ID配对，并替换为当前请求的ID
+    #[tokio::test]
+    async fn test_replay_pairs_by_id() {
+        let path = temp_file("out-of-order.jsonl");
+        let lines = [
+            json!({ "timestamp": "2024-01-01T00:00:00Z", "direction": "outgoing",
+                "message": { "jsonrpc": "2.0", "id": 1, "method": "tools/call", "params": { "name": "slow" } } }),
+            json!({ "timestamp": "2024-01-01T00:00:01Z", "direction": "outgoing",
+                "message": { "jsonrpc": "2.0", "id": 2, "method": "resources/list", "params": {} } }),
+            json!({ "timestamp": "2024-01-01T00:00:02Z", "direction": "incoming",
+                "message": { "jsonrpc": "2.0", "id": 2, "result": { "resources": [] } } }),
+            json!({ "timestamp": "2024-01-01T00:00:03Z", "direction": "incoming",
+                "message": { "jsonrpc": "2.0", "id": 1, "result": { "content": [] } } }),
+            json!({ "timestamp": "2024-01-01T00:00:04Z", "direction": "outgoing",
+                "message": { "jsonrpc": "2.0", "id": 3, "method": "prompts/list", "params": {} } }),
+            json!({ "timestamp": "2024-01-01T00:00:05Z", "direction": "incoming",
+                "error": "Channel closed" }),
+        ];
+        let content: Vec<String> = lines.iter().map(Value::to_string).collect();
+        std::fs::write(&path, content.join("\n")).unwrap();
+
+        let handle = ReplayTransport::new(&path).start().await.unwrap();
+
+        let resources = handle
+            .send(message(
+                json!({ "jsonrpc": "2.0", "id": 7, "method": "resources/list", "params": {} }),
+            ))
+            .await
+            .unwrap();
+        let resources = serde_json::to_value(resources).unwrap();
+        assert_eq!(resources["id"], 7);
+        assert_eq!(resources["result"], json!({ "resources": [] }));
+
+        // 工具名称与录制不一致时失败，不会消耗录制的响应
+        assert!(handle
+            .send(message(
+                json!({ "jsonrpc": "2.0", "id": 8, "method": "tools/call", "params": { "name": "fast" } })
+            ))
+            .await
+            .is_err());
+        let call = handle
+            .send(message(
+                json!({ "jsonrpc": "2.0", "id": 8, "method": "tools/call", "params": { "name": "slow" } }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(call).unwrap()["result"],
+            json!({ "content": [] })
+        );
+
+        // 录制的传输错误原样回放为错误
+        assert!(handle
+            .send(message(
+                json!({ "jsonrpc": "2.0", "id": 9, "method": "prompts/list", "params": {} })
+            ))
+            .await
+            .is_err());
+        assert!(handle
+            .send(message(
+                json!({ "jsonrpc": "2.0", "id": 10, "method": "tools/call", "params": { "name": "slow" } })
+            ))
+            .await
+            .is_err());
+    }
+
+    // 测试无效的录制文件报告行号
+    #[test]
+    fn test_load_invalid_session() {
+        let path = temp_file("invalid.jsonl");
+        std::fs::write(
+            &path,
+            "\n{\"timestamp\":\"2024-01-01T00:00:00Z\",\"direction\":\"incoming\"}\nnot json\n",
+        )
+        .unwrap();
+        let err = load_session(&path).unwrap_err();
+        assert!(err.contains("line 3"), "unexpected error: {}", err);
+    }
+}
@@ -0,0 +1,191 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::{OnceLock, RwLock};
+
+/// 替换敏感值使用的占位符
+pub const REDACTED: &str = "***";
+
+// 总是视为敏感的请求头
+const SENSITIVE_HEADERS: &[&str] = &[
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "set-cookie",
+    "x-api-key",
+];
+
+// 视为敏感的环境变量/参数名后缀
+const SENSITIVE_SUFFIXES: &[&str] = &["_KEY", "_TOKEN", "_SECRET", "_PASSWORD"];
+
+/// 脱敏配置
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RedactionConfig {
+    /// 工具参数中需要脱敏的路径，以 `.` 分隔，`*` 匹配任意键或数组下标，例如 `auth.token`
+    pub argument_paths: Vec<String>,
+    /// 额外视为敏感的键名（不区分大小写）
+    pub extra_keys: Vec<String>,
+}
+
+fn config() -> &'static RwLock<RedactionConfig> {
+    static CONFIG: OnceLock<RwLock<RedactionConfig>> = OnceLock::new();
+    CONFIG.get_or_init(|| RwLock::new(RedactionConfig::default()))
+}
+
+/// 获取当前脱敏配置
+pub fn get_config() -> RedactionConfig {
+    config().read().unwrap().clone()
+}
+
+/// 更新脱敏配置
+pub fn set_config(new_config: RedactionConfig) {
+    *config().write().unwrap() = new_config;
+}
+
+// 已解析的密钥值，出现在任何日志文本中时都会被替换
+fn secret_values() -> &'static RwLock<HashSet<String>> {
+    static VALUES: OnceLock<RwLock<HashSet<String>>> = OnceLock::new();
+    VALUES.get_or_init(|| RwLock::new(HashSet::new()))
+}
+
+/// 登记一个敏感值，之后的日志和错误信息中出现该值时都会被脱敏
+///
+/// 过短的值容易误伤正常文本，不做登记。
+pub fn register_secret(value: &str) {
+    if value.len() >= 4 {
+        secret_values().write().unwrap().insert(value.to_string());
+    }
+}
+
+/// 判断键名（请求头或环境变量）是否敏感
+pub fn is_sensitive_key(key: &str) -> bool {
+    is_sensitive_key_with(&config().read().unwrap(), key)
+}
+
+fn is_sensitive_key_with(config: &RedactionConfig, key: &str) -> bool {
+    let lower = key.to_ascii_lowercase();
+    let upper = key.to_ascii_uppercase();
+    SENSITIVE_HEADERS.contains(&lower.as_str())
+        || SENSITIVE_SUFFIXES.iter().any(|s| upper.ends_with(s))
+        || matches!(upper.as_str(), "KEY" | "TOKEN" | "SECRET" | "PASSWORD")
+        || config
+            .extra_keys
+            .iter()
+            .any(|k| k.eq_ignore_ascii_case(key))
+}
+
+/// 对请求头或环境变量映射脱敏
+pub fn redact_map(map: &HashMap<String, String>) -> HashMap<String, String> {
+    map.iter()
+        .map(|(k, v)| {
+            let value = if is_sensitive_key(k) {
+                REDACTED.to_string()
+            } else {
+                v.clone()
+            };
+            (k.clone(), value)
+        })
+        .collect()
+}
+
+/// 对可选映射脱敏，便于直接用于日志
+pub fn redact_opt_map(map: &Option<HashMap<String, String>>) -> Option<HashMap<String, String>> {
+    map.as_ref().map(redact_map)
+}
+
+/// 对 JSON 值脱敏：敏感键名和配置的路径都会被替换
+pub fn redact_value(value: &serde_json::Value) -> serde_json::Value {
+    redact_value_with(&get_config(), value)
+}
+
+/// 使用指定的配置对 JSON 值脱敏
+pub fn redact_value_with(config: &RedactionConfig, value: &serde_json::Value) -> serde_json::Value {
+    let mut redacted = redact_keys(config, value);
+    for path in &config.argument_paths {
+        let segments: Vec<&str> = path.split('.').filter(|s| !s.is_empty()).collect();
+        redact_path(&mut redacted, &segments);
+    }
+    redacted
+}
+
+fn redact_keys(config: &RedactionConfig, value: &serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => serde_json::Value::Object(
+            map.iter()
+                .map(|(k, v)| {
+                    let v = if is_sensitive_key_with(config, k) && !v.is_object() && !v.is_array() {
+                        serde_json::Value::String(REDACTED.to_string())
+                    } else {
+                        redact_keys(config, v)
+                    };
+                    (k.clone(), v)
+                })
+                .collect(),
+        ),
+        serde_json::Value::Array(items) => {
+            serde_json::Value::Array(items.iter().map(|item| redact_keys(config, item)).collect())
+        }
+        other => other.clone(),
+    }
+}
+
+fn redact_path(value: &mut serde_json::Value, segments: &[&str]) {
+    let Some((first, rest)) = segments.split_first() else {
+        *value = serde_json::Value::String(REDACTED.to_string());
+        return;
+    };
+
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, child) in map.iter_mut() {
+                if *first == "*" || key == first {
+                    redact_path(child, rest);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for (index, child) in items.iter_mut().enumerate() {
+                if *first == "*" || first.parse::<usize>() == Ok(index) {
+                    redact_path(child, rest);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// 对任意文本脱敏，用于日志行和错误信息
+///
+/// 识别 `Bearer` 令牌、Debug/JSON 格式中的敏感键值对以及 `key=value` 形式。
+pub fn redact_text(text: &str) -> String {
+    static PATTERNS: OnceLock<Vec<(Regex, &'static str)>> = OnceLock::new();
+    let patterns = PATTERNS.get_or_init(|| {
+        let keys = r"[A-Za-z0-9_\-]*(?:_KEY|_TOKEN|_SECRET|_PASSWORD|_key|_token|_secret|_password)|(?i:authorization|proxy-authorization|cookie|set-cookie|x-api-key|api_key|apikey|password|secret|token)";
+        vec![
+            (
+                Regex::new(r"(?i)(bearer|basic)\s+[A-Za-z0-9\-._~+/=]+").unwrap(),
+                "$1 ***",
+            ),
+            (
+                Regex::new(&format!(r#""({})"(\s*[:=]\s*)"[^"]*""#, keys)).unwrap(),
+                r#""$1"$2"***""#,
+            ),
+            (
+                Regex::new(&format!(r"\b({})=([^\s&,;]+)", keys)).unwrap(),
+                "$1=***",
+            ),
+        ]
+    });
+
+    let mut result = text.to_string();
+    for value in secret_values().read().unwrap().iter() {
+        if result.contains(value.as_str()) {
+            result = result.replace(value.as_str(), REDACTED);
+        }
+    }
+    for (regex, replacement) in patterns {
+        result = regex.replace_all(&result, *replacement).into_owned();
+    }
+    result
+}
@@ -0,0 +1,79 @@
+#[cfg(test)]
+mod tests {
+    use crate::mcp::redact::{
+        is_sensitive_key, redact_map, redact_text, redact_value, redact_value_with,
+        RedactionConfig, REDACTED,
+    };
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    // 测试敏感请求头和环境变量识别
+    #[test]
+    fn test_is_sensitive_key() {
+        assert!(is_sensitive_key("Authorization"));
+        assert!(is_sensitive_key("cookie"));
+        assert!(is_sensitive_key("OPENAI_API_KEY"));
+        assert!(is_sensitive_key("github_token"));
+        assert!(is_sensitive_key("DB_SECRET"));
+        assert!(!is_sensitive_key("PATH"));
+        assert!(!is_sensitive_key("Content-Type"));
+    }
+
+    // 测试映射脱敏
+    #[test]
+    fn test_redact_map() {
+        let mut headers = HashMap::new();
+        headers.insert("Authorization".to_string(), "Bearer abc".to_string());
+        headers.insert("Accept".to_string(), "text/event-stream".to_string());
+
+        let redacted = redact_map(&headers);
+        assert_eq!(redacted["Authorization"], REDACTED);
+        assert_eq!(redacted["Accept"], "text/event-stream");
+    }
+
+    // 测试参数按键名和配置路径脱敏
+    //
+    // 显式传入配置，不修改全局配置，避免影响并行执行的其他测试
+    #[test]
+    fn test_redact_value() {
+        let config = RedactionConfig {
+            argument_paths: vec!["connection.dsn".to_string(), "items.*.pin".to_string()],
+            extra_keys: vec!["account".to_string()],
+        };
+
+        let value = json!({
+            "query": "select 1",
+            "api_token": "t-123",
+            "connection": { "dsn": "postgres://user:pw@host/db", "pool": 4 },
+            "items": [{ "pin": "1234" }, { "pin": "5678" }],
+            "Account": "alice"
+        });
+        let redacted = redact_value_with(&config, &value);
+
+        assert_eq!(redacted["query"], "select 1");
+        assert_eq!(redacted["api_token"], REDACTED);
+        assert_eq!(redacted["connection"]["dsn"], REDACTED);
+        assert_eq!(redacted["connection"]["pool"], 4);
+        assert_eq!(redacted["items"][0]["pin"], REDACTED);
+        assert_eq!(redacted["items"][1]["pin"], REDACTED);
+        assert_eq!(redacted["Account"], REDACTED);
+
+        // 默认配置只按键名脱敏
+        let redacted = redact_value(&value);
+        assert_eq!(redacted["api_token"], REDACTED);
+        assert_eq!(redacted["connection"]["dsn"], "postgres://user:pw@host/db");
+    }
+
+    // 测试文本脱敏
+    #[test]
+    fn test_redact_text() {
+        let line = r#"headers: {"Authorization": "Bearer abc.def", "GITHUB_TOKEN": "ghp_x"} url=https://x?api_key=123&q=1"#;
+        let redacted = redact_text(line);
+
+        assert!(!redacted.contains("abc.def"));
+        assert!(!redacted.contains("ghp_x"));
+        assert!(!redacted.contains("123&"));
+        assert!(redacted.contains("q=1"));
+        assert!(redacted.contains(r#""GITHUB_TOKEN": "***""#));
+    }
+}
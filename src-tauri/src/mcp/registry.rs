@@ -0,0 +1,162 @@
+use crate::mcp::types::{InitializeClientRequest, McpServerConfig};
+use chrono::Utc;
+use log::{error, info};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// 持久化的 MCP 服务器配置注册表
+///
+/// 配置以 JSON 保存在应用数据目录下，未指定路径时只保存在内存中。
+pub struct ServerRegistry {
+    path: Option<PathBuf>,
+    servers: Mutex<Vec<McpServerConfig>>,
+}
+
+impl ServerRegistry {
+    /// 创建仅保存在内存中的注册表
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            servers: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// 从文件加载注册表，文件不存在时创建空注册表
+    ///
+    /// 文件无法解析时移到一旁保留，以空注册表继续使用原路径，
+    /// 之后的修改仍然会保存到磁盘。
+    pub fn open(path: PathBuf) -> Result<Self, String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create config directory: {}", e))?;
+        }
+
+        let servers = if path.exists() {
+            let content = fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read server registry: {}", e))?;
+            match serde_json::from_str(&content) {
+                Ok(servers) => servers,
+                Err(e) => {
+                    let moved_to = move_aside(&path)?;
+                    error!(
+                        "[MCP] 服务器配置无法解析: {}, 原文件已移至: {}",
+                        e,
+                        moved_to.display()
+                    );
+                    Vec::new()
+                }
+            }
+        } else {
+            Vec::new()
+        };
+        info!(
+            "[MCP] 加载服务器配置: {}, 数量: {}",
+            path.display(),
+            servers.len()
+        );
+
+        Ok(Self {
+            path: Some(path),
+            servers: Mutex::new(servers),
+        })
+    }
+
+    /// 列出所有服务器配置
+    pub fn list(&self) -> Vec<McpServerConfig> {
+        self.servers.lock().unwrap().clone()
+    }
+
+    /// 获取服务器配置
+    pub fn get(&self, id: &str) -> Option<McpServerConfig> {
+        self.servers
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|server| server.request.id == id)
+            .cloned()
+    }
+
+    /// 获取所有启用的服务器的初始化请求
+    pub fn enabled_requests(&self) -> Vec<InitializeClientRequest> {
+        self.servers
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|server| server.enabled)
+            .map(|server| server.request.clone())
+            .collect()
+    }
+
+    /// 新增或更新服务器配置
+    pub fn save(&self, config: McpServerConfig) -> Result<(), String> {
+        if config.request.id.trim().is_empty() {
+            return Err("Server ID cannot be empty".to_string());
+        }
+
+        let mut servers = self.servers.lock().unwrap();
+        match servers
+            .iter_mut()
+            .find(|server| server.request.id == config.request.id)
+        {
+            Some(existing) => *existing = config,
+            None => servers.push(config),
+        }
+        self.persist(&servers)
+    }
+
+    /// 删除服务器配置
+    pub fn delete(&self, id: &str) -> Result<(), String> {
+        let mut servers = self.servers.lock().unwrap();
+        let count = servers.len();
+        servers.retain(|server| server.request.id != id);
+        if servers.len() == count {
+            return Err(format!("Server with ID '{}' not found", id));
+        }
+        self.persist(&servers)
+    }
+
+    /// 启用或禁用服务器的自动连接
+    pub fn set_enabled(&self, id: &str, enabled: bool) -> Result<(), String> {
+        let mut servers = self.servers.lock().unwrap();
+        let server = servers
+            .iter_mut()
+            .find(|server| server.request.id == id)
+            .ok_or_else(|| format!("Server with ID '{}' not found", id))?;
+        server.enabled = enabled;
+        self.persist(&servers)
+    }
+
+    // 先写临时文件再替换，避免写入中断导致配置损坏
+    fn persist(&self, servers: &[McpServerConfig]) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let content = serde_json::to_string_pretty(servers)
+            .map_err(|e| format!("Failed to serialize server registry: {}", e))?;
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, content)
+            .and_then(|_| fs::rename(&tmp_path, path))
+            .map_err(|e| {
+                error!("[MCP] 保存服务器配置失败: {}", e);
+                format!("Failed to save server registry: {}", e)
+            })
+    }
+}
+
+/// 将无法解析的文件重命名为 `<文件名>.corrupt-<时间>`，返回新路径
+pub(crate) fn move_aside(path: &Path) -> Result<PathBuf, String> {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let moved_to = path.with_file_name(format!(
+        "{}.corrupt-{}",
+        file_name,
+        Utc::now().format("%Y%m%d%H%M%S")
+    ));
+    fs::rename(path, &moved_to)
+        .map_err(|e| format!("Failed to move aside {}: {}", path.display(), e))?;
+    Ok(moved_to)
+}
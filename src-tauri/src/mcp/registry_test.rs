@@ -0,0 +1,118 @@
+#[cfg(test)]
+mod tests {
+    use crate::mcp::registry::ServerRegistry;
+    use crate::mcp::types::McpServerConfig;
+    use serde_json::json;
+    use std::path::PathBuf;
+
+    // 为每个测试创建独立的配置文件路径
+    fn temp_registry_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "fishmind-registry-test-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir.join("mcp_servers.json")
+    }
+
+    // 创建 Stdio 服务器配置
+    fn stdio_config(id: &str) -> McpServerConfig {
+        serde_json::from_value(json!({
+            "id": id,
+            "transport_type": "stdio",
+            "command": "npx",
+            "args": ["-y", "@modelcontextprotocol/server-everything"],
+            "client_name": "fishmind",
+            "client_version": "0.1.0"
+        }))
+        .unwrap()
+    }
+
+    // 测试新增、更新和删除
+    #[test]
+    fn test_crud() {
+        let registry = ServerRegistry::in_memory();
+
+        registry.save(stdio_config("a")).unwrap();
+        registry.save(stdio_config("b")).unwrap();
+        assert_eq!(registry.list().len(), 2);
+        assert!(registry.get("a").unwrap().enabled);
+
+        let mut updated = stdio_config("a");
+        updated.name = Some("Server A".to_string());
+        registry.save(updated).unwrap();
+        assert_eq!(registry.list().len(), 2);
+        assert_eq!(registry.get("a").unwrap().name.as_deref(), Some("Server A"));
+
+        registry.delete("a").unwrap();
+        assert!(registry.get("a").is_none());
+        assert!(registry.delete("a").is_err());
+    }
+
+    // 测试只返回启用的服务器
+    #[test]
+    fn test_enabled_requests() {
+        let registry = ServerRegistry::in_memory();
+        registry.save(stdio_config("a")).unwrap();
+        registry.save(stdio_config("b")).unwrap();
+        registry.set_enabled("b", false).unwrap();
+
+        let requests = registry.enabled_requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].id, "a");
+        assert!(registry.set_enabled("missing", true).is_err());
+    }
+
+    // 测试配置持久化后可以重新加载
+    #[test]
+    fn test_persist_and_reload() {
+        let path = temp_registry_path("reload");
+        {
+            let registry = ServerRegistry::open(path.clone()).unwrap();
+            registry.save(stdio_config("a")).unwrap();
+            registry.set_enabled("a", false).unwrap();
+        }
+
+        let registry = ServerRegistry::open(path).unwrap();
+        let server = registry.get("a").unwrap();
+        assert!(!server.enabled);
+        assert_eq!(server.request.command.as_deref(), Some("npx"));
+    }
+
+    // 测试配置文件损坏时移到一旁，新配置仍然保存到磁盘
+    #[test]
+    fn test_corrupt_file_moved_aside() {
+        let path = temp_registry_path("corrupt");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "{ not json").unwrap();
+
+        let registry = ServerRegistry::open(path.clone()).unwrap();
+        assert!(registry.list().is_empty());
+        registry.save(stdio_config("a")).unwrap();
+
+        let moved: Vec<_> = std::fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                entry
+                    .file_name()
+                    .to_string_lossy()
+                    .starts_with("mcp_servers.json.corrupt-")
+            })
+            .collect();
+        assert_eq!(moved.len(), 1);
+        assert_eq!(
+            std::fs::read_to_string(moved[0].path()).unwrap(),
+            "{ not json"
+        );
+        assert!(ServerRegistry::open(path).unwrap().get("a").is_some());
+    }
+
+    // 测试拒绝空ID
+    #[test]
+    fn test_reject_empty_id() {
+        let registry = ServerRegistry::in_memory();
+        assert!(registry.save(stdio_config(" ")).is_err());
+    }
+}
@@ -0,0 +1,281 @@
+use crate::mcp::types::ResourceContent;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use log::{debug, warn};
+use ring::digest;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// 默认的内联阈值，超过该大小的二进制内容写入缓存文件
+pub const DEFAULT_INLINE_LIMIT: usize = 256 * 1024;
+
+/// 默认的资源大小上限
+pub const DEFAULT_MAX_SIZE: usize = 50 * 1024 * 1024;
+
+/// 默认的缓存目录总大小上限
+pub const DEFAULT_CACHE_MAX_BYTES: u64 = 500 * 1024 * 1024;
+
+/// 默认的缓存文件保留时间（秒）
+pub const DEFAULT_CACHE_MAX_AGE_SECS: u64 = 7 * 24 * 60 * 60;
+
+/// 资源读取限制
+///
+/// 缓存目录由应用决定，前端只能修改其余的限制。
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ResourceLimits {
+    /// 二进制内容内联返回的最大字节数
+    pub inline_limit: usize,
+    /// 单个资源所有内容解码后的最大字节数
+    pub max_size: usize,
+    /// 缓存目录的总大小上限，超出时从最早的文件开始删除
+    pub cache_max_bytes: u64,
+    /// 缓存文件的保留时间（秒），从最后一次读取算起
+    pub cache_max_age_secs: u64,
+    /// 缓存文件目录
+    #[serde(skip)]
+    pub cache_dir: PathBuf,
+}
+
+impl Default for ResourceLimits {
+    fn default() -> Self {
+        Self {
+            inline_limit: DEFAULT_INLINE_LIMIT,
+            max_size: DEFAULT_MAX_SIZE,
+            cache_max_bytes: DEFAULT_CACHE_MAX_BYTES,
+            cache_max_age_secs: DEFAULT_CACHE_MAX_AGE_SECS,
+            cache_dir: std::env::temp_dir().join("fishmind").join("resources"),
+        }
+    }
+}
+
+/// 把 `resources/read` 的结果转换为类型化的资源内容
+///
+/// 服务器未提供 MIME 类型时按 URI 扩展名推断。
+pub fn convert_contents(
+    result: &Value,
+    limits: &ResourceLimits,
+) -> Result<Vec<ResourceContent>, String> {
+    let contents = result
+        .get("contents")
+        .and_then(Value::as_array)
+        .ok_or_else(|| "Invalid resource response: missing 'contents'".to_string())?;
+
+    let mut total = 0;
+    let mut converted = Vec::with_capacity(contents.len());
+    for content in contents {
+        let uri = content
+            .get("uri")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+        let mime_type = content
+            .get("mimeType")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+
+        if let Some(text) = content.get("text").and_then(Value::as_str) {
+            total += text.len();
+            check_size(&uri, total, limits)?;
+            converted.push(ResourceContent::Text {
+                mime_type: mime_type
+                    .or_else(|| guess_mime_type(&uri).map(str::to_string))
+                    .unwrap_or_else(|| "text/plain".to_string()),
+                uri,
+                text: text.to_string(),
+            });
+            continue;
+        }
+
+        let blob = content
+            .get("blob")
+            .and_then(Value::as_str)
+            .ok_or_else(|| format!("Invalid resource content for '{}'", uri))?;
+        // 解码前按 base64 长度估算大小，超大的内容不分配解码缓冲区；填充最多少 2 个字节
+        check_size(&uri, total + (blob.len() / 4 * 3).saturating_sub(2), limits)?;
+        let bytes = BASE64
+            .decode(blob)
+            .map_err(|e| format!("Invalid base64 content for '{}': {}", uri, e))?;
+        total += bytes.len();
+        check_size(&uri, total, limits)?;
+
+        let mime_type = mime_type
+            .or_else(|| guess_mime_type(&uri).map(str::to_string))
+            .unwrap_or_else(|| "application/octet-stream".to_string());
+        let size = bytes.len() as u64;
+        if bytes.len() <= limits.inline_limit {
+            converted.push(ResourceContent::Blob {
+                uri,
+                mime_type,
+                blob: blob.to_string(),
+                size,
+            });
+        } else {
+            let path = write_cache_file(&limits.cache_dir, &bytes, &mime_type)?;
+            debug!("[MCP] 资源内容已写入缓存文件: {}", path.display());
+            prune_cache(limits, Some(&path));
+            converted.push(ResourceContent::File {
+                uri,
+                mime_type,
+                path: path.display().to_string(),
+                size,
+            });
+        }
+    }
+    Ok(converted)
+}
+
+fn check_size(uri: &str, size: usize, limits: &ResourceLimits) -> Result<(), String> {
+    if size > limits.max_size {
+        return Err(format!(
+            "Resource '{}' exceeds the maximum size of {} bytes",
+            uri, limits.max_size
+        ));
+    }
+    Ok(())
+}
+
+// 文件名使用内容摘要，同一内容只写入一次
+fn write_cache_file(dir: &Path, bytes: &[u8], mime_type: &str) -> Result<PathBuf, String> {
+    let hash: String = digest::digest(&digest::SHA256, bytes)
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    let path = dir.join(format!("{}.{}", hash, extension(mime_type)));
+    if path.exists() {
+        // 更新修改时间，清理时按最后一次读取计算
+        if let Err(e) = fs::File::options()
+            .write(true)
+            .open(&path)
+            .and_then(|file| file.set_modified(SystemTime::now()))
+        {
+            debug!(
+                "[MCP] 无法更新缓存文件时间: {}, 错误: {}",
+                path.display(),
+                e
+            );
+        }
+    } else {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create resource cache directory: {}", e))?;
+        fs::write(&path, bytes)
+            .map_err(|e| format!("Failed to write resource cache file: {}", e))?;
+    }
+    Ok(path)
+}
+
+/// 清理资源缓存目录，返回删除的文件数
+///
+/// 先删除超过保留时间的文件，总大小仍超过上限时从最早的文件开始删除。
+/// `keep` 为刚返回给调用方的文件，不会被删除。
+pub fn prune_cache(limits: &ResourceLimits, keep: Option<&Path>) -> usize {
+    let Ok(entries) = fs::read_dir(&limits.cache_dir) else {
+        return 0;
+    };
+    let mut files: Vec<(PathBuf, SystemTime, u64)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok().filter(|m| m.is_file())?;
+            Some((entry.path(), metadata.modified().ok()?, metadata.len()))
+        })
+        .collect();
+    // 最早的文件在前
+    files.sort_by_key(|(_, modified, _)| *modified);
+
+    let max_age = Duration::from_secs(limits.cache_max_age_secs);
+    let now = SystemTime::now();
+    let mut total: u64 = files.iter().map(|(_, _, size)| size).sum();
+    let mut removed = 0;
+    for (path, modified, size) in &files {
+        if keep == Some(path.as_path()) {
+            continue;
+        }
+        let expired = now.duration_since(*modified).is_ok_and(|age| age > max_age);
+        if !expired && total <= limits.cache_max_bytes {
+            continue;
+        }
+        match fs::remove_file(path) {
+            Ok(()) => {
+                total -= size;
+                removed += 1;
+            }
+            Err(e) => warn!("[MCP] 删除缓存文件失败: {}, 错误: {}", path.display(), e),
+        }
+    }
+    if removed > 0 {
+        debug!("[MCP] 清理资源缓存文件: {} 个", removed);
+    }
+    removed
+}
+
+// 常见的扩展名和 MIME 类型
+const MIME_TYPES: [(&str, &str); 14] = [
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("webp", "image/webp"),
+    ("svg", "image/svg+xml"),
+    ("pdf", "application/pdf"),
+    ("json", "application/json"),
+    ("txt", "text/plain"),
+    ("md", "text/markdown"),
+    ("html", "text/html"),
+    ("csv", "text/csv"),
+    ("mp3", "audio/mpeg"),
+    ("wav", "audio/wav"),
+];
+
+/// 按 URI 扩展名推断 MIME 类型
+pub fn guess_mime_type(uri: &str) -> Option<&'static str> {
+    let path = uri.split(['?', '#']).next().unwrap_or_default();
+    let (_, extension) = path.rsplit_once('.')?;
+    let extension = extension.to_ascii_lowercase();
+    MIME_TYPES
+        .iter()
+        .find(|(ext, _)| *ext == extension)
+        .map(|(_, mime_type)| *mime_type)
+}
+
+fn extension(mime_type: &str) -> &'static str {
+    MIME_TYPES
+        .iter()
+        .find(|(_, mime)| *mime == mime_type)
+        .map_or("bin", |(ext, _)| *ext)
+}
+
+/// 把资源内容转换回 MCP 的 `resources/read` 结果，缓存文件重新编码为 base64
+pub fn to_mcp_contents(contents: &[ResourceContent]) -> Result<Value, String> {
+    let contents = contents
+        .iter()
+        .map(|content| {
+            Ok(match content {
+                ResourceContent::Text {
+                    uri,
+                    mime_type,
+                    text,
+                } => json!({ "uri": uri, "mimeType": mime_type, "text": text }),
+                ResourceContent::Blob {
+                    uri,
+                    mime_type,
+                    blob,
+                    ..
+                } => json!({ "uri": uri, "mimeType": mime_type, "blob": blob }),
+                ResourceContent::File {
+                    uri,
+                    mime_type,
+                    path,
+                    ..
+                } => {
+                    let bytes = std::fs::read(path)
+                        .map_err(|e| format!("Failed to read resource cache file: {}", e))?;
+                    json!({ "uri": uri, "mimeType": mime_type, "blob": BASE64.encode(bytes) })
+                }
+            })
+        })
+        .collect::<Result<Vec<Value>, String>>()?;
+    Ok(json!({ "contents": contents }))
+}
@@ -0,0 +1,210 @@
+#[cfg(test)]
+mod tests {
+    use crate::mcp::client::McpClientManager;
+    use crate::mcp::mock_server::{start_sse, MockMcpServer};
+    use crate::mcp::resources::{
+        convert_contents, guess_mime_type, prune_cache, to_mcp_contents, ResourceLimits,
+    };
+    use crate::mcp::types::{
+        InitializeClientRequest, ResourceContent, ResourceReadRequest, TransportType,
+    };
+    use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    fn limits(name: &str) -> ResourceLimits {
+        ResourceLimits {
+            inline_limit: 16,
+            max_size: 64,
+            cache_dir: std::env::temp_dir()
+                .join(format!("fishmind-resources-{}", std::process::id()))
+                .join(name),
+            ..ResourceLimits::default()
+        }
+    }
+
+    // 测试 MIME 类型推断
+    #[test]
+    fn test_guess_mime_type() {
+        assert_eq!(guess_mime_type("file:///a/B.PNG"), Some("image/png"));
+        assert_eq!(
+            guess_mime_type("https://x.test/doc.pdf?v=1#page=2"),
+            Some("application/pdf")
+        );
+        assert_eq!(guess_mime_type("mock://text/hello"), None);
+    }
+
+    // 测试文本、内联二进制和缓存文件三种内容
+    #[test]
+    fn test_convert_contents() {
+        let limits = limits("convert");
+        let small = BASE64.encode([1u8; 8]);
+        let large = BASE64.encode([2u8; 32]);
+        let result = json!({
+            "contents": [
+                { "uri": "mock://notes.md", "text": "# Notes" },
+                { "uri": "mock://icon.png", "blob": small },
+                { "uri": "mock://report", "mimeType": "application/pdf", "blob": large },
+            ],
+        });
+
+        let contents = convert_contents(&result, &limits).unwrap();
+        assert_eq!(
+            contents[0],
+            ResourceContent::Text {
+                uri: "mock://notes.md".to_string(),
+                mime_type: "text/markdown".to_string(),
+                text: "# Notes".to_string(),
+            }
+        );
+        assert_eq!(
+            contents[1],
+            ResourceContent::Blob {
+                uri: "mock://icon.png".to_string(),
+                mime_type: "image/png".to_string(),
+                blob: small,
+                size: 8,
+            }
+        );
+        let ResourceContent::File {
+            mime_type,
+            path,
+            size,
+            ..
+        } = &contents[2]
+        else {
+            panic!("expected file content, got {:?}", contents[2]);
+        };
+        assert_eq!(mime_type, "application/pdf");
+        assert_eq!(*size, 32);
+        assert!(path.ends_with(".pdf"));
+        assert_eq!(std::fs::read(path).unwrap(), vec![2u8; 32]);
+
+        // 网关转发时缓存文件重新编码为 base64
+        let mcp = to_mcp_contents(&contents).unwrap();
+        assert_eq!(mcp["contents"][2]["blob"], large);
+        assert_eq!(mcp["contents"][0]["mimeType"], "text/markdown");
+    }
+
+    // 测试超过大小上限和无效内容
+    #[test]
+    fn test_convert_errors() {
+        let limits = limits("errors");
+        let too_large = json!({
+            "contents": [
+                { "uri": "mock://a", "blob": BASE64.encode([0u8; 40]) },
+                { "uri": "mock://b", "text": "x".repeat(40) },
+            ],
+        });
+        let err = convert_contents(&too_large, &limits).unwrap_err();
+        assert!(err.contains("maximum size"), "unexpected error: {}", err);
+
+        // 超大的 base64 内容在解码前拒绝，即使内容本身无效
+        let oversized = json!({ "contents": [{ "uri": "mock://a", "blob": "!".repeat(100) }] });
+        let err = convert_contents(&oversized, &limits).unwrap_err();
+        assert!(err.contains("maximum size"), "unexpected error: {}", err);
+        // 恰好等于上限的内容不会被误判
+        let exact =
+            json!({ "contents": [{ "uri": "mock://a", "blob": BASE64.encode([0u8; 64]) }] });
+        assert!(convert_contents(&exact, &limits).is_ok());
+
+        let invalid = json!({ "contents": [{ "uri": "mock://a", "blob": "not base64!" }] });
+        assert!(convert_contents(&invalid, &limits).is_err());
+        assert!(convert_contents(&json!({}), &limits).is_err());
+    }
+
+    // 测试缓存目录按保留时间和总大小清理，刚返回的文件不会被删除
+    #[test]
+    fn test_prune_cache() {
+        let limits = ResourceLimits {
+            cache_max_bytes: 64,
+            ..limits("prune")
+        };
+        let _ = std::fs::remove_dir_all(&limits.cache_dir);
+        let blob = |byte: u8| json!({ "contents": [{ "uri": "mock://a", "blob": BASE64.encode([byte; 32]) }] });
+        let path = |contents: Vec<ResourceContent>| match &contents[0] {
+            ResourceContent::File { path, .. } => std::path::PathBuf::from(path),
+            other => panic!("expected file content, got {:?}", other),
+        };
+
+        let first = path(convert_contents(&blob(1), &limits).unwrap());
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        let second = path(convert_contents(&blob(2), &limits).unwrap());
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        // 第三个文件使总大小超过上限，最早的文件被删除
+        let third = path(convert_contents(&blob(3), &limits).unwrap());
+        assert!(!first.exists());
+        assert!(second.exists() && third.exists());
+
+        // 超过保留时间的文件全部删除
+        let expired = ResourceLimits {
+            cache_max_age_secs: 0,
+            ..limits.clone()
+        };
+        std::thread::sleep(std::time::Duration::from_millis(1100));
+        assert_eq!(prune_cache(&expired, Some(&third)), 1);
+        assert!(!second.exists() && third.exists());
+    }
+
+    // 测试通过客户端读取二进制资源
+    #[tokio::test]
+    async fn test_read_binary_resource() {
+        let mut manager = McpClientManager::new();
+        manager.set_resource_cache_dir(limits("client").cache_dir);
+        manager.set_resource_limits(ResourceLimits {
+            inline_limit: 1024,
+            max_size: 4096,
+            ..ResourceLimits::default()
+        });
+        assert_eq!(
+            manager.resource_limits().cache_dir,
+            limits("client").cache_dir
+        );
+        let request = InitializeClientRequest {
+            id: "mock".to_string(),
+            transport_type: TransportType::SSE,
+            sse_url: Some(
+                start_sse(MockMcpServer::new(), "127.0.0.1:0")
+                    .await
+                    .unwrap(),
+            ),
+            headers: Some(HashMap::new()),
+            timeout_secs: Some(30),
+            client_name: "resources-test".to_string(),
+            client_version: "1.0.0".to_string(),
+            ..Default::default()
+        };
+        manager.initialize_client(request).await.unwrap();
+
+        let read = |uri: &str| ResourceReadRequest {
+            client_id: "mock".to_string(),
+            resource_uri: uri.to_string(),
+        };
+
+        let small = manager
+            .read_resource(read("mock://binary/512.png"))
+            .await
+            .unwrap();
+        assert!(small.success, "read failed: {:?}", small.error);
+        assert!(matches!(
+            &small.data.unwrap()[0],
+            ResourceContent::Blob { mime_type, size: 512, .. } if mime_type == "image/png"
+        ));
+
+        let large = manager
+            .read_resource(read("mock://binary/2048.pdf"))
+            .await
+            .unwrap();
+        assert!(matches!(
+            &large.data.unwrap()[0],
+            ResourceContent::File { mime_type, size: 2048, .. } if mime_type == "application/pdf"
+        ));
+
+        let too_large = manager
+            .read_resource(read("mock://binary/8192.bin"))
+            .await
+            .unwrap();
+        assert!(!too_large.success);
+        assert!(too_large.error.unwrap().contains("maximum size"));
+    }
+}
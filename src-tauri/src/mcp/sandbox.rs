@@ -0,0 +1,219 @@
+use crate::mcp::types::SandboxProfile;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// 沙箱启动器参数标记，主进程以该参数重新执行自身
+pub const LAUNCHER_FLAG: &str = "--mcp-sandbox-exec";
+
+/// 传递沙箱规格的环境变量
+pub const SPEC_ENV: &str = "FISHMIND_SANDBOX_SPEC";
+
+/// 包装后的命令、参数和环境变量
+pub type WrappedCommand = (String, Vec<String>, HashMap<String, String>);
+
+/// 启动器实际执行的沙箱规格
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SandboxSpec {
+    pub working_dir: String,
+    pub env_allowlist: Vec<String>,
+    pub explicit_env: Vec<String>,
+    pub max_memory_mb: Option<u64>,
+    pub max_cpu_secs: Option<u64>,
+    pub max_open_files: Option<u64>,
+    pub deny_network: bool,
+}
+
+/// 解析沙箱工作目录，未指定时使用临时目录下的客户端专属目录
+pub fn resolve_working_dir(client_id: &str, profile: &SandboxProfile) -> PathBuf {
+    match &profile.working_dir {
+        Some(dir) => PathBuf::from(dir),
+        None => {
+            let safe_id: String = client_id
+                .chars()
+                .map(|c| {
+                    if c.is_ascii_alphanumeric() || c == '-' {
+                        c
+                    } else {
+                        '_'
+                    }
+                })
+                .collect();
+            std::env::temp_dir().join("fishmind-sandbox").join(safe_id)
+        }
+    }
+}
+
+/// 校验沙箱配置，初始化客户端时调用，避免无效的限制到启动器中才失败
+pub fn validate_profile(profile: &SandboxProfile) -> Result<(), String> {
+    if let Some(mb) = profile.max_memory_mb {
+        memory_limit_bytes(mb)?;
+    }
+    Ok(())
+}
+
+// 内存上限换算为字节，溢出时返回错误
+fn memory_limit_bytes(mb: u64) -> Result<u64, String> {
+    mb.checked_mul(1024 * 1024)
+        .ok_or_else(|| format!("Sandbox memory limit of {} MB is too large", mb))
+}
+
+/// 将 Stdio 命令包装为经由沙箱启动器执行
+///
+/// 返回新的命令、参数和环境变量，可直接传给 `StdioTransport::new`。
+#[cfg(target_os = "linux")]
+pub fn wrap_command(
+    client_id: &str,
+    profile: &SandboxProfile,
+    command: String,
+    args: Vec<String>,
+    mut env_vars: HashMap<String, String>,
+) -> Result<WrappedCommand, String> {
+    let working_dir = resolve_working_dir(client_id, profile);
+    std::fs::create_dir_all(&working_dir).map_err(|e| {
+        format!(
+            "Failed to create sandbox working directory '{}': {}",
+            working_dir.display(),
+            e
+        )
+    })?;
+
+    let spec = SandboxSpec {
+        working_dir: working_dir.to_string_lossy().to_string(),
+        env_allowlist: profile.env_allowlist.clone(),
+        explicit_env: env_vars.keys().cloned().collect(),
+        max_memory_mb: profile.max_memory_mb,
+        max_cpu_secs: profile.max_cpu_secs,
+        max_open_files: profile.max_open_files,
+        deny_network: profile.deny_network,
+    };
+    let spec_json = serde_json::to_string(&spec)
+        .map_err(|e| format!("Failed to serialize sandbox spec: {}", e))?;
+    env_vars.insert(SPEC_ENV.to_string(), spec_json);
+
+    let launcher =
+        std::env::current_exe().map_err(|e| format!("Failed to locate sandbox launcher: {}", e))?;
+
+    info!(
+        "[MCP] 启用沙箱, 客户端ID: {}, 工作目录: {}, 禁止网络: {}",
+        client_id, spec.working_dir, spec.deny_network
+    );
+
+    let mut launcher_args = vec![LAUNCHER_FLAG.to_string(), command];
+    launcher_args.extend(args);
+    Ok((
+        launcher.to_string_lossy().to_string(),
+        launcher_args,
+        env_vars,
+    ))
+}
+
+/// 非 Linux 平台不支持沙箱
+#[cfg(not(target_os = "linux"))]
+pub fn wrap_command(
+    _client_id: &str,
+    _profile: &SandboxProfile,
+    _command: String,
+    _args: Vec<String>,
+    _env_vars: HashMap<String, String>,
+) -> Result<WrappedCommand, String> {
+    Err("Sandbox profiles are only supported on Linux".to_string())
+}
+
+/// 沙箱启动器入口，应用限制后执行目标命令，不会返回
+#[cfg(target_os = "linux")]
+pub fn run_launcher() -> ! {
+    let err = match launch() {
+        Ok(never) => match never {},
+        Err(e) => e,
+    };
+    eprintln!("[MCP Sandbox] {}", err);
+    std::process::exit(126)
+}
+
+#[cfg(target_os = "linux")]
+fn launch() -> Result<std::convert::Infallible, String> {
+    use std::os::unix::process::CommandExt;
+    use std::process::Command;
+
+    let spec: SandboxSpec = serde_json::from_str(
+        &std::env::var(SPEC_ENV).map_err(|_| format!("{} is not set", SPEC_ENV))?,
+    )
+    .map_err(|e| format!("Invalid sandbox spec: {}", e))?;
+
+    let mut args = std::env::args().skip(2);
+    let program = args
+        .next()
+        .ok_or_else(|| "No command given to sandbox launcher".to_string())?;
+
+    // 只保留白名单和显式配置的环境变量
+    let env: HashMap<String, String> = std::env::vars()
+        .filter(|(k, _)| k != SPEC_ENV)
+        .filter(|(k, _)| spec.env_allowlist.contains(k) || spec.explicit_env.contains(k))
+        .collect();
+
+    apply_rlimits(&spec)?;
+    if spec.deny_network {
+        deny_network()?;
+    }
+
+    let err = Command::new(&program)
+        .args(args)
+        .current_dir(&spec.working_dir)
+        .env_clear()
+        .envs(env)
+        .exec();
+    Err(format!("Failed to exec '{}': {}", program, err))
+}
+
+/// 通过 setrlimit 设置资源限制，限制会在 exec 后继承
+#[cfg(target_os = "linux")]
+fn apply_rlimits(spec: &SandboxSpec) -> Result<(), String> {
+    let max_memory = spec.max_memory_mb.map(memory_limit_bytes).transpose()?;
+    let limits = [
+        (libc::RLIMIT_AS, max_memory, "memory"),
+        (libc::RLIMIT_CPU, spec.max_cpu_secs, "cpu time"),
+        (libc::RLIMIT_NOFILE, spec.max_open_files, "open files"),
+    ];
+
+    for (resource, value, name) in limits {
+        if let Some(value) = value {
+            let rlim = libc::rlimit {
+                rlim_cur: value as libc::rlim_t,
+                rlim_max: value as libc::rlim_t,
+            };
+            if unsafe { libc::setrlimit(resource, &rlim) } != 0 {
+                return Err(format!(
+                    "Failed to set {} limit: {}",
+                    name,
+                    std::io::Error::last_os_error()
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 进入新的用户和网络命名空间，新命名空间中只有未启用的回环接口
+#[cfg(target_os = "linux")]
+fn deny_network() -> Result<(), String> {
+    let uid = unsafe { libc::getuid() };
+    let gid = unsafe { libc::getgid() };
+
+    if unsafe { libc::unshare(libc::CLONE_NEWUSER | libc::CLONE_NEWNET) } != 0 {
+        return Err(format!(
+            "Network isolation is unavailable (unshare failed: {})",
+            std::io::Error::last_os_error()
+        ));
+    }
+
+    // 保持原有的 uid/gid 映射，避免进程以 nobody 身份运行
+    let write = |path: &str, content: String| {
+        std::fs::write(path, content).map_err(|e| format!("Failed to write {}: {}", path, e))
+    };
+    write("/proc/self/setgroups", "deny".to_string())?;
+    write("/proc/self/uid_map", format!("{} {} 1", uid, uid))?;
+    write("/proc/self/gid_map", format!("{} {} 1", gid, gid))?;
+    Ok(())
+}
@@ -0,0 +1,100 @@
+#[cfg(test)]
+mod tests {
+    use crate::mcp::sandbox::{
+        resolve_working_dir, validate_profile, wrap_command, SandboxSpec, LAUNCHER_FLAG, SPEC_ENV,
+    };
+    use crate::mcp::types::SandboxProfile;
+    use std::collections::HashMap;
+
+    // 测试未指定工作目录时使用客户端专属临时目录
+    #[test]
+    fn test_resolve_default_working_dir() {
+        let profile = SandboxProfile::default();
+        let dir = resolve_working_dir("my client/1", &profile);
+
+        assert!(dir.starts_with(std::env::temp_dir().join("fishmind-sandbox")));
+        assert!(dir.ends_with("my_client_1"));
+    }
+
+    // 测试指定工作目录时直接使用
+    #[test]
+    fn test_resolve_explicit_working_dir() {
+        let profile = SandboxProfile {
+            working_dir: Some("/tmp/explicit".to_string()),
+            ..Default::default()
+        };
+        let dir = resolve_working_dir("client", &profile);
+        assert_eq!(dir.to_string_lossy(), "/tmp/explicit");
+    }
+
+    // 测试换算为字节后溢出的内存上限在初始化时被拒绝
+    #[test]
+    fn test_validate_memory_overflow() {
+        let profile = SandboxProfile {
+            max_memory_mb: Some(u64::MAX / 1024),
+            ..Default::default()
+        };
+        assert!(validate_profile(&profile)
+            .unwrap_err()
+            .contains("too large"));
+
+        let profile = SandboxProfile {
+            max_memory_mb: Some(512),
+            ..Default::default()
+        };
+        assert!(validate_profile(&profile).is_ok());
+    }
+
+    // 测试命令被包装为沙箱启动器调用
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_wrap_command() {
+        let profile = SandboxProfile {
+            env_allowlist: vec!["HOME".to_string()],
+            max_memory_mb: Some(512),
+            deny_network: true,
+            ..Default::default()
+        };
+        let mut env = HashMap::new();
+        env.insert("API_TOKEN".to_string(), "secret".to_string());
+
+        let (command, args, env) = wrap_command(
+            "sandbox-test",
+            &profile,
+            "node".to_string(),
+            vec!["server.js".to_string()],
+            env,
+        )
+        .expect("wrap_command should succeed");
+
+        assert_eq!(
+            command,
+            std::env::current_exe()
+                .unwrap()
+                .to_string_lossy()
+                .to_string()
+        );
+        assert_eq!(args, vec![LAUNCHER_FLAG, "node", "server.js"]);
+
+        let spec: SandboxSpec = serde_json::from_str(env.get(SPEC_ENV).unwrap()).unwrap();
+        assert_eq!(spec.env_allowlist, vec!["HOME"]);
+        assert_eq!(spec.explicit_env, vec!["API_TOKEN"]);
+        assert_eq!(spec.max_memory_mb, Some(512));
+        assert!(spec.deny_network);
+        assert!(std::path::Path::new(&spec.working_dir).is_dir());
+    }
+
+    // 测试非 Linux 平台拒绝沙箱配置
+    #[cfg(not(target_os = "linux"))]
+    #[test]
+    fn test_wrap_command_unsupported() {
+        let result = wrap_command(
+            "sandbox-test",
+            &SandboxProfile::default(),
+            "node".to_string(),
+            vec![],
+            HashMap::new(),
+        );
+        assert!(result.is_err());
+    }
+}
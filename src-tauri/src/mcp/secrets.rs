@@ -0,0 +1,231 @@
+use crate::encryption::KeyStore;
+use crate::mcp::registry::move_aside;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use log::{error, info, warn};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+// 数据密钥长度，旧版本以此长度的明文保存
+const DATA_KEY_LEN: usize = 32;
+
+// 加密后的密钥值
+#[derive(Clone, Serialize, Deserialize)]
+struct EncryptedSecret {
+    nonce: String,
+    ciphertext: String,
+}
+
+/// 加密保存的密钥存储
+///
+/// 使用 AES-256-GCM 加密，每个值使用随机 nonce，并以名称作为附加认证数据，
+/// 防止密文在不同名称之间被替换。数据密钥由系统钥匙串中的主密钥加密后保存在同目录下，
+/// 单独拷走数据目录无法解密。
+pub struct SecretStore {
+    path: Option<PathBuf>,
+    key: LessSafeKey,
+    secrets: Mutex<HashMap<String, EncryptedSecret>>,
+}
+
+impl SecretStore {
+    /// 创建仅保存在内存中的密钥存储
+    pub fn in_memory() -> Self {
+        let key = generate_key().expect("failed to generate secret store key");
+        Self {
+            path: None,
+            key: build_key(&key).expect("failed to build secret store key"),
+            secrets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 打开目录下的密钥存储，不存在时创建
+    ///
+    /// 旧版本以明文保存的数据密钥会在打开时改为加密保存。主密钥变化导致数据密钥无法解密，
+    /// 或存储文件无法解析时，原文件移到一旁保留，以空存储继续使用原路径。
+    pub fn open(dir: &Path, key_store: &KeyStore) -> Result<Self, String> {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create secret store directory: {}", e))?;
+
+        let key_path = dir.join("mcp_secrets.key");
+        let path = dir.join("mcp_secrets.json");
+        let key = match fs::read(&key_path) {
+            Ok(stored) if stored.len() == DATA_KEY_LEN => {
+                info!("[MCP] 将明文保存的密钥存储数据密钥改为加密保存");
+                write_private(&key_path, &key_store.wrap_key(&stored)?)?;
+                stored
+            }
+            Ok(stored) => match key_store.unwrap_key(&stored) {
+                Ok(key) => key,
+                Err(e) => {
+                    error!("[MCP] 无法解密密钥存储的数据密钥: {}", e);
+                    for file in [&key_path, &path] {
+                        if file.exists() {
+                            let moved_to = move_aside(file)?;
+                            warn!("[MCP] 密钥存储文件已移至: {}", moved_to.display());
+                        }
+                    }
+                    new_data_key(&key_path, key_store)?
+                }
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                new_data_key(&key_path, key_store)?
+            }
+            Err(e) => return Err(format!("Failed to read secret store key: {}", e)),
+        };
+
+        let secrets = if path.exists() {
+            let content = fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read secret store: {}", e))?;
+            match serde_json::from_str(&content) {
+                Ok(secrets) => secrets,
+                Err(e) => {
+                    let moved_to = move_aside(&path)?;
+                    error!(
+                        "[MCP] 密钥存储无法解析: {}, 原文件已移至: {}",
+                        e,
+                        moved_to.display()
+                    );
+                    HashMap::new()
+                }
+            }
+        } else {
+            HashMap::new()
+        };
+        info!("[MCP] 打开密钥存储: {}", path.display());
+
+        Ok(Self {
+            path: Some(path),
+            key: build_key(&key)?,
+            secrets: Mutex::new(secrets),
+        })
+    }
+
+    /// 保存密钥值
+    pub fn set(&self, name: &str, value: &str) -> Result<(), String> {
+        if name.trim().is_empty() {
+            return Err("Secret name cannot be empty".to_string());
+        }
+
+        let mut nonce = [0u8; NONCE_LEN];
+        SystemRandom::new()
+            .fill(&mut nonce)
+            .map_err(|_| "Failed to generate nonce".to_string())?;
+
+        let mut in_out = value.as_bytes().to_vec();
+        self.key
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(name.as_bytes()),
+                &mut in_out,
+            )
+            .map_err(|_| format!("Failed to encrypt secret '{}'", name))?;
+
+        let mut secrets = self.secrets.lock().unwrap();
+        secrets.insert(
+            name.to_string(),
+            EncryptedSecret {
+                nonce: BASE64.encode(nonce),
+                ciphertext: BASE64.encode(&in_out),
+            },
+        );
+        self.persist(&secrets)
+    }
+
+    /// 读取密钥值，不存在时返回 None
+    pub fn get(&self, name: &str) -> Result<Option<String>, String> {
+        let Some(secret) = self.secrets.lock().unwrap().get(name).cloned() else {
+            return Ok(None);
+        };
+
+        let decode_error = |_| format!("Secret '{}' is corrupted", name);
+        let nonce: [u8; NONCE_LEN] = BASE64
+            .decode(&secret.nonce)
+            .map_err(decode_error)?
+            .try_into()
+            .map_err(|_| format!("Secret '{}' is corrupted", name))?;
+        let mut in_out = BASE64.decode(&secret.ciphertext).map_err(decode_error)?;
+
+        let plaintext = self
+            .key
+            .open_in_place(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(name.as_bytes()),
+                &mut in_out,
+            )
+            .map_err(|_| format!("Failed to decrypt secret '{}'", name))?;
+        String::from_utf8(plaintext.to_vec())
+            .map(Some)
+            .map_err(|_| format!("Secret '{}' is not valid UTF-8", name))
+    }
+
+    /// 删除密钥值，返回是否存在
+    pub fn delete(&self, name: &str) -> Result<bool, String> {
+        let mut secrets = self.secrets.lock().unwrap();
+        if secrets.remove(name).is_none() {
+            return Ok(false);
+        }
+        self.persist(&secrets)?;
+        Ok(true)
+    }
+
+    /// 列出所有密钥名称
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.secrets.lock().unwrap().keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    fn persist(&self, secrets: &HashMap<String, EncryptedSecret>) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let content = serde_json::to_string_pretty(secrets)
+            .map_err(|e| format!("Failed to serialize secret store: {}", e))?;
+        write_private(path, content.as_bytes()).map_err(|e| {
+            error!("[MCP] 保存密钥存储失败: {}", e);
+            e
+        })
+    }
+}
+
+// 生成新的数据密钥，加密后保存
+fn new_data_key(key_path: &Path, key_store: &KeyStore) -> Result<Vec<u8>, String> {
+    let key = generate_key()?;
+    write_private(key_path, &key_store.wrap_key(&key)?)?;
+    Ok(key)
+}
+
+fn generate_key() -> Result<Vec<u8>, String> {
+    let mut key = vec![0u8; DATA_KEY_LEN]; // AES-256 需要 32 字节密钥
+    SystemRandom::new()
+        .fill(&mut key)
+        .map_err(|_| "Failed to generate secret store key".to_string())?;
+    Ok(key)
+}
+
+fn build_key(key: &[u8]) -> Result<LessSafeKey, String> {
+    UnboundKey::new(&AES_256_GCM, key)
+        .map(LessSafeKey::new)
+        .map_err(|_| "Invalid secret store key".to_string())
+}
+
+// 写入仅当前用户可读写的文件，先写临时文件再替换
+fn write_private(path: &Path, content: &[u8]) -> Result<(), String> {
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, content)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&tmp_path, fs::Permissions::from_mode(0o600))
+            .map_err(|e| format!("Failed to set permissions on {}: {}", path.display(), e))?;
+    }
+
+    fs::rename(&tmp_path, path).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
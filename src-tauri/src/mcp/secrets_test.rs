@@ -0,0 +1,115 @@
+#[cfg(test)]
+mod tests {
+    use crate::encryption::KeyStore;
+    use crate::mcp::secrets::SecretStore;
+    use std::path::PathBuf;
+
+    // 为每个测试创建独立的目录
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "fishmind-secrets-test-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn key_store() -> KeyStore {
+        KeyStore::with_master_key(vec![7; 32])
+    }
+
+    // 测试保存、读取和删除
+    #[test]
+    fn test_set_get_delete() {
+        let store = SecretStore::in_memory();
+        store.set("github", "ghp_secret").unwrap();
+
+        assert_eq!(store.get("github").unwrap().as_deref(), Some("ghp_secret"));
+        assert_eq!(store.get("missing").unwrap(), None);
+        assert_eq!(store.names(), vec!["github".to_string()]);
+
+        assert!(store.delete("github").unwrap());
+        assert!(!store.delete("github").unwrap());
+        assert_eq!(store.get("github").unwrap(), None);
+    }
+
+    // 测试文件中不包含明文，重新打开后可以解密
+    #[test]
+    fn test_encrypted_at_rest() {
+        let dir = temp_dir("at-rest");
+        let key_store = key_store();
+        {
+            let store = SecretStore::open(&dir, &key_store).unwrap();
+            store.set("db_password", "hunter2-hunter2").unwrap();
+        }
+
+        let content = std::fs::read_to_string(dir.join("mcp_secrets.json")).unwrap();
+        assert!(content.contains("db_password"));
+        assert!(!content.contains("hunter2"));
+
+        let store = SecretStore::open(&dir, &key_store).unwrap();
+        assert_eq!(
+            store.get("db_password").unwrap().as_deref(),
+            Some("hunter2-hunter2")
+        );
+    }
+
+    // 测试数据密钥只以加密形式保存，旧版本的明文数据密钥在打开时改为加密保存
+    #[test]
+    fn test_data_key_wrapped() {
+        let dir = temp_dir("wrapped");
+        let key_store = key_store();
+        {
+            let store = SecretStore::open(&dir, &key_store).unwrap();
+            store.set("token", "abc").unwrap();
+        }
+        let wrapped = std::fs::read(dir.join("mcp_secrets.key")).unwrap();
+        let data_key = key_store.unwrap_key(&wrapped).unwrap();
+        assert_eq!(data_key.len(), 32);
+        assert_ne!(wrapped, data_key);
+
+        // 模拟旧版本的明文数据密钥
+        std::fs::write(dir.join("mcp_secrets.key"), &data_key).unwrap();
+        let store = SecretStore::open(&dir, &key_store).unwrap();
+        assert_eq!(store.get("token").unwrap().as_deref(), Some("abc"));
+        let rewrapped = std::fs::read(dir.join("mcp_secrets.key")).unwrap();
+        assert_eq!(key_store.unwrap_key(&rewrapped).unwrap(), data_key);
+    }
+
+    // 测试主密钥变化或存储文件损坏时原文件移到一旁，新存储仍保存到磁盘
+    #[test]
+    fn test_unreadable_store_moved_aside() {
+        let dir = temp_dir("moved-aside");
+        {
+            let store = SecretStore::open(&dir, &key_store()).unwrap();
+            store.set("token", "abc").unwrap();
+        }
+
+        let other_key_store = KeyStore::with_master_key(vec![8; 32]);
+        let store = SecretStore::open(&dir, &other_key_store).unwrap();
+        assert!(store.names().is_empty());
+        store.set("token", "def").unwrap();
+
+        std::fs::write(dir.join("mcp_secrets.json"), "{ not json").unwrap();
+        let store = SecretStore::open(&dir, &other_key_store).unwrap();
+        assert!(store.names().is_empty());
+        store.set("token", "ghi").unwrap();
+
+        let reopened = SecretStore::open(&dir, &other_key_store).unwrap();
+        assert_eq!(reopened.get("token").unwrap().as_deref(), Some("ghi"));
+        let moved = std::fs::read_dir(&dir)
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().contains(".corrupt-"))
+            .count();
+        assert!(moved >= 2);
+    }
+
+    // 测试拒绝空名称
+    #[test]
+    fn test_reject_empty_name() {
+        let store = SecretStore::in_memory();
+        assert!(store.set("", "value").is_err());
+    }
+}
@@ -0,0 +1,482 @@
+use crate::mcp::client::AppState;
+use crate::mcp::history::{HistoryStore, MAX_LIMIT};
+use crate::mcp::redact;
+use crate::mcp::server_transport::{
+    self, rpc_response, McpMessageHandler, RpcResult, SseServer, INTERNAL_ERROR, INVALID_PARAMS,
+    METHOD_NOT_FOUND, PROTOCOL_VERSION, RESOURCE_NOT_FOUND,
+};
+use crate::mcp::types::{ClientStatus, FilterRequest, FishmindServerStatus};
+use async_trait::async_trait;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use log::{debug, error, info};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+
+/// 以 MCP 服务器模式启动的命令行参数
+///
+/// `fishmind --mcp-server` 通过 stdio 向其他 MCP 客户端提供 fishmind 的聊天记录、
+/// 话题、助手和工具目录，`--data-dir <目录>` 指定应用数据目录。
+pub const SERVER_FLAG: &str = "--mcp-server";
+
+/// 本机 SSE 服务访问令牌在密钥存储中的名称
+pub const ACCESS_TOKEN_SECRET: &str = "fishmind_mcp_server_token";
+
+const APP_IDENTIFIER: &str = "com.fishmind.app";
+
+const TOPIC_PREFIX: &str = "fishmind://topics/";
+const ASSISTANT_PREFIX: &str = "fishmind://assistants/";
+const CATALOG_URI: &str = "fishmind://mcp/catalog";
+
+/// fishmind 自身的 MCP 服务器
+///
+/// 提供以下只读工具：
+/// - `search_chat_history`：按关键字搜索聊天记录
+/// - `list_topics`：列出最近的话题
+/// - `get_topic_messages`：获取话题中的消息
+/// - `list_assistants`：列出助手
+/// - `list_mcp_tools`：列出已配置的 MCP 服务器及已连接服务器的工具
+///
+/// 以及话题、助手和工具目录资源。
+pub struct FishmindMcpServer {
+    history: Arc<HistoryStore>,
+    state: Arc<AppState>,
+}
+
+impl FishmindMcpServer {
+    pub fn new(state: Arc<AppState>) -> Arc<Self> {
+        Arc::new(Self {
+            history: state.history_store.clone(),
+            state,
+        })
+    }
+
+    async fn call_tool(&self, params: &Value) -> RpcResult {
+        let name = params
+            .get("name")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let arguments = params.get("arguments").cloned().unwrap_or(json!({}));
+        debug!("[MCP] fishmind 服务器调用工具: {}", name);
+
+        let result = match name {
+            "search_chat_history" => {
+                let query = required_str(&arguments, "query")?;
+                self.history
+                    .search_messages(
+                        query,
+                        arguments.get("topic_id").and_then(Value::as_str),
+                        limit_arg(&arguments),
+                    )
+                    .await
+                    .map(|matches| json!(matches))
+            }
+            "list_topics" => self
+                .history
+                .list_topics(limit_arg(&arguments))
+                .await
+                .map(|topics| json!(topics)),
+            "get_topic_messages" => {
+                let topic_id = required_str(&arguments, "topic_id")?;
+                self.history
+                    .topic_messages(topic_id, limit_arg(&arguments))
+                    .await
+                    .map(|messages| json!(messages))
+            }
+            "list_assistants" => self
+                .history
+                .list_assistants()
+                .await
+                .map(|assistants| json!(assistants)),
+            "list_mcp_tools" => Ok(self.catalog().await),
+            other => return Err((INVALID_PARAMS, format!("Unknown tool: {}", other))),
+        };
+
+        // 数据读取失败作为工具错误返回，由调用方的模型处理
+        Ok(match result {
+            Ok(value) => json!({
+                "content": [{ "type": "text", "text": pretty(&value) }],
+                "structuredContent": { "result": value },
+                "isError": false,
+            }),
+            Err(e) => {
+                error!("[MCP] fishmind 服务器工具调用失败: {}, 错误: {}", name, e);
+                json!({ "content": [{ "type": "text", "text": e }], "isError": true })
+            }
+        })
+    }
+
+    /// 已配置的服务器及已连接服务器的工具
+    async fn catalog(&self) -> Value {
+        let manager = self.state.mcp_client_manager.lock().await;
+        let mut servers = Vec::new();
+        for config in self.state.server_registry.list() {
+            let id = config.request.id.clone();
+            let status = manager.get_client_status(&id).ok();
+            let connected = status
+                .as_ref()
+                .map(|status| matches!(status.status, ClientStatus::Connected))
+                .unwrap_or(false);
+
+            let tools = if connected {
+                match manager
+                    .list_tools(FilterRequest {
+                        client_id: id.clone(),
+                        filter: None,
+                        cursor: None,
+                    })
+                    .await
+                {
+                    Ok(response) => json!(response.data.unwrap_or_default()),
+                    Err(_) => json!([]),
+                }
+            } else {
+                json!([])
+            };
+
+            servers.push(json!({
+                "id": id,
+                "name": config.name,
+                "enabled": config.enabled,
+                "transport_type": config.request.transport_type,
+                "status": status.map(|status| status.status),
+                "tools": tools,
+            }));
+        }
+        json!({ "servers": servers })
+    }
+
+    async fn list_resources(&self) -> RpcResult {
+        let mut resources = vec![json!({
+            "uri": CATALOG_URI,
+            "name": "MCP tool catalog",
+            "description": "Configured MCP servers and the tools of connected servers",
+            "mimeType": "application/json",
+        })];
+
+        // 聊天数据库不可用时仍然提供工具目录
+        if let Ok(topics) = self.history.list_topics(Some(MAX_LIMIT)).await {
+            resources.extend(topics.into_iter().map(|topic| {
+                json!({
+                    "uri": format!("{}{}", TOPIC_PREFIX, topic.id),
+                    "name": topic.title,
+                    "description": topic.preview,
+                    "mimeType": "text/markdown",
+                })
+            }));
+        }
+        if let Ok(assistants) = self.history.list_assistants().await {
+            resources.extend(assistants.into_iter().map(|assistant| {
+                json!({
+                    "uri": format!("{}{}", ASSISTANT_PREFIX, assistant.id),
+                    "name": assistant.name,
+                    "description": assistant.description,
+                    "mimeType": "application/json",
+                })
+            }));
+        }
+        Ok(json!({ "resources": resources }))
+    }
+
+    async fn read_resource(&self, params: &Value) -> RpcResult {
+        let uri = required_str(params, "uri")?;
+        let (text, mime_type) = if uri == CATALOG_URI {
+            (pretty(&self.catalog().await), "application/json")
+        } else if let Some(topic_id) = uri.strip_prefix(TOPIC_PREFIX) {
+            (self.topic_transcript(topic_id).await?, "text/markdown")
+        } else if let Some(assistant_id) = uri.strip_prefix(ASSISTANT_PREFIX) {
+            let assistant = self
+                .history
+                .get_assistant(assistant_id)
+                .await
+                .map_err(|e| (RESOURCE_NOT_FOUND, e))?;
+            (pretty(&json!(assistant)), "application/json")
+        } else {
+            return Err((RESOURCE_NOT_FOUND, format!("Resource not found: {}", uri)));
+        };
+
+        Ok(json!({
+            "contents": [{ "uri": uri, "mimeType": mime_type, "text": text }],
+        }))
+    }
+
+    // 以 Markdown 格式输出话题的完整对话
+    async fn topic_transcript(&self, topic_id: &str) -> Result<String, (i64, String)> {
+        let topic = self
+            .history
+            .get_topic(topic_id)
+            .await
+            .map_err(|e| (RESOURCE_NOT_FOUND, e))?;
+        let messages = self
+            .history
+            .topic_messages(topic_id, Some(MAX_LIMIT))
+            .await
+            .map_err(|e| (INTERNAL_ERROR, e))?;
+
+        let mut transcript = format!("# {}\n", topic.title);
+        for message in messages {
+            transcript.push_str(&format!(
+                "\n## {} ({})\n\n{}\n",
+                message.role, message.timestamp, message.content
+            ));
+        }
+        Ok(transcript)
+    }
+}
+
+#[async_trait]
+impl McpMessageHandler for FishmindMcpServer {
+    async fn handle_message(
+        &self,
+        message: Value,
+        _outbound: &mpsc::UnboundedSender<Value>,
+    ) -> Option<Value> {
+        let method = message.get("method")?.as_str()?.to_string();
+        let id = message.get("id").cloned();
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        debug!("[MCP] fishmind 服务器收到: {}", method);
+
+        let result = match method.as_str() {
+            "initialize" => Ok(json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": {
+                    "tools": { "listChanged": false },
+                    "resources": { "listChanged": false, "subscribe": false },
+                },
+                "serverInfo": { "name": "fishmind", "version": env!("CARGO_PKG_VERSION") },
+                "instructions": "Read-only access to fishmind chat history, topics, assistants and MCP tools",
+            })),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": tools() })),
+            "tools/call" => self.call_tool(&params).await,
+            "resources/list" => self.list_resources().await,
+            "resources/read" => self.read_resource(&params).await,
+            _ if id.is_none() => return None,
+            other => Err((METHOD_NOT_FOUND, format!("Method not found: {}", other))),
+        };
+        rpc_response(id, result)
+    }
+}
+
+fn tools() -> Vec<Value> {
+    let limit = json!({ "type": "integer", "minimum": 1, "maximum": MAX_LIMIT });
+    vec![
+        tool(
+            "search_chat_history",
+            "Search chat messages by keyword, optionally within one topic",
+            json!({
+                "query": { "type": "string" },
+                "topic_id": { "type": "string" },
+                "limit": limit,
+            }),
+            &["query"],
+        ),
+        tool(
+            "list_topics",
+            "List the most recently updated chat topics",
+            json!({ "limit": limit }),
+            &[],
+        ),
+        tool(
+            "get_topic_messages",
+            "Get the messages of a chat topic in chronological order",
+            json!({ "topic_id": { "type": "string" }, "limit": limit }),
+            &["topic_id"],
+        ),
+        tool(
+            "list_assistants",
+            "List the configured assistants",
+            json!({}),
+            &[],
+        ),
+        tool(
+            "list_mcp_tools",
+            "List configured MCP servers and the tools of connected servers",
+            json!({}),
+            &[],
+        ),
+    ]
+}
+
+fn tool(name: &str, description: &str, properties: Value, required: &[&str]) -> Value {
+    json!({
+        "name": name,
+        "description": description,
+        "inputSchema": { "type": "object", "properties": properties, "required": required },
+        "annotations": { "readOnlyHint": true },
+    })
+}
+
+fn required_str<'a>(arguments: &'a Value, name: &str) -> Result<&'a str, (i64, String)> {
+    arguments.get(name).and_then(Value::as_str).ok_or((
+        INVALID_PARAMS,
+        format!("Missing required argument: {}", name),
+    ))
+}
+
+fn limit_arg(arguments: &Value) -> Option<u32> {
+    arguments
+        .get("limit")
+        .and_then(Value::as_u64)
+        .map(|limit| limit.min(MAX_LIMIT as u64) as u32)
+}
+
+fn pretty(value: &Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_default()
+}
+
+/// 未通过 Tauri 启动时的默认应用数据目录，与 Tauri 的 `app_data_dir` 一致
+pub fn default_data_dir() -> Option<PathBuf> {
+    let base = if cfg!(target_os = "windows") {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        std::env::var_os("HOME").map(|home| {
+            PathBuf::from(home)
+                .join("Library")
+                .join("Application Support")
+        })
+    } else {
+        std::env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| {
+                std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share"))
+            })
+    };
+    base.map(|base| base.join(APP_IDENTIFIER))
+}
+
+/// 以 MCP 服务器模式运行进程，参数见 [`SERVER_FLAG`]
+///
+/// 标准输出用于协议消息，不能写日志。
+pub fn run_from_args(args: Vec<String>) {
+    let data_dir = args
+        .iter()
+        .position(|arg| arg == "--data-dir")
+        .and_then(|index| args.get(index + 1))
+        .map(PathBuf::from)
+        .or_else(default_data_dir);
+    let Some(data_dir) = data_dir else {
+        eprintln!("Cannot determine the fishmind data directory, use --data-dir");
+        return;
+    };
+
+    let runtime = tokio::runtime::Runtime::new().expect("failed to start tokio runtime");
+    runtime.block_on(async {
+        let state = Arc::new(AppState::with_data_dir(data_dir));
+        info!("[MCP] fishmind 服务器通过 stdio 提供服务");
+        server_transport::serve_io(
+            FishmindMcpServer::new(state),
+            tokio::io::stdin(),
+            tokio::io::stdout(),
+        )
+        .await;
+    });
+}
+
+/// 在本机端口上启动 SSE 服务，已在运行时返回当前状态
+///
+/// 只监听 127.0.0.1，端口为 0 时随机选择。访问令牌首次启动时生成并保存在密钥存储中。
+pub async fn start_sse_server(
+    state: Arc<AppState>,
+    port: u16,
+) -> Result<FishmindServerStatus, String> {
+    let handler = FishmindMcpServer::new(state.clone());
+    start_local_service(
+        &state,
+        &state.fishmind_server,
+        ACCESS_TOKEN_SECRET,
+        handler,
+        port,
+    )
+    .await
+}
+
+/// 停止本机 SSE 服务，未运行时返回 false
+pub fn stop_sse_server(state: &AppState) -> bool {
+    stop_local_service(&state.fishmind_server)
+}
+
+/// 本机 SSE 服务状态
+pub fn sse_server_status(state: &AppState) -> Result<FishmindServerStatus, String> {
+    local_service_status(state, &state.fishmind_server, ACCESS_TOKEN_SECRET)
+}
+
+/// 在本机端口上启动需要访问令牌的 SSE 服务，保存到 `slot` 中
+///
+/// fishmind 服务器和网关共用，各自使用独立的令牌。
+pub(crate) async fn start_local_service<H: McpMessageHandler>(
+    state: &AppState,
+    slot: &Mutex<Option<SseServer>>,
+    token_secret: &str,
+    handler: Arc<H>,
+    port: u16,
+) -> Result<FishmindServerStatus, String> {
+    if slot.lock().unwrap().is_some() {
+        return local_service_status(state, slot, token_secret);
+    }
+
+    let token = access_token(state, token_secret)?;
+    let server =
+        server_transport::start_sse(handler, &format!("127.0.0.1:{}", port), Some(token)).await?;
+    info!("[MCP] 本机 MCP 服务已启动: {}", server.url);
+
+    let mut running = slot.lock().unwrap();
+    // 并发启动时保留先启动的服务
+    match running.as_ref() {
+        Some(_) => server.stop(),
+        None => *running = Some(server),
+    }
+    drop(running);
+    local_service_status(state, slot, token_secret)
+}
+
+pub(crate) fn stop_local_service(slot: &Mutex<Option<SseServer>>) -> bool {
+    match slot.lock().unwrap().take() {
+        Some(server) => {
+            server.stop();
+            info!("[MCP] 本机 MCP 服务已停止: {}", server.url);
+            true
+        }
+        None => false,
+    }
+}
+
+pub(crate) fn local_service_status(
+    state: &AppState,
+    slot: &Mutex<Option<SseServer>>,
+    token_secret: &str,
+) -> Result<FishmindServerStatus, String> {
+    let url = slot
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|server| server.url.clone());
+    let access_token = match url {
+        Some(_) => state.placeholder_resolver.secrets().get(token_secret)?,
+        None => None,
+    };
+    Ok(FishmindServerStatus {
+        running: url.is_some(),
+        url,
+        access_token,
+    })
+}
+
+// 读取或生成访问令牌
+fn access_token(state: &AppState, token_secret: &str) -> Result<String, String> {
+    let secrets = state.placeholder_resolver.secrets();
+    if let Some(token) = secrets.get(token_secret)? {
+        redact::register_secret(&token);
+        return Ok(token);
+    }
+    let mut bytes = [0u8; 32];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| "Failed to generate access token".to_string())?;
+    let token = URL_SAFE_NO_PAD.encode(bytes);
+    secrets.set(token_secret, &token)?;
+    redact::register_secret(&token);
+    Ok(token)
+}
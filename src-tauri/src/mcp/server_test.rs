@@ -0,0 +1,199 @@
+#[cfg(test)]
+mod tests {
+    use crate::mcp::client::AppState;
+    use crate::mcp::history::HistoryStore;
+    use crate::mcp::server::{
+        sse_server_status, start_sse_server, stop_sse_server, FishmindMcpServer,
+    };
+    use crate::mcp::server_transport::McpMessageHandler;
+    use serde_json::{json, Value};
+    use sqlx::sqlite::SqliteConnectOptions;
+    use sqlx::{Connection, SqliteConnection};
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use tokio::sync::mpsc;
+
+    // 为每个测试创建独立的数据目录，写入最小的聊天数据库
+    async fn data_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "fishmind-server-test-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = HistoryStore::default_path(&dir);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+
+        let options = SqliteConnectOptions::new()
+            .filename(&path)
+            .create_if_missing(true);
+        let mut conn = SqliteConnection::connect_with(&options).await.unwrap();
+        sqlx::raw_sql(
+            "CREATE TABLE topics (id TEXT PRIMARY KEY, title TEXT NOT NULL, created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL, message_count INTEGER DEFAULT 0, preview TEXT,
+                source_assistant_id TEXT);
+             CREATE TABLE messages (id TEXT PRIMARY KEY, topic_id TEXT NOT NULL, role TEXT NOT NULL,
+                content TEXT NOT NULL, timestamp TEXT NOT NULL, model_id TEXT);
+             CREATE TABLE assistants (id TEXT PRIMARY KEY, name TEXT NOT NULL, description TEXT,
+                provider_id TEXT, model_id TEXT, system_prompt TEXT NOT NULL, is_default INTEGER,
+                tags TEXT);
+             INSERT INTO topics VALUES ('t1', '周末计划', '2024-01-01', '2024-01-01', 2, NULL, NULL);
+             INSERT INTO messages VALUES
+                ('m1', 't1', 'user', '周末去爬山吗？', '2024-01-01T10:00:00', NULL),
+                ('m2', 't1', 'assistant', '好主意，记得带水。', '2024-01-01T10:00:05', 'gpt');
+             INSERT INTO assistants VALUES ('a1', 'Planner', NULL, NULL, NULL, 'Plan trips', 1, NULL);",
+        )
+        .execute(&mut conn)
+        .await
+        .unwrap();
+        conn.close().await.unwrap();
+        dir
+    }
+
+    async fn call(server: &FishmindMcpServer, method: &str, params: Value) -> Value {
+        let (tx, _rx) = mpsc::unbounded_channel();
+        server
+            .handle_message(
+                json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }),
+                &tx,
+            )
+            .await
+            .unwrap()
+    }
+
+    // 测试初始化、工具列表和聊天数据库不可用时的降级
+    #[tokio::test]
+    async fn test_without_history() {
+        let server = FishmindMcpServer::new(Arc::new(AppState::new()));
+
+        let initialize = call(&server, "initialize", json!({})).await;
+        assert_eq!(initialize["result"]["serverInfo"]["name"], "fishmind");
+
+        let tools = call(&server, "tools/list", json!({})).await;
+        assert_eq!(tools["result"]["tools"].as_array().unwrap().len(), 5);
+
+        let search = call(
+            &server,
+            "tools/call",
+            json!({ "name": "search_chat_history", "arguments": { "query": "x" } }),
+        )
+        .await;
+        assert_eq!(search["result"]["isError"], true);
+
+        let missing = call(
+            &server,
+            "tools/call",
+            json!({ "name": "search_chat_history", "arguments": {} }),
+        )
+        .await;
+        assert_eq!(missing["error"]["code"], -32602);
+
+        let catalog = call(
+            &server,
+            "tools/call",
+            json!({ "name": "list_mcp_tools", "arguments": {} }),
+        )
+        .await;
+        assert_eq!(
+            catalog["result"]["structuredContent"]["result"]["servers"],
+            json!([])
+        );
+
+        let resources = call(&server, "resources/list", json!({})).await;
+        let resources = resources["result"]["resources"].as_array().unwrap();
+        assert_eq!(resources.len(), 1);
+        assert_eq!(resources[0]["uri"], "fishmind://mcp/catalog");
+
+        let unknown = call(
+            &server,
+            "resources/read",
+            json!({ "uri": "fishmind://unknown" }),
+        )
+        .await;
+        assert_eq!(unknown["error"]["code"], -32002);
+    }
+
+    // 测试通过工具和资源读取聊天记录
+    #[tokio::test]
+    async fn test_history_tools_and_resources() {
+        let state = Arc::new(AppState::with_data_dir(data_dir("history").await));
+        let server = FishmindMcpServer::new(state);
+
+        let search = call(
+            &server,
+            "tools/call",
+            json!({ "name": "search_chat_history", "arguments": { "query": "爬山" } }),
+        )
+        .await;
+        let matches = &search["result"]["structuredContent"]["result"];
+        assert_eq!(matches[0]["message_id"], "m1");
+        assert_eq!(matches[0]["topic_title"], "周末计划");
+
+        let resources = call(&server, "resources/list", json!({})).await;
+        let uris: Vec<&str> = resources["result"]["resources"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|resource| resource["uri"].as_str().unwrap())
+            .collect();
+        assert!(uris.contains(&"fishmind://topics/t1"));
+        assert!(uris.contains(&"fishmind://assistants/a1"));
+
+        let transcript = call(
+            &server,
+            "resources/read",
+            json!({ "uri": "fishmind://topics/t1" }),
+        )
+        .await;
+        let text = transcript["result"]["contents"][0]["text"]
+            .as_str()
+            .unwrap();
+        assert!(text.starts_with("# 周末计划"));
+        assert!(text.find("周末去爬山吗").unwrap() < text.find("记得带水").unwrap());
+
+        let assistant = call(
+            &server,
+            "resources/read",
+            json!({ "uri": "fishmind://assistants/a1" }),
+        )
+        .await;
+        assert!(assistant["result"]["contents"][0]["text"]
+            .as_str()
+            .unwrap()
+            .contains("Plan trips"));
+    }
+
+    // 测试本机 SSE 服务需要访问令牌，停止后不再运行
+    #[tokio::test]
+    async fn test_sse_server_requires_token() {
+        let state = Arc::new(AppState::with_data_dir(data_dir("sse").await));
+
+        let status = start_sse_server(state.clone(), 0).await.unwrap();
+        assert!(status.running);
+        let url = status.url.unwrap();
+        let token = status.access_token.unwrap();
+        assert!(url.starts_with("http://127.0.0.1:"));
+
+        // 重复启动返回同一个服务
+        let again = start_sse_server(state.clone(), 0).await.unwrap();
+        assert_eq!(again.url.as_deref(), Some(url.as_str()));
+
+        let http = reqwest::Client::new();
+        let rejected = http.get(&url).send().await.unwrap();
+        assert_eq!(rejected.status(), 401);
+        let accepted = http.get(&url).bearer_auth(&token).send().await.unwrap();
+        assert_eq!(accepted.status(), 200);
+        drop(accepted);
+
+        assert!(stop_sse_server(&state));
+        assert!(!stop_sse_server(&state));
+        let stopped = sse_server_status(&state).unwrap();
+        assert!(!stopped.running);
+        assert!(stopped.access_token.is_none());
+
+        // 令牌保存在密钥存储中，重新启动后不变
+        let restarted = start_sse_server(state.clone(), 0).await.unwrap();
+        assert_eq!(restarted.access_token.as_deref(), Some(token.as_str()));
+        stop_sse_server(&state);
+    }
+}
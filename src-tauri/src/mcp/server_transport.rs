@@ -0,0 +1,296 @@
+use async_trait::async_trait;
+use log::{info, warn};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// MCP 服务端最新支持的协议版本
+pub const PROTOCOL_VERSION: &str = "2024-11-05";
+
+// JSON-RPC 错误码
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+pub const RESOURCE_NOT_FOUND: i64 = -32002;
+
+/// 方法处理结果，错误为 (错误码, 错误信息)
+pub type RpcResult = Result<Value, (i64, String)>;
+
+/// 服务端消息处理器
+///
+/// 模拟服务器和 fishmind 自身的 MCP 服务器都通过该接口接入 stdio 和 SSE 传输。
+#[async_trait]
+pub trait McpMessageHandler: Send + Sync + 'static {
+    /// 处理一条客户端消息，请求返回响应，通知返回 None
+    ///
+    /// 进度和列表变更等服务器通知通过 `outbound` 发送。
+    async fn handle_message(
+        &self,
+        message: Value,
+        outbound: &mpsc::UnboundedSender<Value>,
+    ) -> Option<Value>;
+}
+
+/// 把方法处理结果包装为 JSON-RPC 响应，通知（没有 ID）返回 None
+pub fn rpc_response(id: Option<Value>, result: RpcResult) -> Option<Value> {
+    let id = id?;
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err((code, message)) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": code, "message": message },
+        }),
+    })
+}
+
+/// 通过按行分隔的 JSON-RPC 流提供服务，用于 stdio
+///
+/// 每个请求在独立任务中处理，慢请求不会阻塞其他请求。
+pub async fn serve_io<H, R, W>(handler: Arc<H>, reader: R, mut writer: W)
+where
+    H: McpMessageHandler,
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin + Send + 'static,
+{
+    let (tx, mut rx) = mpsc::unbounded_channel::<Value>();
+    let writer_task = tokio::spawn(async move {
+        while let Some(message) = rx.recv().await {
+            let line = format!("{}\n", message);
+            if writer.write_all(line.as_bytes()).await.is_err() || writer.flush().await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let message: Value = match serde_json::from_str(&line) {
+            Ok(message) => message,
+            Err(e) => {
+                warn!("[MCP] 服务端无法解析消息: {}", e);
+                continue;
+            }
+        };
+        let handler = handler.clone();
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            if let Some(response) = handler.handle_message(message, &tx).await {
+                let _ = tx.send(response);
+            }
+        });
+    }
+
+    drop(tx);
+    let _ = writer_task.await;
+}
+
+// SSE 会话ID -> 发送给该会话事件流的消息
+type Sessions = Arc<Mutex<HashMap<String, mpsc::UnboundedSender<Value>>>>;
+
+/// 运行中的 SSE 服务
+pub struct SseServer {
+    /// 事件流地址
+    pub url: String,
+    task: JoinHandle<()>,
+    sessions: Sessions,
+}
+
+impl SseServer {
+    /// 停止接受连接并关闭所有事件流
+    pub fn stop(&self) {
+        self.task.abort();
+        self.sessions.lock().unwrap().clear();
+    }
+}
+
+/// 在本机端口上启动 SSE 服务
+///
+/// 地址中端口为 0 时随机选择端口。设置 `access_token` 时，建立事件流需要
+/// `Authorization: Bearer <token>` 请求头或 `token` 查询参数；消息端点使用随机会话ID，
+/// 只有持有事件流的客户端知道。
+pub async fn start_sse<H: McpMessageHandler>(
+    handler: Arc<H>,
+    addr: &str,
+    access_token: Option<String>,
+) -> Result<SseServer, String> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .map_err(|e| format!("Failed to bind MCP server to {}: {}", addr, e))?;
+    let local_addr = listener
+        .local_addr()
+        .map_err(|e| format!("Failed to bind MCP server to {}: {}", addr, e))?;
+    let url = format!("http://{}/sse", local_addr);
+    info!("[MCP] 服务端 SSE 地址: {}", url);
+
+    let sessions: Sessions = Arc::new(Mutex::new(HashMap::new()));
+    let accept_sessions = sessions.clone();
+    let access_token = access_token.map(Arc::new);
+    let task = tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let handler = handler.clone();
+            let sessions = accept_sessions.clone();
+            let access_token = access_token.clone();
+            tokio::spawn(async move {
+                handle_http(stream, handler, sessions, access_token).await;
+            });
+        }
+    });
+
+    Ok(SseServer {
+        url,
+        task,
+        sessions,
+    })
+}
+
+async fn handle_http<H: McpMessageHandler>(
+    mut stream: TcpStream,
+    handler: Arc<H>,
+    sessions: Sessions,
+    access_token: Option<Arc<String>>,
+) {
+    let Some(request) = read_http_request(&mut stream).await else {
+        return;
+    };
+    let path = request.target.split('?').next().unwrap_or_default();
+
+    match (request.method.as_str(), path) {
+        ("GET", "/sse") => {
+            if let Some(token) = &access_token {
+                if !request.has_token(token) {
+                    return write_status(&mut stream, "401 Unauthorized").await;
+                }
+            }
+
+            let Some(session_id) = random_id() else {
+                return write_status(&mut stream, "500 Internal Server Error").await;
+            };
+            let (tx, mut rx) = mpsc::unbounded_channel::<Value>();
+            sessions.lock().unwrap().insert(session_id.clone(), tx);
+
+            let head = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\n\r\nevent: endpoint\ndata: /message?sessionId={}\n\n",
+                session_id
+            );
+            if stream.write_all(head.as_bytes()).await.is_ok() {
+                while let Some(message) = rx.recv().await {
+                    let event = format!("event: message\ndata: {}\n\n", message);
+                    if stream.write_all(event.as_bytes()).await.is_err() {
+                        break;
+                    }
+                }
+            }
+            sessions.lock().unwrap().remove(&session_id);
+        }
+        ("POST", "/message") => {
+            let session = request
+                .query("sessionId")
+                .and_then(|id| sessions.lock().unwrap().get(&id).cloned());
+            let Some(tx) = session else {
+                return write_status(&mut stream, "404 Not Found").await;
+            };
+            let Ok(message) = serde_json::from_str::<Value>(&request.body) else {
+                return write_status(&mut stream, "400 Bad Request").await;
+            };
+            write_status(&mut stream, "202 Accepted").await;
+            if let Some(response) = handler.handle_message(message, &tx).await {
+                let _ = tx.send(response);
+            }
+        }
+        _ => write_status(&mut stream, "404 Not Found").await,
+    }
+}
+
+struct HttpRequest {
+    method: String,
+    target: String,
+    headers: HashMap<String, String>,
+    body: String,
+}
+
+impl HttpRequest {
+    fn query(&self, name: &str) -> Option<String> {
+        let (_, query) = self.target.split_once('?')?;
+        query.split('&').find_map(|pair| {
+            let (key, value) = pair.split_once('=')?;
+            (key == name).then(|| value.to_string())
+        })
+    }
+
+    fn has_token(&self, token: &str) -> bool {
+        let bearer = self
+            .headers
+            .get("authorization")
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(str::trim);
+        bearer == Some(token) || self.query("token").as_deref() == Some(token)
+    }
+}
+
+async fn write_status(stream: &mut TcpStream, status: &str) {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        status
+    );
+    let _ = stream.write_all(response.as_bytes()).await;
+}
+
+fn random_id() -> Option<String> {
+    let mut bytes = [0u8; 16];
+    SystemRandom::new().fill(&mut bytes).ok()?;
+    Some(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+// 读取一个 HTTP 请求
+async fn read_http_request(stream: &mut TcpStream) -> Option<HttpRequest> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    loop {
+        let n = stream.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        let Some(end) = buf.windows(4).position(|w| w == b"\r\n\r\n") else {
+            continue;
+        };
+
+        let head = String::from_utf8_lossy(&buf[..end]).to_string();
+        let mut lines = head.lines();
+        let mut request_line = lines.next()?.split_whitespace();
+        let method = request_line.next()?.to_string();
+        let target = request_line.next()?.to_string();
+        let headers: HashMap<String, String> = lines
+            .filter_map(|line| line.split_once(':'))
+            .map(|(key, value)| (key.trim().to_ascii_lowercase(), value.trim().to_string()))
+            .collect();
+
+        let length: usize = headers
+            .get("content-length")
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(0);
+        while buf.len() < end + 4 + length {
+            let n = stream.read(&mut chunk).await.ok()?;
+            if n == 0 {
+                return None;
+            }
+            buf.extend_from_slice(&chunk[..n]);
+        }
+        let body = String::from_utf8_lossy(&buf[end + 4..end + 4 + length]).to_string();
+        return Some(HttpRequest {
+            method,
+            target,
+            headers,
+            body,
+        });
+    }
+}
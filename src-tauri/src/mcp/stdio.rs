@@ -0,0 +1,259 @@
+use crate::mcp::http_sse::{
+    dispatch, send_message, serve_requests, MessageObserver, Observers, Outbound, PendingRequests,
+    ServerRequestHandler,
+};
+use async_trait::async_trait;
+use log::{debug, warn};
+use mcp_client_fishcode2025::transport::{Error as TransportError, Transport, TransportHandle};
+use mcp_core_fishcode2025::protocol::JsonRpcMessage;
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStderr, ChildStdin, ChildStdout, Command};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+// 进程退出时附带到错误信息中的标准错误输出行数
+const STDERR_TAIL_LINES: usize = 20;
+
+// 进程退出后等待读取剩余标准错误输出的时间
+const STDERR_DRAIN_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// 通过子进程标准输入输出通信的传输
+///
+/// 进程的启动方式与库自带的 `StdioTransport` 相同，消息按行分隔。区别在于服务器发起的请求
+/// （如 `elicitation/create`）交给请求处理器，结果写回标准输入；服务器通知交给消息观察者；
+/// 标准错误输出逐行写入日志，进程退出时最后几行附带在等待中请求的错误信息里。
+pub struct StdioProcessTransport {
+    command: String,
+    args: Vec<String>,
+    env: HashMap<String, String>,
+    handler: Option<Arc<dyn ServerRequestHandler>>,
+    observers: Vec<Arc<dyn MessageObserver>>,
+}
+
+impl StdioProcessTransport {
+    pub fn new(command: &str, args: Vec<String>, env: HashMap<String, String>) -> Self {
+        Self {
+            command: command.to_string(),
+            args,
+            env,
+            handler: None,
+            observers: Vec::new(),
+        }
+    }
+
+    /// 添加消息观察者
+    pub fn with_observer(mut self, observer: Arc<dyn MessageObserver>) -> Self {
+        self.observers.push(observer);
+        self
+    }
+
+    /// 设置服务器请求处理器，未设置时只响应 `ping`
+    pub fn with_request_handler(mut self, handler: Arc<dyn ServerRequestHandler>) -> Self {
+        self.handler = Some(handler);
+        self
+    }
+
+    fn spawn(&self) -> Result<(Child, ChildStdin, ChildStdout, ChildStderr), TransportError> {
+        let mut command = Command::new(&self.command);
+        command
+            .envs(&self.env)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+
+        // 不继承父进程的信号处理
+        #[cfg(unix)]
+        command.process_group(0);
+
+        // Windows 上不显示控制台窗口（CREATE_NO_WINDOW）
+        #[cfg(windows)]
+        command.creation_flags(0x08000000);
+
+        let mut child = command
+            .spawn()
+            .map_err(|e| TransportError::StdioProcessError(e.to_string()))?;
+        let missing =
+            |name: &str| TransportError::StdioProcessError(format!("Failed to get {}", name));
+        let stdin = child.stdin.take().ok_or_else(|| missing("stdin"))?;
+        let stdout = child.stdout.take().ok_or_else(|| missing("stdout"))?;
+        let stderr = child.stderr.take().ok_or_else(|| missing("stderr"))?;
+        Ok((child, stdin, stdout, stderr))
+    }
+}
+
+#[async_trait]
+impl Transport for StdioProcessTransport {
+    type Handle = StdioProcessTransportHandle;
+
+    async fn start(&self) -> Result<Self::Handle, TransportError> {
+        let (child, stdin, stdout, stderr) = self.spawn()?;
+
+        let pending: PendingRequests = Arc::new(Mutex::new(HashMap::new()));
+        let observers: Observers = Arc::new(self.observers.clone());
+        let (line_tx, line_rx) = mpsc::unbounded_channel();
+        let (request_tx, request_rx) = mpsc::unbounded_channel();
+
+        let writer = tokio::spawn(write_lines(stdin, line_rx, pending.clone()));
+        let stderr = tokio::spawn(read_stderr(stderr));
+        let reader = tokio::spawn(read_messages(
+            stdout,
+            stderr,
+            pending.clone(),
+            request_tx,
+            observers.clone(),
+        ));
+        let lines = LineSender(line_tx);
+        let responder = tokio::spawn(serve_requests(
+            lines.clone(),
+            self.handler.clone(),
+            request_rx,
+            observers,
+        ));
+
+        Ok(StdioProcessTransportHandle {
+            inner: Arc::new(HandleInner {
+                lines,
+                capabilities: self.handler.as_ref().map(|handler| handler.capabilities()),
+                pending,
+                _child: child,
+                reader,
+                writer,
+                responder,
+            }),
+        })
+    }
+
+    async fn close(&self) -> Result<(), TransportError> {
+        // 子进程在最后一个句柄释放时结束
+        Ok(())
+    }
+}
+
+/// 子进程传输句柄
+#[derive(Clone)]
+pub struct StdioProcessTransportHandle {
+    inner: Arc<HandleInner>,
+}
+
+struct HandleInner {
+    lines: LineSender,
+    // 请求处理器声明的能力
+    capabilities: Option<Value>,
+    pending: PendingRequests,
+    // 释放时结束子进程
+    _child: Child,
+    reader: JoinHandle<()>,
+    writer: JoinHandle<()>,
+    responder: JoinHandle<()>,
+}
+
+impl Drop for HandleInner {
+    fn drop(&mut self) {
+        self.reader.abort();
+        self.writer.abort();
+        self.responder.abort();
+    }
+}
+
+#[async_trait]
+impl TransportHandle for StdioProcessTransportHandle {
+    async fn send(&self, message: JsonRpcMessage) -> Result<JsonRpcMessage, TransportError> {
+        send_message(
+            &self.inner.lines,
+            &self.inner.pending,
+            self.inner.capabilities.as_ref(),
+            message,
+        )
+        .await
+    }
+}
+
+// 把消息交给写入任务，写入任务结束后发送失败
+#[derive(Clone)]
+struct LineSender(mpsc::UnboundedSender<String>);
+
+#[async_trait]
+impl Outbound for LineSender {
+    async fn send_value(&self, message: &Value) -> Result<(), TransportError> {
+        self.0
+            .send(format!("{}\n", message))
+            .map_err(|_| TransportError::StdioProcessError("Process has exited".to_string()))
+    }
+}
+
+// 按行写入标准输入；写入失败时进程已不可用，让所有等待中的请求失败
+async fn write_lines(
+    mut stdin: ChildStdin,
+    mut lines: mpsc::UnboundedReceiver<String>,
+    pending: PendingRequests,
+) {
+    while let Some(line) = lines.recv().await {
+        let written = match stdin.write_all(line.as_bytes()).await {
+            Ok(()) => stdin.flush().await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = written {
+            warn!("[MCP] 写入服务器进程失败: {}", e);
+            fail_pending(&pending, format!("Failed to write to process: {}", e));
+            return;
+        }
+    }
+}
+
+// 读取标准输出并分发消息；进程结束时让所有等待中的请求失败
+async fn read_messages(
+    stdout: ChildStdout,
+    stderr: JoinHandle<String>,
+    pending: PendingRequests,
+    requests: mpsc::UnboundedSender<Value>,
+    observers: Observers,
+) {
+    let mut lines = BufReader::new(stdout).lines();
+    let reason = loop {
+        match lines.next_line().await {
+            Ok(Some(line)) if line.trim().is_empty() => {}
+            Ok(Some(line)) => dispatch(&pending, &requests, &observers, &line),
+            Ok(None) => break "Process ended unexpectedly".to_string(),
+            Err(e) => break format!("Failed to read from process: {}", e),
+        }
+    };
+
+    let tail = match tokio::time::timeout(STDERR_DRAIN_TIMEOUT, stderr).await {
+        Ok(Ok(tail)) => tail,
+        _ => String::new(),
+    };
+    let reason = if tail.is_empty() {
+        reason
+    } else {
+        format!("{}: {}", reason, tail)
+    };
+    warn!("[MCP] 服务器进程已结束: {}", reason);
+    fail_pending(&pending, reason);
+}
+
+// 逐行记录标准错误输出，返回最后几行
+async fn read_stderr(stderr: ChildStderr) -> String {
+    let mut tail = VecDeque::with_capacity(STDERR_TAIL_LINES);
+    let mut lines = BufReader::new(stderr).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        debug!("[MCP] 服务器进程输出: {}", line);
+        if tail.len() == STDERR_TAIL_LINES {
+            tail.pop_front();
+        }
+        tail.push_back(line);
+    }
+    Vec::from(tail).join("\n")
+}
+
+fn fail_pending(pending: &PendingRequests, reason: String) {
+    for (_, tx) in pending.lock().unwrap().drain() {
+        let _ = tx.send(Err(TransportError::StdioProcessError(reason.clone())));
+    }
+}
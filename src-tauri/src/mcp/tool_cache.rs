@@ -0,0 +1,283 @@
+use crate::mcp::types::{ToolCacheConfig, ToolCacheEntryInfo, ToolCacheSnapshot};
+use log::{debug, info};
+use ring::digest;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 服务器对工具行为的标注，来自 `tools/list` 中的 `annotations`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ToolHints {
+    pub read_only: Option<bool>,
+    pub idempotent: Option<bool>,
+    pub destructive: Option<bool>,
+}
+
+impl ToolHints {
+    /// 从工具定义中读取标注
+    pub fn from_tool(tool: &Value) -> Self {
+        let hint = |name: &str| {
+            tool.get("annotations")
+                .and_then(|annotations| annotations.get(name))
+                .and_then(Value::as_bool)
+        };
+        Self {
+            read_only: hint("readOnlyHint"),
+            idempotent: hint("idempotentHint"),
+            destructive: hint("destructiveHint"),
+        }
+    }
+}
+
+// (客户端ID, 工具名, 参数摘要)
+type CacheKey = (String, String, String);
+
+struct CacheEntry {
+    stored_at: Instant,
+    ttl: Duration,
+    result: Value,
+    hits: u64,
+    size_bytes: usize,
+}
+
+/// 工具结果缓存
+///
+/// 默认关闭。启用后，服务器标注为只读（`readOnlyHint`）的工具按参数缓存成功的结果；
+/// 幂等（`idempotentHint`）但不只读的工具和没有标注的工具需要单独配置缓存时间，
+/// 标注为破坏性（`destructiveHint`）或会修改状态且不幂等的工具即使配置了也不缓存。
+pub struct ToolCache {
+    config: Mutex<ToolCacheConfig>,
+    // 客户端ID -> 工具名 -> 标注
+    hints: Mutex<HashMap<String, HashMap<String, ToolHints>>>,
+    entries: Mutex<HashMap<CacheKey, CacheEntry>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl ToolCache {
+    pub fn new() -> Self {
+        Self {
+            config: Mutex::new(ToolCacheConfig::default()),
+            hints: Mutex::new(HashMap::new()),
+            entries: Mutex::new(HashMap::new()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// 获取当前配置
+    pub fn config(&self) -> ToolCacheConfig {
+        self.config.lock().unwrap().clone()
+    }
+
+    /// 替换配置，关闭缓存时清除所有结果
+    pub fn set_config(&self, config: ToolCacheConfig) {
+        info!(
+            "[MCP] 更新工具结果缓存配置, 启用: {}, 默认缓存时间: {}s",
+            config.enabled, config.default_ttl_secs
+        );
+        if !config.enabled {
+            self.entries.lock().unwrap().clear();
+        }
+        *self.config.lock().unwrap() = config;
+    }
+
+    /// 更新客户端的工具标注，列出工具时调用（分页时逐页合并）
+    pub fn set_hints(&self, client_id: &str, hints: HashMap<String, ToolHints>) {
+        self.hints
+            .lock()
+            .unwrap()
+            .entry(client_id.to_string())
+            .or_default()
+            .extend(hints);
+    }
+
+    /// 工具结果的缓存时间，不可缓存时返回 None
+    pub fn ttl(&self, client_id: &str, tool_name: &str) -> Option<Duration> {
+        let config = self.config.lock().unwrap();
+        if !config.enabled {
+            return None;
+        }
+        let configured = config
+            .servers
+            .get(client_id)
+            .and_then(|tools| tools.get(tool_name))
+            .copied();
+        let hints = self
+            .hints
+            .lock()
+            .unwrap()
+            .get(client_id)
+            .and_then(|tools| tools.get(tool_name))
+            .copied()
+            .unwrap_or_default();
+
+        // 幂等的写操作跳过副作用后结果可能已经过时，只有只读工具使用默认缓存时间
+        let ttl = if hints.destructive == Some(true) {
+            return None;
+        } else if hints.read_only == Some(true) {
+            configured.unwrap_or(config.default_ttl_secs)
+        } else if hints.read_only == Some(false) && hints.idempotent != Some(true) {
+            return None;
+        } else {
+            configured?
+        };
+        (ttl > 0).then(|| Duration::from_secs(ttl))
+    }
+
+    /// 查找未过期的结果
+    pub fn get(&self, client_id: &str, tool_name: &str, arguments: &Value) -> Option<Value> {
+        self.ttl(client_id, tool_name)?;
+        let key = cache_key(client_id, tool_name, arguments);
+        let mut entries = self.entries.lock().unwrap();
+        if let Some(entry) = entries.get_mut(&key) {
+            if entry.stored_at.elapsed() < entry.ttl {
+                entry.hits += 1;
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                debug!("[MCP] 工具结果缓存命中: {}", tool_name);
+                return Some(entry.result.clone());
+            }
+            entries.remove(&key);
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        None
+    }
+
+    /// 缓存成功的结果，工具不可缓存时忽略
+    pub fn insert(&self, client_id: &str, tool_name: &str, arguments: &Value, result: &Value) {
+        let Some(ttl) = self.ttl(client_id, tool_name) else {
+            return;
+        };
+        let max_entries = self.config.lock().unwrap().max_entries;
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, entry| entry.stored_at.elapsed() < entry.ttl);
+        // 超出上限时淘汰最早缓存的结果
+        while entries.len() >= max_entries.max(1) {
+            let Some(oldest) = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.stored_at)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+            entries.remove(&oldest);
+        }
+        entries.insert(
+            cache_key(client_id, tool_name, arguments),
+            CacheEntry {
+                stored_at: Instant::now(),
+                ttl,
+                size_bytes: result.to_string().len(),
+                result: result.clone(),
+                hits: 0,
+            },
+        );
+    }
+
+    /// 清除缓存的结果，可以只清除某个客户端或某个工具，返回清除的数量
+    pub fn clear(&self, client_id: Option<&str>, tool_name: Option<&str>) -> usize {
+        let mut entries = self.entries.lock().unwrap();
+        let before = entries.len();
+        entries.retain(|(client, tool, _), _| {
+            !(client_id.is_none_or(|id| id == client) && tool_name.is_none_or(|name| name == tool))
+        });
+        let removed = before - entries.len();
+        info!("[MCP] 清除工具结果缓存, 数量: {}", removed);
+        removed
+    }
+
+    /// 移除客户端的所有结果和工具标注，断开或删除客户端时调用
+    pub fn remove_client(&self, client_id: &str) {
+        self.clear(Some(client_id), None);
+        self.hints.lock().unwrap().remove(client_id);
+    }
+
+    /// 当前配置、未过期的结果和命中统计
+    pub fn snapshot(&self) -> ToolCacheSnapshot {
+        let mut entries: Vec<ToolCacheEntryInfo> = self
+            .entries
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, entry)| entry.stored_at.elapsed() < entry.ttl)
+            .map(|((client_id, tool_name, hash), entry)| ToolCacheEntryInfo {
+                client_id: client_id.clone(),
+                tool_name: tool_name.clone(),
+                arguments_hash: hash.clone(),
+                age_secs: entry.stored_at.elapsed().as_secs(),
+                ttl_secs: entry.ttl.as_secs(),
+                hits: entry.hits,
+                size_bytes: entry.size_bytes,
+            })
+            .collect();
+        entries.sort_by(|a, b| {
+            (&a.client_id, &a.tool_name, a.age_secs).cmp(&(&b.client_id, &b.tool_name, b.age_secs))
+        });
+        ToolCacheSnapshot {
+            config: self.config(),
+            entries,
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl Default for ToolCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn cache_key(client_id: &str, tool_name: &str, arguments: &Value) -> CacheKey {
+    (
+        client_id.to_string(),
+        tool_name.to_string(),
+        arguments_hash(arguments),
+    )
+}
+
+/// 规范化参数的 SHA-256，对象的键按字典序排列，空参数与空对象相同
+pub fn arguments_hash(arguments: &Value) -> String {
+    let mut canonical = String::new();
+    match arguments {
+        Value::Null => canonical.push_str("{}"),
+        arguments => write_canonical(arguments, &mut canonical),
+    }
+    digest::digest(&digest::SHA256, canonical.as_bytes())
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            out.push('{');
+            for (i, key) in keys.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(&map[key], out);
+            }
+            out.push('}');
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        other => out.push_str(&other.to_string()),
+    }
+}
@@ -0,0 +1,231 @@
+#[cfg(test)]
+mod tests {
+    use crate::mcp::client::McpClientManager;
+    use crate::mcp::mock_server::{start_sse, MockMcpServer};
+    use crate::mcp::tool_cache::{arguments_hash, ToolCache, ToolHints};
+    use crate::mcp::types::{
+        FilterRequest, InitializeClientRequest, ToolCacheConfig, ToolCallRequest, TransportType,
+    };
+    use serde_json::json;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    fn hints(
+        read_only: Option<bool>,
+        idempotent: Option<bool>,
+        destructive: Option<bool>,
+    ) -> ToolHints {
+        ToolHints {
+            read_only,
+            idempotent,
+            destructive,
+        }
+    }
+
+    fn enabled(servers: HashMap<String, HashMap<String, u64>>) -> ToolCacheConfig {
+        ToolCacheConfig {
+            enabled: true,
+            servers,
+            ..ToolCacheConfig::default()
+        }
+    }
+
+    // 测试参数摘要与键顺序无关
+    #[test]
+    fn test_arguments_hash() {
+        let a = json!({ "b": 1, "a": { "y": [1, 2], "x": "s" } });
+        let b = json!({ "a": { "x": "s", "y": [1, 2] }, "b": 1 });
+        assert_eq!(arguments_hash(&a), arguments_hash(&b));
+        assert_eq!(arguments_hash(&json!(null)), arguments_hash(&json!({})));
+        assert_ne!(
+            arguments_hash(&json!({ "a": [1, 2] })),
+            arguments_hash(&json!({ "a": [2, 1] }))
+        );
+        assert_eq!(arguments_hash(&a).len(), 64);
+
+        let tool = json!({ "name": "t", "annotations": { "readOnlyHint": true } });
+        assert_eq!(ToolHints::from_tool(&tool), hints(Some(true), None, None));
+        let tool = json!({ "name": "t", "annotations": { "readOnlyHint": false, "destructiveHint": true } });
+        assert_eq!(
+            ToolHints::from_tool(&tool),
+            hints(Some(false), None, Some(true))
+        );
+        assert_eq!(
+            ToolHints::from_tool(&json!({ "name": "t" })),
+            ToolHints::default()
+        );
+    }
+
+    // 测试按工具标注和配置判断是否可以缓存
+    #[test]
+    fn test_ttl_eligibility() {
+        let cache = ToolCache::new();
+        cache.set_hints(
+            "mock",
+            HashMap::from([
+                ("read".to_string(), hints(Some(true), None, None)),
+                (
+                    "idempotent".to_string(),
+                    hints(Some(false), Some(true), None),
+                ),
+                (
+                    "set_value".to_string(),
+                    hints(Some(false), Some(true), None),
+                ),
+                ("write".to_string(), hints(Some(false), None, None)),
+                (
+                    "delete_file".to_string(),
+                    hints(Some(false), Some(true), Some(true)),
+                ),
+            ]),
+        );
+        // 默认关闭
+        assert_eq!(cache.ttl("mock", "read"), None);
+
+        let servers = HashMap::from([(
+            "mock".to_string(),
+            HashMap::from([
+                ("read".to_string(), 10),
+                ("set_value".to_string(), 20),
+                ("write".to_string(), 10),
+                ("delete_file".to_string(), 10),
+                ("plain".to_string(), 5),
+                ("disabled".to_string(), 0),
+            ]),
+        )]);
+        cache.set_config(enabled(servers));
+        assert_eq!(cache.ttl("mock", "read"), Some(Duration::from_secs(10)));
+        // 幂等但不只读的工具只在配置后缓存
+        assert_eq!(cache.ttl("mock", "idempotent"), None);
+        assert_eq!(
+            cache.ttl("mock", "set_value"),
+            Some(Duration::from_secs(20))
+        );
+        // 明确会修改状态或破坏性的工具即使配置了也不缓存
+        assert_eq!(cache.ttl("mock", "write"), None);
+        assert_eq!(cache.ttl("mock", "delete_file"), None);
+        // 没有标注的工具只在配置后缓存
+        assert_eq!(cache.ttl("mock", "plain"), Some(Duration::from_secs(5)));
+        assert_eq!(cache.ttl("mock", "unknown"), None);
+        assert_eq!(cache.ttl("mock", "disabled"), None);
+        assert_eq!(cache.ttl("other", "read"), None);
+    }
+
+    // 测试命中、过期、淘汰和清除
+    #[test]
+    fn test_get_insert_and_clear() {
+        let cache = ToolCache::new();
+        let servers = HashMap::from([
+            (
+                "a".to_string(),
+                HashMap::from([("t".to_string(), 60), ("short".to_string(), 1)]),
+            ),
+            ("b".to_string(), HashMap::from([("t".to_string(), 60)])),
+        ]);
+        cache.set_config(ToolCacheConfig {
+            max_entries: 3,
+            ..enabled(servers)
+        });
+
+        let result = json!({ "content": [], "isError": false });
+        assert_eq!(cache.get("a", "t", &json!({ "x": 1 })), None);
+        cache.insert("a", "t", &json!({ "x": 1 }), &result);
+        assert_eq!(
+            cache.get("a", "t", &json!({ "x": 1 })),
+            Some(result.clone())
+        );
+        assert_eq!(cache.get("a", "t", &json!({ "x": 2 })), None);
+        // 不可缓存的工具不保存结果
+        cache.insert("a", "other", &json!({}), &result);
+        assert_eq!(cache.snapshot().entries.len(), 1);
+
+        cache.insert("a", "short", &json!({}), &result);
+        cache.insert("b", "t", &json!({}), &result);
+        // 超出上限时淘汰最早的结果
+        cache.insert("a", "t", &json!({ "x": 3 }), &result);
+        assert_eq!(cache.get("a", "t", &json!({ "x": 1 })), None);
+
+        let snapshot = cache.snapshot();
+        assert_eq!(snapshot.entries.len(), 3);
+        assert_eq!(snapshot.hits, 1);
+        assert_eq!(snapshot.misses, 3);
+
+        std::thread::sleep(Duration::from_millis(1100));
+        assert_eq!(cache.get("a", "short", &json!({})), None);
+
+        assert_eq!(cache.clear(Some("a"), Some("t")), 1);
+        assert_eq!(cache.clear(None, None), 1);
+        assert!(cache.snapshot().entries.is_empty());
+
+        // 关闭缓存时清除结果
+        cache.set_config(enabled(HashMap::from([(
+            "a".to_string(),
+            HashMap::from([("t".to_string(), 60)]),
+        )])));
+        cache.insert("a", "t", &json!({}), &result);
+        cache.set_config(ToolCacheConfig::default());
+        assert!(cache.snapshot().entries.is_empty());
+    }
+
+    // 测试通过管理器调用工具时使用服务器的标注
+    #[tokio::test]
+    async fn test_manager_caches_read_only_tools() {
+        let mut manager = McpClientManager::new();
+        let request = InitializeClientRequest {
+            id: "mock".to_string(),
+            transport_type: TransportType::SSE,
+            sse_url: Some(
+                start_sse(MockMcpServer::new(), "127.0.0.1:0")
+                    .await
+                    .unwrap(),
+            ),
+            headers: Some(HashMap::new()),
+            timeout_secs: Some(30),
+            client_name: "tool-cache-test".to_string(),
+            client_version: "1.0.0".to_string(),
+            ..Default::default()
+        };
+        manager.initialize_client(request).await.unwrap();
+        manager.tool_cache().set_config(ToolCacheConfig {
+            enabled: true,
+            ..ToolCacheConfig::default()
+        });
+        manager
+            .list_tools(FilterRequest {
+                client_id: "mock".to_string(),
+                filter: None,
+                cursor: None,
+            })
+            .await
+            .unwrap();
+
+        let call = |tool_name: &str, params| ToolCallRequest {
+            client_id: "mock".to_string(),
+            tool_name: tool_name.to_string(),
+            params,
+        };
+        for _ in 0..2 {
+            let result = manager
+                .call_tool(call("echo", json!({ "text": "hi" })))
+                .await
+                .unwrap();
+            assert_eq!(result.data.unwrap()["content"][0]["text"], "hi");
+        }
+        for _ in 0..2 {
+            let result = manager
+                .call_tool(call("list_changed", json!({})))
+                .await
+                .unwrap();
+            assert!(result.success);
+        }
+
+        let snapshot = manager.tool_cache().snapshot();
+        assert_eq!(snapshot.hits, 1);
+        assert_eq!(snapshot.entries.len(), 1);
+        assert_eq!(snapshot.entries[0].tool_name, "echo");
+        assert_eq!(snapshot.entries[0].hits, 1);
+
+        manager.disconnect_client("mock").await.unwrap();
+        assert!(manager.tool_cache().snapshot().entries.is_empty());
+    }
+}
@@ -3,15 +3,21 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// 传输类型
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 #[serde(rename_all = "lowercase")]
 pub enum TransportType {
+    #[default]
+    #[allow(clippy::upper_case_acronyms)]
     SSE,
     Stdio,
+    /// 回放录制的会话，不连接服务器
+    Replay,
 }
 
 /// 初始化客户端请求
-#[derive(Debug, Clone, Deserialize)]
+///
+/// 可选配置较多，测试中可以只填写需要的字段，其余使用 `..Default::default()`。
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct InitializeClientRequest {
     // 服务器配置
     pub id: String,
@@ -21,12 +27,224 @@ pub struct InitializeClientRequest {
     pub args: Option<Vec<String>>,
     pub headers: Option<HashMap<String, String>>,
     pub timeout_secs: Option<u64>,
+    // 沙箱配置（仅对 Stdio 传输生效）
+    pub sandbox: Option<SandboxProfile>,
+    // 限流配置
+    pub limits: Option<LimitConfig>,
+    // 熔断配置
+    pub circuit_breaker: Option<CircuitBreakerConfig>,
+    // OAuth 配置（仅对 SSE 传输生效）
+    pub oauth: Option<OAuthConfig>,
+    // TLS 配置（仅对 SSE 传输生效）
+    pub tls: Option<TlsConfig>,
+    // 代理配置（仅对 SSE 传输生效）
+    pub proxy: Option<ProxyConfig>,
+    // 录制会话文件路径（仅对 Replay 传输生效）
+    pub replay_path: Option<String>,
 
     // 客户端信息
     pub client_name: String,
     pub client_version: String,
 }
 
+/// 持久化的 MCP 服务器配置
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct McpServerConfig {
+    /// 显示名称
+    #[serde(default)]
+    pub name: Option<String>,
+    /// 应用启动时是否自动连接
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(flatten)]
+    pub request: InitializeClientRequest,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// 外部 MCP 配置文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum McpConfigFormat {
+    /// claude_desktop_config.json
+    ClaudeDesktop,
+    /// .cursor/mcp.json
+    Cursor,
+    /// .vscode/mcp.json
+    Vscode,
+}
+
+/// 导入的单个服务器配置
+#[derive(Debug, Clone, Serialize)]
+pub struct McpServerImport {
+    pub config: McpServerConfig,
+    /// 与已保存的服务器重复时为该服务器ID（ID相同，或命令和参数/URL相同）
+    pub duplicate_of: Option<String>,
+    /// 无法导入而被忽略的字段
+    pub unsupported_fields: Vec<String>,
+}
+
+/// 导入结果
+#[derive(Debug, Clone, Serialize)]
+pub struct McpImportResult {
+    pub format: McpConfigFormat,
+    pub servers: Vec<McpServerImport>,
+    /// 已保存到注册表的服务器数量
+    pub saved: usize,
+}
+
+/// Stdio 服务器沙箱配置（仅 Linux）
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SandboxProfile {
+    /// 隔离的工作目录，未指定时为每个客户端创建临时目录
+    pub working_dir: Option<String>,
+    /// 允许从宿主进程继承的环境变量，显式配置的环境变量始终保留
+    pub env_allowlist: Vec<String>,
+    /// 虚拟内存上限 (MB)
+    pub max_memory_mb: Option<u64>,
+    /// CPU 时间上限 (秒)
+    pub max_cpu_secs: Option<u64>,
+    /// 打开文件数上限
+    pub max_open_files: Option<u64>,
+    /// 通过网络命名空间禁止网络访问
+    pub deny_network: bool,
+}
+
+/// 单个工具的限流配置，未设置的项不做限制
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ToolLimitConfig {
+    /// 每秒允许的调用次数
+    pub rate_per_sec: Option<f64>,
+    /// 令牌桶容量，默认等于每秒调用次数
+    pub burst: Option<u32>,
+    /// 同时进行中的调用上限
+    pub max_concurrent: Option<usize>,
+}
+
+/// 客户端限流配置
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct LimitConfig {
+    /// 每秒允许的调用次数
+    pub rate_per_sec: Option<f64>,
+    /// 令牌桶容量，默认等于每秒调用次数
+    pub burst: Option<u32>,
+    /// 同时进行中的调用上限
+    pub max_concurrent: Option<usize>,
+    /// 超出限制时排队等待的最长时间 (毫秒)
+    pub queue_timeout_ms: Option<u64>,
+    /// 按工具名覆盖的限流配置，与客户端限制同时生效
+    pub tools: HashMap<String, ToolLimitConfig>,
+}
+
+/// 远程服务器 TLS 配置
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TlsConfig {
+    /// 额外信任的 CA 证书文件（PEM，可包含多个证书），与系统根证书一起使用
+    pub ca_bundle_path: Option<String>,
+    /// mTLS 客户端证书文件（PEM），需要同时设置私钥
+    pub client_cert_path: Option<String>,
+    /// mTLS 客户端私钥文件（PEM）
+    pub client_key_path: Option<String>,
+    /// 跳过证书校验，仅用于开发环境，连接时会输出警告
+    pub insecure_skip_verify: bool,
+}
+
+/// 远程服务器代理配置
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ProxyConfig {
+    /// 代理地址，支持 `http://`、`https://`、`socks5://` 和 `socks5h://`
+    pub url: String,
+    /// 不经过代理的主机，支持域名后缀、IP 和 CIDR
+    pub no_proxy: Vec<String>,
+}
+
+/// 远程服务器 OAuth 配置
+///
+/// 连接时若服务器返回 401 和 `WWW-Authenticate`，会自动发现授权服务器并执行授权码 + PKCE 流程。
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct OAuthConfig {
+    /// 预先注册的客户端ID，未设置时使用动态客户端注册
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+    /// 请求的权限范围，未设置时使用服务器声明的范围
+    pub scopes: Vec<String>,
+    /// 本地回调端口，未设置时随机选择
+    pub redirect_port: Option<u16>,
+    /// 禁用自动授权
+    pub disabled: bool,
+}
+
+/// 发送给前端的授权事件，前端可以展示授权进度或手动打开链接
+#[derive(Debug, Clone, Serialize)]
+pub struct OAuthAuthorizeEvent {
+    pub server_id: String,
+    pub authorization_url: String,
+}
+
+/// 已保存的 OAuth 令牌状态，不包含令牌本身
+#[derive(Debug, Clone, Serialize)]
+pub struct OAuthTokenStatus {
+    pub server_id: String,
+    pub resource: String,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub has_refresh_token: bool,
+}
+
+/// fishmind 本机 SSE MCP 服务状态
+#[derive(Debug, Clone, Serialize)]
+pub struct FishmindServerStatus {
+    pub running: bool,
+    /// 事件流地址，未运行时为 None
+    pub url: Option<String>,
+    /// 连接事件流时使用的 Bearer 令牌
+    pub access_token: Option<String>,
+}
+
+/// 熔断器配置
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct CircuitBreakerConfig {
+    /// 连续失败（含超时）多少次后熔断
+    pub failure_threshold: u32,
+    /// 熔断后等待多久进入半开状态并放行一次探测调用 (秒)
+    pub cooldown_secs: u64,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            cooldown_secs: 30,
+        }
+    }
+}
+
+/// 熔断器状态
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakerState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+/// 熔断器状态快照
+#[derive(Debug, Clone, Serialize)]
+pub struct BreakerStatus {
+    pub state: BreakerState,
+    pub consecutive_failures: u32,
+    pub opened_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+}
+
 /// 客户端连接状态
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "lowercase")]
@@ -53,22 +271,62 @@ pub struct ClientStatusResponse {
     pub error: Option<String>,
     pub connected_at: Option<DateTime<Utc>>,
     pub server_info: Option<ServerInfo>,
-}
-
-/// 操作请求基础结构
-#[derive(Debug, Deserialize)]
-pub struct OperationRequest {
-    pub client_id: String,
+    pub sandbox: Option<SandboxProfile>,
+    pub circuit_breaker: Option<BreakerStatus>,
 }
 
 /// 工具调用请求
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct ToolCallRequest {
     pub client_id: String,
     pub tool_name: String,
     pub params: serde_json::Value,
 }
 
+/// 批量工具调用请求，调用可以属于不同的客户端
+#[derive(Debug, Clone, Deserialize)]
+pub struct ToolBatchRequest {
+    /// 批次ID，前端用于匹配结果事件，未设置时自动生成
+    #[serde(default)]
+    pub batch_id: Option<String>,
+    pub calls: Vec<ToolCallRequest>,
+    /// 每个客户端同时执行的最大调用数
+    #[serde(default)]
+    pub max_concurrent_per_client: Option<usize>,
+}
+
+/// 批量调用中单个调用的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolBatchItem {
+    /// 调用在请求中的位置
+    pub index: usize,
+    pub client_id: String,
+    pub tool_name: String,
+    pub success: bool,
+    pub data: Option<serde_json::Value>,
+    pub error: Option<String>,
+    /// 相对批次开始时间的开始执行时间，不包括排队等待
+    pub started_ms: u64,
+    pub duration_ms: u64,
+}
+
+/// 批量工具调用结果，按请求顺序排列
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolBatchResult {
+    pub batch_id: String,
+    pub results: Vec<ToolBatchItem>,
+    pub duration_ms: u64,
+}
+
+/// 批量调用中每个调用完成时发送给前端的事件
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolBatchEvent {
+    pub batch_id: String,
+    pub completed: usize,
+    pub total: usize,
+    pub result: ToolBatchItem,
+}
+
 /// 资源读取请求
 #[derive(Debug, Deserialize)]
 pub struct ResourceReadRequest {
@@ -84,11 +342,54 @@ pub struct PromptRequest {
     pub params: serde_json::Value,
 }
 
+/// 补全的目标，对应 MCP 的 `ref/prompt` 和 `ref/resource`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(tag = "type")]
+pub enum CompletionReference {
+    /// 提示参数
+    #[serde(rename = "ref/prompt")]
+    Prompt { name: String },
+    /// 资源模板变量
+    #[serde(rename = "ref/resource")]
+    Resource { uri: String },
+}
+
+/// 参数补全请求
+#[derive(Debug, Clone, Deserialize)]
+pub struct CompletionRequest {
+    pub client_id: String,
+    pub reference: CompletionReference,
+    /// 参数名或模板变量名
+    pub argument_name: String,
+    /// 用户已输入的部分值
+    pub value: String,
+    /// 已填写的其他参数，部分服务器据此缩小候选范围
+    #[serde(default)]
+    pub context: Option<HashMap<String, String>>,
+}
+
+/// 参数补全结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CompletionResult {
+    /// 按匹配程度排序的候选值
+    pub values: Vec<String>,
+    /// 服务器报告的候选总数
+    pub total: Option<u64>,
+    pub has_more: bool,
+    /// 是否来自缓存
+    pub cached: bool,
+    /// 防抖期间有更新的请求，本次请求未发送
+    pub superseded: bool,
+}
+
 /// 过滤请求
 #[derive(Debug, Deserialize)]
 pub struct FilterRequest {
     pub client_id: String,
     pub filter: Option<String>,
+    /// 列出工具时的分页游标，来自上一页结果的 `nextCursor`
+    #[serde(default)]
+    pub cursor: Option<String>,
 }
 
 /// 通用响应结构
@@ -105,7 +406,140 @@ pub struct ToolInfo {
     pub name: String,
     pub description: String,
     pub parameters_schema: Option<serde_json::Value>,
+    /// 服务器声明的结构化结果格式（`outputSchema`）
     pub result_schema: Option<serde_json::Value>,
+    /// 显示名称，未提供时使用 annotations 中的 title
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub annotations: Option<ToolAnnotations>,
+    #[serde(default)]
+    pub icons: Vec<ToolIcon>,
+}
+
+/// 服务器对工具行为的提示，不保证准确，只用于展示和审批等决策
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ToolAnnotations {
+    pub title: Option<String>,
+    /// 不修改任何状态
+    pub read_only_hint: Option<bool>,
+    /// 可能执行破坏性的修改，只在非只读时有意义
+    pub destructive_hint: Option<bool>,
+    /// 相同参数重复调用没有额外影响
+    pub idempotent_hint: Option<bool>,
+    /// 会与外部系统交互
+    pub open_world_hint: Option<bool>,
+}
+
+/// 工具图标
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolIcon {
+    pub src: String,
+    pub mime_type: Option<String>,
+    #[serde(default)]
+    pub sizes: Vec<String>,
+}
+
+/// 工具结果缓存配置
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ToolCacheConfig {
+    /// 是否启用缓存，默认关闭
+    pub enabled: bool,
+    /// 服务器标注为只读的工具的默认缓存时间（秒）
+    pub default_ttl_secs: u64,
+    /// 按服务器ID和工具名配置的缓存时间（秒），0 表示不缓存；
+    /// 没有标注或幂等但不只读的工具只有在这里配置后才会缓存
+    pub servers: HashMap<String, HashMap<String, u64>>,
+    /// 最多缓存的结果数
+    pub max_entries: usize,
+}
+
+impl Default for ToolCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            default_ttl_secs: 300,
+            servers: HashMap::new(),
+            max_entries: 500,
+        }
+    }
+}
+
+/// 缓存的工具结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolCacheEntryInfo {
+    pub client_id: String,
+    pub tool_name: String,
+    /// 规范化参数的 SHA-256
+    pub arguments_hash: String,
+    pub age_secs: u64,
+    pub ttl_secs: u64,
+    pub hits: u64,
+    pub size_bytes: usize,
+}
+
+/// 工具结果缓存的当前状态
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolCacheSnapshot {
+    pub config: ToolCacheConfig,
+    pub entries: Vec<ToolCacheEntryInfo>,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+/// 工具调用统计
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CallStats {
+    pub calls: u64,
+    pub successes: u64,
+    /// 服务器返回错误、工具结果为 isError 或被限流、熔断拒绝的调用数，不含超时
+    pub errors: u64,
+    pub timeouts: u64,
+    /// 发送的参数字节数
+    pub bytes_out: u64,
+    /// 收到的结果字节数
+    pub bytes_in: u64,
+    pub latency_ms: LatencySummary,
+    pub last_error: Option<LastCallError>,
+}
+
+/// 调用耗时分布（毫秒），分位数按直方图桶的上限估算
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LatencySummary {
+    pub p50: u64,
+    pub p95: u64,
+    pub p99: u64,
+    pub max: u64,
+    pub sum: u64,
+    /// 各直方图桶的调用数（不累加），与 `LATENCY_BUCKETS_MS` 对应，最后一个是溢出桶
+    #[serde(default)]
+    pub buckets: Vec<u64>,
+}
+
+/// 最近一次失败的调用
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LastCallError {
+    pub tool_name: String,
+    pub message: String,
+    pub at: DateTime<Utc>,
+}
+
+/// 单个工具的调用统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolMetrics {
+    pub tool_name: String,
+    #[serde(flatten)]
+    pub stats: CallStats,
+}
+
+/// 客户端的调用统计，包含所有工具的汇总和每个工具的统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientMetrics {
+    pub client_id: String,
+    #[serde(flatten)]
+    pub stats: CallStats,
+    pub tools: Vec<ToolMetrics>,
 }
 
 /// 资源信息
@@ -116,10 +550,264 @@ pub struct ResourceInfo {
     pub content_type: String,
 }
 
+/// 资源内容
+///
+/// 二进制内容超过内联阈值时解码写入缓存文件，只返回文件路径，避免大段 base64 经过 IPC。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResourceContent {
+    Text {
+        uri: String,
+        mime_type: String,
+        text: String,
+    },
+    /// base64 编码的二进制内容
+    Blob {
+        uri: String,
+        mime_type: String,
+        blob: String,
+        /// 解码后的字节数
+        size: u64,
+    },
+    /// 已写入缓存文件的二进制内容，前端通过 `convertFileSrc` 经 asset 协议预览
+    File {
+        uri: String,
+        mime_type: String,
+        path: String,
+        size: u64,
+    },
+}
+
+/// 提示参数
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PromptArgument {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+}
+
 /// 提示信息
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PromptInfo {
     pub name: String,
     pub description: String,
-    pub parameters_schema: Option<serde_json::Value>,
+    pub arguments: Vec<PromptArgument>,
+}
+
+/// 消息角色
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageRole {
+    User,
+    Assistant,
+}
+
+/// 消息内容块
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentBlock {
+    Text {
+        text: String,
+    },
+    /// base64 编码的图片
+    Image {
+        data: String,
+        mime_type: String,
+    },
+    /// 内嵌资源，文本资源有 `text`，二进制资源有 base64 编码的 `blob`
+    Resource {
+        uri: String,
+        mime_type: Option<String>,
+        text: Option<String>,
+        blob: Option<String>,
+    },
+}
+
+/// 可以直接插入对话的消息
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PromptMessage {
+    pub role: MessageRole,
+    pub content: Vec<ContentBlock>,
+}
+
+/// 提示获取结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptResult {
+    pub description: Option<String>,
+    pub messages: Vec<PromptMessage>,
+}
+
+/// 工具调用审批模式
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum ApprovalMode {
+    /// 总是允许
+    #[default]
+    AlwaysAllow,
+    /// 每次调用前询问用户
+    Ask,
+    /// 总是拒绝
+    Deny,
+    /// 参数匹配时允许，否则询问用户；键为参数名，值为需完整匹配的正则表达式
+    AllowIfMatch { arguments: HashMap<String, String> },
+}
+
+/// 单个服务器的审批策略
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ServerApprovalPolicy {
+    /// 服务器默认模式，未设置时使用全局默认模式
+    pub default_mode: Option<ApprovalMode>,
+    /// 按工具名覆盖的模式
+    pub tools: HashMap<String, ApprovalMode>,
+}
+
+/// 工具调用审批策略
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ApprovalPolicy {
+    pub default_mode: ApprovalMode,
+    /// 按客户端ID配置的服务器策略
+    pub servers: HashMap<String, ServerApprovalPolicy>,
+    /// 等待用户审批的超时时间
+    pub ask_timeout_secs: u64,
+}
+
+impl Default for ApprovalPolicy {
+    fn default() -> Self {
+        Self {
+            default_mode: ApprovalMode::AlwaysAllow,
+            servers: HashMap::new(),
+            ask_timeout_secs: 300,
+        }
+    }
+}
+
+/// 发送给前端的审批请求事件
+#[derive(Debug, Clone, Serialize)]
+pub struct ApprovalRequestEvent {
+    pub request_id: String,
+    pub client_id: String,
+    pub tool_name: String,
+    pub arguments: serde_json::Value,
+}
+
+/// 前端返回的审批结果
+#[derive(Debug, Clone, Deserialize)]
+pub struct ApprovalResponse {
+    pub request_id: String,
+    pub approved: bool,
+    /// 本次会话内记住该决定
+    #[serde(default)]
+    pub remember: bool,
+    /// 审批人
+    pub user: Option<String>,
+}
+
+/// 发送给前端的配置输入请求事件，对应 `${input:prompt}` 占位符
+#[derive(Debug, Clone, Serialize)]
+pub struct InputRequestEvent {
+    pub request_id: String,
+    pub client_id: String,
+    pub prompt: String,
+}
+
+/// 前端返回的配置输入，value 为 None 表示取消
+#[derive(Debug, Clone, Deserialize)]
+pub struct InputResponse {
+    pub request_id: String,
+    pub value: Option<String>,
+}
+
+/// 发送给前端的结构化输入请求事件，对应服务器的 `elicitation/create` 请求
+#[derive(Debug, Clone, Serialize)]
+pub struct ElicitationRequestEvent {
+    pub request_id: String,
+    pub client_id: String,
+    pub message: String,
+    /// 服务器要求的输入格式，只包含基本类型属性的对象 JSON Schema
+    pub requested_schema: serde_json::Value,
+}
+
+/// 用户对结构化输入请求的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ElicitationAction {
+    Accept,
+    Decline,
+    Cancel,
+}
+
+/// 前端返回的结构化输入，只有 accept 时需要 content
+#[derive(Debug, Clone, Deserialize)]
+pub struct ElicitationResponse {
+    pub request_id: String,
+    pub action: ElicitationAction,
+    #[serde(default)]
+    pub content: Option<serde_json::Value>,
+}
+
+/// 审计记录结果
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditOutcome {
+    Success,
+    Error,
+    Denied,
+}
+
+/// MCP 操作审计记录
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub client_id: String,
+    pub server_name: Option<String>,
+    pub server_version: Option<String>,
+    pub operation: String,
+    /// 工具名、资源 URI 或提示名
+    pub target: Option<String>,
+    /// 脱敏后参数的 SHA-256 摘要
+    pub arguments_hash: Option<String>,
+    /// 脱敏后的参数
+    pub arguments: Option<serde_json::Value>,
+    pub duration_ms: u64,
+    pub outcome: AuditOutcome,
+    pub error: Option<String>,
+    /// 审批人
+    pub approved_by: Option<String>,
+}
+
+/// 审计日志查询条件
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct AuditQuery {
+    pub client_id: Option<String>,
+    pub operation: Option<String>,
+    pub target: Option<String>,
+    pub outcome: Option<AuditOutcome>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+/// 审计日志保留策略
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AuditRetention {
+    /// 最多保留的记录数
+    pub max_entries: usize,
+    /// 最多保留的天数
+    pub max_age_days: u32,
+}
+
+impl Default for AuditRetention {
+    fn default() -> Self {
+        Self {
+            max_entries: 100_000,
+            max_age_days: 90,
+        }
+    }
 }
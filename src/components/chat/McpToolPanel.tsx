@@ -175,8 +175,19 @@ const McpToolPanel: React.FC<McpToolPanelProps> = ({
 
     if (tabIndex === 0 && selectedTool?.parameters_schema) {
       schema = selectedTool.parameters_schema;
-    } else if (tabIndex === 2 && selectedPrompt?.parameters_schema) {
-      schema = selectedPrompt.parameters_schema;
+    } else if (tabIndex === 2 && selectedPrompt?.arguments?.length) {
+      // 提示参数都是字符串，必填参数在标题后加星号
+      schema = {
+        properties: Object.fromEntries(
+          selectedPrompt.arguments.map((arg) => [
+            arg.name,
+            {
+              title: arg.required ? `${arg.name} *` : arg.name,
+              description: arg.description,
+            },
+          ])
+        ),
+      };
     }
 
     if (!schema) return null;
@@ -1,5 +1,7 @@
 // 现有类型定义的扩展
 
+import type { ToolAnnotations, ToolIcon } from "./mcpTypes";
+
 /**
  * 工具信息
  */
@@ -27,6 +29,21 @@ export interface ToolInfo {
      * 工具结果模式
      */
     result_schema?: any;
+
+    /**
+     * 显示名称
+     */
+    title?: string | null;
+
+    /**
+     * 服务器对工具行为的提示（只读、破坏性、幂等、访问外部系统）
+     */
+    annotations?: ToolAnnotations | null;
+
+    /**
+     * 工具图标
+     */
+    icons?: ToolIcon[];
   }
   
   /**
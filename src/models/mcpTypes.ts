@@ -96,7 +96,26 @@ export interface ToolInfo {
   name: string;
   description: string;
   parameters_schema?: any;  // JSON Schema
-  result_schema?: any;      // JSON Schema
+  result_schema?: any;      // JSON Schema（服务器的 outputSchema）
+  title?: string | null;    // 显示名称
+  annotations?: ToolAnnotations | null;
+  icons?: ToolIcon[];
+}
+
+// 服务器对工具行为的提示，只用于展示和审批等决策
+export interface ToolAnnotations {
+  title?: string | null;
+  read_only_hint?: boolean | null;
+  destructive_hint?: boolean | null;
+  idempotent_hint?: boolean | null;
+  open_world_hint?: boolean | null;
+}
+
+// 工具图标
+export interface ToolIcon {
+  src: string;
+  mime_type?: string | null;
+  sizes: string[];
 }
 
 // 资源信息
@@ -107,11 +126,23 @@ export interface ResourceInfo {
   content_type: string;
 }
 
+// 资源内容，超过内联阈值的二进制内容写入缓存文件
+export type ResourceContent =
+  | { type: 'text'; uri: string; mime_type: string; text: string }
+  | { type: 'blob'; uri: string; mime_type: string; blob: string; size: number }
+  | { type: 'file'; uri: string; mime_type: string; path: string; size: number };
+
 // 提示信息
+export interface PromptArgument {
+  name: string;
+  description?: string;
+  required: boolean;
+}
+
 export interface PromptInfo {
   name: string;
   description: string;
-  parameters_schema?: any;  // JSON Schema
+  arguments: PromptArgument[];
 }
 
 // 客户端初始化请求（用于与Tauri通信）
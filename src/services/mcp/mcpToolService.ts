@@ -3,6 +3,7 @@ import { IMcpToolService, IMcpService } from '../interfaces';
 import { 
   ToolInfo, 
   ResourceInfo, 
+  ResourceContent,
   PromptInfo, 
   McpResponse,
   FilterRequest,
@@ -188,7 +189,7 @@ export class McpToolService implements IMcpToolService {
    * @param resourceUri 资源URI
    * @returns 资源内容
    */
-  async readResource(configId: string, resourceUri: string): Promise<any> {
+  async readResource(configId: string, resourceUri: string): Promise<ResourceContent[]> {
     try {
       // 确保MCP客户端已连接
       await this.ensureClientConnected(configId);
@@ -202,11 +203,11 @@ export class McpToolService implements IMcpToolService {
       };
       
       // 调用Rust后端
-      const response = await invoke<McpResponse<any>>('read_mcp_resource', { request });
+      const response = await invoke<McpResponse<ResourceContent[]>>('read_mcp_resource', { request });
       console.log(`资源 ${resourceUri} 读取结果:`, response);
       
       if (response.success) {
-        return response.data;
+        return response.data ?? [];
       } else {
         throw new Error(response.error || '资源读取失败');
       }